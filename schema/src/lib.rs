@@ -0,0 +1,455 @@
+//! The JSON notation of the workouts of Exercise Timer, for tools which create workouts
+//! for it or read them. The notation is described in `README.md` next to this crate.
+//!
+//! Workouts of all earlier versions of the notation are still read. Workouts of newer
+//! versions are read as far as their fields are known, unknown fields are ignored.
+//!
+//! With the `serde` feature, the types can be (de)serialized with serde as well. Reading with
+//! serde goes through `from_json`, so that it upgrades earlier versions the same way.
+//!
+//! Besides the workouts, the crate describes the sessions of the history, as they are backed
+//! up and synchronized. Exercise Timer has no playlists, so there is no notation for them.
+
+#[cfg(feature = "serde")]
+mod raw_json;
+
+/// Version of the notation. When it changes, it is increased and a migration from the
+/// previous version is appended to `MIGRATIONS`.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// The preparation of workouts which do not state it, in seconds.
+pub const DEFAULT_PREPARE_S: usize = 5;
+
+/// A workout: `sets` times an exercise and a rest, after a preparation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(try_from = "raw_json::RawJson")
+)]
+pub struct Workout {
+    /// The version of the notation which the workout was written in.
+    pub version: u32,
+    /// Identifies the workout across devices. Workouts without one get a new identity when imported.
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub id: Option<String>,
+    /// Increased on every change, so that the newer edit wins when synchronizing.
+    pub revision: u64,
+    pub name: String,
+    pub sets: usize,
+    pub exercise_s: usize,
+    pub rest_s: usize,
+    pub prepare_s: usize,
+    /// Pinned workouts are kept at the top of the list.
+    pub pinned: bool,
+    /// The `id` of the harder workout which follows this one, e.g. push-ups after knee push-ups.
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub progression: Option<String>,
+    /// The kind of workout, e.g. `emom`, or `None` for classic intervals.
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub mode: Option<String>,
+    /// How hard the exercise is meant to be: `easy`, `moderate` or `hard`.
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub exercise_intensity: Option<String>,
+    /// How hard the rest is meant to be, e.g. `moderate` for jogging between sprints.
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub rest_intensity: Option<String>,
+    /// The URIs of the images of the rounds in order, e.g. how to do each stretch.
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Vec::is_empty"))]
+    pub images: Vec<String>,
+    /// The named steps of the preparation, which replace its countdown of `prepare_s`.
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Vec::is_empty"))]
+    pub warmup: Vec<WarmupStep>,
+    /// The preparation is the default warmup of the reader, e.g. as set in its preferences.
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "is_false"))]
+    pub default_warmup: bool,
+}
+
+/// A step of a warmup, e.g. 30 seconds of jumping jacks.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct WarmupStep {
+    pub name: String,
+    pub duration_s: usize,
+}
+
+impl WarmupStep {
+    /// Reads a step, or `None` if its `name` or `duration_s` is missing.
+    pub fn from_json(value: &json::JsonValue) -> Option<WarmupStep> {
+        Some(WarmupStep {
+            name: value["name"].as_str()?.to_owned(),
+            duration_s: value["duration_s"].as_usize()?,
+        })
+    }
+
+    pub fn to_json(&self) -> json::JsonValue {
+        json::object! {
+            name: self.name.clone(),
+            duration_s: self.duration_s,
+        }
+    }
+
+    /// Reads the valid steps of a list and skips the others.
+    pub fn list_from_json(value: &json::JsonValue) -> Vec<WarmupStep> {
+        value.members().filter_map(WarmupStep::from_json).collect()
+    }
+
+    pub fn list_to_json(steps: &[WarmupStep]) -> json::JsonValue {
+        json::JsonValue::Array(steps.iter().map(WarmupStep::to_json).collect())
+    }
+}
+
+#[cfg(feature = "serde")]
+fn is_false(value: &bool) -> bool {
+    !value
+}
+
+/// The list of workouts, as it is stored and synchronized.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(try_from = "raw_json::RawJson")
+)]
+pub struct WorkoutList {
+    /// The time of the last change, in seconds since the Unix epoch.
+    pub modified: i64,
+    pub trainings: Vec<Workout>,
+}
+
+type Migration = fn(&mut json::JsonValue);
+
+/// The migration at index `i` upgrades a workout from version `i` to version `i + 1`.
+const MIGRATIONS: [Migration; SCHEMA_VERSION as usize] = [workout_v0_to_v1];
+
+/// Version 0 is the unversioned notation, which called the preparation `warmup_s` in the
+/// default workout and did not identify the workouts.
+fn workout_v0_to_v1(workout: &mut json::JsonValue) {
+    if workout["prepare_s"].is_null() && !workout["warmup_s"].is_null() {
+        workout["prepare_s"] = workout.remove("warmup_s");
+    }
+    if workout["revision"].is_null() {
+        workout["revision"] = 0.into();
+    }
+}
+
+/// Upgrades a workout in JSON notation to the current version.
+/// Workouts written by a newer version are left as they are, their known fields are still read.
+pub fn upgrade_workout(workout: &mut json::JsonValue) {
+    let version = workout["version"].as_u32().unwrap_or(0) as usize;
+    if version >= MIGRATIONS.len() {
+        return;
+    }
+    for migration in &MIGRATIONS[version..] {
+        migration(workout);
+    }
+    workout["version"] = SCHEMA_VERSION.into();
+}
+
+impl Workout {
+    /// Reads a workout of any version, or `None` if one of the mandatory fields
+    /// `name`, `sets`, `exercise_s` and `rest_s` is missing.
+    pub fn from_json(value: &json::JsonValue) -> Option<Workout> {
+        let mut value = value.clone();
+        upgrade_workout(&mut value);
+        Some(Workout {
+            version: value["version"].as_u32().unwrap_or(SCHEMA_VERSION),
+            id: value["id"].as_str().map(String::from),
+            revision: value["revision"].as_u64().unwrap_or(0),
+            name: value["name"].as_str()?.to_owned(),
+            sets: value["sets"].as_usize()?,
+            exercise_s: value["exercise_s"].as_usize()?,
+            rest_s: value["rest_s"].as_usize()?,
+            prepare_s: value["prepare_s"].as_usize().unwrap_or(DEFAULT_PREPARE_S),
+            pinned: value["pinned"].as_bool().unwrap_or(false),
+            progression: value["progression"].as_str().map(String::from),
+            mode: value["mode"].as_str().map(String::from),
+            exercise_intensity: value["exercise_intensity"].as_str().map(String::from),
+            rest_intensity: value["rest_intensity"].as_str().map(String::from),
+            images: value["images"]
+                .members()
+                .filter_map(|image| image.as_str().map(String::from))
+                .collect(),
+            warmup: WarmupStep::list_from_json(&value["warmup"]),
+            default_warmup: value["default_warmup"].as_bool().unwrap_or(false),
+        })
+    }
+
+    pub fn parse(raw_json: &str) -> Option<Workout> {
+        Workout::from_json(&json::parse(raw_json).ok()?)
+    }
+
+    /// Writes the workout in the current version of the notation.
+    pub fn to_json(&self) -> json::JsonValue {
+        let mut value = json::object! {
+            version: SCHEMA_VERSION,
+            revision: self.revision,
+            name: self.name.clone(),
+            sets: self.sets,
+            exercise_s: self.exercise_s,
+            rest_s: self.rest_s,
+            prepare_s: self.prepare_s,
+            pinned: self.pinned,
+        };
+        if let Some(id) = &self.id {
+            value["id"] = id.clone().into();
+        }
+        if let Some(progression) = &self.progression {
+            value["progression"] = progression.clone().into();
+        }
+        if let Some(mode) = &self.mode {
+            value["mode"] = mode.clone().into();
+        }
+        if let Some(intensity) = &self.exercise_intensity {
+            value["exercise_intensity"] = intensity.clone().into();
+        }
+        if let Some(intensity) = &self.rest_intensity {
+            value["rest_intensity"] = intensity.clone().into();
+        }
+        if !self.images.is_empty() {
+            value["images"] = self.images.clone().into();
+        }
+        if !self.warmup.is_empty() {
+            value["warmup"] = WarmupStep::list_to_json(&self.warmup);
+        }
+        if self.default_warmup {
+            value["default_warmup"] = true.into();
+        }
+        value
+    }
+}
+
+impl WorkoutList {
+    /// Reads a list, or `None` if it or one of its workouts is invalid.
+    pub fn from_json(value: &json::JsonValue) -> Option<WorkoutList> {
+        if !value["trainings"].is_array() {
+            return None;
+        }
+        Some(WorkoutList {
+            modified: value["modified"].as_i64()?,
+            trainings: value["trainings"]
+                .members()
+                .map(Workout::from_json)
+                .collect::<Option<Vec<Workout>>>()?,
+        })
+    }
+
+    pub fn parse(raw_json: &str) -> Option<WorkoutList> {
+        WorkoutList::from_json(&json::parse(raw_json).ok()?)
+    }
+
+    pub fn to_json(&self) -> json::JsonValue {
+        let trainings: Vec<json::JsonValue> = self.trainings.iter().map(Workout::to_json).collect();
+        json::object! {
+            version: SCHEMA_VERSION,
+            modified: self.modified,
+            trainings: trainings,
+        }
+    }
+}
+
+/// A round of a session as it went.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SessionRound {
+    pub exercise_s: usize,
+    pub rest_s: usize,
+    /// The round was skipped before its exercise was over.
+    pub skipped: bool,
+}
+
+/// A period of a session as it went, e.g. for replaying it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SessionPeriod {
+    /// `preparation`, `exercise` or `rest`.
+    pub phase: String,
+    /// The time the timer ran in the period, including the time which was rewound.
+    pub length_s: usize,
+    pub skipped: bool,
+    /// How hard the period was meant to be, with the values of `Workout::exercise_intensity`.
+    pub intensity: Option<String>,
+}
+
+/// Something the user did during a session.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SessionMark {
+    /// The running time since the start of the session, without the pauses.
+    pub at_s: usize,
+    /// `pause`, or `rewind` when a period was started over.
+    pub kind: String,
+    /// The length of the pause, or the time which the rewind added.
+    pub duration_s: usize,
+}
+
+/// A reading of the heart rate monitor during a session.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct HeartRateSample {
+    /// The running time since the start of the session, without the pauses.
+    pub at_s: usize,
+    pub bpm: u32,
+}
+
+/// A session of the history, as it is backed up and synchronized.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(try_from = "raw_json::RawJson")
+)]
+pub struct Session {
+    /// The `id` of the workout. Together with `started`, it identifies the session.
+    pub training_id: String,
+    pub training_name: String,
+    /// The start, in seconds since the Unix epoch.
+    pub started: i64,
+    /// The time with the timer running, in seconds.
+    pub duration_s: usize,
+    /// The time with the timer paused, in seconds.
+    pub pause_s: usize,
+    /// The number of sets of the workout.
+    pub sets: usize,
+    /// The workout was carried out to the end, rather than aborted.
+    pub completed: bool,
+    pub rounds: Vec<SessionRound>,
+    pub periods: Vec<SessionPeriod>,
+    pub marks: Vec<SessionMark>,
+    /// The rate of perceived exertion from 1 to 10.
+    pub rpe: Option<u8>,
+    pub note: String,
+    /// The estimated burned kilocalories.
+    pub kcal: Option<u32>,
+    /// The drop of the heart rate in the minute after the workout.
+    pub hr_recovery_bpm: Option<u32>,
+    /// The readings of the heart rate monitor, at most one per second.
+    pub heart_rate: Vec<HeartRateSample>,
+}
+
+impl Session {
+    /// Reads a session, or `None` if one of the mandatory fields `training_id`,
+    /// `training_name`, `started` and `duration_s` is missing.
+    pub fn from_json(value: &json::JsonValue) -> Option<Session> {
+        Some(Session {
+            training_id: value["training_id"].as_str()?.into(),
+            training_name: value["training_name"].as_str()?.into(),
+            started: value["started"].as_i64()?,
+            duration_s: value["duration_s"].as_usize()?,
+            pause_s: value["pause_s"].as_usize().unwrap_or(0),
+            sets: value["sets"].as_usize().unwrap_or(value["rounds"].len()),
+            // Sessions were only recorded when completed before this was stored
+            completed: value["completed"].as_bool().unwrap_or(true),
+            rounds: value["rounds"]
+                .members()
+                .map(|round| SessionRound {
+                    exercise_s: round["exercise_s"].as_usize().unwrap_or(0),
+                    rest_s: round["rest_s"].as_usize().unwrap_or(0),
+                    skipped: round["skipped"].as_bool().unwrap_or(false),
+                })
+                .collect(),
+            periods: value["periods"]
+                .members()
+                .filter_map(|period| {
+                    Some(SessionPeriod {
+                        phase: period["phase"].as_str()?.into(),
+                        length_s: period["length_s"].as_usize()?,
+                        skipped: period["skipped"].as_bool().unwrap_or(false),
+                        intensity: period["intensity"].as_str().map(String::from),
+                    })
+                })
+                .collect(),
+            marks: value["marks"]
+                .members()
+                .filter_map(|mark| {
+                    Some(SessionMark {
+                        at_s: mark["at_s"].as_usize()?,
+                        kind: mark["kind"].as_str()?.into(),
+                        duration_s: mark["duration_s"].as_usize()?,
+                    })
+                })
+                .collect(),
+            rpe: value["rpe"].as_u8(),
+            note: value["note"].as_str().unwrap_or_default().into(),
+            kcal: value["kcal"].as_u32(),
+            hr_recovery_bpm: value["hr_recovery_bpm"].as_u32(),
+            heart_rate: value["heart_rate"]
+                .members()
+                .filter_map(|sample| {
+                    Some(HeartRateSample {
+                        at_s: sample["at_s"].as_usize()?,
+                        bpm: sample["bpm"].as_u32()?,
+                    })
+                })
+                .collect(),
+        })
+    }
+
+    pub fn parse(raw_json: &str) -> Option<Session> {
+        Session::from_json(&json::parse(raw_json).ok()?)
+    }
+
+    pub fn to_json(&self) -> json::JsonValue {
+        let rounds: Vec<json::JsonValue> = self
+            .rounds
+            .iter()
+            .map(|round| {
+                json::object! {
+                    exercise_s: round.exercise_s,
+                    rest_s: round.rest_s,
+                    skipped: round.skipped,
+                }
+            })
+            .collect();
+        let periods: Vec<json::JsonValue> = self
+            .periods
+            .iter()
+            .map(|period| {
+                json::object! {
+                    phase: period.phase.clone(),
+                    length_s: period.length_s,
+                    skipped: period.skipped,
+                    intensity: period.intensity.clone(),
+                }
+            })
+            .collect();
+        let marks: Vec<json::JsonValue> = self
+            .marks
+            .iter()
+            .map(|mark| {
+                json::object! {
+                    at_s: mark.at_s,
+                    kind: mark.kind.clone(),
+                    duration_s: mark.duration_s,
+                }
+            })
+            .collect();
+        let heart_rate: Vec<json::JsonValue> = self
+            .heart_rate
+            .iter()
+            .map(|sample| {
+                json::object! {
+                    at_s: sample.at_s,
+                    bpm: sample.bpm,
+                }
+            })
+            .collect();
+        json::object! {
+            training_id: self.training_id.clone(),
+            training_name: self.training_name.clone(),
+            started: self.started,
+            duration_s: self.duration_s,
+            pause_s: self.pause_s,
+            sets: self.sets,
+            completed: self.completed,
+            rounds: rounds,
+            periods: periods,
+            marks: marks,
+            rpe: self.rpe,
+            note: self.note.clone(),
+            kcal: self.kcal,
+            hr_recovery_bpm: self.hr_recovery_bpm,
+            heart_rate: heart_rate,
+        }
+    }
+}
@@ -0,0 +1,98 @@
+//! Reading with serde goes through the JSON notation, so that it upgrades earlier versions
+//! and fills in the missing fields the same way as `from_json`.
+
+use crate::{Session, Workout, WorkoutList};
+use serde::de::{Deserialize, Deserializer, MapAccess, SeqAccess, Visitor};
+use std::fmt;
+
+/// A value of any self-describing format, e.g. JSON or TOML, in JSON notation.
+pub struct RawJson(json::JsonValue);
+
+struct RawJsonVisitor;
+
+impl<'de> Visitor<'de> for RawJsonVisitor {
+    type Value = RawJson;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a value in JSON notation")
+    }
+
+    fn visit_bool<E>(self, value: bool) -> Result<RawJson, E> {
+        Ok(RawJson(value.into()))
+    }
+
+    fn visit_i64<E>(self, value: i64) -> Result<RawJson, E> {
+        Ok(RawJson(value.into()))
+    }
+
+    fn visit_u64<E>(self, value: u64) -> Result<RawJson, E> {
+        Ok(RawJson(value.into()))
+    }
+
+    fn visit_f64<E>(self, value: f64) -> Result<RawJson, E> {
+        Ok(RawJson(value.into()))
+    }
+
+    fn visit_str<E>(self, value: &str) -> Result<RawJson, E> {
+        Ok(RawJson(value.into()))
+    }
+
+    fn visit_unit<E>(self) -> Result<RawJson, E> {
+        Ok(RawJson(json::JsonValue::Null))
+    }
+
+    fn visit_none<E>(self) -> Result<RawJson, E> {
+        Ok(RawJson(json::JsonValue::Null))
+    }
+
+    fn visit_some<D: Deserializer<'de>>(self, deserializer: D) -> Result<RawJson, D::Error> {
+        RawJson::deserialize(deserializer)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<RawJson, A::Error> {
+        let mut values = Vec::new();
+        while let Some(RawJson(value)) = seq.next_element()? {
+            values.push(value);
+        }
+        Ok(RawJson(json::JsonValue::Array(values)))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<RawJson, A::Error> {
+        let mut object = json::JsonValue::new_object();
+        while let Some((key, RawJson(value))) = map.next_entry::<String, RawJson>()? {
+            object[key] = value;
+        }
+        Ok(RawJson(object))
+    }
+}
+
+impl<'de> Deserialize<'de> for RawJson {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(RawJsonVisitor)
+    }
+}
+
+impl TryFrom<RawJson> for Workout {
+    type Error = &'static str;
+
+    fn try_from(RawJson(value): RawJson) -> Result<Self, Self::Error> {
+        Workout::from_json(&value).ok_or("a workout needs a name, sets, exercise_s and rest_s")
+    }
+}
+
+impl TryFrom<RawJson> for WorkoutList {
+    type Error = &'static str;
+
+    fn try_from(RawJson(value): RawJson) -> Result<Self, Self::Error> {
+        WorkoutList::from_json(&value).ok_or("a list needs modified and valid trainings")
+    }
+}
+
+impl TryFrom<RawJson> for Session {
+    type Error = &'static str;
+
+    fn try_from(RawJson(value): RawJson) -> Result<Self, Self::Error> {
+        Session::from_json(&value)
+            .ok_or("a session needs a training_id, training_name, started and duration_s")
+    }
+}
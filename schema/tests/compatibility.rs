@@ -0,0 +1,112 @@
+//! The workouts written by earlier versions of Exercise Timer are still read.
+
+use hiit_schema::{
+    upgrade_workout, Session, WarmupStep, Workout, WorkoutList, DEFAULT_PREPARE_S, SCHEMA_VERSION,
+};
+
+#[test]
+fn reads_unversioned_workout() {
+    let workout = Workout::parse(include_str!("fixtures/v0_default_workout.json")).unwrap();
+    assert_eq!(workout.version, SCHEMA_VERSION);
+    assert_eq!(workout.id, None);
+    assert_eq!(workout.revision, 0);
+    assert_eq!(workout.name, "Default");
+    assert_eq!(
+        (workout.sets, workout.exercise_s, workout.rest_s),
+        (8, 20, 10)
+    );
+    assert_eq!(workout.prepare_s, 15);
+    assert!(!workout.pinned);
+    assert!(workout.warmup.is_empty());
+}
+
+#[test]
+fn reads_unversioned_training_list() {
+    let list = WorkoutList::parse(include_str!("fixtures/v0_training_list.json")).unwrap();
+    assert_eq!(list.modified, 1700000000);
+    assert_eq!(list.trainings.len(), 2);
+    assert_eq!(list.trainings[0].prepare_s, 10);
+    assert_eq!(list.trainings[1].prepare_s, DEFAULT_PREPARE_S);
+    assert!(list.trainings.iter().all(|workout| workout.id.is_none()));
+}
+
+#[test]
+fn prefers_prepare_s_over_warmup_s() {
+    let mut value = json::object! {
+        name: "Both",
+        sets: 1,
+        exercise_s: 1,
+        rest_s: 1,
+        prepare_s: 7,
+        warmup_s: 15,
+    };
+    upgrade_workout(&mut value);
+    assert_eq!(value["prepare_s"].as_usize(), Some(7));
+    assert_eq!(value["version"].as_u32(), Some(SCHEMA_VERSION));
+}
+
+#[test]
+fn rejects_workout_without_mandatory_field() {
+    assert_eq!(
+        Workout::parse(r#"{"name": "No Rest", "sets": 8, "exercise_s": 20}"#),
+        None
+    );
+    // A single invalid workout makes the whole list invalid, rather than losing it silently
+    assert_eq!(
+        WorkoutList::parse(
+            r#"{"modified": 0, "trainings": [{"name": "No Sets", "exercise_s": 20, "rest_s": 10}]}"#
+        ),
+        None
+    );
+}
+
+#[test]
+fn reads_known_fields_of_newer_version() {
+    let workout = Workout::parse(
+        r#"{"version": 99, "name": "Future", "sets": 4, "exercise_s": 40, "rest_s": 20, "prepare_s": 0, "tempo": "3-1-1"}"#,
+    )
+    .unwrap();
+    assert_eq!(workout.version, 99);
+    assert_eq!(workout.name, "Future");
+    assert_eq!(workout.prepare_s, 0);
+}
+
+#[test]
+fn round_trips_workout() {
+    let workout = Workout::parse(include_str!("fixtures/v1_workout.json")).unwrap();
+    assert_eq!(
+        workout.warmup,
+        vec![
+            WarmupStep {
+                name: "Jumping Jacks".into(),
+                duration_s: 30,
+            },
+            WarmupStep {
+                name: "Arm Circles".into(),
+                duration_s: 20,
+            },
+        ]
+    );
+    assert_eq!(Workout::from_json(&workout.to_json()), Some(workout));
+}
+
+#[test]
+fn round_trips_upgraded_training_list() {
+    let list = WorkoutList::parse(include_str!("fixtures/v0_training_list.json")).unwrap();
+    let written = list.to_json();
+    assert_eq!(written["version"].as_u32(), Some(SCHEMA_VERSION));
+    assert_eq!(WorkoutList::from_json(&written), Some(list));
+}
+
+#[test]
+fn reads_session_of_earlier_version() {
+    let session = Session::parse(include_str!("fixtures/v0_session.json")).unwrap();
+    assert_eq!(session.started, 1718000000);
+    // Only completed sessions were recorded before this was stored
+    assert!(session.completed);
+    assert_eq!(session.sets, 2);
+    assert_eq!(session.pause_s, 0);
+    assert!(session.rounds[1].skipped);
+    assert!(session.periods.is_empty());
+    assert_eq!(Session::from_json(&session.to_json()), Some(session));
+}
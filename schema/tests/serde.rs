@@ -0,0 +1,54 @@
+//! Reading and writing with serde behaves the same as `from_json` and `to_json`.
+#![cfg(feature = "serde")]
+
+use hiit_schema::{Session, Workout, WorkoutList, DEFAULT_PREPARE_S, SCHEMA_VERSION};
+
+#[test]
+fn upgrades_unversioned_workout() {
+    let raw_json = include_str!("fixtures/v0_default_workout.json");
+    let workout: Workout = serde_json::from_str(raw_json).unwrap();
+    assert_eq!(Some(&workout), Workout::parse(raw_json).as_ref());
+    assert_eq!(workout.version, SCHEMA_VERSION);
+    assert_eq!(workout.prepare_s, 15);
+}
+
+#[test]
+fn upgrades_unversioned_training_list() {
+    let raw_json = include_str!("fixtures/v0_training_list.json");
+    let list: WorkoutList = serde_json::from_str(raw_json).unwrap();
+    assert_eq!(Some(&list), WorkoutList::parse(raw_json).as_ref());
+    assert_eq!(list.trainings[1].prepare_s, DEFAULT_PREPARE_S);
+}
+
+#[test]
+fn rejects_workout_without_mandatory_field() {
+    assert!(
+        serde_json::from_str::<Workout>(r#"{"name": "No Rest", "sets": 8, "exercise_s": 20}"#)
+            .is_err()
+    );
+}
+
+#[test]
+fn writes_workout_like_to_json() {
+    let workout = Workout::parse(include_str!("fixtures/v0_default_workout.json")).unwrap();
+    assert_eq!(workout.id, None);
+    let written = serde_json::to_string(&workout).unwrap();
+    assert!(!written.contains("\"id\""));
+    assert_eq!(json::parse(&written).unwrap(), workout.to_json());
+}
+
+#[test]
+fn round_trips_workout() {
+    let workout = Workout::parse(include_str!("fixtures/v1_workout.json")).unwrap();
+    let written = serde_json::to_string(&workout).unwrap();
+    assert_eq!(json::parse(&written).unwrap(), workout.to_json());
+    assert_eq!(serde_json::from_str::<Workout>(&written).unwrap(), workout);
+}
+
+#[test]
+fn round_trips_session() {
+    let session = Session::parse(include_str!("fixtures/v0_session.json")).unwrap();
+    let written = serde_json::to_string(&session).unwrap();
+    assert_eq!(json::parse(&written).unwrap(), session.to_json());
+    assert_eq!(serde_json::from_str::<Session>(&written).unwrap(), session);
+}
@@ -0,0 +1,186 @@
+use crate::history::SessionRecord;
+use crate::statistics;
+use gettextrs::{gettext, ngettext};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Criterion {
+    /// The number of completed workouts.
+    Workouts(usize),
+    /// The number of completed Tabatas: 8 rounds of 20 s exercise and 10 s rest.
+    Tabatas(usize),
+    /// The number of active days of a streak.
+    Streak(usize),
+    /// The total hours of training.
+    Hours(usize),
+}
+
+const CRITERIA: [Criterion; 9] = [
+    Criterion::Workouts(1),
+    Criterion::Workouts(10),
+    Criterion::Workouts(100),
+    Criterion::Tabatas(10),
+    Criterion::Streak(7),
+    Criterion::Streak(30),
+    Criterion::Hours(1),
+    Criterion::Hours(10),
+    Criterion::Hours(100),
+];
+
+#[derive(Debug, Clone)]
+pub struct Achievement {
+    pub title: String,
+    pub description: String,
+    /// The start of the session which earned the achievement, in seconds since the Unix epoch.
+    pub earned: Option<i64>,
+}
+
+fn title(criterion: Criterion) -> String {
+    match criterion {
+        // Translators: The title of the achievement for the first completed workout
+        Criterion::Workouts(1) => gettext("First Workout"),
+        Criterion::Workouts(count) => {
+            if false {
+                // Translators: The title of the achievement for a number of completed workouts, e.g. "10 Workouts"
+                gettext("{} Workouts")
+            } else {
+                gettext!("{} Workouts", count)
+            }
+        }
+        Criterion::Tabatas(count) => {
+            if false {
+                // Translators: The title of the achievement for a number of completed Tabatas, e.g. "10 Tabatas"
+                gettext("{} Tabatas")
+            } else {
+                gettext!("{} Tabatas", count)
+            }
+        }
+        Criterion::Streak(days) => {
+            if false {
+                // Translators: The title of the achievement for a streak of workout days, e.g. "30-Day Streak"
+                gettext("{}-Day Streak")
+            } else {
+                gettext!("{}-Day Streak", days)
+            }
+        }
+        Criterion::Hours(hours) => {
+            if false {
+                // Translators: The title of the achievement for the total time of training, e.g. "10 Hours"
+                ngettext("{} Hour", "{} Hours", hours as u32)
+            } else {
+                ngettext!("{} Hour", "{} Hours", hours as u32, hours)
+            }
+        }
+    }
+}
+
+fn description(criterion: Criterion) -> String {
+    match criterion {
+        Criterion::Workouts(count) => {
+            if false {
+                // Translators: How to earn the achievement for a number of completed workouts
+                ngettext("Complete a workout", "Complete {} workouts", count as u32)
+            } else {
+                ngettext!(
+                    "Complete a workout",
+                    "Complete {} workouts",
+                    count as u32,
+                    count
+                )
+            }
+        }
+        Criterion::Tabatas(count) => {
+            if false {
+                // Translators: How to earn the achievement for a number of completed Tabatas
+                gettext(
+                    "Complete {} workouts of 8 rounds of 20 seconds exercise and 10 seconds rest",
+                )
+            } else {
+                gettext!(
+                    "Complete {} workouts of 8 rounds of 20 seconds exercise and 10 seconds rest",
+                    count
+                )
+            }
+        }
+        Criterion::Streak(days) => {
+            if false {
+                // Translators: How to earn the achievement for a streak of workout days
+                gettext("Work out on {} days in a row")
+            } else {
+                gettext!("Work out on {} days in a row", days)
+            }
+        }
+        Criterion::Hours(hours) => {
+            if false {
+                // Translators: How to earn the achievement for the total time of training
+                ngettext(
+                    "Train for an hour in total",
+                    "Train for {} hours in total",
+                    hours as u32,
+                )
+            } else {
+                ngettext!(
+                    "Train for an hour in total",
+                    "Train for {} hours in total",
+                    hours as u32,
+                    hours
+                )
+            }
+        }
+    }
+}
+
+fn is_tabata(session: &SessionRecord) -> bool {
+    statistics::is_workout(session)
+        && session.rounds.len() == 8
+        && session.rounds.iter().all(|round| round.exercise_s == 20)
+        && session.rounds[..7].iter().all(|round| round.rest_s == 10)
+}
+
+/// Replays the sessions in chronological order and returns all achievements,
+/// with the session which earned each of them.
+pub fn achievements(sessions: &[SessionRecord], rest_days: u32) -> Vec<Achievement> {
+    let mut sorted: Vec<&SessionRecord> = sessions.iter().collect();
+    sorted.sort_by_key(|session| session.started);
+    let max_gap = rest_days as i64 + 1;
+    let mut earned: Vec<Option<i64>> = vec![None; CRITERIA.len()];
+    let (mut workouts, mut tabatas, mut total_s) = (0, 0, 0);
+    let mut streak = 0;
+    let mut previous_day: Option<i64> = None;
+    for session in sorted {
+        total_s += session.duration_s;
+        // As in the statistics, only workouts count for the streak
+        if statistics::is_workout(session) {
+            workouts += 1;
+            let day = statistics::local_day(session.started);
+            streak = match previous_day {
+                Some(previous_day) if previous_day == day => streak,
+                Some(previous_day) if day - previous_day <= max_gap => streak + 1,
+                _ => 1,
+            };
+            previous_day = Some(day);
+        }
+        if is_tabata(session) {
+            tabatas += 1;
+        }
+        for (criterion, earned) in CRITERIA.iter().zip(earned.iter_mut()) {
+            let is_met = match *criterion {
+                Criterion::Workouts(count) => workouts >= count,
+                Criterion::Tabatas(count) => tabatas >= count,
+                Criterion::Streak(days) => streak >= days,
+                Criterion::Hours(hours) => total_s >= hours * 60 * 60,
+            };
+            if is_met && earned.is_none() {
+                *earned = Some(session.started);
+            }
+        }
+    }
+    CRITERIA
+        .iter()
+        .zip(earned)
+        .map(|(criterion, earned)| Achievement {
+            title: title(*criterion),
+            description: description(*criterion),
+            earned,
+        })
+        .collect()
+}
@@ -1,12 +1,34 @@
+use crate::achievements;
+use crate::app_state;
+use crate::background;
+use crate::backup;
 use crate::config;
+use crate::crash::{self, Recovery};
+use crate::debug_window;
+use crate::deep_link::{self, DeepLink};
+use crate::error::AppError;
+use crate::hiit_core::TimerPosition;
+use crate::history;
+use crate::importers;
+use crate::intensity::INTENSITY_CSS_CLASSES;
+use crate::preferences_dialog::*;
+use crate::profiles;
 use crate::settings;
 use crate::shortcuts_window::*;
+use crate::snapshot_dialog::*;
+use crate::statistics;
+use crate::statistics_page::{self, StatisticsPage};
+use crate::storage;
+use crate::sync;
 use crate::training_editor::*;
+use crate::training_list::TrainingList;
 use crate::training_setup::*;
 use crate::training_timer::*;
+use crate::trophies_dialog::*;
 use futures::prelude::*;
-use gettextrs::gettext;
+use gettextrs::{gettext, ngettext};
 use relm4::actions::AccelsPlus;
+use relm4::binding::Binding;
 use relm4::{
     self,
     adw::{self, prelude::*},
@@ -16,15 +38,86 @@ use relm4::{
 };
 use relm4_icons::icon_names;
 
+/// Delay before the training list is written to the settings, so that bursts of changes are saved at once.
+const SAVE_DEBOUNCE: std::time::Duration = std::time::Duration::from_millis(500);
+
+/// The width below which the timer is laid out for phones.
+const NARROW_WIDTH_SP: f64 = 400.0;
+/// The height below which the timer is laid out for phones, which keeps the controls in view.
+const SHORT_HEIGHT_SP: f64 = 480.0;
+
+pub static APP_BROKER: relm4::MessageBroker<AppModelInput> = relm4::MessageBroker::new();
+
 #[derive(Debug)]
 pub enum AppModelInput {
     PromptNewTraining,
+    PromptImportTraining(TrainingSetup),
+    OpenUri(String),
+    OpenFile(gtk::gio::File),
     CreateTrainingSetup(TrainingSetup),
-    RemoveTrainingSetup(DynamicIndex),
+    EditTrainingSetup(TrainingSetup),
+    UpdateTrainingSetup(TrainingSetup),
+    RemoveTrainingSetup(String),
+    UndoRemoveTrainingSetup,
+    PinTrainingSetup(String),
+    /// Tells about a failure, with a button which sends `retry` if it can be tried again.
+    ShowError {
+        error: AppError,
+        retry: Option<Box<AppModelInput>>,
+    },
     LoadTraining(TrainingSetup),
+    /// Shows the training paused, e.g. the last one after the launch.
+    ShowTraining(TrainingSetup),
+    /// Shows the training which was interrupted by a crash, paused where it stopped.
+    RestoreSession(TrainingSetup, TimerPosition),
+    RecoverTrainingList(storage::StoredTrainingList),
+    TimerPeriod(&'static str, Option<&'static str>),
+    TimerProgress(Option<String>, bool),
+    ReturnToTimer,
+    /// The finished training with the id was dismissed, the training list is shown again.
+    TimerDone(String),
+    /// Starts the harder training which was suggested after the last one.
+    StartProgression,
+    TimerPageHidden,
+    /// The window became active or inactive.
+    WindowActive,
+    StartQuickTimer,
+    RepeatLastWorkout,
     Popped,
+    ShowStatistics,
+    ShowTrophies,
     StartStop,
+    Skip,
+    SetBigDigits(bool),
+    SetNarrow(bool),
+    SetTvMode(bool),
+    ToggleMiniTimer,
+    StartSearch,
+    FilterTrainings(String),
     Reset,
+    TrainingListChanged,
+    SaveTrainingList(u64),
+    RestoreTrainingListBackup,
+    DiscardDamagedTrainingList,
+    ExportBackup,
+    ExportHistory {
+        rounds: bool,
+    },
+    ImportBackup,
+    PromptImportFile,
+    ImportTrainings(Vec<TrainingSetup>),
+    BackupRestored(storage::StoredTrainingList),
+    PromptRestoreSnapshot,
+    SwitchProfile(String),
+    PromptNewProfile,
+    CreateProfile(String),
+    Synchronize,
+    SyncFinished(sync::SyncResult, i64),
+    PreferencesClosed,
+    NewWindow,
+    ShowDebugWindow,
+    /// Another window saved the training list.
+    ReloadTrainingList,
 }
 
 relm4::new_action_group!(WindowActionGroup, "win");
@@ -32,19 +125,70 @@ relm4::new_stateless_action!(ShortcutsAction, WindowActionGroup, "show-help-over
 relm4::new_stateless_action!(AboutAction, WindowActionGroup, "about");
 relm4::new_stateless_action!(StartStopAction, WindowActionGroup, "start-stop");
 relm4::new_stateless_action!(ResetAction, WindowActionGroup, "reset");
+relm4::new_stateless_action!(SkipAction, WindowActionGroup, "skip");
+relm4::new_stateless_action!(FullscreenAction, WindowActionGroup, "fullscreen");
+relm4::new_stateless_action!(MiniTimerAction, WindowActionGroup, "mini-timer");
+relm4::new_stateful_action!(BigDigitsAction, WindowActionGroup, "big-digits", (), bool);
+relm4::new_stateful_action!(TvModeAction, WindowActionGroup, "tv-mode", (), bool);
+relm4::new_stateless_action!(NewTrainingAction, WindowActionGroup, "new-training");
+relm4::new_stateless_action!(SearchAction, WindowActionGroup, "search");
+relm4::new_stateless_action!(PreferencesAction, WindowActionGroup, "preferences");
+relm4::new_stateless_action!(SynchronizeAction, WindowActionGroup, "synchronize");
+relm4::new_stateless_action!(ImportTrainingAction, WindowActionGroup, "import-training");
+relm4::new_stateless_action!(ExportBackupAction, WindowActionGroup, "export-backup");
+relm4::new_stateless_action!(ImportBackupAction, WindowActionGroup, "import-backup");
+relm4::new_stateful_action!(ProfileAction, WindowActionGroup, "profile", String, String);
+relm4::new_stateless_action!(NewProfileAction, WindowActionGroup, "new-profile");
+relm4::new_stateless_action!(StatisticsAction, WindowActionGroup, "statistics");
+relm4::new_stateless_action!(ExportHistoryAction, WindowActionGroup, "export-history");
+relm4::new_stateless_action!(ExportRoundsAction, WindowActionGroup, "export-rounds");
+relm4::new_stateless_action!(TrophiesAction, WindowActionGroup, "trophies");
+relm4::new_stateless_action!(RestoreSnapshotAction, WindowActionGroup, "restore-snapshot");
+relm4::new_stateless_action!(NewWindowAction, WindowActionGroup, "new-window");
+relm4::new_stateless_action!(DebugWindowAction, WindowActionGroup, "debug-window");
 
 pub struct AppModel {
     training_timer: Option<Controller<TrainingTimer>>,
-    list_trainings: relm4::factory::FactoryVecDeque<TrainingSetup>,
-    output_stream: rodio::OutputStreamHandle,
+    /// The training is running rather than paused or finished.
+    timer_running: bool,
+    /// The harder training which is suggested in the banner of the training list.
+    suggested_progression: Option<TrainingSetup>,
+    statistics_page: Option<Controller<StatisticsPage>>,
+    list_trainings: TrainingList,
+    /// The id of the window, which is notified of changes to the training list by the other windows.
+    window_id: usize,
+    output_stream: Option<rodio::OutputStreamHandle>,
     window_geometry: settings::WindowGeometry,
     global_settings: settings::GlobalTrainingSetup,
+    training_defaults: settings::TrainingDefaults,
+    appearance: settings::Appearance,
     shortcuts_window: Controller<ShortcutsWindowModel>,
+    sync_settings: settings::SyncSettings,
+    reminder_settings: settings::ReminderSettings,
+    preferences_dialog: Controller<PreferencesDialog>,
+    profiles_menu: gtk::gio::Menu,
+    list_modified: i64,
+    save_generation: u64,
+    /// The trainings as they were last loaded or saved, the common ancestor for merging the
+    /// changes of this window with those which another window saved meanwhile.
+    stored_trainings: Vec<TrainingSetup>,
+    /// The list was changed since it was last saved.
+    unsaved_changes: bool,
+    /// The stored training list could not be loaded and must not be overwritten until the user decides what to do.
+    list_damaged: bool,
+    /// Achievements earned by sessions started before this time were already announced.
+    achievements_announced: i64,
+    /// The last deleted training and its position, until the deletion is undone.
+    removed_training: Option<(usize, TrainingSetup)>,
+    /// The window is as narrow as a phone in portrait orientation.
+    narrow: bool,
+    /// The timer is shown for a group on a big screen.
+    tv_mode: bool,
 }
 
 #[relm4::component(pub)]
 impl Component for AppModel {
-    type Init = rodio::OutputStreamHandle;
+    type Init = ();
     type Input = AppModelInput;
     type Output = ();
     type CommandOutput = ();
@@ -52,6 +196,34 @@ impl Component for AppModel {
     menu! {
         primary_menu: {
             section! {
+                // Translators: The title of the menu entry which opens another window, e.g. to train with a partner
+                &gettext("New _Window") => NewWindowAction,
+            },
+            section! {
+                // Translators: The title of the menu entry which synchronizes the training list with the remote folder
+                &gettext("_Synchronize Now") => SynchronizeAction,
+            },
+            section! {
+                // Translators: The title of the menu entry which shows the achievements
+                &gettext("_Trophies") => TrophiesAction,
+            },
+            section! {
+                // Translators: The title of the menu entry which imports a training from a file
+                &gettext("Import _Training…") => ImportTrainingAction,
+                // Translators: The title of the menu entry which saves the trainings, the preferences and the history to a file
+                &gettext("_Export All Data…") => ExportBackupAction,
+                // Translators: The title of the menu entry which saves the recorded sessions to a CSV file
+                &gettext("Export _History as CSV…") => ExportHistoryAction,
+                // Translators: The title of the menu entry which saves the rounds of the recorded sessions to a CSV file
+                &gettext("Export R_ounds as CSV…") => ExportRoundsAction,
+                // Translators: The title of the menu entry which restores the trainings, the preferences and the history from a file
+                &gettext("_Import Backup…") => ImportBackupAction,
+                // Translators: The title of the menu entry which lists the automatic snapshots of the data
+                &gettext("Restore from _Snapshot…") => RestoreSnapshotAction,
+            },
+            section! {
+                // Translators: The title of the preferences menu entry
+                &gettext("_Preferences") => PreferencesAction,
                 // Translators: The title of the keyboard shortcuts menu entry
                 &gettext("_Keyboard Shortcuts") => ShortcutsAction,
                 // Translators: The title of the about dialog menu entry
@@ -67,20 +239,42 @@ impl Component for AppModel {
             add_binding: (&model.window_geometry.width, "default_width"),
             add_binding: (&model.window_geometry.height, "default_height"),
             add_binding: (&model.window_geometry.is_maximized, "maximized"),
+            #[name = "toast_overlay"]
+            adw::ToastOverlay {
             #[name = "navigation_view"]
             adw::NavigationView {
                 add = &adw::NavigationPage {
+                    set_tag: Some("training-list"),
                     // Translators: This is the title of the page which lists all trainings
                     set_title: &gettext("Training List"),
                     #[wrap(Some)]
                     set_child = &adw::ToolbarView {
                         add_top_bar = &adw::HeaderBar {
+                            #[wrap(Some)]
+                            #[name = "training_list_title"]
+                            set_title_widget = &adw::WindowTitle {
+                                // Translators: This is the title of the page which lists all trainings
+                                set_title: &gettext("Training List"),
+                            },
                             pack_start = &gtk::Button {
                                 set_icon_name: "list-add",
-                                connect_clicked => AppModelInput::PromptNewTraining,
+                                set_action_name: Some("win.new-training"),
                                 // Translators: tooltip for the add training image button
                                 set_tooltip: &gettext("Add Training"),
                             },
+                            #[name = "training_search_button"]
+                            pack_start = &gtk::ToggleButton {
+                                set_icon_name: "system-search-symbolic",
+                                // Translators: tooltip for the button which searches the training list
+                                set_tooltip: &gettext("Search Trainings"),
+                            },
+                            pack_end = &gtk::Button {
+                                set_icon_name: icon_names::GRAPH,
+                                set_action_name: Some("win.statistics"),
+                                // Translators: tooltip for the button which shows the statistics page
+                                set_tooltip: &gettext("Statistics"),
+                            },
+                            #[name = "primary_menu_button"]
                             pack_end = &gtk::MenuButton {
                                 set_icon_name: "open-menu-symbolic",
                                 set_menu_model: Some(&primary_menu),
@@ -88,6 +282,31 @@ impl Component for AppModel {
                                 set_tooltip: &gettext("Main Menu"),
                             },
                         },
+                        #[name = "training_search_bar"]
+                        add_top_bar = &gtk::SearchBar {
+                            #[wrap(Some)]
+                            #[name = "training_search_entry"]
+                            set_child = &gtk::SearchEntry {
+                                // Translators: The placeholder of the field which searches the training list
+                                set_placeholder_text: Some(&gettext("Search trainings")),
+                                connect_search_changed[sender] => move |entry| {
+                                    sender.input(AppModelInput::FilterTrainings(entry.text().into()));
+                                },
+                            },
+                            connect_entry: &training_search_entry,
+                        },
+                        #[name = "timer_banner"]
+                        add_top_bar = &adw::Banner {
+                            // Translators: The button of the banner on the training list which shows the running training again
+                            set_button_label: Some(&gettext("_Return")),
+                            connect_button_clicked => AppModelInput::ReturnToTimer,
+                        },
+                        #[name = "progression_banner"]
+                        add_top_bar = &adw::Banner {
+                            // Translators: The button of the banner on the training list which starts the suggested harder training
+                            set_button_label: Some(&gettext("_Start")),
+                            connect_button_clicked => AppModelInput::StartProgression,
+                        },
                         #[wrap(Some)]
                         #[name = "training_list_stack"]
                         set_content = &gtk::Stack {
@@ -95,11 +314,8 @@ impl Component for AppModel {
                             gtk::ScrolledWindow {
                                 set_vexpand: true,
                                 #[local_ref]
-                                list_trainings -> gtk::Box {
-                                    set_orientation: gtk::Orientation::Vertical,
-                                    set_margin_start: 12,
-                                    set_margin_end: 12,
-                                    set_spacing: 8,
+                                list_trainings -> gtk::ListView {
+                                    add_css_class: "training-list",
                                 }
                             },
                             #[name = "training_list_status"]
@@ -107,13 +323,50 @@ impl Component for AppModel {
                                 set_icon_name: Some(icon_names::WEIGHT2),
                                 // Translators: The message which is shown on the background of the empty training list
                                 set_title: &gettext("No training is created yet"),
-                                gtk::Button {
-                                    set_css_classes: &["suggested-action", "pill"],
-                                    // Translators: Big label button to create the first training if none exists
-                                    set_label: &gettext("Create training"),
-                                    set_halign: gtk::Align::Center,
-                                    connect_clicked => AppModelInput::PromptNewTraining,
-                                }
+                                gtk::Box {
+                                    set_orientation: gtk::Orientation::Vertical,
+                                    set_spacing: 24,
+                                    gtk::Button {
+                                        set_css_classes: &["suggested-action", "pill"],
+                                        // Translators: Big label button to create the first training if none exists
+                                        set_label: &gettext("Create training"),
+                                        set_halign: gtk::Align::Center,
+                                        connect_clicked => AppModelInput::PromptNewTraining,
+                                    },
+                                    gtk::Box {
+                                        set_spacing: 12,
+                                        set_halign: gtk::Align::Center,
+                                        gtk::Button {
+                                            add_css_class: "pill",
+                                            // Translators: Button on the empty training list which starts the default training without saving it
+                                            set_label: &gettext("Quick Timer"),
+                                            connect_clicked => AppModelInput::StartQuickTimer,
+                                        },
+                                        #[name = "repeat_last_button"]
+                                        gtk::Button {
+                                            add_css_class: "pill",
+                                            // Translators: Button on the empty training list which starts the training of the last session again
+                                            set_label: &gettext("Repeat Last Workout"),
+                                            connect_clicked => AppModelInput::RepeatLastWorkout,
+                                        },
+                                    },
+                                    #[name = "weekly_goal_box"]
+                                    gtk::Box {
+                                        set_orientation: gtk::Orientation::Vertical,
+                                        set_spacing: 8,
+                                        set_halign: gtk::Align::Center,
+                                        #[name = "weekly_goal_ring"]
+                                        gtk::DrawingArea {
+                                            add_css_class: "accent",
+                                            set_content_width: 96,
+                                            set_content_height: 96,
+                                        },
+                                        #[name = "weekly_goal_label"]
+                                        gtk::Label {
+                                            add_css_class: "dim-label",
+                                        },
+                                    },
+                                },
                             },
                         },
                     },
@@ -122,46 +375,135 @@ impl Component for AppModel {
                 add = &adw::NavigationPage {
                     // Translators: The name of the timer page
                     set_title: &gettext("Timer"),
+                    connect_hidden => AppModelInput::TimerPageHidden,
                     #[wrap(Some)]
                     #[name = "main_view"]
+                    set_child = &adw::ToolbarView {
+                        add_css_class: "timer-page",
+                        add_top_bar = &adw::HeaderBar {
+                            pack_end = &gtk::Button {
+                                set_icon_name: icon_names::PICTURE_IN_PICTURE_REGULAR,
+                                set_action_name: Some("win.mini-timer"),
+                                // Translators: tooltip for the button which shows the remaining time in a small separate window
+                                set_tooltip: &gettext("Mini Timer"),
+                            },
+                            pack_end = &gtk::Button {
+                                set_icon_name: "view-fullscreen-symbolic",
+                                set_action_name: Some("win.fullscreen"),
+                                // Translators: tooltip for the button which shows the timer on the full screen
+                                set_tooltip: &gettext("Fullscreen"),
+                            },
+                            pack_end = &gtk::ToggleButton {
+                                set_icon_name: "video-display-symbolic",
+                                set_action_name: Some("win.tv-mode"),
+                                // Translators: tooltip for the button which shows the timer for a group on a big screen
+                                set_tooltip: &gettext("TV Mode"),
+                            },
+                            pack_end = &gtk::ToggleButton {
+                                set_icon_name: icon_names::TEXT_FONT_SIZE_REGULAR,
+                                set_action_name: Some("win.big-digits"),
+                                // Translators: tooltip for the button which shows only the remaining time in large digits
+                                set_tooltip: &gettext("Big Digits"),
+                            },
+                        },
+                    }
+                },
+                #[name = "statistics_navigation_page"]
+                add = &adw::NavigationPage {
+                    // Translators: The title of the statistics page
+                    set_title: &gettext("Statistics"),
+                    #[wrap(Some)]
+                    #[name = "statistics_view"]
                     set_child = &adw::ToolbarView {
                         add_top_bar = &adw::HeaderBar {},
                     }
                 },
                 connect_popped[sender] => move |_, _| { sender.input(AppModelInput::Popped); },
             }
+            }
         }
     }
 
     fn init(
-        init: Self::Init,
+        _init: Self::Init,
         root: Self::Root,
         sender: ComponentSender<Self>,
     ) -> ComponentParts<Self> {
-        let mut list_trainings = relm4::factory::FactoryVecDeque::builder()
-            .launch(gtk::Box::default())
-            .forward(sender.input_sender(), |output| match output {
-                TrainingSetupOutput::Remove(index) => AppModelInput::RemoveTrainingSetup(index),
+        let app_state = app_state::app_state();
+        let is_new_profile = !storage::has_training_list();
+        let (mut stored_list, load_error) = split_load_result(storage::load_training_list());
+        let list_damaged = load_error.is_some();
+        let onboarding = is_new_profile && stored_list.trainings.is_empty();
+        let stored_trainings = stored_list.trainings.clone();
+        if onboarding {
+            stored_list.trainings = sample_trainings();
+        }
+        let (row_sender, row_receiver) = relm4::channel();
+        relm4::spawn_local(row_receiver.forward(
+            sender.input_sender().clone(),
+            |output| match output {
+                TrainingSetupOutput::Remove(id) => AppModelInput::RemoveTrainingSetup(id),
                 TrainingSetupOutput::Load(training_setup) => {
                     AppModelInput::LoadTraining(training_setup)
                 }
-            });
-        {
-            let mut guard = list_trainings.guard();
-            for training_setup in settings::load_training_list_from_gsettings().into_iter() {
-                guard.push_back(training_setup);
-            }
-        }
+                TrainingSetupOutput::Edit(training_setup) => {
+                    AppModelInput::EditTrainingSetup(training_setup)
+                }
+                TrainingSetupOutput::Duplicate(training_setup) => {
+                    AppModelInput::CreateTrainingSetup(training_setup.duplicate())
+                }
+                TrainingSetupOutput::TogglePin(id) => AppModelInput::PinTrainingSetup(id),
+            },
+        ));
+        let mut list_trainings = TrainingList::new(row_sender);
+        list_trainings.set_trainings(stored_list.trainings);
+        let app_state::AppState {
+            output_stream,
+            global_settings,
+            training_defaults,
+            appearance,
+            sync_settings,
+            reminder_settings,
+        } = app_state;
         let model = AppModel {
             training_timer: None,
+            timer_running: false,
+            suggested_progression: None,
+            statistics_page: None,
             list_trainings,
-            output_stream: init,
+            window_id: app_state::register_window(sender.input_sender().clone()),
+            output_stream,
             window_geometry: settings::WindowGeometry::new_from_gsettings(),
-            global_settings: settings::GlobalTrainingSetup::new_from_gsettings(),
+            global_settings: global_settings.clone(),
+            training_defaults: training_defaults.clone(),
+            appearance: appearance.clone(),
             shortcuts_window: ShortcutsWindowModel::builder()
                 .transient_for(&root)
                 .launch(())
                 .detach(),
+            sync_settings: sync_settings.clone(),
+            reminder_settings: reminder_settings.clone(),
+            preferences_dialog: PreferencesDialog::builder()
+                .launch(PreferencesDialogInit {
+                    global_setup: global_settings,
+                    training_defaults,
+                    appearance,
+                    sync_settings,
+                    reminder_settings,
+                })
+                .forward(sender.input_sender(), |output| match output {
+                    PreferencesDialogOutput::Closed => AppModelInput::PreferencesClosed,
+                }),
+            profiles_menu: gtk::gio::Menu::new(),
+            list_modified: stored_list.modified,
+            save_generation: 0,
+            stored_trainings,
+            unsaved_changes: false,
+            list_damaged,
+            achievements_announced: sync::unix_now(),
+            removed_training: None,
+            narrow: false,
+            tv_mode: crate::START_IN_TV_MODE.get(),
         };
         let mut actions = relm4::actions::RelmActionGroup::<WindowActionGroup>::new();
         let about_action = {
@@ -187,9 +529,7 @@ impl Component for AppModel {
         let shortcuts_action = {
             let shortcuts_window_sender = model.shortcuts_window.sender().clone();
             relm4::actions::RelmAction::<ShortcutsAction>::new_stateless(move |_| {
-                shortcuts_window_sender
-                    .send(ShortcutsWindowInput::Show)
-                    .unwrap();
+                shortcuts_window_sender.emit(ShortcutsWindowInput::Show);
             })
         };
         let start_stop_action = {
@@ -198,26 +538,281 @@ impl Component for AppModel {
                 sender.input(AppModelInput::StartStop);
             })
         };
+        let fullscreen_action = {
+            let root = root.clone();
+            relm4::actions::RelmAction::<FullscreenAction>::new_stateless(move |_| {
+                root.set_fullscreened(!root.is_fullscreen());
+            })
+        };
+        let mini_timer_action = {
+            let sender = sender.clone();
+            relm4::actions::RelmAction::<MiniTimerAction>::new_stateless(move |_| {
+                sender.input(AppModelInput::ToggleMiniTimer);
+            })
+        };
+        let big_digits_action = {
+            let sender = sender.clone();
+            relm4::actions::RelmAction::<BigDigitsAction>::new_stateful(
+                &model.global_settings.big_digits.get(),
+                move |_, state| {
+                    *state = !*state;
+                    sender.input(AppModelInput::SetBigDigits(*state));
+                },
+            )
+        };
+        let tv_mode_action = {
+            let sender = sender.clone();
+            relm4::actions::RelmAction::<TvModeAction>::new_stateful(
+                &model.tv_mode,
+                move |_, state| {
+                    *state = !*state;
+                    sender.input(AppModelInput::SetTvMode(*state));
+                },
+            )
+        };
+        let skip_action = {
+            let sender = sender.clone();
+            relm4::actions::RelmAction::<SkipAction>::new_stateless(move |_| {
+                sender.input(AppModelInput::Skip);
+            })
+        };
+        let new_training_action = {
+            let sender = sender.clone();
+            relm4::actions::RelmAction::<NewTrainingAction>::new_stateless(move |_| {
+                sender.input(AppModelInput::PromptNewTraining);
+            })
+        };
+        let search_action = {
+            let sender = sender.clone();
+            relm4::actions::RelmAction::<SearchAction>::new_stateless(move |_| {
+                sender.input(AppModelInput::StartSearch);
+            })
+        };
         let reset_action = {
             let sender = sender.clone();
             relm4::actions::RelmAction::<ResetAction>::new_stateless(move |_| {
                 sender.input(AppModelInput::Reset);
             })
         };
+        let preferences_action = {
+            let root = root.clone();
+            let preferences_dialog = model.preferences_dialog.widget().clone();
+            relm4::actions::RelmAction::<PreferencesAction>::new_stateless(move |_| {
+                preferences_dialog.present(Some(&root));
+            })
+        };
+        let synchronize_action = {
+            let sender = sender.clone();
+            relm4::actions::RelmAction::<SynchronizeAction>::new_stateless(move |_| {
+                sender.input(AppModelInput::Synchronize);
+            })
+        };
+        let import_training_action = {
+            let sender = sender.clone();
+            relm4::actions::RelmAction::<ImportTrainingAction>::new_stateless(move |_| {
+                sender.input(AppModelInput::PromptImportFile);
+            })
+        };
+        let export_backup_action = {
+            let sender = sender.clone();
+            relm4::actions::RelmAction::<ExportBackupAction>::new_stateless(move |_| {
+                sender.input(AppModelInput::ExportBackup);
+            })
+        };
+        let export_history_action = {
+            let sender = sender.clone();
+            relm4::actions::RelmAction::<ExportHistoryAction>::new_stateless(move |_| {
+                sender.input(AppModelInput::ExportHistory { rounds: false });
+            })
+        };
+        let export_rounds_action = {
+            let sender = sender.clone();
+            relm4::actions::RelmAction::<ExportRoundsAction>::new_stateless(move |_| {
+                sender.input(AppModelInput::ExportHistory { rounds: true });
+            })
+        };
+        let import_backup_action = {
+            let sender = sender.clone();
+            relm4::actions::RelmAction::<ImportBackupAction>::new_stateless(move |_| {
+                sender.input(AppModelInput::ImportBackup);
+            })
+        };
+        let restore_snapshot_action = {
+            let sender = sender.clone();
+            relm4::actions::RelmAction::<RestoreSnapshotAction>::new_stateless(move |_| {
+                sender.input(AppModelInput::PromptRestoreSnapshot);
+            })
+        };
+        let profile_action = {
+            let sender = sender.clone();
+            relm4::actions::RelmAction::<ProfileAction>::new_stateful_with_target_value(
+                &profiles::active_profile_id(),
+                move |_, state, id| {
+                    *state = id.clone();
+                    sender.input(AppModelInput::SwitchProfile(id));
+                },
+            )
+        };
+        let new_profile_action = {
+            let sender = sender.clone();
+            relm4::actions::RelmAction::<NewProfileAction>::new_stateless(move |_| {
+                sender.input(AppModelInput::PromptNewProfile);
+            })
+        };
+        let trophies_action = {
+            let sender = sender.clone();
+            relm4::actions::RelmAction::<TrophiesAction>::new_stateless(move |_| {
+                sender.input(AppModelInput::ShowTrophies);
+            })
+        };
+        let new_window_action = {
+            let sender = sender.clone();
+            relm4::actions::RelmAction::<NewWindowAction>::new_stateless(move |_| {
+                sender.input(AppModelInput::NewWindow);
+            })
+        };
+        // Not in the menu, for bug reports
+        let debug_window_action = {
+            let sender = sender.clone();
+            relm4::actions::RelmAction::<DebugWindowAction>::new_stateless(move |_| {
+                sender.input(AppModelInput::ShowDebugWindow);
+            })
+        };
+        let statistics_action = {
+            let sender = sender.clone();
+            relm4::actions::RelmAction::<StatisticsAction>::new_stateless(move |_| {
+                sender.input(AppModelInput::ShowStatistics);
+            })
+        };
+        actions.add_action(new_window_action);
+        actions.add_action(debug_window_action);
         actions.add_action(about_action);
         actions.add_action(shortcuts_action);
         actions.add_action(start_stop_action);
+        actions.add_action(skip_action);
+        actions.add_action(big_digits_action);
+        actions.add_action(tv_mode_action);
+        actions.add_action(fullscreen_action);
+        actions.add_action(mini_timer_action);
+        actions.add_action(new_training_action);
+        actions.add_action(search_action);
         actions.add_action(reset_action);
+        actions.add_action(preferences_action);
+        actions.add_action(synchronize_action);
+        actions.add_action(import_training_action);
+        actions.add_action(export_backup_action);
+        actions.add_action(import_backup_action);
+        actions.add_action(restore_snapshot_action);
+        actions.add_action(statistics_action);
+        actions.add_action(trophies_action);
+        actions.add_action(export_history_action);
+        actions.add_action(export_rounds_action);
+        actions.add_action(profile_action);
+        actions.add_action(new_profile_action);
         let list_trainings = model.list_trainings.widget();
         let widgets = view_output!();
         actions.register_for_widget(&widgets.main_window);
+        fill_profiles_menu(&model.profiles_menu);
+        if let Some(primary_menu) = widgets
+            .primary_menu_button
+            .menu_model()
+            .and_downcast::<gtk::gio::Menu>()
+        {
+            let profile_section = gtk::gio::Menu::new();
+            // Translators: The title of the submenu which lists the profiles
+            profile_section.append_submenu(Some(&gettext("_Profile")), &model.profiles_menu);
+            primary_menu.prepend_section(None, &profile_section);
+        }
         relm4::main_application()
             .set_accelerators_for_action::<ShortcutsAction>(&["<Control>question"]);
         relm4::main_application()
             .set_accelerators_for_action::<StartStopAction>(&["<Control>space"]);
         relm4::main_application().set_accelerators_for_action::<ResetAction>(&["<Control>r"]);
+        relm4::main_application()
+            .set_accelerators_for_action::<ImportTrainingAction>(&["<Control>o"]);
+        relm4::main_application()
+            .set_accelerators_for_action::<PreferencesAction>(&["<Control>comma"]);
+        relm4::main_application().set_accelerators_for_action::<NewTrainingAction>(&["<Control>n"]);
+        relm4::main_application()
+            .set_accelerators_for_action::<NewWindowAction>(&["<Control><Shift>n"]);
+        relm4::main_application()
+            .set_accelerators_for_action::<DebugWindowAction>(&["<Control><Shift>d"]);
+        relm4::main_application().set_accelerators_for_action::<SearchAction>(&["<Control>f"]);
+        relm4::main_application().set_accelerators_for_action::<FullscreenAction>(&["F11"]);
+        {
+            let sender = sender.clone();
+            root.connect_is_active_notify(move |_| sender.input(AppModelInput::WindowActive));
+        }
+        {
+            // Only the timer is shown on the full screen
+            let main_view = widgets.main_view.clone();
+            root.connect_fullscreened_notify(move |window| {
+                main_view.set_reveal_top_bars(!window.is_fullscreen());
+            });
+        }
+        {
+            // Phones in portrait orientation. The lengths are in sp, so that large text
+            // switches to the compact layout in larger windows too.
+            let breakpoint = adw::Breakpoint::new(adw::BreakpointCondition::new_or(
+                adw::BreakpointCondition::new_length(
+                    adw::BreakpointConditionLengthType::MaxWidth,
+                    NARROW_WIDTH_SP,
+                    adw::LengthUnit::Sp,
+                ),
+                adw::BreakpointCondition::new_length(
+                    adw::BreakpointConditionLengthType::MaxHeight,
+                    SHORT_HEIGHT_SP,
+                    adw::LengthUnit::Sp,
+                ),
+            ));
+            let apply_sender = sender.clone();
+            breakpoint.connect_apply(move |_| apply_sender.input(AppModelInput::SetNarrow(true)));
+            let unapply_sender = sender.clone();
+            breakpoint
+                .connect_unapply(move |_| unapply_sender.input(AppModelInput::SetNarrow(false)));
+            root.add_breakpoint(breakpoint);
+        }
+        widgets
+            .main_navigation_page
+            .add_controller(timer_shortcut_controller());
+        widgets
+            .training_search_bar
+            .bind_property(
+                "search-mode-enabled",
+                &widgets.training_search_button,
+                "active",
+            )
+            .bidirectional()
+            .sync_create()
+            .build();
 
         update_status_visible(&widgets, &model);
+        update_streak(&widgets);
+        update_weekly_goal(&widgets);
+        if let Some(error) = load_error {
+            report_load_error(error, &root, &sender);
+        } else if onboarding {
+            show_onboarding(&root);
+            sender.input(AppModelInput::TrainingListChanged);
+        } else {
+            sender.input(AppModelInput::Synchronize);
+            // The daily routine is a single click away
+            let last_training_id = settings::load_last_training_id_from_gsettings();
+            if let Some(index) = model.list_trainings.position(&last_training_id) {
+                model.list_trainings.widget().scroll_to(
+                    index as u32,
+                    gtk::ListScrollFlags::FOCUS,
+                    None,
+                );
+                if let Some(setup) = model.list_trainings.get(index) {
+                    sender.input(AppModelInput::ShowTraining(setup));
+                }
+            }
+        }
+        model.record_for_recovery();
+        if let Some(recovery) = crash::take_recovery() {
+            prompt_recovery(recovery, &root, &sender);
+        }
         ComponentParts { model, widgets }
     }
 
@@ -230,62 +825,1222 @@ impl Component for AppModel {
     ) {
         match message {
             AppModelInput::PromptNewTraining => {
-                if let Some(timer) = self.training_timer.as_ref() {
-                    timer.sender().emit(TrainingTimerInput::Pause);
+                self.prompt_training_editor(
+                    TrainingEditorRole::New,
+                    TrainingSetup::default(),
+                    root,
+                    sender,
+                );
+            }
+            AppModelInput::PromptImportTraining(setup) => {
+                self.prompt_training_editor(TrainingEditorRole::Import, setup, root, sender);
+            }
+            AppModelInput::OpenUri(uri) if !deep_link::is_deep_link(&uri) => {
+                sender.input(AppModelInput::OpenFile(gtk::gio::File::for_uri(&uri)));
+            }
+            AppModelInput::OpenUri(uri) => match deep_link::parse(&uri) {
+                Some(DeepLink::Start(name)) => {
+                    match self
+                        .list_trainings
+                        .trainings()
+                        .into_iter()
+                        .find(|setup| setup.name == name)
+                    {
+                        Some(setup) => sender.input(AppModelInput::LoadTraining(setup)),
+                        None => tracing::warn!(
+                            %name,
+                            "{}",
+                            // Translators: Error message printed to the console when a link refers to a training which does not exist
+                            gettext("Could not find training")
+                        ),
+                    }
+                }
+                Some(DeepLink::Import(setup)) => {
+                    sender.input(AppModelInput::PromptImportTraining(setup));
                 }
-                let editor = TrainingEditor::builder()
-                    .launch((TrainingEditorRole::New, TrainingSetup::default()));
-                editor.widget().present(Some(root.widget_ref()));
-                let mut editor = editor.into_stream();
+                None => {
+                    // Translators: Error message printed to the console when the application is opened with an invalid link
+                    tracing::warn!(%uri, "{}", gettext("Could not open link"));
+                }
+            },
+            AppModelInput::PromptImportFile => {
+                let filter = gtk::FileFilter::new();
+                filter.add_mime_type("application/json");
+                filter.add_mime_type("text/csv");
+                // Translators: The name of the file type filter in the file dialog which opens a training
+                filter.set_name(Some(&gettext("Trainings")));
+                let filters = gtk::gio::ListStore::new::<gtk::FileFilter>();
+                filters.append(&filter);
+                let file_dialog = gtk::FileDialog::builder()
+                    // Translators: The title of the file dialog which opens a training
+                    .title(gettext("Import Training"))
+                    .filters(&filters)
+                    .build();
+                let window = root.clone().upcast::<gtk::Window>();
                 relm4::spawn_local(async move {
-                    if let Some(TrainingEditorOutput::Create(setup)) = editor.next().await.unwrap()
-                    {
-                        sender.input(AppModelInput::CreateTrainingSetup(setup));
+                    match file_dialog.open_future(Some(&window)).await {
+                        Ok(file) => sender.input(AppModelInput::OpenFile(file)),
+                        Err(err) if err.matches(gtk::DialogError::Dismissed) => {}
+                        Err(err) => {
+                            // Translators: Error message printed to the console when the file dialog fails
+                            tracing::warn!(%err, "{}", gettext("Could not choose the file"));
+                        }
                     }
                 });
             }
-            AppModelInput::RemoveTrainingSetup(index) => {
-                let index = index.current_index();
-                self.list_trainings.guard().remove(index);
+            AppModelInput::OpenFile(file) => {
+                let window = root.clone().upcast::<gtk::Window>();
+                relm4::spawn_local(async move {
+                    let trainings =
+                        file.load_contents_future()
+                            .await
+                            .ok()
+                            .and_then(|(contents, _)| {
+                                importers::import_trainings(&String::from_utf8_lossy(&contents))
+                            });
+                    match trainings {
+                        // A single training is shown in the editor before it is added
+                        Some(mut trainings) if trainings.len() == 1 => {
+                            sender.input(AppModelInput::PromptImportTraining(trainings.remove(0)))
+                        }
+                        Some(trainings) => sender.input(AppModelInput::ImportTrainings(trainings)),
+                        None => show_error(
+                            &window,
+                            // Translators: The heading of the dialog shown when the opened file does not contain a training
+                            &gettext("Could not import training from file"),
+                            &file.uri(),
+                        ),
+                    }
+                });
+            }
+            AppModelInput::ImportTrainings(trainings) => {
+                let count = trainings.len();
+                for training_setup in trainings.into_iter() {
+                    self.list_trainings.push_back(training_setup);
+                }
+                sender.input(AppModelInput::TrainingListChanged);
+                widgets.toast_overlay.add_toast(adw::Toast::new(&if false {
+                    // Translators: Shown in a toast when trainings were imported from a file
+                    ngettext(
+                        "{} training imported",
+                        "{} trainings imported",
+                        count as u32,
+                    )
+                } else {
+                    ngettext!(
+                        "{} training imported",
+                        "{} trainings imported",
+                        count as u32,
+                        count
+                    )
+                }));
+            }
+            AppModelInput::EditTrainingSetup(setup) => {
+                self.prompt_training_editor(TrainingEditorRole::Edit, setup, root, sender);
+            }
+            AppModelInput::UpdateTrainingSetup(setup) => {
+                if let Some(index) = self.list_trainings.position(&setup.id) {
+                    let pinned = self
+                        .list_trainings
+                        .get(index)
+                        .is_some_and(|setup| setup.pinned);
+                    // The editor does not know about pinning
+                    self.list_trainings
+                        .replace(index, TrainingSetup { pinned, ..setup });
+                    sender.input(AppModelInput::TrainingListChanged);
+                }
+            }
+            AppModelInput::RemoveTrainingSetup(id) => {
+                let Some(index) = self.list_trainings.position(&id) else {
+                    return;
+                };
+                let Some(removed) = self.list_trainings.remove(index) else {
+                    return;
+                };
+                let toast = adw::Toast::new(&if false {
+                    // Translators: Shown in a toast when a training was deleted. {} is the name of the training.
+                    gettext("“{}” deleted")
+                } else {
+                    gettext!("“{}” deleted", removed.name)
+                });
+                // Translators: The button of the toast which restores the deleted training
+                toast.set_button_label(Some(&gettext("_Undo")));
+                let undo_sender = sender.clone();
+                toast.connect_button_clicked(move |_| {
+                    undo_sender.input(AppModelInput::UndoRemoveTrainingSetup);
+                });
+                widgets.toast_overlay.add_toast(toast);
+                self.removed_training = Some((index, removed));
+                sender.input(AppModelInput::TrainingListChanged);
+            }
+            AppModelInput::UndoRemoveTrainingSetup => {
+                if let Some((index, training_setup)) = self.removed_training.take() {
+                    let index = index.min(self.list_trainings.len());
+                    self.list_trainings.insert(index, training_setup);
+                    sender.input(AppModelInput::TrainingListChanged);
+                }
+            }
+            AppModelInput::ShowError { error, retry } => {
+                tracing::warn!("{}", error);
+                let toast = adw::Toast::new(&error.title());
+                if let Some(retry) = retry {
+                    // Stays until it is dismissed, the failure is not over by itself
+                    toast.set_timeout(0);
+                    // Translators: The button of the toast which tries again what failed
+                    toast.set_button_label(Some(&gettext("_Retry")));
+                    let retry = std::cell::Cell::new(Some(*retry));
+                    let retry_sender = sender.clone();
+                    toast.connect_button_clicked(move |_| {
+                        if let Some(retry) = retry.take() {
+                            retry_sender.input(retry);
+                        }
+                    });
+                }
+                widgets.toast_overlay.add_toast(toast);
+            }
+            AppModelInput::PinTrainingSetup(id) => {
+                let Some(index) = self.list_trainings.position(&id) else {
+                    return;
+                };
+                let Some(mut setup) = self.list_trainings.remove(index) else {
+                    return;
+                };
+                setup.pinned = !setup.pinned;
+                setup.revision += 1;
+                // Pinned trainings are moved to the top, unpinned ones below the pinned
+                let target = if setup.pinned {
+                    0
+                } else {
+                    self.list_trainings
+                        .trainings()
+                        .iter()
+                        .filter(|other| other.pinned)
+                        .count()
+                };
+                self.list_trainings.insert(target, setup);
+                sender.input(AppModelInput::TrainingListChanged);
             }
             AppModelInput::CreateTrainingSetup(setup) => {
-                self.list_trainings.guard().push_back(setup);
+                widgets.toast_overlay.add_toast(adw::Toast::new(&if false {
+                    // Translators: Shown in a toast when a training was created. {} is the name of the training.
+                    gettext("“{}” created")
+                } else {
+                    gettext!("“{}” created", setup.name)
+                }));
+                self.list_trainings.push_back(setup);
+                sender.input(AppModelInput::TrainingListChanged);
             }
             AppModelInput::LoadTraining(setup) => {
-                self.training_timer = Some(
-                    TrainingTimer::builder()
-                        .launch(TrainingTimerInit {
-                            setup,
-                            global_setup: self.global_settings.clone(),
-                            output_handle: self.output_stream.clone(),
-                        })
-                        .detach(),
-                );
-                widgets
-                    .main_view
-                    .set_content(Some(self.training_timer.as_ref().unwrap().widget()));
-                widgets.navigation_view.push(&widgets.main_navigation_page);
+                self.load_training(setup, true, None, widgets, &sender);
+            }
+            AppModelInput::ShowTraining(setup) => {
+                self.load_training(setup, false, None, widgets, &sender);
+            }
+            AppModelInput::RestoreSession(setup, position) => {
+                self.load_training(setup, false, Some(position), widgets, &sender);
+            }
+            AppModelInput::RecoverTrainingList(recovered) => {
+                // The recovered list may have changes which were not saved before the crash
+                if self.list_damaged || recovered.modified > self.list_modified {
+                    if self.list_damaged {
+                        storage::set_aside_damaged_training_list();
+                        self.list_damaged = false;
+                    }
+                    self.list_trainings.set_trainings(recovered.trainings);
+                    self.list_modified = recovered.modified;
+                    self.schedule_save(&sender);
+                    sender.input(AppModelInput::Synchronize);
+                }
+            }
+            AppModelInput::TimerPeriod(css_class, intensity_css_class) => {
+                // The whole page is tinted, so that the period is recognizable at a glance
+                for class in PERIOD_CSS_CLASSES.into_iter().chain(INTENSITY_CSS_CLASSES) {
+                    widgets.main_view.remove_css_class(class);
+                }
+                widgets.main_view.add_css_class(css_class);
+                if let Some(intensity_css_class) = intensity_css_class {
+                    widgets.main_view.add_css_class(intensity_css_class);
+                }
+            }
+            AppModelInput::TimerProgress(title, running) => {
+                self.timer_running = running;
+                if widgets.timer_banner.is_revealed() {
+                    match title {
+                        Some(title) if running => widgets.timer_banner.set_title(&title),
+                        // The training came to the end while the training list was shown
+                        _ => {
+                            self.close_timer(widgets);
+                            sender.input(AppModelInput::Popped);
+                        }
+                    }
+                }
+            }
+            AppModelInput::StartQuickTimer => {
+                sender.input(AppModelInput::LoadTraining(TrainingSetup::default()));
+            }
+            AppModelInput::RepeatLastWorkout => {
+                let Some(last_session) = history::load_last_session() else {
+                    return;
+                };
+                let setup = self
+                    .list_trainings
+                    .trainings()
+                    .into_iter()
+                    .find(|setup| setup.id == last_session.training_id)
+                    .or_else(|| TrainingSetup::from_session(&last_session));
+                if let Some(setup) = setup {
+                    sender.input(AppModelInput::LoadTraining(setup));
+                }
+            }
+            AppModelInput::ReturnToTimer => {
+                widgets.timer_banner.set_revealed(false);
+                if self.training_timer.is_some()
+                    && widgets.navigation_view.visible_page().as_ref()
+                        != Some(&widgets.main_navigation_page)
+                {
+                    widgets.navigation_view.push(&widgets.main_navigation_page);
+                }
+            }
+            AppModelInput::TimerDone(training_id) => {
+                self.timer_running = false;
+                self.suggest_progression(&training_id, widgets);
+                // The recorded session is shared with the other devices
+                sender.input(AppModelInput::Synchronize);
+                if widgets.navigation_view.visible_page().as_ref()
+                    == Some(&widgets.main_navigation_page)
+                {
+                    // The training is not running, so the timer is closed once the page is popped
+                    widgets.navigation_view.pop();
+                } else {
+                    self.close_timer(widgets);
+                }
+            }
+            AppModelInput::StartProgression => {
+                widgets.progression_banner.set_revealed(false);
+                if let Some(setup) = self.suggested_progression.take() {
+                    sender.input(AppModelInput::LoadTraining(setup));
+                }
+            }
+            AppModelInput::WindowActive => {
+                self.update_ducking(root);
+            }
+            AppModelInput::TimerPageHidden => {
+                // Rather than leaving the page of a closed timer behind, e.g. for the next push
+                if self.training_timer.is_none() {
+                    widgets.main_view.set_content(gtk::Widget::NONE);
+                }
             }
             AppModelInput::Popped => {
-                self.training_timer = None;
+                let timer_shown = widgets.navigation_view.visible_page().as_ref()
+                    == Some(&widgets.main_navigation_page);
+                if !timer_shown && !widgets.timer_banner.is_revealed() {
+                    if self.timer_running {
+                        // A running training keeps going, and can be followed in the banner
+                        widgets.timer_banner.set_revealed(true);
+                    } else {
+                        self.close_timer(widgets);
+                    }
+                }
+                self.statistics_page = None;
+                // A session may have been recorded, or the allowed rest days changed
+                update_streak(widgets);
+                update_weekly_goal(widgets);
+                announce_achievements(root, self.achievements_announced);
+                self.achievements_announced = sync::unix_now();
+            }
+            AppModelInput::ShowTrophies => {
+                let dialog = TrophiesDialog::builder().launch(achievements::achievements(
+                    &history::load_sessions(),
+                    settings::load_streak_rest_days_from_gsettings(),
+                ));
+                dialog.widget().present(Some(root));
+                let mut dialog = dialog.into_stream();
+                relm4::spawn_local(async move {
+                    dialog.next().await;
+                });
+            }
+            AppModelInput::ShowStatistics => {
+                let statistics_page = StatisticsPage::builder().launch(()).detach();
+                widgets
+                    .statistics_view
+                    .set_content(Some(statistics_page.widget()));
+                self.statistics_page = Some(statistics_page);
+                if widgets.navigation_view.visible_page().as_ref()
+                    != Some(&widgets.statistics_navigation_page)
+                {
+                    widgets
+                        .navigation_view
+                        .push(&widgets.statistics_navigation_page);
+                }
             }
             AppModelInput::StartStop => {
                 if let Some(controller) = &self.training_timer {
                     controller.emit(TrainingTimerInput::StartStop);
                 }
             }
+            AppModelInput::Skip => {
+                if let Some(controller) = &self.training_timer {
+                    controller.emit(TrainingTimerInput::Skip);
+                }
+            }
+            AppModelInput::SetBigDigits(big_digits) => {
+                self.global_settings.big_digits.set(big_digits);
+                if let Some(controller) = &self.training_timer {
+                    controller.emit(TrainingTimerInput::SetBigDigits(big_digits));
+                }
+            }
+            AppModelInput::SetNarrow(narrow) => {
+                self.narrow = narrow;
+                if let Some(controller) = &self.training_timer {
+                    controller.emit(TrainingTimerInput::SetNarrow(narrow));
+                }
+            }
+            AppModelInput::SetTvMode(tv_mode) => {
+                self.tv_mode = tv_mode;
+                if let Some(controller) = &self.training_timer {
+                    controller.emit(TrainingTimerInput::SetTvMode(tv_mode));
+                }
+            }
+            AppModelInput::ToggleMiniTimer => {
+                if let Some(controller) = &self.training_timer {
+                    controller.emit(TrainingTimerInput::ToggleMiniWindow);
+                }
+            }
+            AppModelInput::StartSearch => {
+                widgets.navigation_view.pop_to_tag("training-list");
+                widgets.training_search_bar.set_search_mode(true);
+                widgets.training_search_entry.grab_focus();
+            }
+            AppModelInput::FilterTrainings(search_text) => {
+                self.list_trainings.filter(&search_text);
+            }
             AppModelInput::Reset => {
                 if let Some(controller) = &self.training_timer {
                     controller.emit(TrainingTimerInput::Reset);
                 }
             }
+            AppModelInput::TrainingListChanged => {
+                self.list_modified = sync::unix_now();
+                self.schedule_save(&sender);
+                sender.input(AppModelInput::Synchronize);
+            }
+            AppModelInput::SaveTrainingList(generation) => {
+                // Only the last scheduled save is carried out
+                if generation == self.save_generation && !self.list_damaged {
+                    self.save();
+                }
+            }
+            AppModelInput::ReloadTrainingList => match storage::load_training_list() {
+                Ok(stored_list) if self.unsaved_changes && !self.list_damaged => {
+                    // The changes of this window which are not saved yet are merged with those
+                    // of the other window and saved right away, rather than overwriting them
+                    let merged = sync::merge(
+                        &self.stored_trainings,
+                        &self.list_trainings.trainings(),
+                        &stored_list.trainings,
+                    );
+                    self.list_trainings.set_trainings(merged);
+                    self.list_modified = self.list_modified.max(stored_list.modified);
+                    self.save_generation += 1;
+                    self.save();
+                }
+                Ok(stored_list) => {
+                    self.stored_trainings = stored_list.trainings.clone();
+                    self.list_trainings.set_trainings(stored_list.trainings);
+                    self.list_modified = stored_list.modified;
+                    self.list_damaged = false;
+                }
+                // The damaged list was already reported when it was loaded first
+                Err(AppError::TrainingListDamaged) => {}
+                Err(error) => sender.input(AppModelInput::ShowError {
+                    error,
+                    retry: Some(Box::new(AppModelInput::ReloadTrainingList)),
+                }),
+            },
+            AppModelInput::NewWindow => {
+                let mut window = AppModel::builder().launch(());
+                relm4::main_application().add_window(window.widget());
+                window.widget().present();
+                window.detach_runtime();
+            }
+            AppModelInput::ShowDebugWindow => {
+                debug_window::show(Some(root.upcast_ref()));
+            }
+            AppModelInput::Synchronize => {
+                if let Some(folder_uri) = self.sync_settings.folder_uri() {
+                    let list_modified = self.list_modified;
+                    let local = sync::SyncPayload {
+                        modified: list_modified,
+                        trainings: self.list_trainings.trainings(),
+                    };
+                    let base = settings::load_sync_base_from_gsettings();
+                    let window = root.clone().upcast::<gtk::Window>();
+                    relm4::spawn_local(async move {
+                        match sync::synchronize(folder_uri, local, base, Some(window)).await {
+                            Ok(result) => {
+                                sender.input(AppModelInput::SyncFinished(result, list_modified));
+                            }
+                            Err(err) => sender.input(AppModelInput::ShowError {
+                                error: AppError::Sync(err),
+                                retry: Some(Box::new(AppModelInput::Synchronize)),
+                            }),
+                        }
+                    });
+                }
+            }
+            AppModelInput::SyncFinished(result, list_modified) => {
+                if result.history_changed {
+                    update_streak(widgets);
+                    update_weekly_goal(widgets);
+                }
+                if list_modified != self.list_modified {
+                    // The list was modified during the synchronization, the merge is stale
+                    sender.input(AppModelInput::Synchronize);
+                } else {
+                    settings::save_sync_base_to_gsettings(&result.trainings);
+                    if result.local_changed {
+                        self.list_trainings.set_trainings(result.trainings);
+                        self.schedule_save(&sender);
+                    }
+                }
+            }
+            AppModelInput::RestoreTrainingListBackup => {
+                let backup =
+                    storage::load_training_list_backup().unwrap_or(storage::StoredTrainingList {
+                        modified: 0,
+                        trainings: Vec::new(),
+                    });
+                storage::set_aside_damaged_training_list();
+                self.list_damaged = false;
+                self.list_trainings.set_trainings(backup.trainings);
+                self.list_modified = backup.modified;
+                self.schedule_save(&sender);
+                sender.input(AppModelInput::Synchronize);
+            }
+            AppModelInput::DiscardDamagedTrainingList => {
+                storage::set_aside_damaged_training_list();
+                self.list_damaged = false;
+                sender.input(AppModelInput::Synchronize);
+            }
+            AppModelInput::ExportBackup => {
+                let contents = backup::create_backup(
+                    self.list_trainings.trainings().iter(),
+                    self.list_modified,
+                );
+                let file_dialog = gtk::FileDialog::builder()
+                    // Translators: The title of the file dialog which saves the backup
+                    .title(gettext("Export All Data"))
+                    .initial_name("hiit-backup.json")
+                    .build();
+                let window = root.clone().upcast::<gtk::Window>();
+                relm4::spawn_local(async move {
+                    save_to_chosen_file(&window, file_dialog, contents).await;
+                });
+            }
+            AppModelInput::ExportHistory { rounds } => {
+                let sessions = history::load_sessions();
+                let (contents, initial_name) = if rounds {
+                    (history::rounds_to_csv(&sessions), "hiit-rounds.csv")
+                } else {
+                    (history::sessions_to_csv(&sessions), "hiit-history.csv")
+                };
+                let file_dialog = gtk::FileDialog::builder()
+                    // Translators: The title of the file dialog which saves the history as CSV
+                    .title(gettext("Export History"))
+                    .initial_name(initial_name)
+                    .build();
+                let window = root.clone().upcast::<gtk::Window>();
+                relm4::spawn_local(async move {
+                    save_to_chosen_file(&window, file_dialog, contents).await;
+                });
+            }
+            AppModelInput::ImportBackup => {
+                let filter = gtk::FileFilter::new();
+                filter.add_mime_type("application/json");
+                // Translators: The name of the file type filter in the file dialog which opens a backup
+                filter.set_name(Some(&gettext("Backups")));
+                let filters = gtk::gio::ListStore::new::<gtk::FileFilter>();
+                filters.append(&filter);
+                let file_dialog = gtk::FileDialog::builder()
+                    // Translators: The title of the file dialog which opens a backup
+                    .title(gettext("Import Backup"))
+                    .filters(&filters)
+                    .build();
+                let window = root.clone().upcast::<gtk::Window>();
+                relm4::spawn_local(async move {
+                    let file = match file_dialog.open_future(Some(&window)).await {
+                        Ok(file) => file,
+                        Err(err) if err.matches(gtk::DialogError::Dismissed) => return,
+                        Err(err) => {
+                            // Translators: Error message printed to the console when the file dialog fails
+                            tracing::warn!(%err, "{}", gettext("Could not choose the file"));
+                            return;
+                        }
+                    };
+                    let raw_json = file
+                        .load_contents_future()
+                        .await
+                        .ok()
+                        .map(|(contents, _)| String::from_utf8_lossy(&contents).into_owned())
+                        .filter(|raw_json| backup::is_backup(raw_json));
+                    match raw_json {
+                        Some(raw_json) => confirm_restore_backup(&window, raw_json, sender),
+                        None => show_error(
+                            &window,
+                            // Translators: The heading of the dialog shown when the backup cannot be restored
+                            &gettext("Could Not Import Backup"),
+                            // Translators: The body of the dialog shown when the backup cannot be restored
+                            &gettext("The file is not a valid Exercise Timer backup."),
+                        ),
+                    }
+                });
+            }
+            AppModelInput::PromptRestoreSnapshot => {
+                let dialog = SnapshotDialog::builder().launch(storage::list_snapshots());
+                dialog.widget().present(Some(root));
+                let mut dialog = dialog.into_stream();
+                let window = root.clone().upcast::<gtk::Window>();
+                relm4::spawn_local(async move {
+                    if let Some(SnapshotDialogOutput::Restore(path)) = dialog.next().await {
+                        match std::fs::read_to_string(&path)
+                            .ok()
+                            .filter(|raw_json| backup::is_backup(raw_json))
+                        {
+                            Some(raw_json) => confirm_restore_backup(&window, raw_json, sender),
+                            None => {
+                                tracing::warn!(
+                                    path = %path.display(),
+                                    "{}",
+                                    // Translators: Error message printed to the console when a snapshot cannot be restored
+                                    gettext("Could not restore the snapshot")
+                                );
+                            }
+                        }
+                    }
+                });
+            }
+            AppModelInput::SwitchProfile(id) => {
+                if id == profiles::active_profile_id() || self.list_damaged {
+                    return;
+                }
+                storage::save_training_list(
+                    self.list_trainings.trainings().iter(),
+                    self.list_modified,
+                );
+                profiles::switch_profile(&id);
+                // The other windows show the trainings of the new profile too
+                app_state::training_list_saved(self.window_id);
+                let is_new_profile = !storage::has_training_list();
+                self.global_settings.reload_from_gsettings();
+                self.training_defaults.reload_from_gsettings();
+                self.appearance.reload_from_gsettings();
+                self.sync_settings.reload_from_gsettings();
+                self.reminder_settings.reload_from_gsettings();
+                let (mut stored_list, load_error) =
+                    split_load_result(storage::load_training_list());
+                self.list_damaged = load_error.is_some();
+                let onboarding = is_new_profile && stored_list.trainings.is_empty();
+                self.stored_trainings = stored_list.trainings.clone();
+                self.unsaved_changes = false;
+                if onboarding {
+                    stored_list.trainings = sample_trainings();
+                }
+                self.list_trainings.set_trainings(stored_list.trainings);
+                self.list_modified = stored_list.modified;
+                self.record_for_recovery();
+                // The timer belongs to the previous profile
+                if widgets.navigation_view.visible_page().as_ref()
+                    == Some(&widgets.main_navigation_page)
+                {
+                    widgets.navigation_view.pop();
+                }
+                if let Some(error) = load_error {
+                    report_load_error(error, root, &sender);
+                } else if onboarding {
+                    show_onboarding(root);
+                    sender.input(AppModelInput::TrainingListChanged);
+                } else {
+                    sender.input(AppModelInput::Synchronize);
+                }
+            }
+            AppModelInput::PromptNewProfile => {
+                let entry = gtk::Entry::builder().activates_default(true).build();
+                let dialog = adw::AlertDialog::builder()
+                    // Translators: The heading of the dialog which creates a new profile
+                    .heading(gettext("New Profile"))
+                    // Translators: The body of the dialog which creates a new profile
+                    .body(gettext(
+                        "Each profile has its own trainings, history and preferences.",
+                    ))
+                    .extra_child(&entry)
+                    .default_response("create")
+                    .close_response("cancel")
+                    .build();
+                // Translators: The response of the new profile dialog which closes it without creating a profile
+                dialog.add_response("cancel", &gettext("_Cancel"));
+                // Translators: The response of the new profile dialog which creates the profile
+                dialog.add_response("create", &gettext("C_reate"));
+                dialog.set_response_appearance("create", adw::ResponseAppearance::Suggested);
+                dialog.set_response_enabled("create", false);
+                let weak_dialog = dialog.downgrade();
+                entry.connect_changed(move |entry| {
+                    if let Some(dialog) = weak_dialog.upgrade() {
+                        dialog.set_response_enabled("create", !entry.text().trim().is_empty());
+                    }
+                });
+                dialog.connect_response(Some("create"), move |_, _| {
+                    sender.input(AppModelInput::CreateProfile(entry.text().trim().into()));
+                });
+                dialog.present(Some(root));
+            }
+            AppModelInput::CreateProfile(name) => {
+                let profile = profiles::add_profile(&name);
+                fill_profiles_menu(&self.profiles_menu);
+                let _ = WidgetExt::activate_action(
+                    &widgets.main_window,
+                    "win.profile",
+                    Some(&profile.id.to_variant()),
+                );
+            }
+            AppModelInput::BackupRestored(restored) => {
+                self.global_settings.reload_from_gsettings();
+                self.training_defaults.reload_from_gsettings();
+                self.appearance.reload_from_gsettings();
+                self.sync_settings.reload_from_gsettings();
+                self.reminder_settings.reload_from_gsettings();
+                self.list_trainings.set_trainings(restored.trainings);
+                sender.input(AppModelInput::TrainingListChanged);
+            }
+            AppModelInput::PreferencesClosed => {
+                // New trainings start from the defaults right away
+                self.training_defaults.save_to_gsettings();
+                sender.input(AppModelInput::Synchronize);
+                // Saving reschedules the reminder of the running instance
+                if self.reminder_settings.save_to_gsettings() {
+                    let autostart = self.reminder_settings.autostart.get();
+                    relm4::spawn_local(async move {
+                        if let Err(err) = background::request_autostart(autostart).await {
+                            tracing::warn!(
+                                %err,
+                                "{}",
+                                // Translators: Error message printed to the console when the application cannot be started at login
+                                gettext("Could not change the autostart setting")
+                            );
+                        }
+                    });
+                }
+            }
         }
         update_status_visible(widgets, self);
     }
 }
 
+fn fill_profiles_menu(menu: &gtk::gio::Menu) {
+    menu.remove_all();
+    let profiles_section = gtk::gio::Menu::new();
+    for profile in profiles::load_profiles() {
+        let item = gtk::gio::MenuItem::new(Some(&profile.name), None);
+        item.set_action_and_target_value(Some("win.profile"), Some(&profile.id.to_variant()));
+        profiles_section.append_item(&item);
+    }
+    menu.append_section(None, &profiles_section);
+    // Translators: The title of the menu entry which creates a new profile
+    menu.append(Some(&gettext("_New Profile…")), Some("win.new-profile"));
+}
+
+/// Lets the user choose a file with the dialog and writes the contents into it.
+async fn save_to_chosen_file(window: &gtk::Window, file_dialog: gtk::FileDialog, contents: String) {
+    let file = match file_dialog.save_future(Some(window)).await {
+        Ok(file) => file,
+        Err(err) if err.matches(gtk::DialogError::Dismissed) => return,
+        Err(err) => {
+            // Translators: Error message printed to the console when the file dialog fails
+            tracing::warn!(%err, "{}", gettext("Could not choose the file"));
+            return;
+        }
+    };
+    if let Err((_, err)) = file
+        .replace_contents_future(
+            contents,
+            None,
+            false,
+            gtk::gio::FileCreateFlags::REPLACE_DESTINATION,
+        )
+        .await
+    {
+        show_error(
+            window,
+            // Translators: The heading of the dialog shown when the data cannot be saved to a file
+            &gettext("Could Not Export Data"),
+            &err.to_string(),
+        );
+    }
+}
+
+fn show_error(window: &gtk::Window, heading: &str, body: &str) {
+    let dialog = adw::AlertDialog::new(Some(heading), Some(body));
+    // Translators: The response of the error dialogs which closes them
+    dialog.add_response("close", &gettext("_Close"));
+    dialog.present(Some(window));
+}
+
+/// Asks before the backup replaces the trainings, the history and the preferences.
+fn confirm_restore_backup(
+    window: &gtk::Window,
+    raw_json: String,
+    sender: ComponentSender<AppModel>,
+) {
+    let dialog = adw::AlertDialog::new(
+        // Translators: The heading of the dialog which asks before a backup is restored
+        Some(&gettext("Replace All Data?")),
+        // Translators: The body of the dialog which asks before a backup is restored
+        Some(&gettext("The trainings, the history and the preferences are replaced by those of the backup. Commands and the sync folder are kept.")),
+    );
+    // Translators: The response of the dialog which asks before a backup is restored, which keeps the current data
+    dialog.add_response("cancel", &gettext("_Cancel"));
+    // Translators: The response of the dialog which asks before a backup is restored, which restores it
+    dialog.add_response("restore", &gettext("_Replace"));
+    dialog.set_response_appearance("restore", adw::ResponseAppearance::Destructive);
+    dialog.set_default_response(Some("cancel"));
+    dialog.set_close_response("cancel");
+    dialog.connect_response(Some("restore"), move |_, _| {
+        if let Some(restored) = backup::restore_backup(&raw_json) {
+            sender.input(AppModelInput::BackupRestored(restored));
+        }
+    });
+    dialog.present(Some(window));
+}
+
+/// Congratulates on the achievements earned by the sessions started since `since`.
+fn announce_achievements(root: &adw::ApplicationWindow, since: i64) {
+    let earned: Vec<achievements::Achievement> = achievements::achievements(
+        &history::load_sessions(),
+        settings::load_streak_rest_days_from_gsettings(),
+    )
+    .into_iter()
+    .filter(|achievement| achievement.earned.is_some_and(|earned| earned >= since))
+    .collect();
+    if earned.is_empty() {
+        return;
+    }
+    let dialog = adw::AlertDialog::new(
+        // Translators: The heading of the dialog shown when achievements were earned
+        Some(&gettext("Achievement Unlocked")),
+        Some(
+            &earned
+                .iter()
+                .map(|achievement| format!("{}: {}", achievement.title, achievement.description))
+                .collect::<Vec<String>>()
+                .join("\n"),
+        ),
+    );
+    // Translators: The response of the achievement dialog which shows all achievements
+    dialog.add_response("trophies", &gettext("Show _Trophies"));
+    // Translators: The response of the achievement dialog which closes it
+    dialog.add_response("close", &gettext("_Close"));
+    dialog.set_default_response(Some("close"));
+    let window = root.clone();
+    dialog.connect_response(Some("trophies"), move |_, _| {
+        let _ = WidgetExt::activate_action(&window, "win.trophies", None);
+    });
+    dialog.present(Some(root));
+}
+
+/// Welcomes a new profile, which starts with the sample trainings.
+fn show_onboarding(root: &adw::ApplicationWindow) {
+    let dialog = adw::AlertDialog::new(
+        // Translators: The heading of the dialog shown for a new profile
+        Some(&gettext("Welcome")),
+        // Translators: The body of the dialog shown for a new profile
+        Some(&gettext("A few sample trainings are ready to start. Create your own with the + button, or edit and delete the samples with the buttons of each training.\n\nEvery training begins with a preparation period to get into position. The durations which new trainings start from can be changed in the preferences.")),
+    );
+    // Translators: The response of the dialog shown for a new profile
+    dialog.add_response("close", &gettext("_Get Started"));
+    dialog.set_default_response(Some("close"));
+    dialog.present(Some(root));
+}
+
+/// The stored training list, or an empty one with the error if it cannot be loaded.
+fn split_load_result(
+    result: Result<storage::StoredTrainingList, AppError>,
+) -> (storage::StoredTrainingList, Option<AppError>) {
+    match result {
+        Ok(stored_list) => (stored_list, None),
+        Err(error) => (
+            storage::StoredTrainingList {
+                modified: 0,
+                trainings: Vec::new(),
+            },
+            Some(error),
+        ),
+    }
+}
+
+/// Offers the backup of a damaged list, or to read an unreadable one again.
+/// The list is not saved meanwhile, so that it is not overwritten by the empty one.
+fn report_load_error(
+    error: AppError,
+    root: &adw::ApplicationWindow,
+    sender: &ComponentSender<AppModel>,
+) {
+    match error {
+        AppError::TrainingListDamaged => prompt_restore_backup(root, sender),
+        error => sender.input(AppModelInput::ShowError {
+            error,
+            retry: Some(Box::new(AppModelInput::ReloadTrainingList)),
+        }),
+    }
+}
+
+/// Offers to continue after a crash, or to report it.
+fn prompt_recovery(
+    recovery: Recovery,
+    root: &adw::ApplicationWindow,
+    sender: &ComponentSender<AppModel>,
+) {
+    let body = match &recovery.session {
+        Some((setup, _)) => {
+            if false {
+                // Translators: The body of the dialog shown after a crash during a training. {} is replaced by the name of the training.
+                gettext("“{}” was interrupted. It can be continued where it stopped, along with the trainings as they were.")
+            } else {
+                gettext!("“{}” was interrupted. It can be continued where it stopped, along with the trainings as they were.", setup.name)
+            }
+        }
+        // Translators: The body of the dialog shown after a crash without a running training
+        None => gettext("The trainings can be restored as they were before the crash."),
+    };
+    let dialog = adw::AlertDialog::new(
+        // Translators: The heading of the dialog shown after a crash
+        Some(&gettext("Exercise Timer Quit Unexpectedly")),
+        Some(&body),
+    );
+    // Translators: Response of the dialog shown after a crash, which starts over without restoring anything
+    dialog.add_response("discard", &gettext("_Discard"));
+    // Translators: Response of the dialog shown after a crash, which saves the crash report and opens the issue tracker
+    dialog.add_response("report", &gettext("_Report Bug"));
+    // Translators: Response of the dialog shown after a crash, which restores the trainings and the interrupted training
+    dialog.add_response("restore", &gettext("_Restore"));
+    dialog.set_response_appearance("restore", adw::ResponseAppearance::Suggested);
+    dialog.set_default_response(Some("restore"));
+    dialog.set_close_response("discard");
+    let sender = sender.clone();
+    let window = root.clone().upcast::<gtk::Window>();
+    dialog.connect_response(None, move |_, response| match response {
+        "restore" => {
+            if let Some(training_list) = &recovery.training_list {
+                sender.input(AppModelInput::RecoverTrainingList(training_list.clone()));
+            }
+            if let Some((setup, position)) = &recovery.session {
+                sender.input(AppModelInput::RestoreSession(setup.clone(), *position));
+            }
+        }
+        "report" => report_crash(&recovery, &window),
+        _ => {}
+    });
+    dialog.present(Some(root));
+}
+
+/// Saves the crash report next to the data, shows it in the file manager to be attached,
+/// and opens the issue tracker.
+fn report_crash(recovery: &Recovery, window: &gtk::Window) {
+    let path = match crash::save_report(recovery) {
+        Ok(path) => path,
+        Err(err) => {
+            // Translators: Error message printed to the console when the crash report cannot be saved
+            tracing::warn!(%err, "{}", gettext("Could not save the crash report"));
+            return;
+        }
+    };
+    let window = window.clone();
+    relm4::spawn_local(async move {
+        let file = gtk::gio::File::for_path(&path);
+        if let Err(err) = gtk::FileLauncher::new(Some(&file))
+            .open_containing_folder_future(Some(&window))
+            .await
+        {
+            // Translators: Error message printed to the console when the folder of the crash report cannot be shown
+            tracing::warn!(path = %path.display(), %err, "{}", gettext("Could not show the crash report"));
+        }
+        if let Err(err) = gtk::UriLauncher::new(config::ISSUE_TRACKER)
+            .launch_future(Some(&window))
+            .await
+        {
+            // Translators: Error message printed to the console when the issue tracker cannot be opened
+            tracing::warn!(%err, "{}", gettext("Could not open the issue tracker"));
+        }
+    });
+}
+
+fn prompt_restore_backup(root: &adw::ApplicationWindow, sender: &ComponentSender<AppModel>) {
+    let has_backup = storage::load_training_list_backup().is_some();
+    let dialog = adw::AlertDialog::new(
+        // Translators: The heading of the dialog shown when the stored training list cannot be loaded
+        Some(&gettext("Training List Damaged")),
+        Some(&if has_backup {
+            // Translators: The body of the dialog shown when the stored training list cannot be loaded, but a backup is available
+            gettext("The saved trainings could not be loaded. The previous version of the list can be restored.")
+        } else {
+            // Translators: The body of the dialog shown when the stored training list cannot be loaded and there is no backup
+            gettext("The saved trainings could not be loaded. The damaged file is kept next to the new list.")
+        }),
+    );
+    // Translators: Response of the dialog shown when the stored training list cannot be loaded, which continues with an empty list
+    dialog.add_response("discard", &gettext("Start _Empty"));
+    if has_backup {
+        // Translators: Response of the dialog shown when the stored training list cannot be loaded, which restores the backup
+        dialog.add_response("restore", &gettext("_Restore Backup"));
+        dialog.set_response_appearance("restore", adw::ResponseAppearance::Suggested);
+        dialog.set_default_response(Some("restore"));
+    }
+    dialog.set_close_response("discard");
+    let sender = sender.clone();
+    dialog.connect_response(None, move |_, response| {
+        sender.input(if response == "restore" {
+            AppModelInput::RestoreTrainingListBackup
+        } else {
+            AppModelInput::DiscardDamagedTrainingList
+        });
+    });
+    dialog.present(Some(root));
+}
+
+impl AppModel {
+    fn schedule_save(&mut self, sender: &ComponentSender<Self>) {
+        self.record_for_recovery();
+        self.unsaved_changes = true;
+        self.save_generation += 1;
+        let generation = self.save_generation;
+        let sender = sender.clone();
+        gtk::glib::timeout_add_local_once(SAVE_DEBOUNCE, move || {
+            sender.input(AppModelInput::SaveTrainingList(generation));
+        });
+    }
+
+    /// Saves the training list and tells the other windows to load it again.
+    fn save(&mut self) {
+        let trainings = self.list_trainings.trainings();
+        storage::save_training_list(trainings.iter(), self.list_modified);
+        self.stored_trainings = trainings;
+        self.unsaved_changes = false;
+        app_state::training_list_saved(self.window_id);
+    }
+
+    /// Keeps the training list, with the changes which are not saved yet, for recovering from a crash.
+    fn record_for_recovery(&self) {
+        crash::record_training_list(storage::training_list_to_json(
+            self.list_trainings.trainings().iter(),
+            self.list_modified,
+        ));
+    }
+
+    /// Suggests the next progression of the training in a banner, once every round of it
+    /// was done in the last sessions.
+    fn suggest_progression(&mut self, training_id: &str, widgets: &AppModelWidgets) {
+        let trainings = self.list_trainings.trainings();
+        let next = trainings
+            .iter()
+            .find(|setup| setup.id == training_id)
+            .and_then(|setup| setup.progression.as_ref())
+            .and_then(|next_id| trainings.iter().find(|next| &next.id == next_id));
+        let Some(next) = next else {
+            return;
+        };
+        if !statistics::ready_to_progress(
+            &history::load_training_sessions(training_id),
+            training_id,
+        ) {
+            return;
+        }
+        widgets.progression_banner.set_title(&if false {
+            // Translators: The banner on the training list after a training was mastered. {} is the name of the harder training.
+            gettext("Every round done for a while, ready for {}?")
+        } else {
+            gettext!(
+                "Every round done for a while, ready for {}?",
+                gtk::glib::markup_escape_text(&next.name)
+            )
+        });
+        widgets.progression_banner.set_revealed(true);
+        self.suggested_progression = Some(next.clone());
+    }
+
+    /// Shows the training on the timer page, and starts it unless it is only shown paused.
+    /// A training is continued at the position, if there is one.
+    fn load_training(
+        &mut self,
+        setup: TrainingSetup,
+        start: bool,
+        position: Option<TimerPosition>,
+        widgets: &AppModelWidgets,
+        sender: &ComponentSender<Self>,
+    ) {
+        settings::save_last_training_id_to_gsettings(&setup.id);
+        self.training_timer = Some(
+            TrainingTimer::builder()
+                .launch(TrainingTimerInit {
+                    setup,
+                    global_setup: self.global_settings.clone(),
+                    output_handle: self.output_stream.clone(),
+                    narrow: self.narrow,
+                    tv_mode: self.tv_mode,
+                    start,
+                    position,
+                })
+                .forward(sender.input_sender(), |output| match output {
+                    TrainingTimerOutput::Period(css_class, intensity_css_class) => {
+                        AppModelInput::TimerPeriod(css_class, intensity_css_class)
+                    }
+                    TrainingTimerOutput::Progress { title, running } => {
+                        AppModelInput::TimerProgress(title, running)
+                    }
+                    TrainingTimerOutput::Done(training_id) => AppModelInput::TimerDone(training_id),
+                    TrainingTimerOutput::Error(error) => {
+                        AppModelInput::ShowError { error, retry: None }
+                    }
+                }),
+        );
+        self.update_ducking(&widgets.main_window);
+        widgets.timer_banner.set_revealed(false);
+        widgets.progression_banner.set_revealed(false);
+        widgets
+            .main_view
+            .set_content(Some(self.training_timer.as_ref().unwrap().widget()));
+        if widgets.navigation_view.visible_page().as_ref() != Some(&widgets.main_navigation_page) {
+            widgets.navigation_view.push(&widgets.main_navigation_page);
+        }
+    }
+
+    /// With several windows, the beeps of the windows in the background are quieter,
+    /// so that partners can tell their timers apart.
+    fn update_ducking(&self, root: &adw::ApplicationWindow) {
+        if let Some(timer) = &self.training_timer {
+            let ducked = !root.is_active() && app_state::window_count() > 1;
+            timer.emit(TrainingTimerInput::SetDucked(ducked));
+        }
+    }
+
+    /// Stops the training, which records an aborted session.
+    fn close_timer(&mut self, widgets: &AppModelWidgets) {
+        self.training_timer = None;
+        self.timer_running = false;
+        widgets.timer_banner.set_revealed(false);
+        for class in PERIOD_CSS_CLASSES.into_iter().chain(INTENSITY_CSS_CLASSES) {
+            widgets.main_view.remove_css_class(class);
+        }
+    }
+
+    fn prompt_training_editor(
+        &self,
+        role: TrainingEditorRole,
+        setup: TrainingSetup,
+        root: &<Self as Component>::Root,
+        sender: ComponentSender<Self>,
+    ) {
+        if let Some(timer) = self.training_timer.as_ref() {
+            timer.sender().emit(TrainingTimerInput::Pause);
+        }
+        let is_edit = matches!(role, TrainingEditorRole::Edit);
+        let candidates = self
+            .list_trainings
+            .trainings()
+            .into_iter()
+            .filter(|candidate| candidate.id != setup.id)
+            .collect();
+        let editor = TrainingEditor::builder().launch((role, setup, candidates));
+        editor.widget().present(Some(root.widget_ref()));
+        let mut editor = editor.into_stream();
+        relm4::spawn_local(async move {
+            // The editor was closed without a training, or dropped
+            if let Some(Some(TrainingEditorOutput::Create(setup))) = editor.next().await {
+                sender.input(if is_edit {
+                    AppModelInput::UpdateTrainingSetup(setup)
+                } else {
+                    AppModelInput::CreateTrainingSetup(setup)
+                });
+            }
+        });
+    }
+}
+
+fn update_streak(widgets: &AppModelWidgets) {
+    let streaks = statistics::streaks(
+        &history::load_completed_sessions(),
+        settings::load_streak_rest_days_from_gsettings(),
+    );
+    widgets
+        .training_list_title
+        .set_subtitle(&if streaks.current == 0 {
+            String::new()
+        } else if false {
+            // Translators: Shown below the title of the training list. {} is replaced by the number of days, e.g. "3 days".
+            gettext("Streak: {}")
+        } else {
+            gettext!("Streak: {}", statistics_page::format_days(streaks.current))
+        });
+}
+
+fn update_weekly_goal(widgets: &AppModelWidgets) {
+    let weekly_goal = settings::load_weekly_goal_from_gsettings();
+    let week = statistics::week_of_day(statistics::today());
+    // The goal in effect is recorded once per week, so that past weeks are judged by their own goal
+    history::record_weekly_goal(week, weekly_goal);
+    widgets.weekly_goal_box.set_visible(weekly_goal.target > 0);
+    if weekly_goal.target == 0 {
+        return;
+    }
+    let progress = statistics::weekly_progress(
+        &history::load_sessions_since(statistics::before_week(week)),
+        week,
+        weekly_goal.kind,
+    );
+    widgets
+        .weekly_goal_ring
+        .set_draw_func(statistics_page::draw_goal_ring(
+            progress as f64 / weekly_goal.target as f64,
+        ));
+    widgets
+        .weekly_goal_label
+        .set_label(&statistics_page::format_goal_progress(
+            progress,
+            weekly_goal,
+        ));
+}
+
+/// Handles the single-key shortcuts of the timer page. They run after the focused widget, so that
+/// Space still activates a focused button and typing a note is not taken for a shortcut.
+fn timer_shortcut_controller() -> gtk::ShortcutController {
+    let controller = gtk::ShortcutController::new();
+    controller.set_propagation_phase(gtk::PropagationPhase::Bubble);
+    for (trigger, action_name) in [("space", "win.start-stop"), ("n", "win.skip")] {
+        controller.add_shortcut(gtk::Shortcut::new(
+            gtk::ShortcutTrigger::parse_string(trigger),
+            Some(gtk::NamedAction::new(action_name)),
+        ));
+    }
+    // Escape leaves the full screen before it leaves the timer page
+    let leave_fullscreen =
+        gtk::CallbackAction::new(
+            |widget, _| match widget.root().and_downcast::<gtk::Window>() {
+                Some(window) if window.is_fullscreen() => {
+                    window.unfullscreen();
+                    gtk::glib::Propagation::Stop
+                }
+                _ => gtk::glib::Propagation::Proceed,
+            },
+        );
+    controller.add_shortcut(gtk::Shortcut::new(
+        gtk::ShortcutTrigger::parse_string("Escape"),
+        Some(leave_fullscreen),
+    ));
+    controller
+}
+
 fn update_status_visible(widgets: &AppModelWidgets, model: &AppModel) {
     if model.list_trainings.is_empty() {
+        widgets
+            .repeat_last_button
+            .set_visible(history::has_sessions());
         widgets
             .training_list_stack
             .set_visible_child(&widgets.training_list_status);
@@ -298,6 +2053,9 @@ fn update_status_visible(widgets: &AppModelWidgets, model: &AppModel) {
 
 impl Drop for AppModel {
     fn drop(&mut self) {
-        settings::save_training_list_to_gsettings(self.list_trainings.iter());
+        if self.list_damaged {
+            return;
+        }
+        storage::save_training_list(self.list_trainings.trainings().iter(), self.list_modified);
     }
 }
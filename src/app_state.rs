@@ -0,0 +1,88 @@
+use crate::app::AppModelInput;
+use crate::{appearance, power_saving, settings};
+use gettextrs::gettext;
+use relm4::{adw, gtk::prelude::*};
+use std::cell::{OnceCell, RefCell};
+
+/// The state which all windows of the application share, so that each window can run its own timer.
+#[derive(Clone)]
+pub struct AppState {
+    /// Without a sound system, the timers run silently.
+    pub output_stream: Option<rodio::OutputStreamHandle>,
+    pub global_settings: settings::GlobalTrainingSetup,
+    pub training_defaults: settings::TrainingDefaults,
+    pub appearance: settings::Appearance,
+    pub sync_settings: settings::SyncSettings,
+    pub reminder_settings: settings::ReminderSettings,
+}
+
+thread_local! {
+    /// Kept open as long as the application runs, the windows only hold handles to it.
+    static OUTPUT_STREAM: OnceCell<rodio::OutputStream> = const { OnceCell::new() };
+    static APP_STATE: OnceCell<AppState> = const { OnceCell::new() };
+    /// The senders of the open windows, with the ids which they were registered with.
+    static WINDOWS: RefCell<Vec<(usize, relm4::Sender<AppModelInput>)>> = const { RefCell::new(Vec::new()) };
+}
+
+fn new_app_state() -> AppState {
+    // The audio output is only opened in the primary instance, secondary instances
+    // hand their arguments over to it and exit
+    let output_stream = match rodio::OutputStream::try_default() {
+        Ok((output_stream, output_stream_handle)) => {
+            OUTPUT_STREAM.with(|cell| {
+                let _ = cell.set(output_stream);
+            });
+            Some(output_stream_handle)
+        }
+        Err(err) => {
+            // Translators: Error message when cannot connect to the audio output
+            tracing::warn!(%err, "{}", gettext("Could not create audio output stream"));
+            None
+        }
+    };
+    let global_settings = settings::GlobalTrainingSetup::new_from_gsettings();
+    let appearance = settings::Appearance::new_from_gsettings();
+    appearance::setup(&appearance);
+    power_saving::setup(&global_settings.power_saving);
+    AppState {
+        output_stream,
+        global_settings,
+        training_defaults: settings::TrainingDefaults::new_from_gsettings(),
+        appearance,
+        sync_settings: settings::SyncSettings::new_from_gsettings(),
+        reminder_settings: settings::ReminderSettings::new_from_gsettings(),
+    }
+}
+
+/// The shared state, which is created along with the first window.
+pub fn app_state() -> AppState {
+    APP_STATE.with(|cell| cell.get_or_init(new_app_state).clone())
+}
+
+/// Registers a window for the notifications of the other windows, and returns its id.
+pub fn register_window(sender: relm4::Sender<AppModelInput>) -> usize {
+    WINDOWS.with_borrow_mut(|windows| {
+        let id = windows.last().map_or(0, |(id, _)| id + 1);
+        windows.push((id, sender));
+        id
+    })
+}
+
+/// The number of open main windows, without dialogs and mini timers.
+pub fn window_count() -> usize {
+    relm4::main_application()
+        .windows()
+        .iter()
+        .filter(|window| window.is::<adw::ApplicationWindow>())
+        .count()
+}
+
+/// Tells the other windows to load the training list again, after the window `from` saved it.
+pub fn training_list_saved(from: usize) {
+    WINDOWS.with_borrow_mut(|windows| {
+        // Closed windows do not receive messages anymore
+        windows.retain(|(id, sender)| {
+            *id == from || sender.send(AppModelInput::ReloadTrainingList).is_ok()
+        });
+    });
+}
@@ -0,0 +1,108 @@
+use crate::{settings::Appearance, training_timer::PERIOD_CSS_CLASSES};
+use relm4::{
+    adw,
+    binding::{Binding, StringBinding, U32Binding},
+    gtk::{self, gdk, glib, prelude::*},
+};
+use std::rc::Rc;
+
+/// The preparation, exercise and rest colors of the built-in palettes, in the order of the preferences.
+const PALETTES: [[&str; 3]; 2] = [
+    ["#e5a50a", "#c64600", "#1a5fb4"],
+    // Distinguishable with deuteranopia and protanopia, from the Okabe-Ito palette
+    ["#f0e442", "#d55e00", "#0072b2"],
+];
+
+/// The tints of the intensities over the color of the period. They are part of the palette, as
+/// its `background` would otherwise reset them.
+const INTENSITY_CSS: &str = "\
+.intensity-easy { background-image: linear-gradient(rgba(255, 255, 255, 0.2), rgba(255, 255, 255, 0.2)); }
+.intensity-hard { background-image: linear-gradient(rgba(165, 29, 45, 0.5), rgba(165, 29, 45, 0.5)); }
+";
+
+/// The text color on a period background, dark on light colors and white otherwise.
+fn foreground(background: &gdk::RGBA) -> &'static str {
+    let luminance =
+        0.2126 * background.red() + 0.7152 * background.green() + 0.0722 * background.blue();
+    if luminance > 0.5 {
+        "#241f31"
+    } else {
+        "#ffffff"
+    }
+}
+
+fn palette_css(palette: &U32Binding, custom: &[StringBinding; 3]) -> String {
+    let palette = PALETTES.get(palette.get() as usize);
+    PERIOD_CSS_CLASSES
+        .iter()
+        .enumerate()
+        .map(|(index, class)| {
+            let default = gdk::RGBA::parse(PALETTES[0][index]).unwrap();
+            let background = match palette {
+                Some(palette) => gdk::RGBA::parse(palette[index]).unwrap(),
+                None => gdk::RGBA::parse(custom[index].get().as_str()).unwrap_or(default),
+            };
+            let foreground = foreground(&background);
+            let background = background.to_str();
+            format!(
+                ".{class} {{ background: {background}; color: {foreground}; }}\n\
+                 .{class} .period-ring {{ color: mix({background}, {foreground}, 0.6); }}\n"
+            )
+        })
+        .chain([INTENSITY_CSS.to_owned()])
+        .collect()
+}
+
+/// Applies the chosen appearance, and again whenever it is changed.
+pub fn setup(appearance: &Appearance) {
+    setup_color_scheme(&appearance.color_scheme);
+    setup_palette(appearance);
+}
+
+fn color_scheme(index: u32) -> adw::ColorScheme {
+    match index {
+        1 => adw::ColorScheme::ForceLight,
+        2 => adw::ColorScheme::ForceDark,
+        _ => adw::ColorScheme::Default,
+    }
+}
+
+fn setup_color_scheme(binding: &U32Binding) {
+    let style_manager = adw::StyleManager::default();
+    style_manager.set_color_scheme(color_scheme(binding.get()));
+    binding.connect_notify_local(Some("value"), move |binding, _| {
+        style_manager.set_color_scheme(color_scheme(binding.get()));
+    });
+}
+
+/// Styles the periods with the chosen palette.
+fn setup_palette(appearance: &Appearance) {
+    let Some(display) = gdk::Display::default() else {
+        return;
+    };
+    let palette = appearance.palette.clone();
+    let custom = [
+        appearance.custom_warmup.clone(),
+        appearance.custom_exercise.clone(),
+        appearance.custom_rest.clone(),
+    ];
+    let provider = gtk::CssProvider::new();
+    provider.load_from_string(&palette_css(&palette, &custom));
+    // Above the application style, which has the default colors
+    gtk::style_context_add_provider_for_display(
+        &display,
+        &provider,
+        gtk::STYLE_PROVIDER_PRIORITY_APPLICATION + 1,
+    );
+    let bindings: [glib::Object; 4] = [
+        palette.clone().upcast(),
+        custom[0].clone().upcast(),
+        custom[1].clone().upcast(),
+        custom[2].clone().upcast(),
+    ];
+    let reload = Rc::new(move || provider.load_from_string(&palette_css(&palette, &custom)));
+    for binding in bindings {
+        let reload = reload.clone();
+        binding.connect_notify_local(Some("value"), move |_, _| reload());
+    }
+}
@@ -0,0 +1,189 @@
+use crate::error::AppError;
+use gettextrs::gettext;
+use relm4::gtk::{gio, glib};
+use rodio::{cpal::traits::HostTrait, DeviceTrait, Source};
+use std::{sync::mpsc, time::Duration};
+
+/// The length of a ping, if the sound does not tell it.
+const PING_DURATION: Duration = Duration::from_millis(200);
+
+/// The share of the volume which is left while the cues are ducked.
+const DUCKED_VOLUME: f64 = 0.3;
+
+/// The sounds of a training.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Cue {
+    /// A second of the countdown before a period ends.
+    Countdown,
+    /// A second of the gap to get ready between a rest and the next exercise, in a higher pitch.
+    GetReady,
+    NextExercise,
+    NextRest,
+    Finished,
+}
+
+impl Cue {
+    /// How many pings in a row make up the cue.
+    fn pings(self) -> u32 {
+        match self {
+            Cue::Countdown | Cue::GetReady => 1,
+            Cue::NextExercise | Cue::NextRest => 2,
+            Cue::Finished => 3,
+        }
+    }
+
+    /// How much faster and higher the ping is played.
+    fn speed(self) -> f32 {
+        match self {
+            Cue::GetReady => 1.5,
+            Cue::Countdown | Cue::NextExercise | Cue::NextRest | Cue::Finished => 1.0,
+        }
+    }
+}
+
+/// Plays the cues of the timer, on a sound system or elsewhere.
+pub trait AudioBackend: Send {
+    fn play_cue(&self, cue: Cue) -> Result<(), AppError>;
+    /// Sets the volume from 0 to 1.
+    fn set_volume(&mut self, volume: f64);
+    /// Lowers the volume of the cues while they are less important, e.g. for a window in the background.
+    fn duck(&mut self, ducked: bool);
+    /// Plays the cues on the output device with the given name, or the default one if it is empty.
+    fn select_device(&mut self, name: &str);
+}
+
+/// The names of the output devices, which can be selected in the backends.
+pub fn output_devices() -> Vec<String> {
+    rodio::cpal::default_host()
+        .output_devices()
+        .map(|devices| devices.filter_map(|device| device.name().ok()).collect())
+        .unwrap_or_default()
+}
+
+fn load_ping() -> glib::Bytes {
+    gio::resources_lookup_data(
+        "/xyz/safeworlds/hiit/audio/ping.wav",
+        gio::ResourceLookupFlags::NONE,
+    )
+    // Translators: Error message printed to the console when cannot load data from resource
+    .unwrap_or_else(|err| panic!("{}: {}", gettext("Could not open resource data"), err))
+}
+
+/// Opens the output device on a thread of its own, as its stream cannot be moved between threads.
+/// The stream is closed when the returned sender is dropped.
+fn open_device(name: &str) -> Option<(rodio::OutputStreamHandle, mpsc::Sender<()>)> {
+    let (handle_sender, handle_receiver) = mpsc::channel();
+    let (close_sender, close_receiver) = mpsc::channel::<()>();
+    let name = name.to_owned();
+    std::thread::spawn(move || {
+        let stream = rodio::cpal::default_host()
+            .output_devices()
+            .ok()
+            .and_then(|mut devices| {
+                devices.find(|device| device.name().is_ok_and(|device_name| device_name == name))
+            })
+            .and_then(|device| rodio::OutputStream::try_from_device(&device).ok());
+        let Some((_stream, handle)) = stream else {
+            let _ = handle_sender.send(None);
+            return;
+        };
+        let _ = handle_sender.send(Some(handle));
+        // Only returns when the sender is dropped
+        let _ = close_receiver.recv();
+    });
+    handle_receiver
+        .recv()
+        .ok()
+        .flatten()
+        .map(|handle| (handle, close_sender))
+}
+
+/// Plays the cues with rodio.
+pub struct RodioBackend {
+    default_stream: rodio::OutputStreamHandle,
+    /// The selected device, and the sender which keeps its stream open.
+    device_stream: Option<(rodio::OutputStreamHandle, mpsc::Sender<()>)>,
+    ping_bytes: glib::Bytes,
+    volume: f64,
+    ducked: bool,
+}
+
+impl RodioBackend {
+    pub fn new(output_stream: rodio::OutputStreamHandle, volume: f64) -> Self {
+        Self {
+            default_stream: output_stream,
+            device_stream: None,
+            ping_bytes: load_ping(),
+            volume,
+            ducked: false,
+        }
+    }
+}
+
+impl AudioBackend for RodioBackend {
+    fn play_cue(&self, cue: Cue) -> Result<(), AppError> {
+        tracing::debug!(
+            ?cue,
+            volume = self.volume,
+            ducked = self.ducked,
+            "Playing cue"
+        );
+        let cursor = std::io::Cursor::new(self.ping_bytes.clone());
+        let decoder =
+            rodio::Decoder::new_wav(cursor).map_err(|err| AppError::Audio(err.to_string()))?;
+        let new_duration =
+            (decoder.total_duration().unwrap_or(PING_DURATION) * cue.pings()).div_f32(cue.speed());
+        let volume = if self.ducked {
+            self.volume * DUCKED_VOLUME
+        } else {
+            self.volume
+        };
+        let d = decoder
+            .speed(cue.speed())
+            .repeat_infinite()
+            .take_duration(new_duration)
+            .amplify(volume as f32);
+        let output_stream = match &self.device_stream {
+            Some((output_stream, _)) => output_stream,
+            None => &self.default_stream,
+        };
+        output_stream
+            .play_raw(d.convert_samples())
+            .map_err(|err| AppError::Audio(err.to_string()))
+    }
+
+    fn set_volume(&mut self, volume: f64) {
+        self.volume = volume;
+    }
+
+    fn duck(&mut self, ducked: bool) {
+        self.ducked = ducked;
+    }
+
+    fn select_device(&mut self, name: &str) {
+        self.device_stream = None;
+        if name.is_empty() {
+            return;
+        }
+        self.device_stream = open_device(name);
+        if self.device_stream.is_none() {
+            // Translators: Error message printed to the console when the chosen audio output device is not available
+            tracing::warn!(%name, "{}", gettext("Could not open the audio device"));
+        }
+    }
+}
+
+/// Plays nothing, e.g. without a sound system.
+pub struct NullBackend;
+
+impl AudioBackend for NullBackend {
+    fn play_cue(&self, _cue: Cue) -> Result<(), AppError> {
+        Ok(())
+    }
+
+    fn set_volume(&mut self, _volume: f64) {}
+
+    fn duck(&mut self, _ducked: bool) {}
+
+    fn select_device(&mut self, _name: &str) {}
+}
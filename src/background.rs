@@ -0,0 +1,45 @@
+use gettextrs::gettext;
+use relm4::gtk::{
+    gio::{self, prelude::*},
+    glib,
+};
+
+const PORTAL_BUS_NAME: &str = "org.freedesktop.portal.Desktop";
+const PORTAL_OBJECT_PATH: &str = "/org/freedesktop/portal/desktop";
+const BACKGROUND_INTERFACE: &str = "org.freedesktop.portal.Background";
+
+/// The command line option which starts the application without showing the window.
+pub const BACKGROUND_OPTION: &str = "background";
+
+/// Asks the Background portal to start the application in the background at login,
+/// or to stop doing so.
+pub async fn request_autostart(autostart: bool) -> Result<(), glib::Error> {
+    let connection = gio::bus_get_future(gio::BusType::Session).await?;
+    let options = glib::VariantDict::new(None);
+    // Translators: Shown by the system when the application asks to be started at login
+    options.insert("reason", gettext("Remind of the daily training"));
+    options.insert("autostart", autostart);
+    options.insert(
+        "commandline",
+        vec![String::from("hiit"), format!("--{}", BACKGROUND_OPTION)],
+    );
+    options.insert("dbus-activatable", false);
+    let parameters = glib::Variant::tuple_from_iter([
+        // The parent window identifier is optional
+        "".to_variant(),
+        options.end(),
+    ]);
+    connection
+        .call_future(
+            Some(PORTAL_BUS_NAME),
+            PORTAL_OBJECT_PATH,
+            BACKGROUND_INTERFACE,
+            "RequestBackground",
+            Some(&parameters),
+            glib::VariantTy::new("(o)").ok(),
+            gio::DBusCallFlags::NONE,
+            -1,
+        )
+        .await
+        .map(|_| ())
+}
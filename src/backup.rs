@@ -0,0 +1,126 @@
+use crate::history;
+use crate::storage::{self, StoredTrainingList};
+use crate::training_setup::TrainingSetup;
+use gettextrs::gettext;
+use relm4::gtk::{
+    gio::{self, prelude::*},
+    glib,
+};
+
+/// Keys which describe this machine or the state of the application rather than preferences,
+/// so they are not carried over to another machine. The commands are run by the shell, so they
+/// are never taken from a backup which may come from someone else, and neither is the folder
+/// which the trainings are synchronized with.
+const EXCLUDED_KEYS: [&str; 17] = [
+    "window-width",
+    "window-height",
+    "window-is-maximized",
+    "exercise-json-list",
+    "exercise-list-modified",
+    "sync-enabled",
+    "sync-folder-uri",
+    "sync-base-json",
+    "hook-start-command",
+    "hook-exercise-command",
+    "hook-rest-command",
+    "hook-finish-command",
+    "voice-model",
+    "voice-command-listener",
+    "heart-rate-monitor",
+    "profile-json-list",
+    "active-profile",
+];
+
+pub fn preferences_to_json() -> json::JsonValue {
+    let settings = gio::Settings::new(crate::config::APP_ID);
+    let mut preferences = json::JsonValue::new_object();
+    let Some(schema) = settings.settings_schema() else {
+        return preferences;
+    };
+    for key in schema.list_keys() {
+        if !EXCLUDED_KEYS.contains(&key.as_str()) {
+            preferences[key.as_str()] = settings.value(&key).print(true).as_str().into();
+        }
+    }
+    preferences
+}
+
+pub fn restore_preferences(preferences: &json::JsonValue) {
+    let settings = gio::Settings::new(crate::config::APP_ID);
+    let Some(schema) = settings.settings_schema() else {
+        return;
+    };
+    settings.delay();
+    for (key, value) in preferences.entries() {
+        if EXCLUDED_KEYS.contains(&key) || !schema.has_key(key) {
+            continue;
+        }
+        let Some(value) = value.as_str() else {
+            continue;
+        };
+        let value_type = settings.value(key).type_().to_owned();
+        match glib::Variant::parse(Some(&value_type), value) {
+            Ok(value) => {
+                let _ = settings.set_value(key, &value);
+            }
+            Err(err) => {
+                tracing::warn!(
+                    %err,
+                    "{}",
+                    if false {
+                        // Translators: Error message printed to the console when a preference in the backup cannot be restored. {} is replaced by the name of the preference.
+                        gettext("Could not restore the preference {}")
+                    } else {
+                        gettext!("Could not restore the preference {}", key)
+                    }
+                );
+            }
+        }
+    }
+    settings.apply();
+}
+
+/// Resets the preferences to their defaults, e.g. for a new profile.
+pub fn reset_preferences() {
+    let settings = gio::Settings::new(crate::config::APP_ID);
+    let Some(schema) = settings.settings_schema() else {
+        return;
+    };
+    settings.delay();
+    for key in schema.list_keys() {
+        if !EXCLUDED_KEYS.contains(&key.as_str()) {
+            settings.reset(&key);
+        }
+    }
+    settings.apply();
+}
+
+/// Bundles the trainings, the preferences and the history into a single JSON document.
+pub fn create_backup<'a>(
+    trainings: impl Iterator<Item = &'a TrainingSetup>,
+    modified: i64,
+) -> String {
+    let mut backup = json::parse(&storage::training_list_to_json(trainings, modified))
+        .unwrap_or_else(|_| json::JsonValue::new_object());
+    backup["version"] = hiit_schema::SCHEMA_VERSION.into();
+    backup["preferences"] = preferences_to_json();
+    backup["history"] = json::JsonValue::Array(history::load_raw_sessions());
+    json::stringify_pretty(backup, 4)
+}
+
+/// Whether the file is a backup which [`restore_backup`] can restore.
+pub fn is_backup(raw_json: &str) -> bool {
+    storage::parse_training_list_json(raw_json).is_some()
+}
+
+/// Restores the preferences and the history from the backup, and returns its trainings.
+/// Nothing is restored if the backup is malformed.
+pub fn restore_backup(raw_json: &str) -> Option<StoredTrainingList> {
+    let trainings = storage::parse_training_list_json(raw_json)?;
+    let backup = json::parse(raw_json).ok()?;
+    restore_preferences(&backup["preferences"]);
+    if backup["history"].is_array() {
+        history::replace_raw_sessions(backup["history"].members());
+    }
+    Some(trainings)
+}
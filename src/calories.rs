@@ -0,0 +1,32 @@
+//! Estimates the burned energy of a session from the metabolic equivalents (METs) of its periods
+//! and the body weight: a MET burns about one kilocalorie per kilogram and hour.
+
+use crate::hiit_core::Phase;
+use crate::history::PeriodLog;
+use crate::workout_mode::WorkoutMode;
+
+const SECONDS_PER_HOUR: f64 = 60.0 * 60.0;
+
+/// The estimated kilocalories of the exercises and rests, or `None` without a body weight.
+/// The preparation and the pauses are not counted.
+pub fn estimate_kcal(
+    periods: &[PeriodLog],
+    mode: &dyn WorkoutMode,
+    body_weight_kg: u32,
+) -> Option<u32> {
+    if body_weight_kg == 0 {
+        return None;
+    }
+    let met_hours: f64 = periods
+        .iter()
+        .map(|period| {
+            let met = match period.phase {
+                Phase::Preparation => 0.0,
+                Phase::Exercise => mode.exercise_met(),
+                Phase::Rest => mode.rest_met(),
+            };
+            met * period.length_s as f64 / SECONDS_PER_HOUR
+        })
+        .sum();
+    Some((met_hours * body_weight_kg as f64).round() as u32)
+}
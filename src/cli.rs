@@ -0,0 +1,282 @@
+use crate::audio_backend::{AudioBackend, Cue, RodioBackend};
+use crate::error::AppError;
+use crate::hiit_core::{IntervalTimer, Phase, TimerEvent};
+use crate::settings::GlobalTrainingSetup;
+use crate::storage;
+use crate::training_setup::TrainingSetup;
+use crate::training_timer::big_digits_text;
+use gettextrs::gettext;
+use relm4::binding::Binding;
+use std::io::Write;
+use std::time::{Duration, Instant};
+
+/// The subcommand which runs the timer in the terminal, e.g. `hiit run --work 30 --rest 15 --rounds 8`.
+pub const RUN_COMMAND: &str = "run";
+
+/// Prints the phases and cues of the training at once instead of running it, e.g. to check it.
+const SIMULATE_OPTION: &str = "--simulate";
+
+/// How often the timer is advanced.
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+const SECOND: Duration = Duration::from_secs(1);
+
+/// Rings the terminal bell instead of the beeps, without a sound system.
+struct BellBackend;
+
+impl AudioBackend for BellBackend {
+    fn play_cue(&self, cue: Cue) -> Result<(), AppError> {
+        let rings = match cue {
+            Cue::Countdown | Cue::GetReady => 1,
+            Cue::NextExercise | Cue::NextRest => 2,
+            Cue::Finished => 3,
+        };
+        print!("{}", "\x07".repeat(rings));
+        Ok(())
+    }
+
+    fn set_volume(&mut self, _volume: f64) {}
+
+    fn duck(&mut self, _ducked: bool) {}
+
+    fn select_device(&mut self, _name: &str) {}
+}
+
+fn usage() -> String {
+    // Translators: The usage of the timer in the terminal. Only translate the words in capitals.
+    gettext("Usage: hiit run [--work SECONDS] [--rest SECONDS] [--rounds COUNT] [--prepare SECONDS] [--exercise NAME] [--simulate]")
+}
+
+fn parse_number(option: &str, value: Option<&String>) -> Result<usize, String> {
+    value.and_then(|value| value.parse().ok()).ok_or_else(|| {
+        if false {
+            // Translators: Error message of the timer in the terminal when an option is not followed by a number
+            gettext("{} needs a number")
+        } else {
+            gettext!("{} needs a number", option)
+        }
+    })
+}
+
+/// The training to run, which is the one given by `--exercise` or the default one,
+/// with the lengths given by the other options.
+fn parse_args(args: &[String]) -> Result<TrainingSetup, String> {
+    let mut setup = match args.iter().position(|arg| arg == "--exercise") {
+        Some(index) => {
+            let name = args.get(index + 1).ok_or_else(|| {
+                // Translators: Error message of the timer in the terminal when the training name is missing
+                gettext("--exercise needs the name of a training")
+            })?;
+            storage::load_training_list()
+                .ok()
+                .and_then(|stored_list| {
+                    stored_list
+                        .trainings
+                        .into_iter()
+                        .find(|setup| &setup.name == name)
+                })
+                .ok_or_else(|| {
+                    // Translators: Error message of the timer in the terminal when there is no training with the given name
+                    format!("{}: {}", gettext("Could not find training"), name)
+                })?
+        }
+        None => TrainingSetup::default(),
+    };
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--work" => setup.exercise_s = parse_number(arg, args.next())?,
+            "--rest" => setup.rest_s = parse_number(arg, args.next())?,
+            "--rounds" => setup.sets = parse_number(arg, args.next())?,
+            "--prepare" => setup.prepare_s = parse_number(arg, args.next())?,
+            "--exercise" => {
+                args.next();
+            }
+            SIMULATE_OPTION => {}
+            _ => {
+                return Err(if false {
+                    // Translators: Error message of the timer in the terminal for an unknown option
+                    gettext("Unknown option: {}")
+                } else {
+                    gettext!("Unknown option: {}", arg)
+                });
+            }
+        }
+    }
+    if setup.exercise_s == 0 || setup.rest_s == 0 || setup.sets == 0 {
+        // Translators: Error message of the timer in the terminal when a length is zero
+        return Err(gettext(
+            "The work, the rest and the rounds must be more than zero",
+        ));
+    }
+    Ok(setup)
+}
+
+/// Overwrites the line of the terminal with the countdown, e.g. "00:45 · Rest — Round 3 of 8".
+fn print_progress(timer: &IntervalTimer, setup: &TrainingSetup) {
+    let line = if timer.is_finished() {
+        big_digits_text(0)
+    } else {
+        let (round, rounds) = setup.round(timer.remaining_sets());
+        let round_counter = if false {
+            // Translators: Shown on the timer page of phones. The first {} is the current round, the second the number of rounds.
+            gettext("Round {} of {}")
+        } else {
+            gettext!("Round {} of {}", round, rounds)
+        };
+        format!(
+            "{} · {} — {}",
+            big_digits_text(timer.remaining_s()),
+            setup.phase_title(timer.phase(), timer.remaining_sets()),
+            round_counter
+        )
+    };
+    print!("\r\x1b[2K{}", line);
+    let _ = std::io::stdout().flush();
+}
+
+/// The cue which is played for the event, if any. The countdown before the end of a rest
+/// is followed by the ticks of the gap to get ready, if there is one.
+fn event_cue(event: &TimerEvent, countdown_s: usize, get_ready_s: usize) -> Option<Cue> {
+    match event {
+        TimerEvent::Tick { remaining_s } if *remaining_s > 0 && *remaining_s <= get_ready_s => {
+            Some(Cue::GetReady)
+        }
+        TimerEvent::Tick { remaining_s }
+            if *remaining_s > get_ready_s && *remaining_s - get_ready_s <= countdown_s =>
+        {
+            Some(Cue::Countdown)
+        }
+        TimerEvent::PhaseStarted {
+            phase: Phase::Rest, ..
+        } => Some(Cue::NextRest),
+        TimerEvent::PhaseStarted { .. } => Some(Cue::NextExercise),
+        TimerEvent::Finished => Some(Cue::Finished),
+        _ => None,
+    }
+}
+
+fn cue_name(cue: Cue) -> String {
+    match cue {
+        // Translators: A cue in the simulation of a training in the terminal
+        Cue::Countdown => gettext("countdown beep"),
+        // Translators: A cue in the simulation of a training in the terminal
+        Cue::GetReady => gettext("get ready tick"),
+        // Translators: A cue in the simulation of a training in the terminal
+        Cue::NextExercise => gettext("exercise beep"),
+        // Translators: A cue in the simulation of a training in the terminal
+        Cue::NextRest => gettext("rest beep"),
+        // Translators: A cue in the simulation of a training in the terminal
+        Cue::Finished => gettext("final beep"),
+    }
+}
+
+/// The time since the start of the training, e.g. "01:05".
+fn clock_text(elapsed_s: usize) -> String {
+    format!("{:02}:{:02}", elapsed_s / 60, elapsed_s % 60)
+}
+
+/// Prints the phase which just began, e.g. "00:35 Rest 15 s — Round 1 of 8".
+fn print_simulated_phase(elapsed_s: usize, timer: &IntervalTimer, setup: &TrainingSetup) {
+    let (round, rounds) = setup.round(timer.remaining_sets());
+    let round_counter = if false {
+        // Translators: Shown on the timer page of phones. The first {} is the current round, the second the number of rounds.
+        gettext("Round {} of {}")
+    } else {
+        gettext!("Round {} of {}", round, rounds)
+    };
+    println!(
+        "{} {} {} s — {}",
+        clock_text(elapsed_s),
+        setup.phase_title(timer.phase(), timer.remaining_sets()),
+        timer.phase_s(),
+        round_counter
+    );
+}
+
+/// Runs the training through the timer in whole seconds without waiting, and prints every phase
+/// with its length and every cue at the time since the start.
+fn simulate(setup: &TrainingSetup, countdown_s: usize, transition_s: usize) {
+    let mut timer = IntervalTimer::new(setup.intervals()).with_transition_s(transition_s);
+    let mut elapsed_s = 0;
+    println!("{}", setup.name);
+    print_simulated_phase(elapsed_s, &timer, setup);
+    while !timer.is_finished() {
+        let events = timer.step(SECOND);
+        elapsed_s += 1;
+        for event in events {
+            if let Some(cue) = event_cue(&event, countdown_s, timer.get_ready_s()) {
+                println!("{} ♪ {}", clock_text(elapsed_s), cue_name(cue));
+            }
+            match event {
+                TimerEvent::PhaseStarted { .. } => print_simulated_phase(elapsed_s, &timer, setup),
+                TimerEvent::Finished => {
+                    println!("{} {}", clock_text(elapsed_s), big_digits_text(0))
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+/// Runs the training in the terminal until it is finished, and returns the exit code.
+pub fn run(args: &[String]) -> i32 {
+    let setup = match parse_args(args) {
+        Ok(setup) => setup,
+        Err(message) => {
+            eprintln!("{}\n{}", message, usage());
+            return 2;
+        }
+    };
+    let global_setup = GlobalTrainingSetup::new_from_gsettings();
+    let countdown_s = global_setup.countdown_s.get() as usize;
+    let transition_s = global_setup.transition_s.get() as usize;
+    if args.iter().any(|arg| arg == SIMULATE_OPTION) {
+        simulate(&setup, countdown_s, transition_s);
+        return 0;
+    }
+    let output_stream = rodio::OutputStream::try_default()
+        .map_err(|err| {
+            eprintln!(
+                "{}: {}",
+                // Translators: Error message printed to the console when the timer in the terminal cannot play sounds
+                gettext("Could not create audio output stream"),
+                err
+            );
+        })
+        .ok();
+    let mut backend: Box<dyn AudioBackend> = match &output_stream {
+        Some((_, output_stream)) => Box::new(RodioBackend::new(
+            output_stream.clone(),
+            global_setup.beep_volume.get(),
+        )),
+        None => Box::new(BellBackend),
+    };
+    backend.select_device(&global_setup.audio_device.get());
+    let mut timer = IntervalTimer::new(setup.intervals()).with_transition_s(transition_s);
+    println!("{}", setup.name);
+    print_progress(&timer, &setup);
+    let mut last_step = Instant::now();
+    while !timer.is_finished() {
+        std::thread::sleep(POLL_INTERVAL);
+        let now = Instant::now();
+        let events = timer.step(now - last_step);
+        last_step = now;
+        if events.is_empty() {
+            continue;
+        }
+        for cue in events
+            .iter()
+            .filter_map(|event| event_cue(event, countdown_s, timer.get_ready_s()))
+        {
+            if let Err(err) = backend.play_cue(cue) {
+                tracing::warn!("{}", err);
+            }
+        }
+        print_progress(&timer, &setup);
+    }
+    println!();
+    // The last beeps are played in the background
+    std::thread::sleep(Duration::from_secs(1));
+    0
+}
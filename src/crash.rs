@@ -0,0 +1,148 @@
+//! Keeps what is needed to recover from a crash: the training list, where the running training
+//! stands and the recent log. A panic writes it to a recovery file, which is offered to be
+//! restored and reported at the next launch.
+
+use crate::hiit_core::{Phase, TimerPosition};
+use crate::logging;
+use crate::storage::{self, StoredTrainingList};
+use crate::training_setup::TrainingSetup;
+use std::{path::PathBuf, sync::Mutex};
+
+const RECOVERY_FILE_NAME: &str = "recovery.json";
+const CRASH_REPORT_FILE_PREFIX: &str = "crash-report-";
+
+#[derive(Default)]
+struct CrashState {
+    /// In the data directory of the active profile, which cannot be looked up while panicking.
+    recovery_path: Option<PathBuf>,
+    /// The training list in JSON notation, including the changes which were not saved yet.
+    training_list: Option<String>,
+    session: Option<(TrainingSetup, TimerPosition)>,
+}
+
+static STATE: Mutex<CrashState> = Mutex::new(CrashState {
+    recovery_path: None,
+    training_list: None,
+    session: None,
+});
+
+/// What was saved at the crash.
+#[derive(Debug)]
+pub struct Recovery {
+    /// The whole recovery file, for the bug report.
+    pub report: String,
+    pub training_list: Option<StoredTrainingList>,
+    pub session: Option<(TrainingSetup, TimerPosition)>,
+}
+
+fn with_state(f: impl FnOnce(&mut CrashState)) {
+    if let Ok(mut state) = STATE.lock() {
+        f(&mut state);
+    }
+}
+
+/// Records the current training list of the active profile.
+pub fn record_training_list(training_list_json: String) {
+    let recovery_path = storage::data_dir().join(RECOVERY_FILE_NAME);
+    with_state(|state| {
+        state.recovery_path = Some(recovery_path);
+        state.training_list = Some(training_list_json);
+    });
+}
+
+/// Records where the running training stands, or `None` when no training is in progress.
+pub fn record_session(session: Option<(&TrainingSetup, TimerPosition)>) {
+    with_state(|state| {
+        state.session = session.map(|(setup, position)| (setup.clone(), position));
+    });
+}
+
+fn session_to_json(setup: &TrainingSetup, position: TimerPosition) -> json::JsonValue {
+    json::object! {
+        training: crate::settings::training_setup_to_json(setup),
+        phase: position.phase.to_setting(),
+        remaining_sets: position.remaining_sets,
+        remaining_s: position.remaining_s,
+    }
+}
+
+/// Writes the recovery file. The state is only tried to be locked, as the panic may have
+/// happened while it was locked.
+fn write_recovery(panic_info: &std::panic::PanicHookInfo) {
+    let Ok(state) = STATE.try_lock() else {
+        return;
+    };
+    let Some(path) = &state.recovery_path else {
+        return;
+    };
+    let log: Vec<json::JsonValue> = logging::records_after(None)
+        .iter()
+        .map(|record| record.to_string().into())
+        .collect();
+    let mut recovery = json::object! {
+        version: hiit_schema::SCHEMA_VERSION,
+        app_version: crate::config::VERSION,
+        time: crate::sync::unix_now(),
+        panic: panic_info.to_string(),
+        log: log,
+    };
+    if let Some(training_list) = state
+        .training_list
+        .as_deref()
+        .and_then(|raw_json| json::parse(raw_json).ok())
+    {
+        recovery["training_list"] = training_list;
+    }
+    if let Some((setup, position)) = &state.session {
+        recovery["session"] = session_to_json(setup, *position);
+    }
+    let _ = storage::write_atomically(path, &json::stringify_pretty(recovery, 4));
+}
+
+/// Saves the recovery file when the application panics, before it aborts as usual.
+pub fn install() {
+    with_state(|state| {
+        state.recovery_path = Some(storage::data_dir().join(RECOVERY_FILE_NAME));
+    });
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |panic_info| {
+        write_recovery(panic_info);
+        default_hook(panic_info);
+    }));
+}
+
+/// Takes the recovery file of the last crash of the active profile, so that it is offered only once.
+pub fn take_recovery() -> Option<Recovery> {
+    let path = storage::data_dir().join(RECOVERY_FILE_NAME);
+    let report = std::fs::read_to_string(&path).ok()?;
+    let _ = std::fs::remove_file(&path);
+    let parsed = json::parse(&report).ok()?;
+    let training_list = storage::parse_training_list_json(&parsed["training_list"].dump());
+    let session = &parsed["session"];
+    let session = hiit_schema::Workout::from_json(&session["training"]).and_then(|workout| {
+        Some((
+            TrainingSetup::from(workout),
+            TimerPosition {
+                phase: Phase::from_setting(session["phase"].as_str()?)?,
+                remaining_sets: session["remaining_sets"].as_usize()?,
+                remaining_s: session["remaining_s"].as_usize()?,
+            },
+        ))
+    });
+    Some(Recovery {
+        report,
+        training_list,
+        session,
+    })
+}
+
+/// Saves the report in the data directory, where it can be attached to a bug report.
+pub fn save_report(recovery: &Recovery) -> std::io::Result<PathBuf> {
+    let path = storage::data_dir().join(format!(
+        "{}{}.json",
+        CRASH_REPORT_FILE_PREFIX,
+        crate::sync::unix_now()
+    ));
+    storage::write_atomically(&path, &recovery.report)?;
+    Ok(path)
+}
@@ -0,0 +1,77 @@
+use crate::logging;
+use gettextrs::gettext;
+use relm4::adw;
+use relm4::gtk::{self, glib, prelude::*};
+use std::{cell::Cell, time::Duration};
+
+/// How often the window looks for new records.
+const REFRESH_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Appends the records which were logged since the last call to the buffer, and scrolls to the end.
+fn append_new_records(buffer: &gtk::TextBuffer, view: &gtk::TextView, last: &Cell<Option<u64>>) {
+    let records = logging::records_after(last.get());
+    let Some(newest) = records.last() else {
+        return;
+    };
+    last.set(Some(newest.sequence));
+    let mut end = buffer.end_iter();
+    for record in &records {
+        buffer.insert(&mut end, &format!("{}\n", record));
+    }
+    view.scroll_to_mark(&buffer.get_insert(), 0.0, false, 0.0, 1.0);
+}
+
+/// A hidden window which shows the recent log records, e.g. timer ticks, audio errors and
+/// synchronization attempts, so that they can be copied into a bug report.
+pub fn show(parent: Option<&gtk::Window>) {
+    let buffer = gtk::TextBuffer::new(None);
+    let view = gtk::TextView::builder()
+        .buffer(&buffer)
+        .editable(false)
+        .monospace(true)
+        .wrap_mode(gtk::WrapMode::WordChar)
+        .top_margin(6)
+        .bottom_margin(6)
+        .left_margin(6)
+        .right_margin(6)
+        .build();
+    let copy_button = gtk::Button::builder()
+        .icon_name("edit-copy-symbolic")
+        // Translators: Tooltip of the button which copies the log records of the debug window
+        .tooltip_text(gettext("Copy Log"))
+        .build();
+    let header_bar = adw::HeaderBar::new();
+    header_bar.pack_start(&copy_button);
+    let toolbar_view = adw::ToolbarView::new();
+    toolbar_view.add_top_bar(&header_bar);
+    toolbar_view.set_content(Some(&gtk::ScrolledWindow::builder().child(&view).build()));
+    let window = adw::Window::builder()
+        // Translators: The title of the hidden window which shows the log records for bug reports
+        .title(gettext("Debug Log"))
+        .default_width(720)
+        .default_height(480)
+        .content(&toolbar_view)
+        .build();
+    window.set_application(parent.and_then(|parent| parent.application()).as_ref());
+    {
+        let buffer = buffer.clone();
+        copy_button.connect_clicked(move |button| {
+            button.clipboard().set_text(&buffer.text(
+                &buffer.start_iter(),
+                &buffer.end_iter(),
+                false,
+            ));
+        });
+    }
+    let last = Cell::new(None);
+    append_new_records(&buffer, &view, &last);
+    let weak_window = window.downgrade();
+    glib::timeout_add_local(REFRESH_INTERVAL, move || match weak_window.upgrade() {
+        Some(window) if window.is_visible() => {
+            append_new_records(&buffer, &view, &last);
+            glib::ControlFlow::Continue
+        }
+        _ => glib::ControlFlow::Break,
+    });
+    window.present();
+}
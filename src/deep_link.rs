@@ -0,0 +1,90 @@
+use crate::settings;
+use crate::training_setup::TrainingSetup;
+use crate::warmup::{Warmup, WarmupStep};
+use crate::workout_mode::CLASSIC_MODE_ID;
+use relm4::gtk::glib;
+
+pub const URI_SCHEME: &str = "hiit";
+
+#[derive(Debug)]
+pub enum DeepLink {
+    /// `hiit://start/<training name>`
+    Start(String),
+    /// `hiit://import?data=<training in JSON notation>`
+    Import(TrainingSetup),
+}
+
+/// Builds the link which imports the training when opened with Exercise Timer.
+pub fn import_uri(setup: &TrainingSetup) -> String {
+    let mut data = json::object! {
+        name: setup.name.clone(),
+        sets: setup.sets,
+        exercise_s: setup.exercise_s,
+        rest_s: setup.rest_s,
+        prepare_s: setup.prepare_s,
+    };
+    if setup.mode != CLASSIC_MODE_ID {
+        data["mode"] = setup.mode.clone().into();
+    }
+    if let Some(intensity) = setup.exercise_intensity {
+        data["exercise_intensity"] = intensity.to_setting().into();
+    }
+    if let Some(intensity) = setup.rest_intensity {
+        data["rest_intensity"] = intensity.to_setting().into();
+    }
+    match &setup.warmup {
+        Warmup::Countdown => {}
+        Warmup::Default => data["default_warmup"] = true.into(),
+        Warmup::Steps(steps) => data["warmup"] = WarmupStep::list_to_json(steps),
+    }
+    let data = json::stringify(data);
+    format!(
+        "{}://import?data={}",
+        URI_SCHEME,
+        glib::Uri::escape_string(&data, None, false)
+    )
+}
+
+/// Builds the link which starts the training with the given name.
+pub fn start_uri(name: &str) -> String {
+    format!(
+        "{}://start/{}",
+        URI_SCHEME,
+        glib::Uri::escape_string(name, None, false)
+    )
+}
+
+/// Returns whether the URI is a link handled by Exercise Timer, as opposed to a file.
+pub fn is_deep_link(uri: &str) -> bool {
+    glib::Uri::peek_scheme(uri).is_some_and(|scheme| scheme == URI_SCHEME)
+}
+
+fn query_param(query: &str, key: &str) -> Option<String> {
+    query.split('&').find_map(|param| {
+        let (param_key, value) = param.split_once('=')?;
+        if param_key == key {
+            glib::Uri::unescape_string(value, None).map(String::from)
+        } else {
+            None
+        }
+    })
+}
+
+/// Parses a `hiit://` link. Returns `None` if the link is not recognized.
+pub fn parse(uri: &str) -> Option<DeepLink> {
+    let uri = glib::Uri::parse(uri, glib::UriFlags::ENCODED_QUERY).ok()?;
+    if uri.scheme() != URI_SCHEME {
+        return None;
+    }
+    match uri.host()?.as_str() {
+        "start" => {
+            let name = uri.path().trim_start_matches('/').to_owned();
+            (!name.is_empty()).then_some(DeepLink::Start(name))
+        }
+        "import" => {
+            let data = query_param(uri.query()?.as_str(), "data")?;
+            settings::parse_training_setup_json(&data).map(DeepLink::Import)
+        }
+        _ => None,
+    }
+}
@@ -0,0 +1,45 @@
+use gettextrs::gettext;
+use relm4::gtk::glib;
+
+/// The failures which the application recovers from. They are shown to the user
+/// rather than aborting, only broken installations still abort, e.g. without resources.
+#[derive(Debug)]
+pub enum AppError {
+    /// The stored training list is no valid list of trainings.
+    TrainingListDamaged,
+    /// The stored training list cannot be read, e.g. for lack of permissions.
+    TrainingListUnreadable(std::io::Error),
+    /// The sound system failed, the timer keeps going without beeps.
+    Audio(String),
+    /// The remote folder cannot be reached, or the list in it cannot be read or written.
+    Sync(glib::Error),
+}
+
+impl AppError {
+    /// A short sentence for a toast or a banner, without the technical details.
+    pub fn title(&self) -> String {
+        match self {
+            // Translators: Shown when the stored training list cannot be loaded
+            AppError::TrainingListDamaged => gettext("The saved trainings are damaged"),
+            // Translators: Shown when the stored training list cannot be read, e.g. for lack of permissions
+            AppError::TrainingListUnreadable(_) => gettext("Could not read the saved trainings"),
+            // Translators: Shown when the beeps cannot be played
+            AppError::Audio(_) => gettext("Could not play the beeps"),
+            // Translators: Shown in a toast when the training list cannot be synchronized
+            AppError::Sync(_) => gettext("Could not synchronize trainings"),
+        }
+    }
+}
+
+impl std::fmt::Display for AppError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AppError::TrainingListDamaged => write!(f, "{}", self.title()),
+            AppError::TrainingListUnreadable(err) => write!(f, "{}: {}", self.title(), err),
+            AppError::Audio(err) => write!(f, "{}: {}", self.title(), err),
+            AppError::Sync(err) => write!(f, "{}: {}", self.title(), err),
+        }
+    }
+}
+
+impl std::error::Error for AppError {}
@@ -0,0 +1,684 @@
+//! The intervals, phases and rounds of a training, without any user interface or sound.
+//! Frontends advance the timer with [`IntervalTimer::step`] and react to the returned events.
+
+use std::time::Duration;
+
+const SECOND: Duration = Duration::from_secs(1);
+
+/// The lengths of the periods of a training, in seconds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Intervals {
+    pub prepare_s: usize,
+    pub exercise_s: usize,
+    pub rest_s: usize,
+    pub sets: usize,
+    /// The length of the rest after every `long_rest_every` sets, e.g. the long break of a Pomodoro.
+    pub long_rest_s: usize,
+    /// 0 if all rests are of the same length.
+    pub long_rest_every: usize,
+}
+
+impl Intervals {
+    /// If the rest after the set is a long one, counting the sets from 1.
+    pub fn is_long_rest_after(&self, set: usize) -> bool {
+        self.long_rest_every > 0 && set.is_multiple_of(self.long_rest_every)
+    }
+
+    /// The length of the rest after the set, counting from 1.
+    pub fn rest_s_after(&self, set: usize) -> usize {
+        if self.is_long_rest_after(set) {
+            self.long_rest_s
+        } else {
+            self.rest_s
+        }
+    }
+
+    /// The length of the rest after the set as the timer runs it, with the gap of `transition_s`
+    /// at its end to get ready for the next exercise.
+    pub fn rest_with_gap_s_after(&self, set: usize, transition_s: usize) -> usize {
+        self.rest_s_after(set) + transition_s
+    }
+
+    /// The length of the whole training as the timer runs it with a gap of `transition_s` at the
+    /// end of every rest, without the rest after the last set.
+    pub fn total_s(&self, transition_s: usize) -> usize {
+        let rests_s: usize = (1..self.sets)
+            .map(|set| self.rest_with_gap_s_after(set, transition_s))
+            .sum();
+        self.prepare_s + self.exercise_s * self.sets + rests_s
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Phase {
+    Preparation,
+    Exercise,
+    Rest,
+}
+
+impl Phase {
+    /// The name of the phase in stored files, e.g. "rest".
+    pub fn to_setting(self) -> &'static str {
+        match self {
+            Phase::Preparation => "preparation",
+            Phase::Exercise => "exercise",
+            Phase::Rest => "rest",
+        }
+    }
+
+    pub fn from_setting(name: &str) -> Option<Self> {
+        match name {
+            "preparation" => Some(Phase::Preparation),
+            "exercise" => Some(Phase::Exercise),
+            "rest" => Some(Phase::Rest),
+            _ => None,
+        }
+    }
+}
+
+/// Where a training stands, to continue it later.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimerPosition {
+    pub phase: Phase,
+    pub remaining_sets: usize,
+    pub remaining_s: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TimerEvent {
+    /// A second of the current phase elapsed, `remaining_s` are left of it.
+    Tick { remaining_s: usize },
+    /// A phase came to the end or was skipped, after `elapsed_s` seconds.
+    PhaseEnded {
+        phase: Phase,
+        elapsed_s: usize,
+        skipped: bool,
+    },
+    /// A phase other than the first one began.
+    PhaseStarted { phase: Phase, duration_s: usize },
+    /// The last exercise came to the end.
+    Finished,
+}
+
+/// The state machine of a training: preparation, then exercise and rest for every set,
+/// without the rest after the last exercise.
+#[derive(Debug, Clone)]
+pub struct IntervalTimer {
+    intervals: Intervals,
+    phase: Phase,
+    remaining_sets: usize,
+    remaining_s: usize,
+    /// The length of the current phase.
+    phase_s: usize,
+    /// The time which elapsed since the last whole second.
+    carry: Duration,
+    /// The length of the next phase instead of the one of the intervals, once.
+    next_phase_s: Option<usize>,
+    /// The gap at the end of every rest, to get into position for the next exercise.
+    transition_s: usize,
+}
+
+impl IntervalTimer {
+    pub fn new(intervals: Intervals) -> Self {
+        let mut timer = Self {
+            intervals,
+            phase: Phase::Preparation,
+            remaining_sets: 0,
+            remaining_s: 0,
+            phase_s: 0,
+            carry: Duration::ZERO,
+            next_phase_s: None,
+            transition_s: 0,
+        };
+        timer.reset();
+        timer
+    }
+
+    /// Adds a gap of `transition_s` to the end of every rest, so that the next exercise does not
+    /// begin the instant the rest is over.
+    pub fn with_transition_s(mut self, transition_s: usize) -> Self {
+        self.transition_s = transition_s;
+        self
+    }
+
+    /// The seconds at the end of the current phase which are the gap to get ready for the next
+    /// exercise, none unless it is a rest.
+    pub fn get_ready_s(&self) -> usize {
+        match self.phase {
+            Phase::Rest => self.transition_s,
+            Phase::Preparation | Phase::Exercise => 0,
+        }
+    }
+
+    /// If the gap at the end of the rest began.
+    pub fn is_getting_ready(&self) -> bool {
+        self.remaining_s > 0 && self.remaining_s <= self.get_ready_s()
+    }
+
+    pub fn phase(&self) -> Phase {
+        self.phase
+    }
+
+    /// The seconds left of the current phase, 0 when the training is finished.
+    pub fn remaining_s(&self) -> usize {
+        self.remaining_s
+    }
+
+    /// The exact time left of the current phase, including the part of the current second
+    /// which has not elapsed yet. The timer keeps the same totals at any step size.
+    pub fn remaining(&self) -> Duration {
+        (SECOND * self.remaining_s as u32).saturating_sub(self.carry)
+    }
+
+    /// The sets which are not done yet, including the current one.
+    pub fn remaining_sets(&self) -> usize {
+        self.remaining_sets
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.remaining_sets == 0
+    }
+
+    /// The full length of the current phase.
+    pub fn phase_s(&self) -> usize {
+        self.phase_s
+    }
+
+    /// Begins the phase, with the length of the intervals unless another one was set for it.
+    fn begin_phase(&mut self, phase: Phase) {
+        let interval_s = match phase {
+            Phase::Preparation => self.intervals.prepare_s,
+            Phase::Exercise => self.intervals.exercise_s,
+            Phase::Rest => self
+                .intervals
+                .rest_s_after(self.intervals.sets - self.remaining_sets),
+        };
+        self.phase = phase;
+        self.phase_s = self.next_phase_s.take().unwrap_or(interval_s) + self.get_ready_s();
+        self.remaining_s = self.phase_s;
+    }
+
+    /// Starts the training from the beginning, with the preparation if there is one.
+    pub fn reset(&mut self) {
+        self.remaining_sets = self.intervals.sets;
+        self.carry = Duration::ZERO;
+        self.next_phase_s = None;
+        self.begin_phase(if self.intervals.prepare_s > 0 {
+            Phase::Preparation
+        } else {
+            Phase::Exercise
+        });
+    }
+
+    pub fn position(&self) -> TimerPosition {
+        TimerPosition {
+            phase: self.phase,
+            remaining_sets: self.remaining_sets,
+            remaining_s: self.remaining_s,
+        }
+    }
+
+    /// Continues the training at the position, e.g. after a crash.
+    /// Positions outside of the intervals are moved into them.
+    pub fn restore(&mut self, position: TimerPosition) {
+        self.reset();
+        if self.is_finished() {
+            return;
+        }
+        self.remaining_sets = position.remaining_sets.clamp(1, self.intervals.sets);
+        self.begin_phase(position.phase);
+        // E.g. the preparation of a training which has none anymore
+        if self.phase_s == 0 {
+            self.begin_phase(Phase::Exercise);
+        }
+        self.remaining_s = position.remaining_s.clamp(1, self.phase_s.max(1));
+    }
+
+    /// Makes the next exercise or rest last `seconds`, rather than as long as set in the intervals.
+    pub fn set_next_phase_s(&mut self, seconds: usize) {
+        self.next_phase_s = Some(seconds.max(1));
+    }
+
+    /// Advances the timer by `dt`, and returns what happened in the meantime in order.
+    pub fn step(&mut self, dt: Duration) -> Vec<TimerEvent> {
+        let mut events = Vec::new();
+        if self.is_finished() {
+            return events;
+        }
+        self.carry += dt;
+        while self.carry >= SECOND && !self.is_finished() {
+            self.carry -= SECOND;
+            self.remaining_s = self.remaining_s.saturating_sub(1);
+            events.push(TimerEvent::Tick {
+                remaining_s: self.remaining_s,
+            });
+            if self.remaining_s == 0 {
+                // The time beyond the end of the phase counts for the next one, so that the
+                // totals do not depend on the step
+                let carry = self.carry;
+                self.end_phase(false, &mut events);
+                self.carry = carry;
+            }
+        }
+        events
+    }
+
+    /// Moves on to the next phase before the current one is over.
+    pub fn skip(&mut self) -> Vec<TimerEvent> {
+        let mut events = Vec::new();
+        if !self.is_finished() {
+            self.end_phase(true, &mut events);
+        }
+        events
+    }
+
+    /// Starts the current phase over.
+    pub fn rewind(&mut self) {
+        if !self.is_finished() {
+            self.remaining_s = self.phase_s();
+            self.carry = Duration::ZERO;
+        }
+    }
+
+    fn end_phase(&mut self, skipped: bool, events: &mut Vec<TimerEvent>) {
+        events.push(TimerEvent::PhaseEnded {
+            phase: self.phase,
+            elapsed_s: self.phase_s() - self.remaining_s,
+            skipped,
+        });
+        self.carry = Duration::ZERO;
+        let next_phase = match self.phase {
+            Phase::Preparation | Phase::Rest => Phase::Exercise,
+            Phase::Exercise => {
+                self.remaining_sets -= 1;
+                if self.remaining_sets == 0 {
+                    self.remaining_s = 0;
+                    events.push(TimerEvent::Finished);
+                    return;
+                }
+                Phase::Rest
+            }
+        };
+        self.begin_phase(next_phase);
+        events.push(TimerEvent::PhaseStarted {
+            phase: next_phase,
+            duration_s: self.remaining_s,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn intervals(prepare_s: usize, exercise_s: usize, rest_s: usize, sets: usize) -> Intervals {
+        Intervals {
+            prepare_s,
+            exercise_s,
+            rest_s,
+            sets,
+            long_rest_s: 0,
+            long_rest_every: 0,
+        }
+    }
+
+    /// Runs the timer to the end in steps of `dt`.
+    fn run(timer: &mut IntervalTimer, dt: Duration) -> Vec<TimerEvent> {
+        let mut events = Vec::new();
+        while !timer.is_finished() {
+            events.extend(timer.step(dt));
+        }
+        events
+    }
+
+    /// The phases in the order in which they began, with their lengths.
+    fn phases(events: &[TimerEvent]) -> Vec<(Phase, usize)> {
+        events
+            .iter()
+            .filter_map(|event| match event {
+                TimerEvent::PhaseStarted { phase, duration_s } => Some((*phase, *duration_s)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn tick(remaining_s: usize) -> TimerEvent {
+        TimerEvent::Tick { remaining_s }
+    }
+
+    fn ended(phase: Phase, elapsed_s: usize) -> TimerEvent {
+        TimerEvent::PhaseEnded {
+            phase,
+            elapsed_s,
+            skipped: false,
+        }
+    }
+
+    fn started(phase: Phase, duration_s: usize) -> TimerEvent {
+        TimerEvent::PhaseStarted { phase, duration_s }
+    }
+
+    #[test]
+    fn runs_phases_in_order() {
+        let mut timer = IntervalTimer::new(intervals(1, 2, 1, 2));
+        assert_eq!(timer.phase(), Phase::Preparation);
+        assert_eq!(
+            run(&mut timer, SECOND),
+            vec![
+                tick(0),
+                ended(Phase::Preparation, 1),
+                started(Phase::Exercise, 2),
+                tick(1),
+                tick(0),
+                ended(Phase::Exercise, 2),
+                started(Phase::Rest, 1),
+                tick(0),
+                ended(Phase::Rest, 1),
+                started(Phase::Exercise, 2),
+                tick(1),
+                tick(0),
+                ended(Phase::Exercise, 2),
+                TimerEvent::Finished,
+            ]
+        );
+        assert_eq!(timer.remaining_s(), 0);
+        assert!(timer.step(SECOND).is_empty());
+    }
+
+    #[test]
+    fn single_set_has_no_rest() {
+        let mut timer = IntervalTimer::new(intervals(0, 3, 10, 1));
+        let events = run(&mut timer, SECOND);
+        assert_eq!(phases(&events), vec![]);
+        assert_eq!(events.last(), Some(&TimerEvent::Finished));
+        assert_eq!(
+            events
+                .iter()
+                .filter(|event| matches!(event, TimerEvent::Tick { .. }))
+                .count(),
+            3
+        );
+    }
+
+    #[test]
+    fn starts_with_exercise_without_preparation() {
+        let timer = IntervalTimer::new(intervals(0, 20, 10, 3));
+        assert_eq!(timer.phase(), Phase::Exercise);
+        assert_eq!(timer.remaining_s(), 20);
+        assert_eq!(timer.remaining_sets(), 3);
+    }
+
+    #[test]
+    fn no_sets_is_finished_at_once() {
+        let mut timer = IntervalTimer::new(intervals(5, 20, 10, 0));
+        assert!(timer.is_finished());
+        assert!(timer.step(SECOND).is_empty());
+        assert!(timer.skip().is_empty());
+    }
+
+    #[test]
+    fn long_rest_after_every_few_sets() {
+        let mut timer = IntervalTimer::new(Intervals {
+            long_rest_s: 5,
+            long_rest_every: 2,
+            ..intervals(0, 1, 2, 5)
+        });
+        assert_eq!(
+            phases(&run(&mut timer, SECOND)),
+            vec![
+                (Phase::Rest, 2),
+                (Phase::Exercise, 1),
+                (Phase::Rest, 5),
+                (Phase::Exercise, 1),
+                (Phase::Rest, 2),
+                (Phase::Exercise, 1),
+                (Phase::Rest, 5),
+                (Phase::Exercise, 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn total_counts_long_rests_and_gaps() {
+        let intervals = Intervals {
+            long_rest_s: 5,
+            long_rest_every: 2,
+            ..intervals(3, 1, 2, 5)
+        };
+        // The rest after the last set is not run
+        assert_eq!(intervals.total_s(0), 3 + 5 + 2 + 5 + 2 + 5);
+        assert_eq!(intervals.total_s(1), 3 + 5 + 2 + 5 + 2 + 5 + 4);
+        for transition_s in [0, 1, 3] {
+            let mut timer = IntervalTimer::new(intervals).with_transition_s(transition_s);
+            let ticks = run(&mut timer, SECOND)
+                .iter()
+                .filter(|event| matches!(event, TimerEvent::Tick { .. }))
+                .count();
+            assert_eq!(ticks, intervals.total_s(transition_s));
+        }
+    }
+
+    #[test]
+    fn next_phase_lasts_as_set_once() {
+        let mut timer = IntervalTimer::new(intervals(1, 2, 3, 3));
+        timer.set_next_phase_s(7);
+        assert_eq!(
+            phases(&run(&mut timer, SECOND)),
+            vec![
+                (Phase::Exercise, 7),
+                (Phase::Rest, 3),
+                (Phase::Exercise, 2),
+                (Phase::Rest, 3),
+                (Phase::Exercise, 2),
+            ]
+        );
+    }
+
+    #[test]
+    fn next_phase_lasts_at_least_a_second() {
+        let mut timer = IntervalTimer::new(intervals(1, 2, 3, 2));
+        timer.set_next_phase_s(0);
+        timer.skip();
+        assert_eq!(timer.remaining_s(), 1);
+    }
+
+    #[test]
+    fn reset_forgets_next_phase_length() {
+        let mut timer = IntervalTimer::new(intervals(1, 2, 3, 2));
+        timer.set_next_phase_s(9);
+        timer.reset();
+        timer.skip();
+        assert_eq!(timer.remaining_s(), 2);
+    }
+
+    #[test]
+    fn gap_to_get_ready_ends_every_rest() {
+        let mut timer = IntervalTimer::new(intervals(0, 1, 3, 2)).with_transition_s(2);
+        assert_eq!(timer.get_ready_s(), 0);
+        assert!(!timer.is_getting_ready());
+        let events = timer.step(SECOND);
+        assert_eq!(events.last(), Some(&started(Phase::Rest, 5)));
+        assert_eq!(timer.get_ready_s(), 2);
+        let getting_ready: Vec<bool> = (0..4)
+            .map(|_| {
+                timer.step(SECOND);
+                timer.is_getting_ready()
+            })
+            .collect();
+        assert_eq!(getting_ready, vec![false, false, true, true]);
+        timer.step(SECOND);
+        assert_eq!(timer.phase(), Phase::Exercise);
+        assert!(!timer.is_getting_ready());
+    }
+
+    #[test]
+    fn gap_follows_next_phase_length() {
+        let mut timer = IntervalTimer::new(intervals(0, 1, 3, 2)).with_transition_s(2);
+        timer.set_next_phase_s(10);
+        timer.skip();
+        assert_eq!(timer.phase(), Phase::Rest);
+        assert_eq!(timer.phase_s(), 12);
+    }
+
+    #[test]
+    fn carries_fractions_of_seconds() {
+        let mut timer = IntervalTimer::new(intervals(0, 2, 1, 1));
+        let step = Duration::from_millis(400);
+        assert!(timer.step(step).is_empty());
+        assert!(timer.step(step).is_empty());
+        assert_eq!(timer.remaining(), Duration::from_millis(1200));
+        assert_eq!(timer.step(step), vec![tick(1)]);
+        assert_eq!(timer.remaining(), Duration::from_millis(800));
+        assert!(timer.step(step).is_empty());
+        assert_eq!(
+            timer.step(step),
+            vec![tick(0), ended(Phase::Exercise, 2), TimerEvent::Finished]
+        );
+    }
+
+    #[test]
+    fn long_steps_catch_up() {
+        let mut timer = IntervalTimer::new(intervals(1, 1, 1, 2));
+        let events = timer.step(Duration::from_millis(2500));
+        assert_eq!(
+            events,
+            vec![
+                tick(0),
+                ended(Phase::Preparation, 1),
+                started(Phase::Exercise, 1),
+                tick(0),
+                ended(Phase::Exercise, 1),
+                started(Phase::Rest, 1),
+            ]
+        );
+        // The half second beyond the end of the exercise counts for the rest
+        assert_eq!(timer.remaining(), Duration::from_millis(500));
+    }
+
+    #[test]
+    fn lasts_as_long_at_any_step() {
+        for millis in [100, 300, 700, 1000] {
+            let mut timer = IntervalTimer::new(intervals(2, 3, 2, 3));
+            let mut steps = 0;
+            while !timer.is_finished() {
+                timer.step(Duration::from_millis(millis));
+                steps += 1;
+            }
+            let total_ms = (2 + 3 * 3 + 2 * 2) * 1000;
+            assert!(
+                (total_ms..total_ms + millis).contains(&(steps * millis)),
+                "steps of {millis} ms"
+            );
+        }
+    }
+
+    #[test]
+    fn total_is_the_same_at_any_step() {
+        for millis in [50, 250, 333, 1000, 1500] {
+            let mut timer = IntervalTimer::new(intervals(2, 3, 2, 3));
+            let ticks = run(&mut timer, Duration::from_millis(millis))
+                .iter()
+                .filter(|event| matches!(event, TimerEvent::Tick { .. }))
+                .count();
+            assert_eq!(ticks, 2 + 3 * 3 + 2 * 2, "steps of {millis} ms");
+        }
+    }
+
+    #[test]
+    fn skip_ends_phase_early() {
+        let mut timer = IntervalTimer::new(intervals(5, 20, 10, 2));
+        timer.step(Duration::from_millis(2300));
+        assert_eq!(
+            timer.skip(),
+            vec![
+                TimerEvent::PhaseEnded {
+                    phase: Phase::Preparation,
+                    elapsed_s: 2,
+                    skipped: true,
+                },
+                started(Phase::Exercise, 20),
+            ]
+        );
+        assert_eq!(timer.remaining(), Duration::from_secs(20));
+        timer.skip();
+        timer.skip();
+        let events = timer.skip();
+        assert_eq!(events.last(), Some(&TimerEvent::Finished));
+        assert!(timer.is_finished());
+        assert!(timer.skip().is_empty());
+    }
+
+    #[test]
+    fn rewind_starts_phase_over() {
+        let mut timer = IntervalTimer::new(intervals(0, 10, 5, 2));
+        timer.step(Duration::from_millis(3700));
+        assert_eq!(timer.remaining_s(), 7);
+        timer.rewind();
+        assert_eq!(timer.remaining_s(), 10);
+        assert_eq!(timer.remaining(), Duration::from_secs(10));
+        assert_eq!(timer.remaining_sets(), 2);
+    }
+
+    #[test]
+    fn rewind_after_finish_does_nothing() {
+        let mut timer = IntervalTimer::new(intervals(0, 1, 1, 1));
+        run(&mut timer, SECOND);
+        timer.rewind();
+        assert_eq!(timer.remaining_s(), 0);
+        assert!(timer.is_finished());
+    }
+
+    #[test]
+    fn restores_position() {
+        let mut timer = IntervalTimer::new(intervals(5, 20, 10, 4));
+        let position = TimerPosition {
+            phase: Phase::Rest,
+            remaining_sets: 2,
+            remaining_s: 4,
+        };
+        timer.restore(position);
+        assert_eq!(timer.position(), position);
+        assert_eq!(timer.phase_s(), 10);
+        let events = timer.step(Duration::from_secs(4));
+        assert_eq!(events.last(), Some(&started(Phase::Exercise, 20)));
+        assert_eq!(timer.remaining_sets(), 2);
+    }
+
+    #[test]
+    fn restores_into_the_intervals() {
+        let mut timer = IntervalTimer::new(intervals(0, 20, 10, 3));
+        timer.restore(TimerPosition {
+            phase: Phase::Preparation,
+            remaining_sets: 9,
+            remaining_s: 99,
+        });
+        // The training has no preparation anymore and fewer sets
+        assert_eq!(
+            timer.position(),
+            TimerPosition {
+                phase: Phase::Exercise,
+                remaining_sets: 3,
+                remaining_s: 20,
+            }
+        );
+        timer.restore(TimerPosition {
+            phase: Phase::Rest,
+            remaining_sets: 0,
+            remaining_s: 0,
+        });
+        assert_eq!(timer.remaining_sets(), 1);
+        assert_eq!(timer.remaining_s(), 1);
+    }
+
+    #[test]
+    fn restores_rest_with_gap() {
+        let mut timer = IntervalTimer::new(intervals(0, 20, 10, 3)).with_transition_s(3);
+        timer.restore(TimerPosition {
+            phase: Phase::Rest,
+            remaining_sets: 2,
+            remaining_s: 2,
+        });
+        assert_eq!(timer.phase_s(), 13);
+        assert!(timer.is_getting_ready());
+    }
+}
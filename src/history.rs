@@ -0,0 +1,900 @@
+use crate::hiit_core::Phase;
+use crate::intensity::Intensity;
+use crate::settings::{WeeklyGoal, WeeklyGoalKind};
+use crate::storage;
+use gettextrs::gettext;
+pub use hiit_schema::HeartRateSample;
+use hiit_schema::{Session, SessionPeriod, SessionRound};
+use rusqlite::{Connection, ToSql};
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+const DATABASE_FILE_NAME: &str = "history.sqlite3";
+/// Earlier versions appended the sessions to this file, which is moved into the database.
+const LEGACY_HISTORY_FILE_NAME: &str = "history.jsonl";
+const GOALS_FILE_NAME: &str = "goals.json";
+
+#[derive(Debug, Clone, Default)]
+pub struct RoundLog {
+    pub exercise_s: usize,
+    pub rest_s: usize,
+    /// If the round was skipped before the exercise period was over.
+    pub skipped: bool,
+}
+
+/// A period of a session as it actually went.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PeriodLog {
+    pub phase: Phase,
+    /// The time the timer ran in the period, including the time which was rewound.
+    pub length_s: usize,
+    /// If the period was skipped before it was over.
+    pub skipped: bool,
+    /// How hard the period was meant to be, if the training stated it.
+    pub intensity: Option<Intensity>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SessionMarkKind {
+    Paused {
+        pause_s: usize,
+    },
+    /// The period was started over, which added the time that had elapsed of it.
+    Rewound {
+        added_s: usize,
+    },
+}
+
+impl SessionMarkKind {
+    /// The name of the kind in stored files, e.g. "pause", and its duration.
+    fn to_setting(self) -> (&'static str, usize) {
+        match self {
+            SessionMarkKind::Paused { pause_s } => ("pause", pause_s),
+            SessionMarkKind::Rewound { added_s } => ("rewind", added_s),
+        }
+    }
+
+    fn from_setting(name: &str, duration_s: usize) -> Option<Self> {
+        match name {
+            "pause" => Some(SessionMarkKind::Paused {
+                pause_s: duration_s,
+            }),
+            "rewind" => Some(SessionMarkKind::Rewound {
+                added_s: duration_s,
+            }),
+            _ => None,
+        }
+    }
+}
+
+/// Something the user did during a session.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SessionMark {
+    /// The running time since the start of the session, without the pauses.
+    pub at_s: usize,
+    pub kind: SessionMarkKind,
+}
+
+/// A training session as it is recorded in the history.
+#[derive(Debug, Clone, Default)]
+pub struct SessionRecord {
+    pub training_id: String,
+    pub training_name: String,
+    /// Start of the session, in seconds since the Unix epoch.
+    pub started: i64,
+    /// Time spent with the timer running, in seconds.
+    pub duration_s: usize,
+    /// Time spent with the timer paused, in seconds.
+    pub pause_s: usize,
+    /// The number of sets of the training.
+    pub sets: usize,
+    /// If the training was carried out to the end, rather than aborted.
+    pub completed: bool,
+    pub rounds: Vec<RoundLog>,
+    /// The periods in order, for replaying the session. Empty for sessions recorded before they were kept.
+    pub periods: Vec<PeriodLog>,
+    pub marks: Vec<SessionMark>,
+    /// The rate of perceived exertion from 1 to 10, if it was entered after the session.
+    pub rpe: Option<u8>,
+    pub note: String,
+    /// The estimated burned kilocalories, if the body weight was known.
+    pub kcal: Option<u32>,
+    /// The drop of the heart rate in the minute after the training, if a heart rate monitor was connected.
+    pub hr_recovery_bpm: Option<u32>,
+}
+
+impl SessionRecord {
+    /// The periods in order. For sessions which were recorded without them, they are
+    /// made up from the rounds, without the preparation.
+    pub fn timeline(&self) -> Vec<PeriodLog> {
+        if !self.periods.is_empty() {
+            return self.periods.clone();
+        }
+        self.rounds
+            .iter()
+            .flat_map(|round| {
+                [
+                    PeriodLog {
+                        phase: Phase::Exercise,
+                        length_s: round.exercise_s,
+                        skipped: round.skipped,
+                        intensity: None,
+                    },
+                    PeriodLog {
+                        phase: Phase::Rest,
+                        length_s: round.rest_s,
+                        skipped: false,
+                        intensity: None,
+                    },
+                ]
+            })
+            .filter(|period| period.length_s > 0)
+            .collect()
+    }
+}
+
+impl From<&SessionRecord> for Session {
+    fn from(session: &SessionRecord) -> Self {
+        Session {
+            training_id: session.training_id.clone(),
+            training_name: session.training_name.clone(),
+            started: session.started,
+            duration_s: session.duration_s,
+            pause_s: session.pause_s,
+            sets: session.sets,
+            completed: session.completed,
+            rounds: session
+                .rounds
+                .iter()
+                .map(|round| SessionRound {
+                    exercise_s: round.exercise_s,
+                    rest_s: round.rest_s,
+                    skipped: round.skipped,
+                })
+                .collect(),
+            periods: session
+                .periods
+                .iter()
+                .map(|period| SessionPeriod {
+                    phase: period.phase.to_setting().to_owned(),
+                    length_s: period.length_s,
+                    skipped: period.skipped,
+                    intensity: period
+                        .intensity
+                        .map(|intensity| intensity.to_setting().to_owned()),
+                })
+                .collect(),
+            marks: session
+                .marks
+                .iter()
+                .map(|mark| {
+                    let (kind, duration_s) = mark.kind.to_setting();
+                    hiit_schema::SessionMark {
+                        at_s: mark.at_s,
+                        kind: kind.to_owned(),
+                        duration_s,
+                    }
+                })
+                .collect(),
+            rpe: session.rpe,
+            note: session.note.clone(),
+            kcal: session.kcal,
+            hr_recovery_bpm: session.hr_recovery_bpm,
+            heart_rate: Vec::new(),
+        }
+    }
+}
+
+impl From<Session> for SessionRecord {
+    /// Periods and marks which this version does not know are left out.
+    fn from(session: Session) -> Self {
+        SessionRecord {
+            training_id: session.training_id,
+            training_name: session.training_name,
+            started: session.started,
+            duration_s: session.duration_s,
+            pause_s: session.pause_s,
+            sets: session.sets,
+            completed: session.completed,
+            rounds: session
+                .rounds
+                .into_iter()
+                .map(|round| RoundLog {
+                    exercise_s: round.exercise_s,
+                    rest_s: round.rest_s,
+                    skipped: round.skipped,
+                })
+                .collect(),
+            periods: session
+                .periods
+                .into_iter()
+                .filter_map(|period| {
+                    Some(PeriodLog {
+                        phase: Phase::from_setting(&period.phase)?,
+                        length_s: period.length_s,
+                        skipped: period.skipped,
+                        intensity: period
+                            .intensity
+                            .as_deref()
+                            .and_then(Intensity::from_setting),
+                    })
+                })
+                .collect(),
+            marks: session
+                .marks
+                .into_iter()
+                .filter_map(|mark| {
+                    Some(SessionMark {
+                        at_s: mark.at_s,
+                        kind: SessionMarkKind::from_setting(&mark.kind, mark.duration_s)?,
+                    })
+                })
+                .collect(),
+            rpe: session.rpe.filter(|rpe| (1..=10).contains(rpe)),
+            note: session.note,
+            kcal: session.kcal,
+            hr_recovery_bpm: session.hr_recovery_bpm,
+        }
+    }
+}
+
+/// Loads the recorded sessions, the oldest first.
+pub fn load_sessions() -> Vec<SessionRecord> {
+    load(|connection| query_sessions(connection, "TRUE", &[]))
+}
+
+/// Loads the sessions started at `started` or later, the oldest first, e.g. those of a week.
+pub fn load_sessions_since(started: i64) -> Vec<SessionRecord> {
+    load(|connection| query_sessions(connection, "started >= ?1", &[&started]))
+}
+
+/// Loads the sessions of a training, the oldest first.
+pub fn load_training_sessions(training_id: &str) -> Vec<SessionRecord> {
+    load(|connection| query_sessions(connection, "training_id = ?1", &[&training_id]))
+}
+
+/// Loads the completed sessions, the oldest first, which are the ones counted as workouts.
+pub fn load_completed_sessions() -> Vec<SessionRecord> {
+    load(|connection| query_sessions(connection, "completed", &[]))
+}
+
+/// Loads the session which was started last.
+pub fn load_last_session() -> Option<SessionRecord> {
+    load(|connection| {
+        query_sessions(
+            connection,
+            "started = (SELECT MAX(started) FROM sessions)",
+            &[],
+        )
+    })
+    .pop()
+}
+
+pub fn has_sessions() -> bool {
+    load(|connection| {
+        connection.query_row("SELECT EXISTS (SELECT 1 FROM sessions)", [], |row| {
+            row.get(0)
+        })
+    })
+}
+
+/// Records the session with the readings of the heart rate monitor.
+pub fn append_session(session: &SessionRecord, heart_rate: &[HeartRateSample]) {
+    let result = with_database(|connection| {
+        let transaction = connection.transaction()?;
+        insert_session(&transaction, session, heart_rate)?;
+        transaction.commit()
+    });
+    if let Err(err) = result {
+        // Translators: Error message printed to the console when a session cannot be written to the history
+        tracing::warn!(%err, "{}", gettext("Could not record the session"));
+    }
+}
+
+/// Stores the exertion, the note and the heart rate recovery, which are only known after the
+/// session, with its recorded entry.
+pub fn annotate_session(session: &SessionRecord) {
+    let result = with_database(|connection| {
+        connection
+            .execute(
+                "UPDATE sessions SET rpe = ?1, note = ?2, hr_recovery_bpm = ?3
+                 WHERE training_id = ?4 AND started = ?5",
+                rusqlite::params![
+                    session.rpe,
+                    session.note,
+                    session.hr_recovery_bpm,
+                    session.training_id,
+                    session.started,
+                ],
+            )
+            .map(|_| ())
+    });
+    if let Err(err) = result {
+        // Translators: Error message printed to the console when the exertion or the note of a session cannot be written to the history
+        tracing::warn!(%err, "{}", gettext("Could not save the note of the session"));
+    }
+}
+
+/// Loads the recorded sessions in the JSON notation of backups, with the readings of the
+/// heart rate monitor, the oldest first.
+pub fn load_raw_sessions() -> Vec<json::JsonValue> {
+    load(|connection| {
+        let mut heart_rate = query_heart_rate(connection)?;
+        Ok(query_sessions(connection, "TRUE", &[])?
+            .into_iter()
+            .map(|session| {
+                let key = (session.training_id.clone(), session.started);
+                Session {
+                    heart_rate: heart_rate.remove(&key).unwrap_or_default(),
+                    ..Session::from(&session)
+                }
+                .to_json()
+            })
+            .collect())
+    })
+}
+
+/// Replaces the whole history, e.g. when restoring a backup. Malformed sessions are skipped.
+pub fn replace_raw_sessions<'a>(sessions: impl Iterator<Item = &'a json::JsonValue>) {
+    let result = with_database(|connection| {
+        let transaction = connection.transaction()?;
+        transaction.execute("DELETE FROM sessions", [])?;
+        for session in sessions.filter_map(Session::from_json) {
+            let heart_rate = session.heart_rate.clone();
+            insert_session(&transaction, &session.into(), &heart_rate)?;
+        }
+        transaction.commit()
+    });
+    if let Err(err) = result {
+        // Translators: Error message printed to the console when the history cannot be written
+        tracing::warn!(%err, "{}", gettext("Could not save the history"));
+    }
+}
+
+thread_local! {
+    /// The database of the active profile, with its path, kept open once it was used.
+    static DATABASE: RefCell<Option<(PathBuf, Connection)>> = const { RefCell::new(None) };
+}
+
+/// Runs `f` with the database of the active profile, which is opened and set up if needed.
+fn with_database<T>(f: impl FnOnce(&mut Connection) -> rusqlite::Result<T>) -> rusqlite::Result<T> {
+    DATABASE.with_borrow_mut(|database| {
+        let path = storage::data_dir().join(DATABASE_FILE_NAME);
+        let connection = match database {
+            Some((open_path, connection)) if *open_path == path => connection,
+            _ => &mut database.insert((path.clone(), open_database(&path)?)).1,
+        };
+        f(connection)
+    })
+}
+
+/// Runs the query with the database, or returns nothing if it cannot be read.
+fn load<T: Default>(query: impl FnOnce(&mut Connection) -> rusqlite::Result<T>) -> T {
+    with_database(query).unwrap_or_else(|err| {
+        // Translators: Error message printed to the console when the history cannot be read
+        tracing::warn!(%err, "{}", gettext("Could not load the history"));
+        T::default()
+    })
+}
+
+fn open_database(path: &Path) -> rusqlite::Result<Connection> {
+    if let Some(parent) = path.parent() {
+        let _ = std::fs::create_dir_all(parent);
+    }
+    let mut connection = Connection::open(path)?;
+    set_up(&connection)?;
+    let legacy_path = path.with_file_name(LEGACY_HISTORY_FILE_NAME);
+    if let Ok(raw) = std::fs::read_to_string(&legacy_path) {
+        import_legacy_history(&mut connection, &raw)?;
+        if let Err(err) = std::fs::remove_file(&legacy_path) {
+            tracing::warn!(%err, path = %legacy_path.display());
+        }
+    }
+    Ok(connection)
+}
+
+/// Creates the tables of a new database.
+fn set_up(connection: &Connection) -> rusqlite::Result<()> {
+    connection.execute_batch(
+        "PRAGMA foreign_keys = ON;
+        CREATE TABLE IF NOT EXISTS sessions (
+            id INTEGER PRIMARY KEY,
+            training_id TEXT NOT NULL,
+            training_name TEXT NOT NULL,
+            started INTEGER NOT NULL,
+            duration_s INTEGER NOT NULL,
+            pause_s INTEGER NOT NULL,
+            sets INTEGER NOT NULL,
+            completed INTEGER NOT NULL,
+            rpe INTEGER,
+            note TEXT NOT NULL,
+            kcal INTEGER,
+            hr_recovery_bpm INTEGER,
+            UNIQUE (training_id, started)
+        );
+        CREATE INDEX IF NOT EXISTS sessions_by_started ON sessions (started);
+        CREATE INDEX IF NOT EXISTS sessions_by_completed ON sessions (completed, started);
+        CREATE TABLE IF NOT EXISTS rounds (
+            session_id INTEGER NOT NULL REFERENCES sessions (id) ON DELETE CASCADE,
+            position INTEGER NOT NULL,
+            exercise_s INTEGER NOT NULL,
+            rest_s INTEGER NOT NULL,
+            skipped INTEGER NOT NULL,
+            PRIMARY KEY (session_id, position)
+        ) WITHOUT ROWID;
+        CREATE TABLE IF NOT EXISTS periods (
+            session_id INTEGER NOT NULL REFERENCES sessions (id) ON DELETE CASCADE,
+            position INTEGER NOT NULL,
+            phase TEXT NOT NULL,
+            length_s INTEGER NOT NULL,
+            skipped INTEGER NOT NULL,
+            intensity TEXT,
+            PRIMARY KEY (session_id, position)
+        ) WITHOUT ROWID;
+        CREATE TABLE IF NOT EXISTS marks (
+            session_id INTEGER NOT NULL REFERENCES sessions (id) ON DELETE CASCADE,
+            position INTEGER NOT NULL,
+            at_s INTEGER NOT NULL,
+            kind TEXT NOT NULL,
+            duration_s INTEGER NOT NULL,
+            PRIMARY KEY (session_id, position)
+        ) WITHOUT ROWID;
+        CREATE TABLE IF NOT EXISTS hr_samples (
+            session_id INTEGER NOT NULL REFERENCES sessions (id) ON DELETE CASCADE,
+            at_s INTEGER NOT NULL,
+            bpm INTEGER NOT NULL,
+            PRIMARY KEY (session_id, at_s)
+        ) WITHOUT ROWID;",
+    )
+}
+
+/// Moves the history of earlier versions, one session in JSON notation per line, into the database.
+fn import_legacy_history(connection: &mut Connection, raw: &str) -> rusqlite::Result<()> {
+    let transaction = connection.transaction()?;
+    for session in raw
+        .lines()
+        .filter_map(|line| json::parse(line).ok())
+        .filter_map(|value| Session::from_json(&value))
+    {
+        insert_session(&transaction, &session.into(), &[])?;
+    }
+    transaction.commit()
+}
+
+/// Inserts the session with its rounds, periods, marks and readings of the heart rate monitor.
+/// A session which is recorded already is left as it is.
+fn insert_session(
+    connection: &Connection,
+    session: &SessionRecord,
+    heart_rate: &[HeartRateSample],
+) -> rusqlite::Result<()> {
+    let inserted = connection.execute(
+        "INSERT OR IGNORE INTO sessions (training_id, training_name, started, duration_s, pause_s,
+            sets, completed, rpe, note, kcal, hr_recovery_bpm)
+        VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+        rusqlite::params![
+            session.training_id,
+            session.training_name,
+            session.started,
+            session.duration_s,
+            session.pause_s,
+            session.sets,
+            session.completed,
+            session.rpe,
+            session.note,
+            session.kcal,
+            session.hr_recovery_bpm,
+        ],
+    )?;
+    if inserted == 0 {
+        return Ok(());
+    }
+    let session_id = connection.last_insert_rowid();
+    let mut insert_round = connection.prepare_cached(
+        "INSERT INTO rounds (session_id, position, exercise_s, rest_s, skipped)
+        VALUES (?1, ?2, ?3, ?4, ?5)",
+    )?;
+    for (position, round) in session.rounds.iter().enumerate() {
+        insert_round.execute(rusqlite::params![
+            session_id,
+            position,
+            round.exercise_s,
+            round.rest_s,
+            round.skipped,
+        ])?;
+    }
+    let mut insert_period = connection.prepare_cached(
+        "INSERT INTO periods (session_id, position, phase, length_s, skipped, intensity)
+        VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+    )?;
+    for (position, period) in session.periods.iter().enumerate() {
+        insert_period.execute(rusqlite::params![
+            session_id,
+            position,
+            period.phase.to_setting(),
+            period.length_s,
+            period.skipped,
+            period.intensity.map(Intensity::to_setting),
+        ])?;
+    }
+    let mut insert_mark = connection.prepare_cached(
+        "INSERT INTO marks (session_id, position, at_s, kind, duration_s)
+        VALUES (?1, ?2, ?3, ?4, ?5)",
+    )?;
+    for (position, mark) in session.marks.iter().enumerate() {
+        let (kind, duration_s) = mark.kind.to_setting();
+        insert_mark.execute(rusqlite::params![
+            session_id, position, mark.at_s, kind, duration_s,
+        ])?;
+    }
+    let mut insert_sample = connection.prepare_cached(
+        "INSERT OR REPLACE INTO hr_samples (session_id, at_s, bpm) VALUES (?1, ?2, ?3)",
+    )?;
+    for sample in heart_rate {
+        insert_sample.execute(rusqlite::params![session_id, sample.at_s, sample.bpm])?;
+    }
+    Ok(())
+}
+
+/// Loads the sessions which meet the SQL `condition`, the oldest first.
+fn query_sessions(
+    connection: &Connection,
+    condition: &str,
+    params: &[&dyn ToSql],
+) -> rusqlite::Result<Vec<SessionRecord>> {
+    let mut sessions = Vec::new();
+    // The index of each session in `sessions` by its id in the database
+    let mut indexes = HashMap::new();
+    let mut statement = connection.prepare(&format!(
+        "SELECT id, training_id, training_name, started, duration_s, pause_s, sets, completed,
+            rpe, note, kcal, hr_recovery_bpm
+        FROM sessions WHERE {condition} ORDER BY started"
+    ))?;
+    let mut rows = statement.query(params)?;
+    while let Some(row) = rows.next()? {
+        indexes.insert(row.get::<_, i64>(0)?, sessions.len());
+        sessions.push(SessionRecord {
+            training_id: row.get(1)?,
+            training_name: row.get(2)?,
+            started: row.get(3)?,
+            duration_s: row.get(4)?,
+            pause_s: row.get(5)?,
+            sets: row.get(6)?,
+            completed: row.get(7)?,
+            rounds: Vec::new(),
+            periods: Vec::new(),
+            marks: Vec::new(),
+            rpe: row.get(8)?,
+            note: row.get(9)?,
+            kcal: row.get(10)?,
+            hr_recovery_bpm: row.get(11)?,
+        });
+    }
+    // The rows of the other tables are read in order, by the primary key
+    let children = |columns: &str, table: &str| {
+        format!(
+            "SELECT session_id, {columns} FROM {table}
+            WHERE session_id IN (SELECT id FROM sessions WHERE {condition})
+            ORDER BY session_id, position"
+        )
+    };
+    let mut statement = connection.prepare(&children("exercise_s, rest_s, skipped", "rounds"))?;
+    let mut rows = statement.query(params)?;
+    while let Some(row) = rows.next()? {
+        sessions[indexes[&row.get::<_, i64>(0)?]]
+            .rounds
+            .push(RoundLog {
+                exercise_s: row.get(1)?,
+                rest_s: row.get(2)?,
+                skipped: row.get(3)?,
+            });
+    }
+    let mut statement =
+        connection.prepare(&children("phase, length_s, skipped, intensity", "periods"))?;
+    let mut rows = statement.query(params)?;
+    while let Some(row) = rows.next()? {
+        let Some(phase) = Phase::from_setting(&row.get::<_, String>(1)?) else {
+            continue;
+        };
+        sessions[indexes[&row.get::<_, i64>(0)?]]
+            .periods
+            .push(PeriodLog {
+                phase,
+                length_s: row.get(2)?,
+                skipped: row.get(3)?,
+                intensity: row
+                    .get::<_, Option<String>>(4)?
+                    .as_deref()
+                    .and_then(Intensity::from_setting),
+            });
+    }
+    let mut statement = connection.prepare(&children("at_s, kind, duration_s", "marks"))?;
+    let mut rows = statement.query(params)?;
+    while let Some(row) = rows.next()? {
+        let Some(kind) = SessionMarkKind::from_setting(&row.get::<_, String>(2)?, row.get(3)?)
+        else {
+            continue;
+        };
+        sessions[indexes[&row.get::<_, i64>(0)?]]
+            .marks
+            .push(SessionMark {
+                at_s: row.get(1)?,
+                kind,
+            });
+    }
+    Ok(sessions)
+}
+
+/// Loads the readings of the heart rate monitor, by the training id and the start of their sessions.
+fn query_heart_rate(
+    connection: &Connection,
+) -> rusqlite::Result<HashMap<(String, i64), Vec<HeartRateSample>>> {
+    let mut heart_rate: HashMap<(String, i64), Vec<HeartRateSample>> = HashMap::new();
+    let mut statement = connection.prepare(
+        "SELECT training_id, started, at_s, bpm FROM hr_samples
+        JOIN sessions ON sessions.id = hr_samples.session_id
+        ORDER BY session_id, at_s",
+    )?;
+    let mut rows = statement.query([])?;
+    while let Some(row) = rows.next()? {
+        heart_rate
+            .entry((row.get(0)?, row.get(1)?))
+            .or_default()
+            .push(HeartRateSample {
+                at_s: row.get(2)?,
+                bpm: row.get(3)?,
+            });
+    }
+    Ok(heart_rate)
+}
+
+fn load_goals_json() -> json::JsonValue {
+    std::fs::read_to_string(storage::data_dir().join(GOALS_FILE_NAME))
+        .ok()
+        .and_then(|raw_json| json::parse(&raw_json).ok())
+        .filter(json::JsonValue::is_object)
+        .unwrap_or_else(json::JsonValue::new_object)
+}
+
+/// Records the weekly goal which applies to the week, so that it can later be told if it was met.
+pub fn record_weekly_goal(week: i64, goal: WeeklyGoal) {
+    let mut goals = load_goals_json();
+    let entry = json::object! {
+        kind: goal.kind.to_setting(),
+        target: goal.target,
+    };
+    if goals[week.to_string()] == entry {
+        return;
+    }
+    goals[week.to_string()] = entry;
+    if let Err(err) = storage::write_atomically(
+        &storage::data_dir().join(GOALS_FILE_NAME),
+        &json::stringify_pretty(goals, 4),
+    ) {
+        // Translators: Error message printed to the console when the weekly goal cannot be written to the history
+        tracing::warn!(%err, "{}", gettext("Could not record the weekly goal"));
+    }
+}
+
+/// Loads the recorded weekly goals, keyed by the week.
+pub fn load_weekly_goals() -> Vec<(i64, WeeklyGoal)> {
+    load_goals_json()
+        .entries()
+        .filter_map(|(week, goal)| {
+            Some((
+                week.parse().ok()?,
+                WeeklyGoal {
+                    kind: WeeklyGoalKind::from_setting(goal["kind"].as_str()?),
+                    target: goal["target"].as_u32()?,
+                },
+            ))
+        })
+        .collect()
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn format_csv_time(unix: i64) -> String {
+    relm4::gtk::glib::DateTime::from_unix_local(unix)
+        .and_then(|date_time| date_time.format("%F %T"))
+        .map(String::from)
+        .unwrap_or_default()
+}
+
+/// Writes the sessions as CSV, one row per session.
+pub fn sessions_to_csv(sessions: &[SessionRecord]) -> String {
+    let mut csv = String::from(
+        "started,training_id,training,duration_s,pause_s,sets,rounds,completed,rpe,note,kcal,hr_recovery_bpm\n",
+    );
+    for session in sessions {
+        csv.push_str(&format!(
+            "{},{},{},{},{},{},{},{},{},{},{},{}\n",
+            format_csv_time(session.started),
+            csv_field(&session.training_id),
+            csv_field(&session.training_name),
+            session.duration_s,
+            session.pause_s,
+            session.sets,
+            session.rounds.len(),
+            session.completed,
+            session.rpe.map(|rpe| rpe.to_string()).unwrap_or_default(),
+            csv_field(&session.note),
+            session
+                .kcal
+                .map(|kcal| kcal.to_string())
+                .unwrap_or_default(),
+            session
+                .hr_recovery_bpm
+                .map(|bpm| bpm.to_string())
+                .unwrap_or_default()
+        ));
+    }
+    csv
+}
+
+/// Writes the rounds of the sessions as CSV, one row per round.
+pub fn rounds_to_csv(sessions: &[SessionRecord]) -> String {
+    let mut csv = String::from("started,training,round,exercise_s,rest_s,skipped\n");
+    for session in sessions {
+        for (index, round) in session.rounds.iter().enumerate() {
+            csv.push_str(&format!(
+                "{},{},{},{},{},{}\n",
+                format_csv_time(session.started),
+                csv_field(&session.training_name),
+                index + 1,
+                round.exercise_s,
+                round.rest_s,
+                round.skipped
+            ));
+        }
+    }
+    csv
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn database() -> Connection {
+        let connection = Connection::open_in_memory().unwrap();
+        set_up(&connection).unwrap();
+        connection
+    }
+
+    fn session(training_id: &str, started: i64) -> SessionRecord {
+        SessionRecord {
+            training_id: training_id.into(),
+            training_name: "Tabata".into(),
+            started,
+            duration_s: 50,
+            sets: 2,
+            completed: true,
+            rounds: vec![
+                RoundLog {
+                    exercise_s: 20,
+                    rest_s: 10,
+                    skipped: false,
+                },
+                RoundLog {
+                    exercise_s: 20,
+                    rest_s: 0,
+                    skipped: false,
+                },
+            ],
+            periods: vec![
+                PeriodLog {
+                    phase: Phase::Exercise,
+                    length_s: 20,
+                    skipped: false,
+                    intensity: Some(Intensity::Hard),
+                },
+                PeriodLog {
+                    phase: Phase::Rest,
+                    length_s: 10,
+                    skipped: false,
+                    intensity: None,
+                },
+                PeriodLog {
+                    phase: Phase::Exercise,
+                    length_s: 20,
+                    skipped: false,
+                    intensity: Some(Intensity::Hard),
+                },
+            ],
+            marks: vec![SessionMark {
+                at_s: 25,
+                kind: SessionMarkKind::Paused { pause_s: 7 },
+            }],
+            pause_s: 7,
+            rpe: Some(8),
+            note: "Windy".into(),
+            kcal: Some(12),
+            hr_recovery_bpm: None,
+        }
+    }
+
+    fn started(sessions: &[SessionRecord]) -> Vec<i64> {
+        sessions.iter().map(|session| session.started).collect()
+    }
+
+    #[test]
+    fn loads_recorded_sessions_oldest_first() {
+        let connection = database();
+        let recorded = [session("a", 300), session("b", 100), session("a", 200)];
+        for session in &recorded {
+            insert_session(&connection, session, &[]).unwrap();
+        }
+        let loaded = query_sessions(&connection, "TRUE", &[]).unwrap();
+        assert_eq!(started(&loaded), vec![100, 200, 300]);
+        let loaded = &loaded[2];
+        assert_eq!(loaded.rounds.len(), 2);
+        assert_eq!(loaded.periods, recorded[0].periods);
+        assert_eq!(loaded.marks, recorded[0].marks);
+        assert_eq!(
+            (loaded.rpe, loaded.note.as_str(), loaded.kcal),
+            (Some(8), "Windy", Some(12))
+        );
+    }
+
+    #[test]
+    fn loads_sessions_by_condition() {
+        let connection = database();
+        for session in [session("a", 100), session("b", 200), session("a", 300)] {
+            insert_session(&connection, &session, &[]).unwrap();
+        }
+        let of_a = query_sessions(&connection, "training_id = ?1", &[&"a"]).unwrap();
+        assert_eq!(started(&of_a), vec![100, 300]);
+        // The rows of the other tables belong to the loaded sessions only
+        assert!(of_a.iter().all(|session| session.rounds.len() == 2));
+        let since = query_sessions(&connection, "started >= ?1", &[&200]).unwrap();
+        assert_eq!(started(&since), vec![200, 300]);
+    }
+
+    #[test]
+    fn records_session_once() {
+        let connection = database();
+        insert_session(&connection, &session("a", 100), &[]).unwrap();
+        insert_session(&connection, &session("a", 100), &[]).unwrap();
+        let loaded = query_sessions(&connection, "TRUE", &[]).unwrap();
+        assert_eq!(loaded.len(), 1);
+        assert_eq!(loaded[0].rounds.len(), 2);
+    }
+
+    #[test]
+    fn keeps_heart_rate_samples() {
+        let connection = database();
+        let heart_rate = [
+            HeartRateSample { at_s: 0, bpm: 96 },
+            HeartRateSample { at_s: 1, bpm: 101 },
+        ];
+        insert_session(&connection, &session("a", 100), &heart_rate).unwrap();
+        insert_session(&connection, &session("b", 200), &[]).unwrap();
+        let loaded = query_heart_rate(&connection).unwrap();
+        assert_eq!(loaded.len(), 1);
+        assert_eq!(loaded[&("a".to_owned(), 100)], heart_rate);
+    }
+
+    #[test]
+    fn imports_legacy_history() {
+        let mut connection = database();
+        let legacy = format!(
+            "{}\nnot json\n{}\n",
+            json::stringify(Session::from(&session("a", 100)).to_json()),
+            r#"{"training_id": "b", "training_name": "Plank", "started": 50, "duration_s": 60}"#,
+        );
+        import_legacy_history(&mut connection, &legacy).unwrap();
+        let loaded = query_sessions(&connection, "TRUE", &[]).unwrap();
+        assert_eq!(started(&loaded), vec![50, 100]);
+        assert!(loaded[0].completed);
+        assert_eq!(loaded[1].marks, session("a", 100).marks);
+    }
+}
@@ -0,0 +1,189 @@
+use crate::settings;
+use crate::training_setup::TrainingSetup;
+use crate::warmup::Warmup;
+use crate::workout_mode::CLASSIC_MODE_ID;
+use gettextrs::gettext;
+
+const NAME_KEYS: [&str; 5] = ["name", "title", "timer", "workout", "label"];
+const SETS_KEYS: [&str; 7] = [
+    "sets",
+    "rounds",
+    "cycles",
+    "repeats",
+    "repetitions",
+    "laps",
+    "intervals",
+];
+const EXERCISE_KEYS: [&str; 8] = [
+    "exercise",
+    "exercises",
+    "work",
+    "worktime",
+    "high",
+    "highintensity",
+    "active",
+    "on",
+];
+const REST_KEYS: [&str; 7] = [
+    "rest",
+    "rests",
+    "resttime",
+    "low",
+    "lowintensity",
+    "recovery",
+    "off",
+];
+const PREPARE_KEYS: [&str; 7] = [
+    "prepare",
+    "prepares",
+    "preparation",
+    "warmup",
+    "prep",
+    "countdown",
+    "getready",
+];
+
+/// Reduces a column or key name to lowercase letters, so that e.g. "Work Time", "work_time" and "workTime" match.
+fn normalize_key(key: &str) -> String {
+    key.chars()
+        .filter(char::is_ascii_alphanumeric)
+        .map(|c| c.to_ascii_lowercase())
+        .collect::<String>()
+        .trim_end_matches("seconds")
+        .trim_end_matches("secs")
+        .trim_end_matches("sec")
+        .to_string()
+}
+
+/// Parses a duration in seconds, e.g. "45", "45s", "1:30" or "0:01:30".
+fn parse_duration(raw: &str) -> Option<usize> {
+    let raw = raw.trim().trim_end_matches('s').trim();
+    raw.split(':').try_fold(0usize, |total, part| {
+        Some(total * 60 + part.trim().parse::<usize>().ok()?)
+    })
+}
+
+/// Builds a training from the fields found under the known names.
+/// `field` returns the value of the first of the given names which is present.
+fn training_from_fields(field: impl Fn(&[&str]) -> Option<String>) -> Option<TrainingSetup> {
+    let default = TrainingSetup::default();
+    let exercise_s = parse_duration(&field(&EXERCISE_KEYS)?).filter(|s| *s > 0)?;
+    let sets = match field(&SETS_KEYS) {
+        Some(sets) => sets.trim().parse::<usize>().ok().filter(|sets| *sets > 0)?,
+        None => 1,
+    };
+    let rest_s = match field(&REST_KEYS) {
+        Some(rest) => parse_duration(&rest)?,
+        None => default.rest_s,
+    };
+    let prepare_s = match field(&PREPARE_KEYS) {
+        Some(prepare) => parse_duration(&prepare)?,
+        None => default.prepare_s,
+    };
+    let name = field(&NAME_KEYS)
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty())
+        // Translators: The name of a training imported from another application, if it has no name
+        .unwrap_or_else(|| gettext("Imported Training"));
+    Some(TrainingSetup {
+        id: TrainingSetup::new_id(),
+        revision: 0,
+        name,
+        exercise_s,
+        rest_s,
+        sets,
+        prepare_s,
+        pinned: false,
+        progression: None,
+        mode: CLASSIC_MODE_ID.to_owned(),
+        exercise_intensity: None,
+        rest_intensity: None,
+        images: Vec::new(),
+        warmup: Warmup::Countdown,
+    })
+}
+
+fn json_field(object: &json::JsonValue, keys: &[&str]) -> Option<String> {
+    object
+        .entries()
+        .find(|(key, value)| !value.is_null() && keys.contains(&normalize_key(key).as_str()))
+        .map(|(_, value)| match value.as_str() {
+            Some(value) => value.to_string(),
+            None => value.dump(),
+        })
+}
+
+/// Imports a single timer object or an array of them from the JSON export of another application.
+fn import_json(parsed: &json::JsonValue) -> Option<Vec<TrainingSetup>> {
+    let objects: Vec<&json::JsonValue> = if parsed.is_array() {
+        parsed.members().collect()
+    } else if parsed.is_object() {
+        vec![parsed]
+    } else {
+        return None;
+    };
+    objects
+        .into_iter()
+        .map(|object| training_from_fields(|keys| json_field(object, keys)))
+        .collect()
+}
+
+/// Splits a CSV line into fields, handling quoted fields with escaped quotes.
+fn split_csv_line(line: &str, delimiter: char) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if in_quotes && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => in_quotes = !in_quotes,
+            c if c == delimiter && !in_quotes => fields.push(std::mem::take(&mut field)),
+            c => field.push(c),
+        }
+    }
+    fields.push(field);
+    fields
+}
+
+/// Imports a generic interval CSV with a header row, one training per line.
+fn import_csv(raw: &str) -> Option<Vec<TrainingSetup>> {
+    let mut lines = raw.lines().filter(|line| !line.trim().is_empty());
+    let header = lines.next()?;
+    let delimiter = [',', ';', '\t']
+        .into_iter()
+        .max_by_key(|delimiter| header.matches(*delimiter).count())?;
+    let columns: Vec<String> = split_csv_line(header, delimiter)
+        .iter()
+        .map(|column| normalize_key(column))
+        .collect();
+    let trainings: Option<Vec<TrainingSetup>> = lines
+        .map(|line| {
+            let values = split_csv_line(line, delimiter);
+            training_from_fields(|keys| {
+                columns
+                    .iter()
+                    .position(|column| keys.contains(&column.as_str()))
+                    .and_then(|index| values.get(index).cloned())
+                    .filter(|value| !value.trim().is_empty())
+            })
+        })
+        .collect();
+    trainings.filter(|trainings| !trainings.is_empty())
+}
+
+/// Imports trainings from a file, either exported by this application or by another interval timer.
+/// Returns `None` if the format is not recognized.
+pub fn import_trainings(raw: &str) -> Option<Vec<TrainingSetup>> {
+    if let Some(training) = settings::parse_training_setup_json(raw) {
+        return Some(vec![training]);
+    }
+    match json::parse(raw) {
+        Ok(parsed) => import_json(&parsed),
+        Err(_) => import_csv(raw),
+    }
+    .filter(|trainings| !trainings.is_empty())
+}
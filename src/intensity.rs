@@ -0,0 +1,54 @@
+//! How hard a period of a training is meant to be. The timer page is tinted by it during the
+//! period, and it is recorded with the periods of a session, so that the statistics can tell
+//! how much of the training was of high intensity.
+
+use gettextrs::gettext;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Intensity {
+    Easy,
+    Moderate,
+    Hard,
+}
+
+/// The CSS classes of the intensities, for removing them from the timer page.
+pub const INTENSITY_CSS_CLASSES: [&str; 3] =
+    ["intensity-easy", "intensity-moderate", "intensity-hard"];
+
+impl Intensity {
+    /// All intensities, in the order of the editor and of the statistics.
+    pub const ALL: [Intensity; 3] = [Intensity::Easy, Intensity::Moderate, Intensity::Hard];
+
+    /// The name of the intensity in stored files, e.g. "hard".
+    pub fn to_setting(self) -> &'static str {
+        match self {
+            Intensity::Easy => "easy",
+            Intensity::Moderate => "moderate",
+            Intensity::Hard => "hard",
+        }
+    }
+
+    pub fn from_setting(name: &str) -> Option<Self> {
+        match name {
+            "easy" => Some(Intensity::Easy),
+            "moderate" => Some(Intensity::Moderate),
+            "hard" => Some(Intensity::Hard),
+            _ => None,
+        }
+    }
+
+    pub fn title(self) -> String {
+        match self {
+            // Translators: The intensity of a period of a training, shown on the timer page and in the statistics
+            Intensity::Easy => gettext("Easy"),
+            // Translators: The intensity of a period of a training, shown on the timer page and in the statistics
+            Intensity::Moderate => gettext("Moderate"),
+            // Translators: The intensity of a period of a training, shown on the timer page and in the statistics
+            Intensity::Hard => gettext("Hard"),
+        }
+    }
+
+    pub fn css_class(self) -> &'static str {
+        INTENSITY_CSS_CLASSES[self as usize]
+    }
+}
@@ -0,0 +1,143 @@
+//! Structured logs with `tracing`. Warnings and errors are printed to the console, all records
+//! down to the debug level are kept in memory for the debug window and bug reports.
+//! `G_MESSAGES_DEBUG=hiit` (or `all`) prints the debug records to the console as well.
+
+use std::{
+    collections::VecDeque,
+    fmt::Write,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Mutex,
+    },
+};
+use tracing::{field, span, Event, Level, Metadata, Subscriber};
+
+/// The number of records which are kept in memory, the older ones are dropped.
+const MAX_RECORDS: usize = 1000;
+
+#[derive(Debug, Clone)]
+pub struct LogRecord {
+    /// Counts the records since the start, to find the new ones.
+    pub sequence: u64,
+    /// Wall-clock time in microseconds since the Unix epoch.
+    pub time_us: i64,
+    pub level: Level,
+    /// The module which logged the record, e.g. "hiit::sync".
+    pub target: String,
+    /// The message followed by the fields, e.g. `Synchronized trainings count=4`.
+    pub text: String,
+}
+
+impl std::fmt::Display for LogRecord {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let time = relm4::gtk::glib::DateTime::from_unix_local_usec(self.time_us)
+            .and_then(|time| time.format("%T.%f"))
+            .map(String::from)
+            .unwrap_or_default();
+        write!(
+            f,
+            "{} {:>5} {}: {}",
+            time, self.level, self.target, self.text
+        )
+    }
+}
+
+static RECORDS: Mutex<VecDeque<LogRecord>> = Mutex::new(VecDeque::new());
+
+/// Formats the message and the fields of an event into a single line.
+#[derive(Default)]
+struct TextVisitor {
+    message: String,
+    fields: String,
+}
+
+impl field::Visit for TextVisitor {
+    fn record_debug(&mut self, field: &field::Field, value: &dyn std::fmt::Debug) {
+        if field.name() == "message" {
+            let _ = write!(self.message, "{:?}", value);
+        } else {
+            let _ = write!(self.fields, " {}={:?}", field.name(), value);
+        }
+    }
+
+    fn record_str(&mut self, field: &field::Field, value: &str) {
+        if field.name() == "message" {
+            self.message.push_str(value);
+        } else {
+            let _ = write!(self.fields, " {}={:?}", field.name(), value);
+        }
+    }
+}
+
+/// Keeps the records in memory and prints the important ones. Spans are not used by the application.
+struct Recorder {
+    print_debug: bool,
+    next_sequence: AtomicU64,
+}
+
+impl Subscriber for Recorder {
+    fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+        *metadata.level() <= Level::DEBUG
+    }
+
+    fn new_span(&self, _span: &span::Attributes<'_>) -> span::Id {
+        span::Id::from_u64(1)
+    }
+
+    fn record(&self, _span: &span::Id, _values: &span::Record<'_>) {}
+
+    fn record_follows_from(&self, _span: &span::Id, _follows: &span::Id) {}
+
+    fn event(&self, event: &Event<'_>) {
+        let mut visitor = TextVisitor::default();
+        event.record(&mut visitor);
+        let metadata = event.metadata();
+        let record = LogRecord {
+            sequence: self.next_sequence.fetch_add(1, Ordering::Relaxed),
+            time_us: relm4::gtk::glib::real_time(),
+            level: *metadata.level(),
+            target: metadata.target().to_owned(),
+            text: visitor.message + visitor.fields.as_str(),
+        };
+        if record.level <= Level::WARN || self.print_debug {
+            eprintln!("{}", record);
+        }
+        if let Ok(mut records) = RECORDS.lock() {
+            if records.len() == MAX_RECORDS {
+                records.pop_front();
+            }
+            records.push_back(record);
+        }
+    }
+
+    fn enter(&self, _span: &span::Id) {}
+
+    fn exit(&self, _span: &span::Id) {}
+}
+
+/// Starts recording the logs, before anything is logged.
+pub fn init() {
+    let print_debug = std::env::var("G_MESSAGES_DEBUG").is_ok_and(|domains| {
+        domains
+            .split([' ', ','])
+            .any(|domain| domain == "hiit" || domain == "all")
+    });
+    let _ = tracing::subscriber::set_global_default(Recorder {
+        print_debug,
+        next_sequence: AtomicU64::new(0),
+    });
+}
+
+/// The records which were logged after the one with the given sequence number, or all with `None`.
+pub fn records_after(sequence: Option<u64>) -> Vec<LogRecord> {
+    RECORDS
+        .lock()
+        .map(|records| {
+            records
+                .iter()
+                .filter(|record| sequence.is_none_or(|sequence| record.sequence > sequence))
+                .cloned()
+                .collect()
+        })
+        .unwrap_or_default()
+}
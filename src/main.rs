@@ -1,28 +1,156 @@
+mod achievements;
 mod app;
+mod app_state;
+mod appearance;
+mod audio_backend;
+mod background;
+mod backup;
+mod calories;
+mod cli;
 mod config;
+mod crash;
+mod debug_window;
+mod deep_link;
+mod error;
+mod hiit_core;
+mod history;
+mod importers;
+mod intensity;
+mod logging;
+mod portable;
+mod power_saving;
+mod preferences_dialog;
+mod print_plan;
+mod profiles;
+mod progress_ring;
+mod qr_code;
+mod reminder;
+mod replay_dialog;
 mod settings;
 mod setup;
+mod share_dialog;
 mod shortcuts_window;
+mod snapshot_dialog;
+mod statistics;
+mod statistics_page;
+mod storage;
+mod summary_card;
+mod sync;
 mod training_editor;
+mod training_list;
 mod training_setup;
 mod training_timer;
+mod trophies_dialog;
+mod warmup;
+mod warmup_editor;
+mod workout_mode;
 use gettextrs::gettext;
-use relm4::{actions::AccelsPlus, gtk::prelude::*};
+use relm4::{
+    actions::AccelsPlus,
+    gtk::{gio, glib, prelude::*},
+};
+use std::cell::Cell;
 
 relm4::new_action_group!(AppActionGroup, "app");
 relm4::new_stateless_action!(QuitAction, AppActionGroup, "quit");
 
+thread_local! {
+    /// If the application was started at login and shall not show the window until activated again.
+    static START_IN_BACKGROUND: Cell<bool> = const { Cell::new(false) };
+    /// If the timer is shown for a group on a big screen from the start.
+    pub static START_IN_TV_MODE: Cell<bool> = const { Cell::new(false) };
+}
+
 fn main() {
-    setup::setup();
-    let (_stream, stream_handle) = rodio::OutputStream::try_default().unwrap_or_else(|err| {
-        panic!(
-            "{}: {}",
-            // Translators: Error message when cannot connect to the audio output
-            gettext("Could not create audio output stream"),
-            err
-        )
+    let args = portable::init(std::env::args().collect()).unwrap_or_else(|message| {
+        eprintln!("{}", message);
+        std::process::exit(2);
     });
+    if args.get(1).map(String::as_str) == Some(cli::RUN_COMMAND) {
+        // The timer runs in the terminal, e.g. over SSH, without GTK
+        setup::setup_headless();
+        std::process::exit(cli::run(&args[2..]));
+    }
+    setup::setup();
+    crash::install();
     let app = relm4::main_adw_application();
+    app.set_flags(if portable::is_portable() {
+        // Rather than handing over to an instance with other data
+        gio::ApplicationFlags::HANDLES_OPEN | gio::ApplicationFlags::NON_UNIQUE
+    } else {
+        gio::ApplicationFlags::HANDLES_OPEN
+    });
+    app.add_main_option(
+        "start",
+        glib::Char::from(b's'),
+        glib::OptionFlags::NONE,
+        glib::OptionArg::String,
+        // Translators: Description of the command line option which starts a training
+        &gettext("Start the training with the given name"),
+        // Translators: Placeholder of the training name in the description of the command line option
+        Some(&gettext("NAME")),
+    );
+    app.add_main_option(
+        background::BACKGROUND_OPTION,
+        glib::Char::from(b'b'),
+        glib::OptionFlags::NONE,
+        glib::OptionArg::None,
+        // Translators: Description of the command line option which starts the application without showing the window
+        &gettext("Start in the background to send reminders"),
+        None,
+    );
+    app.add_main_option(
+        "tv",
+        glib::Char::from(b't'),
+        glib::OptionFlags::NONE,
+        glib::OptionArg::None,
+        // Translators: Description of the command line option which shows the timer for a group on a big screen
+        &gettext("Show the timer in TV mode"),
+        None,
+    );
+    app.connect_handle_local_options(|app, options| {
+        if options.contains(background::BACKGROUND_OPTION) {
+            START_IN_BACKGROUND.set(true);
+        }
+        if options.contains("tv") {
+            START_IN_TV_MODE.set(true);
+        }
+        let Ok(Some(name)) = options.lookup::<String>("start") else {
+            return -1;
+        };
+        if let Err(err) = app.register(gio::Cancellable::NONE) {
+            // Translators: Error message printed to the console when the application cannot be registered on the session bus
+            eprintln!("{}: {}", gettext("Could not register the application"), err);
+            return 1;
+        }
+        // The training is started by the primary instance, which may be this one or an already running one
+        app.open(&[gio::File::for_uri(&deep_link::start_uri(&name))], "");
+        if app.is_remote() {
+            0
+        } else {
+            -1
+        }
+    });
+    app.connect_startup(reminder::schedule_daily_reminder);
+    app.connect_activate(|app| {
+        let Some(window) = app.active_window() else {
+            return;
+        };
+        if START_IN_BACKGROUND.replace(false) {
+            // Keep running after the window is closed, so reminders are still sent
+            window.set_hide_on_close(true);
+        } else {
+            window.present();
+        }
+    });
+    app.connect_open(|app, files, _hint| {
+        for file in files {
+            app::APP_BROKER.send(app::AppModelInput::OpenUri(file.uri().into()));
+        }
+        if let Some(window) = app.active_window() {
+            window.present();
+        }
+    });
 
     let mut actions = relm4::actions::RelmActionGroup::<AppActionGroup>::new();
     let quit_action = {
@@ -35,6 +163,9 @@ fn main() {
     actions.register_for_main_application();
     app.set_accelerators_for_action::<QuitAction>(&["<Control>q"]);
 
-    let app = relm4::RelmApp::from_app(app);
-    app.run::<app::AppModel>(stream_handle);
+    let app = relm4::RelmApp::from_app(app)
+        .with_args(args)
+        .with_broker(&app::APP_BROKER)
+        .visible_on_activate(false);
+    app.run::<app::AppModel>(());
 }
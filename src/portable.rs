@@ -0,0 +1,57 @@
+//! A portable installation keeps all of its data in a single folder, e.g. on a USB stick or in a
+//! test sandbox: the trainings, the history, the backups, the configuration and the preferences,
+//! which are stored in a key file there rather than in GSettings.
+
+use gettextrs::gettext;
+use std::path::{Path, PathBuf};
+
+/// The option which chooses the folder, e.g. `hiit --data-dir /run/media/stick/hiit`.
+pub const DATA_DIR_OPTION: &str = "--data-dir";
+/// The environment variable which chooses the folder, unless the option is given.
+pub const DATA_DIR_VARIABLE: &str = "HIIT_DATA_DIR";
+
+/// Moves the directories of GLib and the settings backend into the folder.
+/// GLib keeps the directories once they were looked up, so this has to happen first.
+fn redirect(data_dir: &Path) {
+    std::env::set_var(DATA_DIR_VARIABLE, data_dir);
+    std::env::set_var("XDG_DATA_HOME", data_dir.join("data"));
+    std::env::set_var("XDG_CONFIG_HOME", data_dir.join("config"));
+    std::env::set_var("XDG_CACHE_HOME", data_dir.join("cache"));
+    // Stored in config/glib-2.0/settings/keyfile
+    std::env::set_var("GSETTINGS_BACKEND", "keyfile");
+}
+
+/// Takes the data directory out of the arguments, or from the environment, and keeps all data in
+/// it from now on. Returns the other arguments, or an error message if the folder is missing.
+/// Must be called at the start, before any thread is started.
+pub fn init(args: Vec<String>) -> Result<Vec<String>, String> {
+    let mut data_dir = std::env::var_os(DATA_DIR_VARIABLE)
+        .filter(|data_dir| !data_dir.is_empty())
+        .map(PathBuf::from);
+    let mut other_args = Vec::with_capacity(args.len());
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        if arg == DATA_DIR_OPTION {
+            let Some(dir) = args.next() else {
+                // Translators: Error message printed to the console when --data-dir is not followed by a folder
+                return Err(gettext("--data-dir needs a folder"));
+            };
+            data_dir = Some(dir.into());
+        } else if let Some(dir) = arg.strip_prefix("--data-dir=") {
+            data_dir = Some(dir.into());
+        } else {
+            other_args.push(arg);
+        }
+    }
+    if let Some(data_dir) = data_dir {
+        // Relative to where the application was started, not where it runs later
+        let data_dir = std::path::absolute(&data_dir).unwrap_or(data_dir);
+        redirect(&data_dir);
+    }
+    Ok(other_args)
+}
+
+/// If all data is kept in the folder given by `--data-dir` or `HIIT_DATA_DIR`.
+pub fn is_portable() -> bool {
+    std::env::var_os(DATA_DIR_VARIABLE).is_some_and(|data_dir| !data_dir.is_empty())
+}
@@ -0,0 +1,31 @@
+use relm4::{
+    binding::{Binding, BoolBinding},
+    gtk::{self, gio, prelude::*},
+};
+use std::rc::Rc;
+
+/// Power saving is on if it was chosen in the preferences, or the system's power saver is on.
+pub fn is_enabled(power_saving: &BoolBinding) -> bool {
+    power_saving.get() || gio::PowerProfileMonitor::get_default().is_power_saver_enabled()
+}
+
+/// Turns the animations of the application off while power saving is on.
+pub fn setup(power_saving: &BoolBinding) {
+    let Some(gtk_settings) = gtk::Settings::default() else {
+        return;
+    };
+    let system_animations = gtk_settings.is_gtk_enable_animations();
+    let apply = Rc::new(move |power_saving: &BoolBinding| {
+        gtk_settings.set_gtk_enable_animations(system_animations && !is_enabled(power_saving));
+    });
+    apply(power_saving);
+    {
+        let apply = apply.clone();
+        power_saving.connect_notify_local(Some("value"), move |binding, _| apply(binding));
+    }
+    let power_saving = power_saving.clone();
+    gio::PowerProfileMonitor::get_default()
+        .connect_notify_local(Some("power-saver-enabled"), move |_, _| {
+            apply(&power_saving)
+        });
+}
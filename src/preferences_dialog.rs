@@ -0,0 +1,360 @@
+use crate::audio_backend;
+use crate::settings::{
+    self, Appearance, GlobalTrainingSetup, ReminderSettings, SyncSettings, TrainingDefaults,
+};
+use crate::warmup_editor::{WarmupEditor, WarmupEditorInit};
+use gettextrs::gettext;
+use relm4::{
+    adw::{self, prelude::*},
+    gtk::{self, gdk},
+    prelude::*,
+    RelmObjectExt,
+};
+
+pub struct PreferencesDialog {
+    global_setup: GlobalTrainingSetup,
+    training_defaults: TrainingDefaults,
+    appearance: Appearance,
+    sync_settings: SyncSettings,
+    reminder_settings: ReminderSettings,
+    /// Edits the default warmup, which is saved on every change.
+    warmup_editor: Controller<WarmupEditor>,
+}
+
+pub struct PreferencesDialogInit {
+    pub global_setup: GlobalTrainingSetup,
+    pub training_defaults: TrainingDefaults,
+    pub appearance: Appearance,
+    pub sync_settings: SyncSettings,
+    pub reminder_settings: ReminderSettings,
+}
+
+/// The index of the custom palette in the palette row.
+const CUSTOM_PALETTE: u32 = 2;
+
+#[derive(Debug)]
+pub enum PreferencesDialogOutput {
+    Closed,
+}
+
+#[relm4::component(pub)]
+impl SimpleComponent for PreferencesDialog {
+    type Init = PreferencesDialogInit;
+    type Input = ();
+    type Output = PreferencesDialogOutput;
+
+    view! {
+        adw::PreferencesDialog {
+            // Translators: The title of the preferences dialog
+            set_title: &gettext("Preferences"),
+            set_search_enabled: true,
+            connect_closed[sender] => move |_| {
+                let _ = sender.output(PreferencesDialogOutput::Closed);
+            },
+            add = &adw::PreferencesPage {
+                add = &adw::PreferencesGroup {
+                    // Translators: The title of the preferences group of the sounds
+                    set_title: &gettext("Sound"),
+                    adw::ActionRow {
+                        // Translators: The title of the row which sets the volume of the beeping sound
+                        set_title: &gettext("Volume"),
+                        add_suffix = &gtk::Scale {
+                            set_hexpand: true,
+                            set_valign: gtk::Align::Center,
+                            set_adjustment = &gtk::Adjustment {
+                                set_lower: 0.0,
+                                set_upper: 1.0,
+                                set_step_increment: 0.1,
+                                add_binding: (&model.global_setup.beep_volume, "value"),
+                            },
+                        },
+                    },
+                    adw::SpinRow {
+                        // Translators: The title of the row which sets how many seconds are counted down with beeps
+                        set_title: &gettext("Countdown"),
+                        // Translators: The subtitle of the row which sets how many seconds are counted down with beeps
+                        set_subtitle: &gettext("Seconds with a beep before each period ends"),
+                        set_adjustment: Some(&gtk::Adjustment::new(0.0, 0.0, 10.0, 1.0, 1.0, 0.0)),
+                        add_binding: (&model.global_setup.countdown_s, "value"),
+                    },
+                    adw::SpinRow {
+                        // Translators: The title of the row which sets the gap to get ready between a rest and the next exercise
+                        set_title: &gettext("Get Ready"),
+                        // Translators: The subtitle of the row which sets the gap to get ready between a rest and the next exercise
+                        set_subtitle: &gettext("Seconds with a tick between each rest and the next exercise"),
+                        set_adjustment: Some(&gtk::Adjustment::new(0.0, 0.0, 5.0, 1.0, 1.0, 0.0)),
+                        add_binding: (&model.global_setup.transition_s, "value"),
+                    },
+                    adw::SwitchRow {
+                        // Translators: The title of the switch which flashes the timer along with the beeps
+                        set_title: &gettext("Flash the Screen"),
+                        // Translators: The subtitle of the switch which flashes the timer along with the beeps
+                        set_subtitle: &gettext("A visual cue at each transition and during the countdown"),
+                        add_binding: (&model.global_setup.screen_flash, "active"),
+                    },
+                    #[name = "audio_device_row"]
+                    adw::ComboRow {
+                        // Translators: The title of the row which selects the device which plays the beeps
+                        set_title: &gettext("Output Device"),
+                    },
+                },
+                add = &adw::PreferencesGroup {
+                    // Translators: The title of the preferences group of the battery usage
+                    set_title: &gettext("Battery"),
+                    adw::SwitchRow {
+                        // Translators: The title of the switch which makes the timer use less power
+                        set_title: &gettext("Power Saving"),
+                        // Translators: The subtitle of the switch which makes the timer use less power
+                        set_subtitle: &gettext("Fewer redraws and no animations for long sessions. Always on when the system saves power."),
+                        add_binding: (&model.global_setup.power_saving, "active"),
+                    },
+                    adw::SpinRow {
+                        // Translators: The title of the row which sets how often the timer redraws its progress
+                        set_title: &gettext("Update Interval"),
+                        // Translators: The subtitle of the row which sets how often the timer redraws its progress
+                        set_subtitle: &gettext("Milliseconds between redraws of the ring. Longer intervals wake the device less often."),
+                        set_adjustment: Some(&gtk::Adjustment::new(100.0, 16.0, 1000.0, 50.0, 100.0, 0.0)),
+                        add_binding: (&model.global_setup.tick_interval_ms, "value"),
+                    },
+                },
+                add = &adw::PreferencesGroup {
+                    // Translators: The title of the preferences group of the calorie estimate
+                    set_title: &gettext("Calories"),
+                    adw::SpinRow {
+                        // Translators: The title of the row which sets the body weight for the calorie estimate
+                        set_title: &gettext("Body Weight"),
+                        // Translators: The subtitle of the row which sets the body weight for the calorie estimate
+                        set_subtitle: &gettext("Kilograms, for estimating the burned calories. 0 to not estimate them."),
+                        set_adjustment: Some(&gtk::Adjustment::new(0.0, 0.0, 500.0, 1.0, 10.0, 0.0)),
+                        add_binding: (&model.global_setup.body_weight_kg, "value"),
+                    },
+                },
+                add = &adw::PreferencesGroup {
+                    // Translators: The title of the preferences group of the colors
+                    set_title: &gettext("Appearance"),
+                    adw::ComboRow {
+                        // Translators: The title of the row which selects if the application is light or dark
+                        set_title: &gettext("Style"),
+                        set_model: Some(&gtk::StringList::new(&[
+                            // Translators: The application is light or dark like the system
+                            &gettext("Follow System"),
+                            // Translators: The application is always light
+                            &gettext("Light"),
+                            // Translators: The application is always dark
+                            &gettext("Dark"),
+                        ])),
+                        add_binding: (&model.appearance.color_scheme, "selected"),
+                    },
+                    adw::ComboRow {
+                        // Translators: The title of the row which selects the colors of the preparation, exercise and rest periods
+                        set_title: &gettext("Period Colors"),
+                        set_model: Some(&gtk::StringList::new(&[
+                            // Translators: The default palette of the period colors
+                            &gettext("Default"),
+                            // Translators: The palette of the period colors which can be told apart with color blindness
+                            &gettext("Color Blind Friendly"),
+                            // Translators: The palette of the period colors which the user chooses
+                            &gettext("Custom"),
+                        ])),
+                        add_binding: (&model.appearance.palette, "selected"),
+                    },
+                    #[name = "warmup_color_row"]
+                    adw::ActionRow {
+                        // Translators: The title of the row which sets the color of the preparation period
+                        set_title: &gettext("Preparation"),
+                        #[name = "warmup_color"]
+                        add_suffix = &gtk::ColorDialogButton {
+                            set_valign: gtk::Align::Center,
+                            set_dialog: &gtk::ColorDialog::builder().with_alpha(false).build(),
+                        },
+                    },
+                    #[name = "exercise_color_row"]
+                    adw::ActionRow {
+                        // Translators: The title of the row which sets the color of the exercise period
+                        set_title: &gettext("Exercise"),
+                        #[name = "exercise_color"]
+                        add_suffix = &gtk::ColorDialogButton {
+                            set_valign: gtk::Align::Center,
+                            set_dialog: &gtk::ColorDialog::builder().with_alpha(false).build(),
+                        },
+                    },
+                    #[name = "rest_color_row"]
+                    adw::ActionRow {
+                        // Translators: The title of the row which sets the color of the rest period
+                        set_title: &gettext("Rest"),
+                        #[name = "rest_color"]
+                        add_suffix = &gtk::ColorDialogButton {
+                            set_valign: gtk::Align::Center,
+                            set_dialog: &gtk::ColorDialog::builder().with_alpha(false).build(),
+                        },
+                    },
+                },
+                add = &adw::PreferencesGroup {
+                    // Translators: The title of the preferences group of the values which new trainings start from
+                    set_title: &gettext("New Trainings"),
+                    adw::SpinRow {
+                        // Translators: The title of the row which sets the default number of sets of new trainings
+                        set_title: &gettext("Sets"),
+                        set_adjustment: Some(&gtk::Adjustment::new(1.0, 1.0, 999.0, 1.0, 5.0, 0.0)),
+                        add_binding: (&model.training_defaults.sets, "value"),
+                    },
+                    adw::SpinRow {
+                        // Translators: The title of the row which sets the default exercise time of new trainings
+                        set_title: &gettext("Exercise Time"),
+                        // Translators: The unit of the default durations of new trainings
+                        set_subtitle: &gettext("Seconds"),
+                        set_adjustment: Some(&gtk::Adjustment::new(1.0, 1.0, 3600.0, 1.0, 10.0, 0.0)),
+                        add_binding: (&model.training_defaults.exercise_s, "value"),
+                    },
+                    adw::SpinRow {
+                        // Translators: The title of the row which sets the default rest time of new trainings
+                        set_title: &gettext("Rest Time"),
+                        set_subtitle: &gettext("Seconds"),
+                        set_adjustment: Some(&gtk::Adjustment::new(0.0, 0.0, 3600.0, 1.0, 10.0, 0.0)),
+                        add_binding: (&model.training_defaults.rest_s, "value"),
+                    },
+                    adw::SpinRow {
+                        // Translators: The title of the row which sets the default preparation time of new trainings
+                        set_title: &gettext("Preparation Time"),
+                        set_subtitle: &gettext("Seconds"),
+                        set_adjustment: Some(&gtk::Adjustment::new(0.0, 0.0, 3600.0, 1.0, 10.0, 0.0)),
+                        add_binding: (&model.training_defaults.prepare_s, "value"),
+                    },
+                },
+                add: model.warmup_editor.widget(),
+                add = &adw::PreferencesGroup {
+                    // Translators: The title of the preferences group of the timer on a big screen for group classes
+                    set_title: &gettext("TV Mode"),
+                    // Translators: Description of the TV mode settings
+                    set_description: Some(&gettext("Enter the names of the participants separated by commas. In TV mode, each of them moves on to the next station every round.")),
+                    adw::EntryRow {
+                        // Translators: The title of the field for the names of the participants, separated by commas
+                        set_title: &gettext("Participants"),
+                        add_binding: (&model.global_setup.participants, "text"),
+                    },
+                },
+                add = &adw::PreferencesGroup {
+                    // Translators: The title of the preferences group of the synchronization
+                    set_title: &gettext("Synchronization"),
+                    // Translators: Description of the synchronization settings. Do not translate the example URI.
+                    set_description: Some(&gettext("Keep the training list and the history in a remote folder, e.g. on Nextcloud: davs://example.com/remote.php/dav/files/user/hiit")),
+                    adw::SwitchRow {
+                        // Translators: The title of the switch which enables synchronization
+                        set_title: &gettext("Synchronize Trainings"),
+                        add_binding: (&model.sync_settings.enabled, "active"),
+                    },
+                    adw::EntryRow {
+                        // Translators: The title of the field for the URI of the remote folder
+                        set_title: &gettext("Remote Folder"),
+                        set_input_purpose: gtk::InputPurpose::Url,
+                        add_binding: (&model.sync_settings.folder_uri, "text"),
+                    },
+                },
+                add = &adw::PreferencesGroup {
+                    // Translators: The title of the preferences group of the reminders
+                    set_title: &gettext("Reminders"),
+                    // Translators: Description of the reminder settings
+                    set_description: Some(&gettext("Get a notification every day at the given time, e.g. 07:30. Leave it empty to disable the reminder.")),
+                    adw::EntryRow {
+                        // Translators: The title of the field for the time of the daily reminder
+                        set_title: &gettext("Daily Reminder"),
+                        set_input_purpose: gtk::InputPurpose::Digits,
+                        add_binding: (&model.reminder_settings.reminder_time, "text"),
+                    },
+                    adw::SwitchRow {
+                        // Translators: The title of the switch which starts the application in the background at login
+                        set_title: &gettext("Start at Login"),
+                        // Translators: The subtitle of the switch which starts the application in the background at login
+                        set_subtitle: &gettext("Run in the background to send reminders"),
+                        add_binding: (&model.reminder_settings.autostart, "active"),
+                    },
+                },
+            },
+        }
+    }
+
+    fn init(
+        init: Self::Init,
+        root: Self::Root,
+        sender: ComponentSender<Self>,
+    ) -> ComponentParts<Self> {
+        let model = PreferencesDialog {
+            global_setup: init.global_setup,
+            training_defaults: init.training_defaults,
+            appearance: init.appearance,
+            sync_settings: init.sync_settings,
+            reminder_settings: init.reminder_settings,
+            warmup_editor: WarmupEditor::builder()
+                .launch(WarmupEditorInit {
+                    // Translators: The title of the preferences group of the steps of the default warmup
+                    title: gettext("Default Warmup"),
+                    // Translators: Description of the default warmup settings
+                    description: Some(gettext("Run during the preparation of the trainings whose warmup is the default warmup.")),
+                    steps: settings::load_default_warmup_from_gsettings(),
+                })
+                .connect_receiver(|_, steps| settings::save_default_warmup_to_gsettings(&steps)),
+        };
+        let widgets = view_output!();
+        // The first entry is the default device, which is stored as an empty name
+        let devices: Vec<String> = std::iter::once(String::new())
+            .chain(audio_backend::output_devices())
+            .collect();
+        // Translators: The entry of the output device row which plays the beeps on the default device of the system
+        let device_labels = gtk::StringList::new(&[&gettext("System Default")]);
+        for device in &devices[1..] {
+            device_labels.append(device);
+        }
+        widgets.audio_device_row.set_model(Some(&device_labels));
+        let selected_devices = devices.clone();
+        model
+            .global_setup
+            .audio_device
+            .bind_property("value", &widgets.audio_device_row, "selected")
+            .transform_to(move |_, device: String| {
+                Some(
+                    selected_devices
+                        .iter()
+                        .position(|name| *name == device)
+                        .unwrap_or(0) as u32,
+                )
+            })
+            .transform_from(move |_, selected: u32| devices.get(selected as usize).cloned())
+            .bidirectional()
+            .sync_create()
+            .build();
+        for (binding, button, row) in [
+            (
+                &model.appearance.custom_warmup,
+                &widgets.warmup_color,
+                &widgets.warmup_color_row,
+            ),
+            (
+                &model.appearance.custom_exercise,
+                &widgets.exercise_color,
+                &widgets.exercise_color_row,
+            ),
+            (
+                &model.appearance.custom_rest,
+                &widgets.rest_color,
+                &widgets.rest_color_row,
+            ),
+        ] {
+            binding
+                .bind_property("value", button, "rgba")
+                .transform_to(|_, color: String| gdk::RGBA::parse(color.as_str()).ok())
+                .transform_from(|_, rgba: gdk::RGBA| Some(rgba.to_str().to_string()))
+                .bidirectional()
+                .sync_create()
+                .build();
+            // Only the custom palette can be changed
+            model
+                .appearance
+                .palette
+                .bind_property("value", row, "sensitive")
+                .transform_to(|_, palette: u32| Some(palette == CUSTOM_PALETTE))
+                .sync_create()
+                .build();
+        }
+        ComponentParts { model, widgets }
+    }
+}
@@ -0,0 +1,269 @@
+//! A printable plan of a training, e.g. to pin it on the wall of the gym: its periods in order
+//! with their start and length, the total time and the images of the rounds. It is printed with
+//! the print dialog of the system, which also saves it as a PDF file.
+
+use crate::hiit_core::Phase;
+use crate::replay_dialog::{format_clock, phase_color};
+use crate::settings;
+use crate::training_setup::{format_seconds, TrainingSetup};
+use crate::warmup;
+use gettextrs::gettext;
+use relm4::gtk::{self, cairo, gdk, gio, prelude::*};
+use std::{cell::RefCell, ops::Range, rc::Rc};
+
+const HEADER_PT: f64 = 64.0;
+const TABLE_HEADER_PT: f64 = 24.0;
+const ROW_PT: f64 = 20.0;
+/// The rows with an image are as high as its thumbnail.
+const IMAGE_ROW_PT: f64 = 64.0;
+const THUMBNAIL_WIDTH_PT: f64 = 80.0;
+const FONT_SIZE_PT: f64 = 11.0;
+
+/// A period of the plan.
+struct PlanRow {
+    phase: Phase,
+    title: String,
+    start_s: usize,
+    duration_s: usize,
+    /// The image of the round, on the first period of the round only.
+    image: Option<cairo::ImageSurface>,
+}
+
+impl PlanRow {
+    fn height(&self) -> f64 {
+        if self.image.is_some() {
+            IMAGE_ROW_PT
+        } else {
+            ROW_PT
+        }
+    }
+}
+
+/// Loads the image for drawing it with cairo, or `None` if it cannot be read.
+fn load_image(uri: &str) -> Option<cairo::ImageSurface> {
+    let texture = gdk::Texture::from_file(&gio::File::for_uri(uri))
+        .map_err(|err| {
+            // Translators: Error message printed to the console when an image of a round cannot be printed
+            tracing::warn!(%uri, %err, "{}", gettext("Could not load the image"));
+        })
+        .ok()?;
+    let mut surface =
+        cairo::ImageSurface::create(cairo::Format::ARgb32, texture.width(), texture.height())
+            .ok()?;
+    let stride = surface.stride() as usize;
+    {
+        // Textures are downloaded in the format of cairo
+        let mut data = surface.data().ok()?;
+        texture.download(&mut data, stride);
+    }
+    surface.mark_dirty();
+    Some(surface)
+}
+
+/// The periods of the training in the order in which the timer runs through them. The rests
+/// last as long as on the timer, with the gap to get ready at their end.
+fn plan_rows(setup: &TrainingSetup) -> Vec<PlanRow> {
+    let intervals = setup.intervals();
+    let transition_s = settings::load_transition_s_from_gsettings();
+    let mut rows = Vec::new();
+    let mut start_s = 0;
+    let mut push = |phase, title, duration_s, image| {
+        rows.push(PlanRow {
+            phase,
+            title,
+            start_s,
+            duration_s,
+            image,
+        });
+        start_s += duration_s;
+    };
+    let warmup = setup.warmup.steps();
+    if !warmup.is_empty() {
+        for step in &warmup {
+            push(
+                Phase::Preparation,
+                warmup::step_title(step),
+                step.duration_s,
+                None,
+            );
+        }
+    } else if intervals.prepare_s > 0 {
+        push(
+            Phase::Preparation,
+            setup.phase_title(Phase::Preparation, intervals.sets),
+            intervals.prepare_s,
+            None,
+        );
+    }
+    let mut previous_image = None;
+    for set in 1..=intervals.sets {
+        let remaining_sets = intervals.sets - set + 1;
+        let image = setup.round_image(remaining_sets);
+        push(
+            Phase::Exercise,
+            setup.phase_title(Phase::Exercise, remaining_sets),
+            intervals.exercise_s,
+            image
+                .filter(|image| Some(*image) != previous_image)
+                .and_then(load_image),
+        );
+        previous_image = image;
+        if set < intervals.sets {
+            push(
+                Phase::Rest,
+                setup.phase_title(Phase::Rest, remaining_sets - 1),
+                intervals.rest_with_gap_s_after(set, transition_s),
+                None,
+            );
+        }
+    }
+    rows
+}
+
+/// Splits the rows into pages of the given height.
+fn paginate(rows: &[PlanRow], page_height: f64) -> Vec<Range<usize>> {
+    let rows_height = (page_height - HEADER_PT - TABLE_HEADER_PT).max(IMAGE_ROW_PT);
+    let mut pages = Vec::new();
+    let mut first = 0;
+    let mut height = 0.0;
+    for (index, row) in rows.iter().enumerate() {
+        if height + row.height() > rows_height && index > first {
+            pages.push(first..index);
+            first = index;
+            height = 0.0;
+        }
+        height += row.height();
+    }
+    pages.push(first..rows.len());
+    pages
+}
+
+fn show_text(cr: &cairo::Context, text: &str, x: f64, baseline_y: f64) {
+    cr.move_to(x, baseline_y);
+    let _ = cr.show_text(text);
+}
+
+/// Draws the text with its end at `end_x`, e.g. for the numbers in a column.
+fn show_text_right(cr: &cairo::Context, text: &str, end_x: f64, baseline_y: f64) {
+    if let Ok(extents) = cr.text_extents(text) {
+        show_text(cr, text, end_x - extents.x_advance(), baseline_y);
+    }
+}
+
+fn draw_header(setup: &TrainingSetup, cr: &cairo::Context, width: f64) {
+    cr.set_source_rgb(0.0, 0.0, 0.0);
+    cr.select_font_face("Sans", cairo::FontSlant::Normal, cairo::FontWeight::Bold);
+    cr.set_font_size(20.0);
+    show_text(cr, &setup.name, 0.0, 24.0);
+    cr.select_font_face("Sans", cairo::FontSlant::Normal, cairo::FontWeight::Normal);
+    cr.set_font_size(FONT_SIZE_PT);
+    let (_, rounds) = setup.round(setup.intervals().sets);
+    let summary = if false {
+        // Translators: The summary below the name of a printed training, e.g. "Intervals · 8 rounds · 4:10 in total"
+        gettext("{} · {} rounds · {} in total")
+    } else {
+        gettext!(
+            "{} · {} rounds · {} in total",
+            setup.mode().name(),
+            rounds,
+            format_clock(setup.total_duration().as_secs() as usize)
+        )
+    };
+    show_text(cr, &summary, 0.0, 44.0);
+    cr.set_line_width(0.5);
+    cr.move_to(0.0, HEADER_PT - 8.0);
+    cr.line_to(width, HEADER_PT - 8.0);
+    let _ = cr.stroke();
+}
+
+fn draw_page(setup: &TrainingSetup, rows: &[PlanRow], cr: &cairo::Context, width: f64) {
+    draw_header(setup, cr, width);
+    let has_images = rows.iter().any(|row| row.image.is_some());
+    let duration_end_x = if has_images {
+        width - THUMBNAIL_WIDTH_PT - 12.0
+    } else {
+        width
+    };
+    let start_end_x = 56.0;
+    let title_x = start_end_x + 24.0;
+    cr.set_source_rgb(0.4, 0.4, 0.4);
+    cr.set_font_size(FONT_SIZE_PT);
+    let mut y = HEADER_PT + TABLE_HEADER_PT - 8.0;
+    // Translators: The column of the start times of the periods in a printed training
+    show_text_right(cr, &gettext("Start"), start_end_x, y);
+    // Translators: The column of the periods in a printed training
+    show_text(cr, &gettext("Period"), title_x, y);
+    // Translators: The column of the lengths of the periods in a printed training
+    show_text_right(cr, &gettext("Duration"), duration_end_x, y);
+    y += 8.0;
+    for row in rows {
+        let (red, green, blue) = phase_color(row.phase);
+        cr.set_source_rgb(red, green, blue);
+        cr.rectangle(0.0, y + 4.0, 4.0, row.height() - 8.0);
+        let _ = cr.fill();
+        let baseline_y = y + ROW_PT - 6.0;
+        cr.set_source_rgb(0.0, 0.0, 0.0);
+        show_text_right(cr, &format_clock(row.start_s), start_end_x, baseline_y);
+        show_text(cr, &row.title, title_x, baseline_y);
+        show_text_right(
+            cr,
+            &format_seconds(row.duration_s),
+            duration_end_x,
+            baseline_y,
+        );
+        if let Some(image) = &row.image {
+            let height = IMAGE_ROW_PT - 8.0;
+            let scale =
+                (THUMBNAIL_WIDTH_PT / image.width() as f64).min(height / image.height() as f64);
+            let _ = cr.save();
+            cr.translate(width - THUMBNAIL_WIDTH_PT, y + 4.0);
+            cr.scale(scale, scale);
+            if cr.set_source_surface(image, 0.0, 0.0).is_ok() {
+                let _ = cr.paint();
+            }
+            let _ = cr.restore();
+        }
+        y += row.height();
+        cr.set_source_rgb(0.85, 0.85, 0.85);
+        cr.set_line_width(0.5);
+        cr.move_to(0.0, y);
+        cr.line_to(width, y);
+        let _ = cr.stroke();
+    }
+}
+
+/// Opens the print dialog for the plan of the training.
+pub fn print(parent: &gtk::Widget, setup: TrainingSetup) {
+    let rows = Rc::new(plan_rows(&setup));
+    let pages: Rc<RefCell<Vec<Range<usize>>>> = Rc::default();
+    let operation = gtk::PrintOperation::builder()
+        .job_name(&setup.name)
+        .unit(gtk::Unit::Points)
+        .embed_page_setup(true)
+        .allow_async(true)
+        .build();
+    {
+        let rows = rows.clone();
+        let pages = pages.clone();
+        operation.connect_begin_print(move |operation, context| {
+            let mut pages = pages.borrow_mut();
+            *pages = paginate(&rows, context.height());
+            operation.set_n_pages(pages.len() as i32);
+        });
+    }
+    operation.connect_draw_page(move |_, context, page| {
+        if let Some(range) = pages.borrow().get(page as usize) {
+            draw_page(
+                &setup,
+                &rows[range.clone()],
+                &context.cairo_context(),
+                context.width(),
+            );
+        }
+    });
+    let window = parent.root().and_downcast::<gtk::Window>();
+    if let Err(err) = operation.run(gtk::PrintOperationAction::PrintDialog, window.as_ref()) {
+        // Translators: Error message printed to the console when a training cannot be printed
+        tracing::warn!(%err, "{}", gettext("Could not print the training"));
+    }
+}
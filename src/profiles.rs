@@ -0,0 +1,101 @@
+use crate::backup;
+use crate::storage;
+use gettextrs::gettext;
+use relm4::gtk::gio::{self, prelude::*};
+
+/// The profile which existed before profiles were introduced, its data is stored directly in the data directory.
+pub const DEFAULT_PROFILE_ID: &str = "";
+const PREFERENCES_FILE_NAME: &str = "preferences.json";
+
+#[derive(Debug, Clone)]
+pub struct Profile {
+    pub id: String,
+    pub name: String,
+}
+
+/// Loads the profiles, the default one first.
+pub fn load_profiles() -> Vec<Profile> {
+    let settings = gio::Settings::new(crate::config::APP_ID);
+    let parsed = json::parse(&settings.string("profile-json-list")).unwrap_or(json::array![]);
+    std::iter::once(Profile {
+        id: DEFAULT_PROFILE_ID.into(),
+        // Translators: The name of the profile which exists from the beginning
+        name: gettext("Default"),
+    })
+    .chain(parsed.members().filter_map(|profile| {
+        Some(Profile {
+            id: profile["id"].as_str()?.into(),
+            name: profile["name"].as_str()?.into(),
+        })
+    }))
+    .collect()
+}
+
+pub fn add_profile(name: &str) -> Profile {
+    let settings = gio::Settings::new(crate::config::APP_ID);
+    let profile = Profile {
+        id: relm4::gtk::glib::uuid_string_random().into(),
+        name: name.into(),
+    };
+    let mut parsed = json::parse(&settings.string("profile-json-list")).unwrap_or(json::array![]);
+    let _ = parsed.push(json::object! {
+        id: profile.id.clone(),
+        name: profile.name.clone(),
+    });
+    let _ = settings.set_string("profile-json-list", &json::stringify(parsed));
+    profile
+}
+
+pub fn active_profile_id() -> String {
+    gio::Settings::new(crate::config::APP_ID)
+        .string("active-profile")
+        .into()
+}
+
+fn save_profile_preferences() {
+    let settings = gio::Settings::new(crate::config::APP_ID);
+    let mut preferences = backup::preferences_to_json();
+    preferences["sync-base-json"] = settings.string("sync-base-json").as_str().into();
+    preferences["last-training-id"] = settings.string("last-training-id").as_str().into();
+    if let Err(err) = storage::write_atomically(
+        &storage::data_dir().join(PREFERENCES_FILE_NAME),
+        &json::stringify_pretty(preferences, 4),
+    ) {
+        // Translators: Error message printed to the console when the preferences of the profile cannot be saved
+        tracing::warn!(%err, "{}", gettext("Could not save the preferences of the profile"));
+    }
+}
+
+fn load_profile_preferences() {
+    let settings = gio::Settings::new(crate::config::APP_ID);
+    let preferences = std::fs::read_to_string(storage::data_dir().join(PREFERENCES_FILE_NAME))
+        .ok()
+        .and_then(|raw_json| json::parse(&raw_json).ok());
+    match preferences {
+        Some(preferences) => {
+            backup::restore_preferences(&preferences);
+            let _ = settings.set_string(
+                "sync-base-json",
+                preferences["sync-base-json"].as_str().unwrap_or("[]"),
+            );
+            let _ = settings.set_string(
+                "last-training-id",
+                preferences["last-training-id"].as_str().unwrap_or(""),
+            );
+        }
+        None => {
+            backup::reset_preferences();
+            settings.reset("sync-base-json");
+            settings.reset("last-training-id");
+        }
+    }
+}
+
+/// Stores the preferences of the active profile in its data directory, and loads the ones of the new profile.
+/// The trainings and the history follow the data directory of the active profile.
+pub fn switch_profile(id: &str) {
+    save_profile_preferences();
+    let settings = gio::Settings::new(crate::config::APP_ID);
+    let _ = settings.set_string("active-profile", id);
+    load_profile_preferences();
+}
@@ -0,0 +1,43 @@
+use relm4::gtk::{cairo, gdk};
+use std::f64::consts::PI;
+
+/// Draws a ring which is filled clockwise from the top according to `fraction`, over a faint full ring.
+/// A `mirrored` ring is filled counterclockwise, for right-to-left locales.
+pub fn draw_progress_ring(
+    cr: &cairo::Context,
+    width: i32,
+    height: i32,
+    fraction: f64,
+    color: &gdk::RGBA,
+    line_width: f64,
+    mirrored: bool,
+) {
+    let radius = (width.min(height) as f64 - line_width) / 2.0;
+    let center_x = width as f64 / 2.0;
+    let center_y = height as f64 / 2.0;
+    let set_color = |alpha: f64| {
+        cr.set_source_rgba(
+            color.red() as f64,
+            color.green() as f64,
+            color.blue() as f64,
+            color.alpha() as f64 * alpha,
+        );
+    };
+    cr.set_line_width(line_width);
+    cr.set_line_cap(cairo::LineCap::Round);
+    set_color(0.15);
+    cr.arc(center_x, center_y, radius, 0.0, 2.0 * PI);
+    let _ = cr.stroke();
+    let fraction = fraction.clamp(0.0, 1.0);
+    if fraction > 0.0 {
+        set_color(1.0);
+        let start = -PI / 2.0;
+        let end = start + 2.0 * PI * fraction;
+        if mirrored {
+            cr.arc(center_x, center_y, radius, PI - end, PI - start);
+        } else {
+            cr.arc(center_x, center_y, radius, start, end);
+        }
+        let _ = cr.stroke();
+    }
+}
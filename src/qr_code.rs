@@ -0,0 +1,409 @@
+//! A minimal QR Code encoder. Supports the byte mode and the medium error correction level only,
+//! which is all that is needed to share trainings.
+
+const ECC_CODEWORDS_PER_BLOCK: [usize; 41] = [
+    0, 10, 16, 26, 18, 24, 16, 18, 22, 22, 26, 30, 22, 22, 24, 24, 28, 28, 26, 26, 26, 26, 28, 28,
+    28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28,
+];
+const NUM_ERROR_CORRECTION_BLOCKS: [usize; 41] = [
+    0, 1, 1, 1, 2, 2, 4, 4, 4, 5, 5, 5, 8, 9, 9, 10, 10, 11, 13, 14, 16, 17, 17, 18, 20, 21, 23,
+    25, 26, 28, 29, 31, 33, 35, 37, 38, 40, 43, 45, 47, 49,
+];
+const MIN_VERSION: usize = 1;
+const MAX_VERSION: usize = 40;
+// The format bits of the medium error correction level
+const ECL_FORMAT_BITS: u32 = 0;
+
+pub struct QrCode {
+    size: usize,
+    version: usize,
+    modules: Vec<bool>,
+    is_function: Vec<bool>,
+}
+
+fn get_bit(value: u32, index: usize) -> bool {
+    (value >> index) & 1 != 0
+}
+
+fn num_raw_data_modules(version: usize) -> usize {
+    let mut result = (16 * version + 128) * version + 64;
+    if version >= 2 {
+        let num_align = version / 7 + 2;
+        result -= (25 * num_align - 10) * num_align - 55;
+        if version >= 7 {
+            result -= 36;
+        }
+    }
+    result
+}
+
+fn num_data_codewords(version: usize) -> usize {
+    num_raw_data_modules(version) / 8
+        - ECC_CODEWORDS_PER_BLOCK[version] * NUM_ERROR_CORRECTION_BLOCKS[version]
+}
+
+fn reed_solomon_multiply(x: u8, y: u8) -> u8 {
+    let mut z: u8 = 0;
+    for i in (0..8).rev() {
+        z = (z << 1) ^ ((z >> 7) * 0x1D);
+        z ^= ((y >> i) & 1) * x;
+    }
+    z
+}
+
+fn reed_solomon_divisor(degree: usize) -> Vec<u8> {
+    let mut result = vec![0u8; degree];
+    result[degree - 1] = 1;
+    let mut root: u8 = 1;
+    for _ in 0..degree {
+        for j in 0..degree {
+            result[j] = reed_solomon_multiply(result[j], root);
+            if j + 1 < degree {
+                result[j] ^= result[j + 1];
+            }
+        }
+        root = reed_solomon_multiply(root, 0x02);
+    }
+    result
+}
+
+fn reed_solomon_remainder(data: &[u8], divisor: &[u8]) -> Vec<u8> {
+    let mut result = vec![0u8; divisor.len()];
+    for byte in data {
+        let factor = byte ^ result.remove(0);
+        result.push(0);
+        for (x, y) in result.iter_mut().zip(divisor.iter()) {
+            *x ^= reed_solomon_multiply(*y, factor);
+        }
+    }
+    result
+}
+
+fn encode_data_codewords(data: &[u8], version: usize) -> Vec<u8> {
+    let char_count_bits = if version <= 9 { 8 } else { 16 };
+    let capacity_bits = num_data_codewords(version) * 8;
+    let mut bits: Vec<bool> = Vec::with_capacity(capacity_bits);
+    let mut append_bits = |value: u32, len: usize| {
+        for i in (0..len).rev() {
+            bits.push(get_bit(value, i));
+        }
+    };
+    // Byte mode indicator
+    append_bits(0b0100, 4);
+    append_bits(data.len() as u32, char_count_bits);
+    for byte in data {
+        append_bits(*byte as u32, 8);
+    }
+    let terminator_len = 4.min(capacity_bits - bits.len());
+    bits.resize(bits.len() + terminator_len, false);
+    bits.resize(bits.len().div_ceil(8) * 8, false);
+    let mut codewords: Vec<u8> = bits
+        .chunks(8)
+        .map(|chunk| chunk.iter().fold(0u8, |acc, bit| (acc << 1) | *bit as u8))
+        .collect();
+    for pad in [0xEC, 0x11].into_iter().cycle() {
+        if codewords.len() >= capacity_bits / 8 {
+            break;
+        }
+        codewords.push(pad);
+    }
+    codewords
+}
+
+fn add_ecc_and_interleave(data: &[u8], version: usize) -> Vec<u8> {
+    let num_blocks = NUM_ERROR_CORRECTION_BLOCKS[version];
+    let block_ecc_len = ECC_CODEWORDS_PER_BLOCK[version];
+    let raw_codewords = num_raw_data_modules(version) / 8;
+    let num_short_blocks = num_blocks - raw_codewords % num_blocks;
+    let short_block_len = raw_codewords / num_blocks;
+    let divisor = reed_solomon_divisor(block_ecc_len);
+    let mut blocks: Vec<Vec<u8>> = Vec::with_capacity(num_blocks);
+    let mut offset = 0;
+    for i in 0..num_blocks {
+        let data_len = short_block_len - block_ecc_len + usize::from(i >= num_short_blocks);
+        let block_data = &data[offset..offset + data_len];
+        offset += data_len;
+        let mut block = block_data.to_vec();
+        if i < num_short_blocks {
+            block.push(0);
+        }
+        block.extend(reed_solomon_remainder(block_data, &divisor));
+        blocks.push(block);
+    }
+    let mut result = Vec::with_capacity(raw_codewords);
+    for i in 0..blocks[0].len() {
+        for (j, block) in blocks.iter().enumerate() {
+            // Skip the padding byte of the short blocks
+            if i != short_block_len - block_ecc_len || j >= num_short_blocks {
+                result.push(block[i]);
+            }
+        }
+    }
+    result
+}
+
+impl QrCode {
+    /// Encodes the binary data with the smallest version that fits it.
+    /// Returns `None` if the data is too long to be encoded.
+    pub fn encode(data: &[u8]) -> Option<Self> {
+        let version = (MIN_VERSION..=MAX_VERSION).find(|version| {
+            let char_count_bits = if *version <= 9 { 8 } else { 16 };
+            data.len() < (1 << char_count_bits)
+                && 4 + char_count_bits + data.len() * 8 <= num_data_codewords(*version) * 8
+        })?;
+        let size = version * 4 + 17;
+        let mut qr_code = QrCode {
+            size,
+            version,
+            modules: vec![false; size * size],
+            is_function: vec![false; size * size],
+        };
+        qr_code.draw_function_patterns();
+        let codewords = add_ecc_and_interleave(&encode_data_codewords(data, version), version);
+        qr_code.draw_codewords(&codewords);
+
+        let mut best_mask = 0;
+        let mut min_penalty = usize::MAX;
+        for mask in 0..8 {
+            qr_code.apply_mask(mask);
+            qr_code.draw_format_bits(mask);
+            let penalty = qr_code.penalty_score();
+            if penalty < min_penalty {
+                best_mask = mask;
+                min_penalty = penalty;
+            }
+            // Masking is an XOR operation, applying it again undoes it
+            qr_code.apply_mask(mask);
+        }
+        qr_code.apply_mask(best_mask);
+        qr_code.draw_format_bits(best_mask);
+        Some(qr_code)
+    }
+
+    /// The width and height of the symbol, in modules.
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Returns whether the module at the given coordinates is dark.
+    pub fn module(&self, x: usize, y: usize) -> bool {
+        self.modules[y * self.size + x]
+    }
+
+    fn set_function_module(&mut self, x: usize, y: usize, is_dark: bool) {
+        self.modules[y * self.size + x] = is_dark;
+        self.is_function[y * self.size + x] = true;
+    }
+
+    fn alignment_pattern_positions(&self) -> Vec<usize> {
+        if self.version == 1 {
+            return Vec::new();
+        }
+        let num_align = self.version / 7 + 2;
+        let step = (self.version * 8 + num_align * 3 + 5) / (num_align * 4 - 4) * 2;
+        let mut result: Vec<usize> = (0..num_align - 1)
+            .map(|i| self.size - 7 - i * step)
+            .collect();
+        result.push(6);
+        result.reverse();
+        result
+    }
+
+    fn draw_function_patterns(&mut self) {
+        for i in 0..self.size {
+            self.set_function_module(6, i, i % 2 == 0);
+            self.set_function_module(i, 6, i % 2 == 0);
+        }
+        self.draw_finder_pattern(3, 3);
+        self.draw_finder_pattern(self.size - 4, 3);
+        self.draw_finder_pattern(3, self.size - 4);
+
+        let positions = self.alignment_pattern_positions();
+        let count = positions.len();
+        for i in 0..count {
+            for j in 0..count {
+                let overlaps_finder =
+                    (i == 0 && (j == 0 || j == count - 1)) || (i == count - 1 && j == 0);
+                if !overlaps_finder {
+                    self.draw_alignment_pattern(positions[i], positions[j]);
+                }
+            }
+        }
+        // Reserve the format areas, the actual bits are drawn after masking
+        self.draw_format_bits(0);
+        self.draw_version();
+    }
+
+    fn draw_finder_pattern(&mut self, x: usize, y: usize) {
+        for dy in -4i32..=4 {
+            for dx in -4i32..=4 {
+                let xx = x as i32 + dx;
+                let yy = y as i32 + dy;
+                if (0..self.size as i32).contains(&xx) && (0..self.size as i32).contains(&yy) {
+                    let distance = dx.abs().max(dy.abs());
+                    self.set_function_module(
+                        xx as usize,
+                        yy as usize,
+                        distance != 2 && distance != 4,
+                    );
+                }
+            }
+        }
+    }
+
+    fn draw_alignment_pattern(&mut self, x: usize, y: usize) {
+        for dy in -2i32..=2 {
+            for dx in -2i32..=2 {
+                self.set_function_module(
+                    (x as i32 + dx) as usize,
+                    (y as i32 + dy) as usize,
+                    dx.abs().max(dy.abs()) != 1,
+                );
+            }
+        }
+    }
+
+    fn draw_format_bits(&mut self, mask: u32) {
+        let data = ECL_FORMAT_BITS << 3 | mask;
+        let mut remainder = data;
+        for _ in 0..10 {
+            remainder = (remainder << 1) ^ ((remainder >> 9) * 0x537);
+        }
+        let bits = (data << 10 | remainder) ^ 0x5412;
+
+        for i in 0..=5 {
+            self.set_function_module(8, i, get_bit(bits, i));
+        }
+        self.set_function_module(8, 7, get_bit(bits, 6));
+        self.set_function_module(8, 8, get_bit(bits, 7));
+        self.set_function_module(7, 8, get_bit(bits, 8));
+        for i in 9..15 {
+            self.set_function_module(14 - i, 8, get_bit(bits, i));
+        }
+
+        let size = self.size;
+        for i in 0..8 {
+            self.set_function_module(size - 1 - i, 8, get_bit(bits, i));
+        }
+        for i in 8..15 {
+            self.set_function_module(8, size - 15 + i, get_bit(bits, i));
+        }
+        // The dark module
+        self.set_function_module(8, size - 8, true);
+    }
+
+    fn draw_version(&mut self) {
+        if self.version < 7 {
+            return;
+        }
+        let mut remainder = self.version as u32;
+        for _ in 0..12 {
+            remainder = (remainder << 1) ^ ((remainder >> 11) * 0x1F25);
+        }
+        let bits = (self.version as u32) << 12 | remainder;
+        for i in 0..18 {
+            let bit = get_bit(bits, i);
+            let a = self.size - 11 + i % 3;
+            let b = i / 3;
+            self.set_function_module(a, b, bit);
+            self.set_function_module(b, a, bit);
+        }
+    }
+
+    fn draw_codewords(&mut self, codewords: &[u8]) {
+        let mut i = 0;
+        let mut right = self.size as i32 - 1;
+        while right >= 1 {
+            if right == 6 {
+                right = 5;
+            }
+            for vert in 0..self.size {
+                for j in 0..2 {
+                    let x = (right - j) as usize;
+                    let upward = (right + 1) & 2 == 0;
+                    let y = if upward { self.size - 1 - vert } else { vert };
+                    if !self.is_function[y * self.size + x] && i < codewords.len() * 8 {
+                        self.modules[y * self.size + x] =
+                            get_bit(codewords[i >> 3] as u32, 7 - (i & 7));
+                        i += 1;
+                    }
+                }
+            }
+            right -= 2;
+        }
+    }
+
+    fn apply_mask(&mut self, mask: u32) {
+        for y in 0..self.size {
+            for x in 0..self.size {
+                let invert = match mask {
+                    0 => (x + y) % 2 == 0,
+                    1 => y % 2 == 0,
+                    2 => x % 3 == 0,
+                    3 => (x + y) % 3 == 0,
+                    4 => (x / 3 + y / 2) % 2 == 0,
+                    5 => x * y % 2 + x * y % 3 == 0,
+                    6 => (x * y % 2 + x * y % 3) % 2 == 0,
+                    _ => ((x + y) % 2 + x * y % 3) % 2 == 0,
+                };
+                let index = y * self.size + x;
+                if invert && !self.is_function[index] {
+                    self.modules[index] = !self.modules[index];
+                }
+            }
+        }
+    }
+
+    fn line_penalty(line: &[bool]) -> usize {
+        const FINDER_LIKE: [bool; 11] = [
+            true, false, true, true, true, false, true, false, false, false, false,
+        ];
+        let mut penalty = 0;
+        let mut run_len = 1;
+        for i in 1..=line.len() {
+            if i < line.len() && line[i] == line[i - 1] {
+                run_len += 1;
+            } else {
+                if run_len >= 5 {
+                    penalty += 3 + run_len - 5;
+                }
+                run_len = 1;
+            }
+        }
+        for window in line.windows(FINDER_LIKE.len()) {
+            if window == FINDER_LIKE || window.iter().rev().eq(FINDER_LIKE.iter()) {
+                penalty += 40;
+            }
+        }
+        penalty
+    }
+
+    fn penalty_score(&self) -> usize {
+        let size = self.size;
+        let mut penalty = 0;
+        for y in 0..size {
+            let row: Vec<bool> = (0..size).map(|x| self.module(x, y)).collect();
+            penalty += Self::line_penalty(&row);
+        }
+        for x in 0..size {
+            let column: Vec<bool> = (0..size).map(|y| self.module(x, y)).collect();
+            penalty += Self::line_penalty(&column);
+        }
+        for y in 0..size - 1 {
+            for x in 0..size - 1 {
+                let color = self.module(x, y);
+                if color == self.module(x + 1, y)
+                    && color == self.module(x, y + 1)
+                    && color == self.module(x + 1, y + 1)
+                {
+                    penalty += 3;
+                }
+            }
+        }
+        let dark = self.modules.iter().filter(|module| **module).count();
+        let total = size * size;
+        let deviation = (dark * 20).abs_diff(total * 10);
+        penalty += deviation.div_ceil(total).saturating_sub(1) * 10;
+        penalty
+    }
+}
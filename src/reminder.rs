@@ -0,0 +1,76 @@
+use gettextrs::gettext;
+use relm4::gtk::{
+    gio::{self, prelude::*},
+    glib,
+};
+use std::{cell::RefCell, rc::Rc};
+
+const NOTIFICATION_ID: &str = "training-reminder";
+
+/// Parses the reminder time in `HH:MM` notation.
+fn parse_reminder_time(raw: &str) -> Option<(i32, i32)> {
+    let (hours, minutes) = raw.split_once(':')?;
+    let hours: i32 = hours.trim().parse().ok()?;
+    let minutes: i32 = minutes.trim().parse().ok()?;
+    ((0..24).contains(&hours) && (0..60).contains(&minutes)).then_some((hours, minutes))
+}
+
+fn seconds_until(hours: i32, minutes: i32) -> Option<u32> {
+    let now = glib::DateTime::now_local().ok()?;
+    let mut next = glib::DateTime::from_local(
+        now.year(),
+        now.month(),
+        now.day_of_month(),
+        hours,
+        minutes,
+        0.0,
+    )
+    .ok()?;
+    if next <= now {
+        next = next.add_days(1).ok()?;
+    }
+    u32::try_from(next.difference(&now).as_seconds()).ok()
+}
+
+fn send_reminder(app: &gio::Application) {
+    // Translators: The title of the notification which reminds of the daily training
+    let notification = gio::Notification::new(&gettext("Time to Train"));
+    // Translators: The body of the notification which reminds of the daily training
+    notification.set_body(Some(&gettext("Your daily training is due")));
+    app.send_notification(Some(NOTIFICATION_ID), &notification);
+}
+
+fn schedule(
+    app: &gio::Application,
+    settings: &gio::Settings,
+    source: &Rc<RefCell<Option<glib::SourceId>>>,
+) {
+    if let Some(previous) = source.borrow_mut().take() {
+        previous.remove();
+    }
+    let Some((hours, minutes)) = parse_reminder_time(&settings.string("reminder-time")) else {
+        return;
+    };
+    let Some(seconds) = seconds_until(hours, minutes) else {
+        return;
+    };
+    let app = app.clone();
+    let settings_clone = settings.clone();
+    let source_clone = source.clone();
+    *source.borrow_mut() = Some(glib::timeout_add_seconds_local_once(seconds, move || {
+        source_clone.borrow_mut().take();
+        send_reminder(&app);
+        schedule(&app, &settings_clone, &source_clone);
+    }));
+}
+
+/// Sends a notification every day at the time configured in the settings, while the application is running.
+pub fn schedule_daily_reminder(app: &impl IsA<gio::Application>) {
+    let app = app.as_ref().clone();
+    let settings = gio::Settings::new(crate::config::APP_ID);
+    let source = Rc::new(RefCell::new(None));
+    schedule(&app, &settings, &source);
+    settings.connect_changed(Some("reminder-time"), move |settings, _| {
+        schedule(&app, settings, &source);
+    });
+}
@@ -0,0 +1,200 @@
+use crate::hiit_core::Phase;
+use crate::history::{PeriodLog, SessionMarkKind, SessionRecord};
+use crate::training_timer::state_title;
+use gettextrs::gettext;
+use relm4::{
+    adw::{self, prelude::*},
+    gtk,
+};
+use relm4_icons::icon_names;
+
+const TIMELINE_HEIGHT_PX: i32 = 40;
+/// The share of the height of the timeline which the periods take, the rest is left for the marks.
+const TIMELINE_BAR_SHARE: f64 = 0.6;
+
+/// The colors of the periods, as on the timer page.
+pub fn phase_color(phase: Phase) -> (f64, f64, f64) {
+    match phase {
+        Phase::Preparation => (0.898, 0.647, 0.039),
+        Phase::Exercise => (0.776, 0.275, 0.0),
+        Phase::Rest => (0.102, 0.373, 0.706),
+    }
+}
+
+/// A length of time, e.g. "1:05".
+pub fn format_clock(seconds: usize) -> String {
+    format!("{}:{:02}", seconds / 60, seconds % 60)
+}
+
+/// Draws the periods side by side in proportion to their length, with the pauses as lines
+/// and the rewinds as triangles above them. Skipped periods are drawn faded.
+fn draw_timeline(
+    session: &SessionRecord,
+    periods: &[PeriodLog],
+    rtl: bool,
+    color: &gtk::gdk::RGBA,
+    cr: &gtk::cairo::Context,
+    width: i32,
+    height: i32,
+) {
+    let total_s: usize = periods.iter().map(|period| period.length_s).sum();
+    if total_s == 0 {
+        return;
+    }
+    let scale = width as f64 / total_s as f64;
+    let x_at = |at_s: usize| {
+        let x = at_s as f64 * scale;
+        if rtl {
+            width as f64 - x
+        } else {
+            x
+        }
+    };
+    let bar_top = height as f64 * (1.0 - TIMELINE_BAR_SHARE);
+    let mut start_s = 0;
+    for period in periods {
+        let (red, green, blue) = phase_color(period.phase);
+        cr.set_source_rgba(red, green, blue, if period.skipped { 0.4 } else { 1.0 });
+        let (start_x, end_x) = (x_at(start_s), x_at(start_s + period.length_s));
+        cr.rectangle(
+            start_x.min(end_x),
+            bar_top,
+            (end_x - start_x).abs(),
+            height as f64 - bar_top,
+        );
+        let _ = cr.fill();
+        start_s += period.length_s;
+    }
+    cr.set_source_rgba(
+        color.red() as f64,
+        color.green() as f64,
+        color.blue() as f64,
+        color.alpha() as f64,
+    );
+    for mark in &session.marks {
+        let x = x_at(mark.at_s.min(total_s));
+        match mark.kind {
+            SessionMarkKind::Paused { .. } => {
+                cr.rectangle(x - 1.0, 0.0, 2.0, height as f64);
+            }
+            SessionMarkKind::Rewound { .. } => {
+                cr.move_to(x - bar_top / 2.0, 0.0);
+                cr.line_to(x + bar_top / 2.0, 0.0);
+                cr.line_to(x, bar_top);
+                cr.close_path();
+            }
+        }
+        let _ = cr.fill();
+    }
+}
+
+fn period_row(period: &PeriodLog, start_s: usize) -> adw::ActionRow {
+    let mut span = format!(
+        "{} – {}",
+        format_clock(start_s),
+        format_clock(start_s + period.length_s)
+    );
+    if let Some(intensity) = period.intensity {
+        span = format!("{} · {}", span, intensity.title());
+    }
+    let row = adw::ActionRow::builder()
+        .title(state_title(period.phase))
+        .subtitle(if period.skipped {
+            // Translators: The subtitle of a skipped period in the replay of a session, e.g. "0:30 – 0:42 · Skipped"
+            format!("{} · {}", span, gettext("Skipped"))
+        } else {
+            span
+        })
+        .build();
+    row.add_suffix(&gtk::Label::new(Some(&format_clock(period.length_s))));
+    row
+}
+
+fn mark_row(kind: SessionMarkKind, at_s: usize) -> adw::ActionRow {
+    let (icon_name, title, duration) = match kind {
+        SessionMarkKind::Paused { pause_s } => (
+            icon_names::PAUSE,
+            // Translators: A pause in the replay of a session
+            gettext("Paused"),
+            format_clock(pause_s),
+        ),
+        SessionMarkKind::Rewound { added_s } => (
+            icon_names::REFRESH,
+            // Translators: A period which was started over in the replay of a session
+            gettext("Restarted Period"),
+            format!("+{}", format_clock(added_s)),
+        ),
+    };
+    let row = adw::ActionRow::builder()
+        .title(title)
+        .subtitle(format_clock(at_s))
+        .build();
+    row.add_prefix(&gtk::Image::from_icon_name(icon_name));
+    row.add_suffix(&gtk::Label::new(Some(&duration)));
+    row
+}
+
+/// Shows how a past session went: every period with its actual length, and where it was
+/// paused, skipped or started over. The session cannot be changed from it.
+pub fn show(parent: &impl IsA<gtk::Widget>, session: &SessionRecord) {
+    let periods = session.timeline();
+    let timeline = gtk::DrawingArea::builder()
+        .content_height(TIMELINE_HEIGHT_PX)
+        .build();
+    {
+        let session = session.clone();
+        let periods = periods.clone();
+        timeline.set_draw_func(move |area, cr, width, height| {
+            let rtl = area.direction() == gtk::TextDirection::Rtl;
+            draw_timeline(&session, &periods, rtl, &area.color(), cr, width, height);
+        });
+    }
+    let timeline_group = adw::PreferencesGroup::builder()
+        .title(
+            gtk::glib::DateTime::from_unix_local(session.started)
+                .and_then(|date_time| date_time.format("%x %R"))
+                .map(String::from)
+                .unwrap_or_default(),
+        )
+        .build();
+    timeline_group.add(&timeline);
+    let course_group = adw::PreferencesGroup::builder()
+        // Translators: The title of the list of periods and pauses in the replay of a session
+        .title(gettext("Course"))
+        .build();
+    // The marks of a moment are listed after the period which began then, as they happened in it
+    let mut rows = Vec::new();
+    let mut start_s = 0;
+    for period in &periods {
+        rows.push(((start_s, 0), period_row(period, start_s)));
+        start_s += period.length_s;
+    }
+    for mark in &session.marks {
+        rows.push(((mark.at_s, 1), mark_row(mark.kind, mark.at_s)));
+    }
+    rows.sort_by_key(|(key, _)| *key);
+    for (_, row) in rows {
+        course_group.add(&row);
+    }
+    if periods.is_empty() {
+        course_group.add(
+            &adw::ActionRow::builder()
+                // Translators: Shown in the replay of a session which was recorded without its periods
+                .title(gettext("No periods recorded"))
+                .build(),
+        );
+    }
+    let page = adw::PreferencesPage::new();
+    page.add(&timeline_group);
+    page.add(&course_group);
+    let toolbar_view = adw::ToolbarView::new();
+    toolbar_view.add_top_bar(&adw::HeaderBar::new());
+    toolbar_view.set_content(Some(&page));
+    adw::Dialog::builder()
+        .title(&session.training_name)
+        .content_width(420)
+        .content_height(560)
+        .child(&toolbar_view)
+        .build()
+        .present(Some(parent));
+}
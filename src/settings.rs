@@ -1,4 +1,5 @@
 use crate::training_setup::*;
+use crate::warmup::WarmupStep;
 use gettextrs::gettext;
 use relm4::{
     self,
@@ -38,6 +39,22 @@ impl Drop for WindowGeometry {
 #[derive(Clone, Debug, Default)]
 pub struct GlobalTrainingSetup {
     pub beep_volume: F64Binding,
+    pub countdown_s: U32Binding,
+    /// The gap to get ready between a rest and the next exercise, in seconds.
+    pub transition_s: U32Binding,
+    pub big_digits: BoolBinding,
+    /// The screen flashes at the same time as the beeps.
+    pub screen_flash: BoolBinding,
+    /// Fewer redraws and no animations, to make the battery last longer.
+    pub power_saving: BoolBinding,
+    /// How often the timer ticks and redraws its progress, unless power saving is on.
+    pub tick_interval_ms: U32Binding,
+    /// The output device of the beeps, empty for the default one.
+    pub audio_device: StringBinding,
+    /// The names of the participants who rotate through the stations in TV mode, separated by commas.
+    pub participants: StringBinding,
+    /// The body weight in kilograms for estimating the burned calories, 0 if unknown.
+    pub body_weight_kg: U32Binding,
 }
 
 impl GlobalTrainingSetup {
@@ -45,8 +62,34 @@ impl GlobalTrainingSetup {
         let settings = gio::Settings::new(crate::config::APP_ID);
         Self {
             beep_volume: F64Binding::new(settings.double("beep-volume")),
+            countdown_s: U32Binding::new(settings.uint("countdown-s")),
+            transition_s: U32Binding::new(settings.uint("transition-s")),
+            big_digits: BoolBinding::new(settings.boolean("big-digits")),
+            screen_flash: BoolBinding::new(settings.boolean("screen-flash")),
+            power_saving: BoolBinding::new(settings.boolean("power-saving")),
+            tick_interval_ms: U32Binding::new(settings.uint("tick-interval-ms")),
+            audio_device: StringBinding::new(settings.string("audio-device")),
+            participants: StringBinding::new(settings.string("tv-participants")),
+            body_weight_kg: U32Binding::new(settings.uint("body-weight-kg")),
         }
     }
+
+    /// Updates the bindings after the settings were changed from outside, e.g. by restoring a backup.
+    pub fn reload_from_gsettings(&self) {
+        let settings = gio::Settings::new(crate::config::APP_ID);
+        self.beep_volume.set(settings.double("beep-volume"));
+        self.countdown_s.set(settings.uint("countdown-s"));
+        self.transition_s.set(settings.uint("transition-s"));
+        self.big_digits.set(settings.boolean("big-digits"));
+        self.screen_flash.set(settings.boolean("screen-flash"));
+        self.power_saving.set(settings.boolean("power-saving"));
+        self.tick_interval_ms.set(settings.uint("tick-interval-ms"));
+        self.audio_device
+            .set(settings.string("audio-device").into());
+        self.participants
+            .set(settings.string("tv-participants").into());
+        self.body_weight_kg.set(settings.uint("body-weight-kg"));
+    }
 }
 
 impl Drop for GlobalTrainingSetup {
@@ -54,84 +97,455 @@ impl Drop for GlobalTrainingSetup {
         let settings = gio::Settings::new(crate::config::APP_ID);
         settings.delay();
         let _ = settings.set_double("beep-volume", self.beep_volume.get());
+        let _ = settings.set_uint("countdown-s", self.countdown_s.get());
+        let _ = settings.set_uint("transition-s", self.transition_s.get());
+        let _ = settings.set_boolean("big-digits", self.big_digits.get());
+        let _ = settings.set_boolean("screen-flash", self.screen_flash.get());
+        let _ = settings.set_boolean("power-saving", self.power_saving.get());
+        let _ = settings.set_uint("tick-interval-ms", self.tick_interval_ms.get());
+        let _ = settings.set_string("audio-device", &self.audio_device.get());
+        let _ = settings.set_string("tv-participants", &self.participants.get());
+        let _ = settings.set_uint("body-weight-kg", self.body_weight_kg.get());
+        settings.apply();
+    }
+}
+
+/// The color schemes of the application, in the order of the preferences.
+const COLOR_SCHEMES: [&str; 3] = ["system", "light", "dark"];
+
+/// The palettes of the period colors, in the order of the preferences.
+const PHASE_PALETTES: [&str; 3] = ["default", "color-blind", "custom"];
+
+/// The index of the choice of a setting in the preferences, or of the first choice if it is unknown.
+fn choice_index(choices: &[&str], value: &str) -> u32 {
+    choices
+        .iter()
+        .position(|choice| *choice == value)
+        .unwrap_or(0) as u32
+}
+
+fn choice_name<'a>(choices: &[&'a str], index: u32) -> &'a str {
+    choices.get(index as usize).unwrap_or(&choices[0])
+}
+
+/// The color scheme, and the colors of the periods on the timer page and in the training list.
+#[derive(Clone, Debug, Default)]
+pub struct Appearance {
+    /// The index of the color scheme in [`COLOR_SCHEMES`].
+    pub color_scheme: U32Binding,
+    /// The index of the palette in [`PHASE_PALETTES`].
+    pub palette: U32Binding,
+    pub custom_warmup: StringBinding,
+    pub custom_exercise: StringBinding,
+    pub custom_rest: StringBinding,
+}
+
+impl Appearance {
+    pub fn new_from_gsettings() -> Self {
+        let appearance = Self::default();
+        appearance.reload_from_gsettings();
+        appearance
+    }
+
+    /// Updates the bindings after the settings were changed from outside, e.g. by restoring a backup.
+    pub fn reload_from_gsettings(&self) {
+        let settings = gio::Settings::new(crate::config::APP_ID);
+        self.color_scheme.set(choice_index(
+            &COLOR_SCHEMES,
+            &settings.string("color-scheme"),
+        ));
+        self.palette.set(choice_index(
+            &PHASE_PALETTES,
+            &settings.string("phase-palette"),
+        ));
+        self.custom_warmup
+            .set(settings.string("custom-warmup-color").into());
+        self.custom_exercise
+            .set(settings.string("custom-exercise-color").into());
+        self.custom_rest
+            .set(settings.string("custom-rest-color").into());
+    }
+}
+
+impl Drop for Appearance {
+    fn drop(&mut self) {
+        let settings = gio::Settings::new(crate::config::APP_ID);
+        settings.delay();
+        let _ = settings.set_string(
+            "color-scheme",
+            choice_name(&COLOR_SCHEMES, self.color_scheme.get()),
+        );
+        let _ = settings.set_string(
+            "phase-palette",
+            choice_name(&PHASE_PALETTES, self.palette.get()),
+        );
+        let _ = settings.set_string("custom-warmup-color", &self.custom_warmup.get());
+        let _ = settings.set_string("custom-exercise-color", &self.custom_exercise.get());
+        let _ = settings.set_string("custom-rest-color", &self.custom_rest.get());
+        settings.apply();
+    }
+}
+
+/// The durations and the number of sets which new trainings start from.
+#[derive(Clone, Debug, Default)]
+pub struct TrainingDefaults {
+    pub sets: U32Binding,
+    pub exercise_s: U32Binding,
+    pub rest_s: U32Binding,
+    pub prepare_s: U32Binding,
+}
+
+impl TrainingDefaults {
+    pub fn new_from_gsettings() -> Self {
+        let defaults = Self::default();
+        defaults.reload_from_gsettings();
+        defaults
+    }
+
+    /// Updates the bindings after the settings were changed from outside, e.g. by restoring a backup.
+    pub fn reload_from_gsettings(&self) {
+        let training = load_default_training_setup();
+        self.sets.set(training.sets as u32);
+        self.exercise_s.set(training.exercise_s as u32);
+        self.rest_s.set(training.rest_s as u32);
+        self.prepare_s.set(training.prepare_s as u32);
+    }
+
+    pub fn save_to_gsettings(&self) {
+        let settings = gio::Settings::new(crate::config::APP_ID);
+        let mut default_json = json::parse(&settings.string("default-exercise-json"))
+            .unwrap_or_else(|_| {
+                json::object! {
+                    name: "Training",
+                }
+            });
+        hiit_schema::upgrade_workout(&mut default_json);
+        // The default training has no identity, each new training gets its own
+        default_json.remove("id");
+        default_json["sets"] = self.sets.get().max(1).into();
+        default_json["exercise_s"] = self.exercise_s.get().max(1).into();
+        default_json["rest_s"] = self.rest_s.get().into();
+        default_json["prepare_s"] = self.prepare_s.get().into();
+        let _ = settings.set_string("default-exercise-json", &json::stringify(default_json));
+    }
+}
+
+impl Drop for TrainingDefaults {
+    fn drop(&mut self) {
+        self.save_to_gsettings();
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct HookCommands {
+    pub start: String,
+    pub exercise: String,
+    pub rest: String,
+    pub finish: String,
+}
+
+impl HookCommands {
+    pub fn new_from_gsettings() -> Self {
+        let settings = gio::Settings::new(crate::config::APP_ID);
+        Self {
+            start: settings.string("hook-start-command").into(),
+            exercise: settings.string("hook-exercise-command").into(),
+            rest: settings.string("hook-rest-command").into(),
+            finish: settings.string("hook-finish-command").into(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct SyncSettings {
+    pub enabled: BoolBinding,
+    pub folder_uri: StringBinding,
+}
+
+impl SyncSettings {
+    pub fn new_from_gsettings() -> Self {
+        let settings = gio::Settings::new(crate::config::APP_ID);
+        Self {
+            enabled: BoolBinding::new(settings.boolean("sync-enabled")),
+            folder_uri: StringBinding::new(settings.string("sync-folder-uri")),
+        }
+    }
+
+    /// Updates the bindings after the settings were changed from outside, e.g. by restoring a backup.
+    pub fn reload_from_gsettings(&self) {
+        let settings = gio::Settings::new(crate::config::APP_ID);
+        self.enabled.set(settings.boolean("sync-enabled"));
+        self.folder_uri
+            .set(settings.string("sync-folder-uri").into());
+    }
+
+    pub fn folder_uri(&self) -> Option<String> {
+        let folder_uri = self.folder_uri.get();
+        if self.enabled.get() && !folder_uri.is_empty() {
+            Some(folder_uri)
+        } else {
+            None
+        }
+    }
+}
+
+impl Drop for SyncSettings {
+    fn drop(&mut self) {
+        let settings = gio::Settings::new(crate::config::APP_ID);
+        settings.delay();
+        let _ = settings.set_boolean("sync-enabled", self.enabled.get());
+        let _ = settings.set_string("sync-folder-uri", &self.folder_uri.get());
+        settings.apply();
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct ReminderSettings {
+    pub autostart: BoolBinding,
+    pub reminder_time: StringBinding,
+}
+
+impl ReminderSettings {
+    pub fn new_from_gsettings() -> Self {
+        let settings = gio::Settings::new(crate::config::APP_ID);
+        Self {
+            autostart: BoolBinding::new(settings.boolean("autostart")),
+            reminder_time: StringBinding::new(settings.string("reminder-time")),
+        }
+    }
+
+    /// Updates the bindings after the settings were changed from outside, e.g. by restoring a backup.
+    pub fn reload_from_gsettings(&self) {
+        let settings = gio::Settings::new(crate::config::APP_ID);
+        self.autostart.set(settings.boolean("autostart"));
+        self.reminder_time
+            .set(settings.string("reminder-time").into());
+    }
+
+    /// Saves the settings and returns if the autostart setting was changed.
+    pub fn save_to_gsettings(&self) -> bool {
+        let settings = gio::Settings::new(crate::config::APP_ID);
+        let autostart_changed = settings.boolean("autostart") != self.autostart.get();
+        settings.delay();
+        let _ = settings.set_boolean("autostart", self.autostart.get());
+        let _ = settings.set_string("reminder-time", &self.reminder_time.get());
         settings.apply();
+        autostart_changed
     }
 }
 
-fn parse_json_to_training_setup(value: &json::JsonValue) -> TrainingSetup {
-    let name = value["name"]
-        .as_str()
-        // Translators: Error message printed to the console when key 'name' is not found in the JSON formatted training
-        .unwrap_or_else(|| panic!("{}", gettext("Cannot find 'name' in settings dictionary")));
-    let sets = value["sets"]
-        .as_usize()
-        // Translators: Error message printed to the console when key 'sets' is not found in the JSON formatted training
-        .unwrap_or_else(|| panic!("{}", gettext("Cannot find 'sets' in settings dictionary")));
-    let exercise_s = value["exercise_s"].as_usize().unwrap_or_else(|| {
-        panic!(
-            "{}",
-            // Translators: Error message printed to the console when key 'exercise_s' is not found in the JSON formatted training
-            gettext("Cannot find 'exercise_s' in settings dictionary")
-        )
-    });
-    let rest_s = value["rest_s"]
-        .as_usize()
-        // Translators: Error message printed to the console when key 'rest_s' is not found in the JSON formatted training
-        .unwrap_or_else(|| panic!("{}", gettext("Cannot find 'rest_s' in settings dictionary")));
-    let prepare_s = value["prepare_s"].as_usize().unwrap_or(5);
-
-    TrainingSetup {
-        name: gettext(name),
-        sets,
-        exercise_s,
-        rest_s,
-        prepare_s,
+impl Drop for ReminderSettings {
+    fn drop(&mut self) {
+        self.save_to_gsettings();
     }
 }
 
+/// Checks that the JSON value contains all the mandatory keys of a training setup.
+/// Parses a single training setup from JSON notation, e.g. from a shared link or an imported file.
+/// The training gets an identity of its own, so that importing an exported training again does
+/// not add a second training with the same id.
+pub fn parse_training_setup_json(raw_json: &str) -> Option<TrainingSetup> {
+    hiit_schema::Workout::parse(raw_json)
+        .map(TrainingSetup::from)
+        .map(|training| TrainingSetup {
+            id: TrainingSetup::new_id(),
+            revision: 0,
+            ..training
+        })
+}
+
+/// Parses a stored training, or `None` if it lacks its name, sets or durations.
+pub fn parse_json_to_training_setup(value: &json::JsonValue) -> Option<TrainingSetup> {
+    let training = hiit_schema::Workout::from_json(value).map(TrainingSetup::from);
+    if training.is_none() {
+        // Translators: Error message printed to the console when a JSON formatted training lacks its name, sets or durations
+        tracing::warn!(%value, "{}", gettext("Invalid training in settings dictionary"));
+    }
+    training
+}
+
+/// Loads the training which new ones start from. An invalid one is reset to the default.
 pub fn load_default_training_setup() -> TrainingSetup {
     let settings = gio::Settings::new(crate::config::APP_ID);
     let raw_json = settings.string("default-exercise-json");
-    parse_json_to_training_setup(&json::parse(&raw_json).unwrap_or_else(|err| {
-        panic!(
-            "{}: {}",
-            // Translators: Error message printed to the console when the default training setup loaded from the settings cannot be parsed
-            gettext("Could not parse default training setup"),
-            err
-        )
-    }))
+    if let Some(training) = json::parse(&raw_json)
+        .ok()
+        .and_then(|parsed| parse_json_to_training_setup(&parsed))
+    {
+        return training;
+    }
+    // Translators: Error message printed to the console when the default training setup loaded from the settings cannot be parsed
+    tracing::warn!(%raw_json, "{}", gettext("Could not parse default training setup"));
+    settings.reset("default-exercise-json");
+    settings
+        .default_value("default-exercise-json")
+        .and_then(|value| value.str().and_then(hiit_schema::Workout::parse))
+        .map(TrainingSetup::from)
+        // The default of the schema is broken only in a broken installation
+        .unwrap_or_else(|| panic!("{}", gettext("Could not parse default training setup")))
 }
 
+/// Loads the training list stored by earlier versions, to migrate it to the data directory.
+/// Invalid trainings are left out.
 pub fn load_training_list_from_gsettings() -> Vec<TrainingSetup> {
     let settings = gio::Settings::new(crate::config::APP_ID);
     let raw_json = settings.string("exercise-json-list");
-    let parsed = json::parse(&raw_json)
-        // Translators: Error message printed to the console when the JSON formatted list of user-created trainings cannot be parsed
-        .unwrap_or_else(|err| panic!("{}: {}", gettext("Could not parse exercise list"), err));
-    parsed.members().map(parse_json_to_training_setup).collect()
-}
-
-pub fn save_training_list_to_gsettings<'a>(exercises: impl Iterator<Item = &'a TrainingSetup>) {
-    let settings = gio::Settings::new(crate::config::APP_ID);
-    let json_list: Vec<json::JsonValue> = exercises
-        .map(|training| {
-            json::object! {
-                name: training.name.clone(),
-                sets: training.sets,
-                exercise_s: training.exercise_s,
-                rest_s: training.rest_s,
-                prepare_s: training.prepare_s,
-            }
+    match json::parse(&raw_json) {
+        Ok(parsed) => parsed
+            .members()
+            .filter_map(parse_json_to_training_setup)
+            .collect(),
+        Err(err) => {
+            // Translators: Error message printed to the console when the JSON formatted list of user-created trainings cannot be parsed
+            tracing::warn!(%err, "{}", gettext("Could not parse exercise list"));
+            Vec::new()
+        }
+    }
+}
+
+pub fn training_setup_to_json(training: &TrainingSetup) -> json::JsonValue {
+    hiit_schema::Workout::from(training).to_json()
+}
+
+pub fn load_sync_base_from_gsettings() -> Vec<TrainingSetup> {
+    let settings = gio::Settings::new(crate::config::APP_ID);
+    let raw_json = settings.string("sync-base-json");
+    json::parse(&raw_json)
+        .map(|parsed| {
+            parsed
+                .members()
+                .filter_map(parse_json_to_training_setup)
+                .collect()
         })
-        .collect();
-    settings
-        .set("exercise-json-list", json::stringify(json_list))
-        .unwrap_or_else(|err| {
-            panic!(
-                "{}: {}",
-                // Translators: Error message printed to the console when the JSON formatted list of user-created trainings cannot be written to the settings
-                gettext("Could not update settings with training list"),
-                err
-            )
-        });
+        .unwrap_or_default()
+}
+
+pub fn save_sync_base_to_gsettings(base: &[TrainingSetup]) {
+    let settings = gio::Settings::new(crate::config::APP_ID);
+    let json_list: Vec<json::JsonValue> = base.iter().map(training_setup_to_json).collect();
+    let _ = settings.set_string("sync-base-json", &json::stringify(json_list));
+}
+
+pub fn load_training_list_modified_from_gsettings() -> i64 {
+    let settings = gio::Settings::new(crate::config::APP_ID);
+    settings.int64("exercise-list-modified")
+}
+
+/// The id of the training which was loaded last, to show it again after the launch.
+pub fn load_last_training_id_from_gsettings() -> String {
+    let settings = gio::Settings::new(crate::config::APP_ID);
+    settings.string("last-training-id").into()
+}
+
+pub fn save_last_training_id_to_gsettings(id: &str) {
+    let settings = gio::Settings::new(crate::config::APP_ID);
+    let _ = settings.set_string("last-training-id", id);
+}
+
+/// The gap to get ready at the end of every rest, in seconds.
+pub fn load_transition_s_from_gsettings() -> usize {
+    let settings = gio::Settings::new(crate::config::APP_ID);
+    settings.uint("transition-s") as usize
+}
+
+/// Whether the built-in recognizer listens for the voice commands.
+#[cfg(feature = "voice-recognition")]
+pub fn load_voice_control_from_gsettings() -> bool {
+    let settings = gio::Settings::new(crate::config::APP_ID);
+    settings.boolean("voice-control")
+}
+
+/// The folder of the speech model of the voice commands, by default the one bundled with the app.
+#[cfg(feature = "voice-recognition")]
+pub fn load_voice_model_from_gsettings() -> std::path::PathBuf {
+    let settings = gio::Settings::new(crate::config::APP_ID);
+    let path = settings.string("voice-model");
+    if path.is_empty() {
+        std::path::Path::new(crate::config::PKGDATADIR).join("vosk-model")
+    } else {
+        path.as_str().into()
+    }
+}
+
+/// The shell command of the listener of the voice commands, or empty if there is none.
+pub fn load_voice_command_listener_from_gsettings() -> String {
+    let settings = gio::Settings::new(crate::config::APP_ID);
+    settings.string("voice-command-listener").into()
+}
+
+/// The steps of the warmup of trainings which use the default warmup.
+pub fn load_default_warmup_from_gsettings() -> Vec<WarmupStep> {
+    let settings = gio::Settings::new(crate::config::APP_ID);
+    json::parse(&settings.string("default-warmup-json"))
+        .map(|steps| WarmupStep::list_from_json(&steps))
+        .unwrap_or_default()
+}
+
+pub fn save_default_warmup_to_gsettings(steps: &[WarmupStep]) {
+    let settings = gio::Settings::new(crate::config::APP_ID);
+    let _ = settings.set_string(
+        "default-warmup-json",
+        &json::stringify(WarmupStep::list_to_json(steps)),
+    );
+}
+
+pub fn load_heart_rate_monitor_from_gsettings() -> String {
+    let settings = gio::Settings::new(crate::config::APP_ID);
+    settings.string("heart-rate-monitor").into()
+}
+
+pub fn load_streak_rest_days_from_gsettings() -> u32 {
+    let settings = gio::Settings::new(crate::config::APP_ID);
+    settings.uint("streak-rest-days")
+}
+
+pub fn save_streak_rest_days_to_gsettings(rest_days: u32) {
+    let settings = gio::Settings::new(crate::config::APP_ID);
+    let _ = settings.set_uint("streak-rest-days", rest_days);
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WeeklyGoalKind {
+    Minutes,
+    Sessions,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WeeklyGoal {
+    pub kind: WeeklyGoalKind,
+    /// The number of minutes or sessions to reach each week, or 0 if there is no goal.
+    pub target: u32,
+}
+
+impl WeeklyGoalKind {
+    pub fn to_setting(self) -> &'static str {
+        match self {
+            WeeklyGoalKind::Minutes => "minutes",
+            WeeklyGoalKind::Sessions => "sessions",
+        }
+    }
+
+    pub fn from_setting(raw: &str) -> Self {
+        if raw == "sessions" {
+            WeeklyGoalKind::Sessions
+        } else {
+            WeeklyGoalKind::Minutes
+        }
+    }
+}
+
+pub fn load_weekly_goal_from_gsettings() -> WeeklyGoal {
+    let settings = gio::Settings::new(crate::config::APP_ID);
+    WeeklyGoal {
+        kind: WeeklyGoalKind::from_setting(&settings.string("weekly-goal-kind")),
+        target: settings.uint("weekly-goal-target"),
+    }
+}
+
+pub fn save_weekly_goal_to_gsettings(goal: WeeklyGoal) {
+    let settings = gio::Settings::new(crate::config::APP_ID);
+    settings.delay();
+    let _ = settings.set_string("weekly-goal-kind", goal.kind.to_setting());
+    let _ = settings.set_uint("weekly-goal-target", goal.target);
+    settings.apply();
 }
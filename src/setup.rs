@@ -3,32 +3,37 @@ use gettextrs::gettext;
 use relm4::gtk::{self, gio, glib};
 
 pub fn setup() {
-    gtk::init().unwrap();
+    // Translators: Error message printed to the console when GTK cannot be initialized, e.g. without a display
+    gtk::init().unwrap_or_else(|err| panic!("{}: {}", gettext("Could not initialize GTK"), err));
     relm4_icons::initialize_icons();
+    setup_headless();
+    setup_css();
+    gtk::Window::set_default_icon_name(crate::config::APP_ID);
+}
 
+/// The setup which the timer in the terminal needs too, without a display.
+pub fn setup_headless() {
+    crate::logging::init();
     setup_gettext();
 
+    // Translators: The name of the application
     glib::set_application_name(&gettext("Exercise Timer"));
     gio::resources_register_include!("hiit.gresource")
         // Translators: Error message printed to the console when the GIO resource file cannot be registered
         .unwrap_or_else(|err| panic!("{}: {}", gettext("Could not register resources"), err));
-    setup_css();
-    gtk::Window::set_default_icon_name(crate::config::APP_ID);
 }
 
+/// Without the translations, the application is shown in English.
 fn setup_gettext() {
     gettextrs::setlocale(gettextrs::LocaleCategory::LcAll, "");
-    gettextrs::bindtextdomain(GETTEXT_PACKAGE, LOCALEDIR)
+    if let Err(err) = gettextrs::bindtextdomain(GETTEXT_PACKAGE, LOCALEDIR) {
         // Translators: Error message printed to the console when the i18n text domain cannot be bound
-        .unwrap_or_else(|err| panic!("{}: {}", gettext("Unable to bind the text domain"), err));
-    gettextrs::textdomain(GETTEXT_PACKAGE).unwrap_or_else(|err| {
-        panic!(
-            "{}: {}",
-            // Translators: Error message printed to the console when the i18n text domain cannot be switched to
-            gettext("Unable to switch to the text domain"),
-            err
-        )
-    });
+        tracing::warn!(%err, "{}", gettext("Unable to bind the text domain"));
+    }
+    if let Err(err) = gettextrs::textdomain(GETTEXT_PACKAGE) {
+        // Translators: Error message printed to the console when the i18n text domain cannot be switched to
+        tracing::warn!(%err, "{}", gettext("Unable to switch to the text domain"));
+    }
 }
 
 fn setup_css() {
@@ -42,4 +47,28 @@ fn setup_css() {
         &provider,
         gtk::STYLE_PROVIDER_PRIORITY_APPLICATION,
     );
+    setup_user_css();
+}
+
+/// Loads the optional style sheet of the user from the configuration directory,
+/// e.g. `~/.config/hiit/style.css`, over the bundled style and the period colors.
+fn setup_user_css() {
+    let path = glib::user_config_dir().join("hiit").join("style.css");
+    if !path.exists() {
+        return;
+    }
+    let Some(display) = gtk::gdk::Display::default() else {
+        return;
+    };
+    let provider = gtk::CssProvider::new();
+    provider.connect_parsing_error(|_, section, err| {
+        // Translators: Error message printed to the console when the style sheet of the user is invalid
+        tracing::warn!(%section, %err, "{}", gettext("Invalid user style"));
+    });
+    provider.load_from_path(&path);
+    gtk::style_context_add_provider_for_display(
+        &display,
+        &provider,
+        gtk::STYLE_PROVIDER_PRIORITY_APPLICATION + 2,
+    );
 }
@@ -0,0 +1,181 @@
+use crate::deep_link;
+use crate::qr_code::QrCode;
+use crate::settings;
+use crate::training_setup::TrainingSetup;
+use gettextrs::gettext;
+use relm4::{
+    adw::{self, prelude::*},
+    gtk,
+    prelude::*,
+};
+
+// The width of the light border around the symbol, in modules
+const QUIET_ZONE_MODULES: usize = 4;
+const QR_CODE_SIZE_PX: i32 = 240;
+
+pub struct ShareDialog {
+    name: String,
+    uri: String,
+    json: String,
+}
+
+pub async fn export_to_file(window: Option<gtk::Window>, name: String, contents: String) {
+    let file_dialog = gtk::FileDialog::builder()
+        // Translators: The title of the file dialog which saves a training to a file
+        .title(gettext("Export Training"))
+        .initial_name(format!("{}.json", name))
+        .build();
+    let file = match file_dialog.save_future(window.as_ref()).await {
+        Ok(file) => file,
+        Err(err) => {
+            if !err.matches(gtk::DialogError::Dismissed) {
+                // Translators: Error message printed to the console when the file dialog fails
+                tracing::warn!(%err, "{}", gettext("Could not choose the file"));
+            }
+            return;
+        }
+    };
+    if let Err((_, err)) = file
+        .replace_contents_future(
+            contents,
+            None,
+            false,
+            gtk::gio::FileCreateFlags::REPLACE_DESTINATION,
+        )
+        .await
+    {
+        // Translators: Error message printed to the console when the training cannot be saved to a file
+        tracing::warn!(%err, "{}", gettext("Could not export training"));
+    }
+}
+
+fn draw_qr_code(qr_code: &QrCode, cr: &gtk::cairo::Context, width: i32, height: i32) {
+    let modules = qr_code.size() + 2 * QUIET_ZONE_MODULES;
+    let module_size = (width.min(height) as f64 / modules as f64).floor();
+    let offset_x = (width as f64 - module_size * modules as f64) / 2.0;
+    let offset_y = (height as f64 - module_size * modules as f64) / 2.0;
+    // The symbol is always dark on light, regardless of the color scheme, so that it remains scannable
+    cr.set_source_rgb(1.0, 1.0, 1.0);
+    cr.rectangle(
+        offset_x,
+        offset_y,
+        module_size * modules as f64,
+        module_size * modules as f64,
+    );
+    let _ = cr.fill();
+    cr.set_source_rgb(0.0, 0.0, 0.0);
+    for y in 0..qr_code.size() {
+        for x in 0..qr_code.size() {
+            if qr_code.module(x, y) {
+                cr.rectangle(
+                    offset_x + ((x + QUIET_ZONE_MODULES) as f64) * module_size,
+                    offset_y + ((y + QUIET_ZONE_MODULES) as f64) * module_size,
+                    module_size,
+                    module_size,
+                );
+            }
+        }
+    }
+    let _ = cr.fill();
+}
+
+#[relm4::component(pub)]
+impl SimpleComponent for ShareDialog {
+    type Init = TrainingSetup;
+    type Input = ();
+    type Output = ();
+
+    view! {
+        adw::Dialog {
+            // Translators: The title of the dialog which shows the QR code of a training
+            set_title: &gettext("Share Training"),
+            set_content_width: 360,
+            connect_closed[sender] => move |_| {
+                let _ = sender.output(());
+            },
+            #[wrap(Some)]
+            set_child = &adw::ToolbarView {
+                add_top_bar = &adw::HeaderBar {},
+                #[wrap(Some)]
+                set_content = &gtk::Box {
+                    set_orientation: gtk::Orientation::Vertical,
+                    set_spacing: 18,
+                    set_margin_all: 24,
+                    gtk::Label {
+                        add_css_class: "title-2",
+                        set_label: &model.name,
+                        set_wrap: true,
+                    },
+                    #[name = "qr_code_area"]
+                    gtk::DrawingArea {
+                        set_content_width: QR_CODE_SIZE_PX,
+                        set_content_height: QR_CODE_SIZE_PX,
+                        set_halign: gtk::Align::Center,
+                    },
+                    #[name = "description_label"]
+                    gtk::Label {
+                        add_css_class: "dim-label",
+                        set_wrap: true,
+                        set_justify: gtk::Justification::Center,
+                        // Translators: Explanation below the QR code of a shared training
+                        set_label: &gettext("Scan the code on another device or send the link to import the training"),
+                    },
+                    gtk::Box {
+                        set_orientation: gtk::Orientation::Horizontal,
+                        set_halign: gtk::Align::Center,
+                        set_spacing: 12,
+                        gtk::Button {
+                            set_css_classes: &["suggested-action", "pill"],
+                            // Translators: Button which copies the link of the shared training to the clipboard
+                            set_label: &gettext("Copy Link"),
+                            connect_clicked[uri = model.uri.clone()] => move |button| {
+                                button.clipboard().set_text(&uri);
+                            },
+                        },
+                        gtk::Button {
+                            add_css_class: "pill",
+                            // Translators: Button which saves the shared training to a JSON file
+                            set_label: &gettext("Export File…"),
+                            connect_clicked[name = model.name.clone(), json = model.json.clone()] => move |button| {
+                                let window = button.root().and_downcast::<gtk::Window>();
+                                relm4::spawn_local(export_to_file(window, name.clone(), json.clone()));
+                            },
+                        },
+                    },
+                },
+            },
+        }
+    }
+
+    fn init(
+        init: Self::Init,
+        root: Self::Root,
+        sender: ComponentSender<Self>,
+    ) -> ComponentParts<Self> {
+        let model = ShareDialog {
+            uri: deep_link::import_uri(&init),
+            json: json::stringify_pretty(settings::training_setup_to_json(&init), 4),
+            name: init.name,
+        };
+        let widgets = view_output!();
+        match QrCode::encode(model.uri.as_bytes()) {
+            Some(qr_code) => {
+                widgets
+                    .qr_code_area
+                    .set_draw_func(move |_, cr, width, height| {
+                        draw_qr_code(&qr_code, cr, width, height);
+                    });
+            }
+            None => {
+                widgets.qr_code_area.set_visible(false);
+                widgets
+                    .description_label
+                    // Translators: Shown instead of the QR code when the training cannot be encoded in it
+                    .set_label(&gettext(
+                        "The training is too long to be shown as a QR code, send the link instead",
+                    ));
+            }
+        }
+        ComponentParts { model, widgets }
+    }
+}
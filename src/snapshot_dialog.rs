@@ -0,0 +1,99 @@
+use crate::storage;
+use gettextrs::gettext;
+use relm4::{
+    adw::{self, prelude::*},
+    gtk::{self, glib},
+    prelude::*,
+};
+use std::path::PathBuf;
+
+pub struct SnapshotDialog {}
+
+#[derive(Debug)]
+pub enum SnapshotDialogOutput {
+    Restore(PathBuf),
+    Closed,
+}
+
+/// Formats the `YYYY-MM-DD` date of the snapshot in the notation of the locale.
+fn format_snapshot_date(date: &str) -> String {
+    let mut parts = date.split('-').map(|part| part.parse::<i32>().ok());
+    let (Some(Some(year)), Some(Some(month)), Some(Some(day))) =
+        (parts.next(), parts.next(), parts.next())
+    else {
+        return date.to_string();
+    };
+    glib::DateTime::from_local(year, month, day, 0, 0, 0.0)
+        .and_then(|date_time| date_time.format("%x"))
+        .map(String::from)
+        .unwrap_or_else(|_| date.to_string())
+}
+
+#[relm4::component(pub)]
+impl SimpleComponent for SnapshotDialog {
+    type Init = Vec<PathBuf>;
+    type Input = ();
+    type Output = SnapshotDialogOutput;
+
+    view! {
+        adw::Dialog {
+            // Translators: The title of the dialog which lists the automatic snapshots of the data
+            set_title: &gettext("Restore from Snapshot"),
+            set_content_width: 400,
+            connect_closed[sender] => move |_| {
+                let _ = sender.output(SnapshotDialogOutput::Closed);
+            },
+            #[wrap(Some)]
+            set_child = &adw::ToolbarView {
+                add_top_bar = &adw::HeaderBar {},
+                #[wrap(Some)]
+                set_content = &adw::PreferencesPage {
+                    #[name = "snapshots_group"]
+                    add = &adw::PreferencesGroup {
+                        // Translators: Description of the list of the automatic snapshots of the data
+                        set_description: Some(&gettext("A snapshot of the trainings, the preferences and the history is taken every day before the first change. Restoring one replaces the current data.")),
+                    },
+                },
+            },
+        }
+    }
+
+    fn init(
+        init: Self::Init,
+        root: Self::Root,
+        sender: ComponentSender<Self>,
+    ) -> ComponentParts<Self> {
+        let model = SnapshotDialog {};
+        let widgets = view_output!();
+        if init.is_empty() {
+            widgets.snapshots_group.add(
+                &adw::ActionRow::builder()
+                    // Translators: Shown in the list of snapshots when there are none yet
+                    .title(gettext("No snapshots yet"))
+                    .build(),
+            );
+        }
+        for path in init {
+            let Some(date) = storage::snapshot_date(&path) else {
+                continue;
+            };
+            let row = adw::ActionRow::builder()
+                .title(format_snapshot_date(&date))
+                .build();
+            let button = gtk::Button::builder()
+                // Translators: The button which restores the data from a snapshot
+                .label(gettext("Restore"))
+                .valign(gtk::Align::Center)
+                .build();
+            let sender = sender.clone();
+            let dialog = root.clone();
+            button.connect_clicked(move |_| {
+                let _ = sender.output(SnapshotDialogOutput::Restore(path.clone()));
+                dialog.close();
+            });
+            row.add_suffix(&button);
+            widgets.snapshots_group.add(&row);
+        }
+        ComponentParts { model, widgets }
+    }
+}
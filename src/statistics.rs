@@ -0,0 +1,240 @@
+use crate::history::SessionRecord;
+use crate::settings::{WeeklyGoal, WeeklyGoalKind};
+use relm4::gtk::glib;
+use std::collections::HashMap;
+
+const SECONDS_PER_DAY: i64 = 24 * 60 * 60;
+
+/// The number of sessions in a row in which every round has to be done before the next progression is suggested.
+pub const PROGRESSION_SESSIONS: usize = 3;
+
+/// The number of days between the Unix epoch and the local calendar day of the timestamp.
+pub fn local_day(unix: i64) -> i64 {
+    let offset_s = glib::DateTime::from_unix_local(unix)
+        .map(|date_time| date_time.utc_offset().as_seconds())
+        .unwrap_or(0);
+    (unix + offset_s).div_euclid(SECONDS_PER_DAY)
+}
+
+/// The number of weeks between the Unix epoch and the week of the day, weeks starting on Monday.
+pub fn week_of_day(day: i64) -> i64 {
+    // The Unix epoch was a Thursday
+    (day + 3).div_euclid(7)
+}
+
+/// The first day (Monday) of the week.
+pub fn first_day_of_week(week: i64) -> i64 {
+    week * 7 - 3
+}
+
+/// A time before the start of the week in any time zone, for loading the sessions of the week.
+pub fn before_week(week: i64) -> i64 {
+    (first_day_of_week(week) - 1) * SECONDS_PER_DAY
+}
+
+pub fn today() -> i64 {
+    local_day(crate::sync::unix_now())
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct TrainingStatistics {
+    pub training_id: String,
+    pub name: String,
+    pub sessions: usize,
+    pub completed_sessions: usize,
+    pub total_s: usize,
+}
+
+/// The number of sessions in each of the last `weeks` weeks, the current week last.
+pub fn sessions_per_week(sessions: &[SessionRecord], weeks: usize) -> Vec<usize> {
+    let current_week = week_of_day(today());
+    let mut counts = vec![0; weeks];
+    for session in sessions {
+        let age = current_week - week_of_day(local_day(session.started));
+        if (0..weeks as i64).contains(&age) {
+            counts[weeks - 1 - age as usize] += 1;
+        }
+    }
+    counts
+}
+
+/// The heart rate recoveries of the last `count` sessions which measured one, the latest last.
+pub fn hr_recovery_trend(sessions: &[SessionRecord], count: usize) -> Vec<u32> {
+    let mut recoveries: Vec<u32> = sessions
+        .iter()
+        .rev()
+        .filter_map(|session| session.hr_recovery_bpm)
+        .take(count)
+        .collect();
+    recoveries.reverse();
+    recoveries
+}
+
+pub fn total_seconds(sessions: &[SessionRecord]) -> usize {
+    sessions.iter().map(|session| session.duration_s).sum()
+}
+
+/// Aggregates the sessions per training, the most frequent training first.
+/// The latest name of the training is shown, in case it was renamed.
+pub fn per_training(sessions: &[SessionRecord]) -> Vec<TrainingStatistics> {
+    let mut statistics: Vec<TrainingStatistics> = Vec::new();
+    for session in sessions {
+        let index = match statistics
+            .iter()
+            .position(|training| training.training_id == session.training_id)
+        {
+            Some(index) => index,
+            None => {
+                statistics.push(TrainingStatistics {
+                    training_id: session.training_id.clone(),
+                    ..Default::default()
+                });
+                statistics.len() - 1
+            }
+        };
+        let training = &mut statistics[index];
+        training.name.clone_from(&session.training_name);
+        training.sessions += 1;
+        training.total_s += session.duration_s;
+        if session.completed {
+            training.completed_sessions += 1;
+        }
+    }
+    statistics.sort_by_key(|training| std::cmp::Reverse(training.sessions));
+    statistics
+}
+
+/// The average share of the sets which were carried out, over the sessions.
+pub fn completion_rate(sessions: &[SessionRecord]) -> f64 {
+    let rates: Vec<f64> = sessions
+        .iter()
+        .filter(|session| session.sets > 0)
+        .map(|session| (session.rounds.len() as f64 / session.sets as f64).min(1.0))
+        .collect();
+    if rates.is_empty() {
+        0.0
+    } else {
+        rates.iter().sum::<f64>() / rates.len() as f64
+    }
+}
+
+/// The time spent training on each local day, keyed by the day.
+pub fn seconds_per_day(sessions: &[SessionRecord]) -> HashMap<i64, usize> {
+    let mut seconds = HashMap::new();
+    for session in sessions {
+        *seconds.entry(local_day(session.started)).or_insert(0) += session.duration_s;
+    }
+    seconds
+}
+
+pub fn sessions_of_day(sessions: &[SessionRecord], day: i64) -> Vec<&SessionRecord> {
+    sessions
+        .iter()
+        .filter(|session| local_day(session.started) == day)
+        .collect()
+}
+
+/// If the session counts as a workout for the streaks, the weekly goal of sessions and the
+/// achievements, rather than a training which was left before its end.
+pub fn is_workout(session: &SessionRecord) -> bool {
+    session.completed
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Streaks {
+    /// The number of active days in the streak which is still going on.
+    pub current: usize,
+    pub best: usize,
+}
+
+/// Computes the streaks of consecutive days with workouts. Up to `rest_days` days without a workout
+/// between two active days do not break the streak, but they are not counted either.
+pub fn streaks(sessions: &[SessionRecord], rest_days: u32) -> Streaks {
+    let mut days: Vec<i64> = sessions
+        .iter()
+        .filter(|session| is_workout(session))
+        .map(|session| local_day(session.started))
+        .collect();
+    days.sort_unstable();
+    days.dedup();
+    let max_gap = rest_days as i64 + 1;
+    let mut streaks = Streaks::default();
+    let mut length = 0;
+    let mut previous_day: Option<i64> = None;
+    for day in &days {
+        length = match previous_day {
+            Some(previous_day) if day - previous_day <= max_gap => length + 1,
+            _ => 1,
+        };
+        streaks.best = streaks.best.max(length);
+        previous_day = Some(*day);
+    }
+    if previous_day.is_some_and(|last_day| today() - last_day <= max_gap) {
+        streaks.current = length;
+    }
+    streaks
+}
+
+/// The minutes of training or the number of workouts in the week. The minutes of trainings which
+/// were left before their end count as well.
+pub fn weekly_progress(sessions: &[SessionRecord], week: i64, kind: WeeklyGoalKind) -> u32 {
+    let sessions_of_week = sessions
+        .iter()
+        .filter(|session| week_of_day(local_day(session.started)) == week);
+    match kind {
+        WeeklyGoalKind::Minutes => {
+            (sessions_of_week
+                .map(|session| session.duration_s)
+                .sum::<usize>()
+                / 60) as u32
+        }
+        WeeklyGoalKind::Sessions => sessions_of_week
+            .filter(|session| is_workout(session))
+            .count() as u32,
+    }
+}
+
+/// The time spent in periods of each intensity in the week: easy, moderate and hard.
+/// Periods without an intensity are left out.
+pub fn seconds_per_intensity(sessions: &[SessionRecord], week: i64) -> [usize; 3] {
+    let mut seconds = [0; 3];
+    for period in sessions
+        .iter()
+        .filter(|session| week_of_day(local_day(session.started)) == week)
+        .flat_map(|session| &session.periods)
+    {
+        if let Some(intensity) = period.intensity {
+            seconds[intensity as usize] += period.length_s;
+        }
+    }
+    seconds
+}
+
+/// The number of past weeks in which the recorded goal was met, and the number of past weeks with a goal.
+pub fn weekly_goals_met(sessions: &[SessionRecord], goals: &[(i64, WeeklyGoal)]) -> (usize, usize) {
+    let current_week = week_of_day(today());
+    let past_goals: Vec<&(i64, WeeklyGoal)> = goals
+        .iter()
+        .filter(|(week, goal)| *week < current_week && goal.target > 0)
+        .collect();
+    let met = past_goals
+        .iter()
+        .filter(|(week, goal)| weekly_progress(sessions, *week, goal.kind) >= goal.target)
+        .count();
+    (met, past_goals.len())
+}
+
+/// If the last sessions of the training were all completed without skipping a round.
+pub fn ready_to_progress(sessions: &[SessionRecord], training_id: &str) -> bool {
+    let mut recent: Vec<&SessionRecord> = sessions
+        .iter()
+        .filter(|session| session.training_id == training_id)
+        .collect();
+    recent.sort_by_key(|session| session.started);
+    recent.len() >= PROGRESSION_SESSIONS
+        && recent
+            .iter()
+            .rev()
+            .take(PROGRESSION_SESSIONS)
+            .all(|session| session.completed && session.rounds.iter().all(|round| !round.skipped))
+}
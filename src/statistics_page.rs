@@ -0,0 +1,796 @@
+use crate::history;
+use crate::intensity::Intensity;
+use crate::progress_ring;
+use crate::replay_dialog;
+use crate::settings::{self, WeeklyGoal, WeeklyGoalKind};
+use crate::statistics;
+use crate::training_timer::{format_hr_recovery, format_kcal};
+use gettextrs::{gettext, ngettext};
+use relm4::{
+    adw::{self, prelude::*},
+    gtk,
+    prelude::*,
+    RelmWidgetExt,
+};
+use relm4_icons::icon_names;
+use std::collections::HashMap;
+
+/// The number of weeks shown in the chart of the workouts per week.
+const CHART_WEEKS: usize = 12;
+const CHART_HEIGHT_PX: i32 = 160;
+/// The number of sessions shown in the chart of the heart rate recovery.
+const RECOVERY_CHART_SESSIONS: usize = 20;
+/// The number of weeks shown in the heatmap, so that a whole year is covered.
+const HEATMAP_WEEKS: i64 = 53;
+const HEATMAP_HEIGHT_PX: i32 = 110;
+const HEATMAP_GAP_PX: f64 = 2.0;
+
+pub struct StatisticsPage {
+    sessions: Vec<history::SessionRecord>,
+    streaks: statistics::Streaks,
+    weekly_goal: WeeklyGoal,
+    goals_met: (usize, usize),
+}
+
+impl StatisticsPage {
+    fn weekly_progress(&self) -> u32 {
+        statistics::weekly_progress(
+            &self.sessions,
+            statistics::week_of_day(statistics::today()),
+            self.weekly_goal.kind,
+        )
+    }
+
+    fn set_weekly_goal(&mut self, weekly_goal: WeeklyGoal) {
+        self.weekly_goal = weekly_goal;
+        settings::save_weekly_goal_to_gsettings(weekly_goal);
+        history::record_weekly_goal(statistics::week_of_day(statistics::today()), weekly_goal);
+    }
+}
+
+#[derive(Debug)]
+pub enum StatisticsPageInput {
+    StreakRestDays(u32),
+    WeeklyGoalKind(WeeklyGoalKind),
+    WeeklyGoalTarget(u32),
+}
+
+/// Draws a bar per week, with the current week at the end, i.e. on the left in right-to-left locales.
+fn draw_weekly_chart(
+    counts: &[usize],
+    rtl: bool,
+    color: &gtk::gdk::RGBA,
+    cr: &gtk::cairo::Context,
+    width: i32,
+    height: i32,
+) {
+    let max_count = counts.iter().copied().max().unwrap_or(0).max(1);
+    let label_height = 16.0;
+    let slot_width = width as f64 / counts.len() as f64;
+    let bar_width = slot_width * 0.6;
+    let chart_height = height as f64 - 2.0 * label_height;
+    cr.set_font_size(11.0);
+    for (index, count) in counts.iter().enumerate() {
+        let is_current_week = index == counts.len() - 1;
+        let alpha = if is_current_week { 1.0 } else { 0.55 };
+        cr.set_source_rgba(
+            color.red() as f64,
+            color.green() as f64,
+            color.blue() as f64,
+            color.alpha() as f64 * alpha,
+        );
+        let bar_height = chart_height * *count as f64 / max_count as f64;
+        let slot = if rtl { counts.len() - 1 - index } else { index };
+        let x = slot_width * slot as f64 + (slot_width - bar_width) / 2.0;
+        let y = label_height + chart_height - bar_height;
+        cr.rectangle(x, y, bar_width, bar_height.max(1.0));
+        let _ = cr.fill();
+        if *count > 0 {
+            let text = count.to_string();
+            if let Ok(extents) = cr.text_extents(&text) {
+                cr.move_to(x + (bar_width - extents.width()) / 2.0, y - 4.0);
+                let _ = cr.show_text(&text);
+            }
+        }
+    }
+}
+
+/// Draws the heart rate recoveries as a line, the latest at the end, i.e. on the left in
+/// right-to-left locales. The scale starts above zero, so that small changes are visible.
+fn draw_recovery_chart(
+    recoveries: &[u32],
+    rtl: bool,
+    color: &gtk::gdk::RGBA,
+    cr: &gtk::cairo::Context,
+    width: i32,
+    height: i32,
+) {
+    let (Some(min), Some(max)) = (recoveries.iter().min(), recoveries.iter().max()) else {
+        return;
+    };
+    let low = min.saturating_sub(5) as f64;
+    let high = (*max + 5) as f64;
+    let label_height = 16.0;
+    let slot_width = width as f64 / recoveries.len() as f64;
+    let chart_height = height as f64 - 2.0 * label_height;
+    let point = |index: usize, bpm: u32| {
+        let slot = if rtl {
+            recoveries.len() - 1 - index
+        } else {
+            index
+        };
+        (
+            slot_width * (slot as f64 + 0.5),
+            label_height + chart_height * (high - bpm as f64) / (high - low),
+        )
+    };
+    cr.set_source_rgba(
+        color.red() as f64,
+        color.green() as f64,
+        color.blue() as f64,
+        color.alpha() as f64,
+    );
+    cr.set_line_width(2.0);
+    for (index, bpm) in recoveries.iter().enumerate() {
+        let (x, y) = point(index, *bpm);
+        cr.line_to(x, y);
+    }
+    let _ = cr.stroke();
+    cr.set_font_size(11.0);
+    for (index, bpm) in recoveries.iter().enumerate() {
+        let (x, y) = point(index, *bpm);
+        cr.arc(x, y, 3.0, 0.0, 2.0 * std::f64::consts::PI);
+        let _ = cr.fill();
+        // The first and the latest value give the scale
+        if index == 0 || index == recoveries.len() - 1 {
+            let text = bpm.to_string();
+            if let Ok(extents) = cr.text_extents(&text) {
+                cr.move_to(x - extents.width() / 2.0, y - 6.0);
+                let _ = cr.show_text(&text);
+            }
+        }
+    }
+}
+
+/// The geometry of the heatmap: the size of a cell including the gap, and the offset of the first column.
+fn heatmap_geometry(width: i32, height: i32) -> (f64, f64) {
+    let cell = (width as f64 / HEATMAP_WEEKS as f64).min(height as f64 / 7.0);
+    let offset_x = (width as f64 - cell * HEATMAP_WEEKS as f64) / 2.0;
+    (cell, offset_x)
+}
+
+/// The position of the column of the heatmap from the left, as the weeks run from right to left in right-to-left locales.
+fn heatmap_slot(column: i64, rtl: bool) -> i64 {
+    if rtl {
+        HEATMAP_WEEKS - 1 - column
+    } else {
+        column
+    }
+}
+
+/// The first day shown in the heatmap, the Monday of the first column.
+fn heatmap_first_day() -> i64 {
+    statistics::first_day_of_week(statistics::week_of_day(statistics::today()) - HEATMAP_WEEKS + 1)
+}
+
+/// The day under the point of the heatmap, if there is one and it is not in the future.
+fn heatmap_day_at(x: f64, y: f64, width: i32, height: i32, rtl: bool) -> Option<i64> {
+    let (cell, offset_x) = heatmap_geometry(width, height);
+    let slot = ((x - offset_x) / cell).floor();
+    let row = (y / cell).floor();
+    if !(0.0..HEATMAP_WEEKS as f64).contains(&slot) || !(0.0..7.0).contains(&row) {
+        return None;
+    }
+    let column = heatmap_slot(slot as i64, rtl);
+    let day = heatmap_first_day() + column * 7 + row as i64;
+    (day <= statistics::today()).then_some(day)
+}
+
+fn draw_heatmap(
+    seconds_per_day: &HashMap<i64, usize>,
+    rtl: bool,
+    color: &gtk::gdk::RGBA,
+    cr: &gtk::cairo::Context,
+    width: i32,
+    height: i32,
+) {
+    let (cell, offset_x) = heatmap_geometry(width, height);
+    let max_seconds = seconds_per_day.values().copied().max().unwrap_or(0).max(1);
+    let first_day = heatmap_first_day();
+    let today = statistics::today();
+    for column in 0..HEATMAP_WEEKS {
+        for row in 0..7 {
+            let day = first_day + column * 7 + row;
+            if day > today {
+                continue;
+            }
+            // Four levels of intensity, relative to the most active day
+            let seconds = seconds_per_day.get(&day).copied().unwrap_or(0);
+            let alpha = if seconds == 0 {
+                0.08
+            } else {
+                0.25 * (1.0 + (3.0 * seconds as f64 / max_seconds as f64).floor()).min(4.0)
+            };
+            cr.set_source_rgba(
+                color.red() as f64,
+                color.green() as f64,
+                color.blue() as f64,
+                color.alpha() as f64 * alpha,
+            );
+            cr.rectangle(
+                offset_x + heatmap_slot(column, rtl) as f64 * cell,
+                row as f64 * cell,
+                cell - HEATMAP_GAP_PX,
+                cell - HEATMAP_GAP_PX,
+            );
+            let _ = cr.fill();
+        }
+    }
+}
+
+pub fn format_day(day: i64) -> String {
+    gtk::glib::DateTime::from_unix_utc(day * 24 * 60 * 60)
+        .and_then(|date_time| date_time.format("%x"))
+        .map(String::from)
+        .unwrap_or_default()
+}
+
+fn format_time_of_day(unix: i64) -> String {
+    gtk::glib::DateTime::from_unix_local(unix)
+        .and_then(|date_time| date_time.format("%R"))
+        .map(String::from)
+        .unwrap_or_default()
+}
+
+/// Shows the sessions of the day in a popover pointing to its cell.
+fn show_day_popover(
+    area: &gtk::DrawingArea,
+    sessions: &[history::SessionRecord],
+    day: i64,
+    x: f64,
+    y: f64,
+) {
+    let content = gtk::Box::builder()
+        .orientation(gtk::Orientation::Vertical)
+        .spacing(6)
+        .build();
+    let heading = gtk::Label::builder()
+        .label(format_day(day))
+        .css_classes(["heading"])
+        .xalign(0.0)
+        .build();
+    content.append(&heading);
+    let popover = gtk::Popover::builder()
+        .child(&content)
+        .pointing_to(&gtk::gdk::Rectangle::new(x as i32, y as i32, 1, 1))
+        .build();
+    let sessions_of_day = statistics::sessions_of_day(sessions, day);
+    if sessions_of_day.is_empty() {
+        // Translators: Shown in the popover of a day of the heatmap without any sessions
+        content.append(&gtk::Label::new(Some(&gettext("No workouts"))));
+    }
+    for session in sessions_of_day {
+        let mut text = format!(
+            "{}  {} · {}",
+            format_time_of_day(session.started),
+            session.training_name,
+            format_minutes(session.duration_s)
+        );
+        if let Some(kcal) = session.kcal {
+            text = format!("{} · {}", text, format_kcal(kcal));
+        }
+        if let Some(bpm) = session.hr_recovery_bpm {
+            text = format!("{} · {}", text, format_hr_recovery(bpm));
+        }
+        let label = gtk::Label::builder()
+            .label(text)
+            .xalign(0.0)
+            .hexpand(true)
+            .build();
+        let replay_button = gtk::Button::builder()
+            .icon_name(icon_names::PLAY)
+            // Translators: Tooltip of the button which shows how a past session went
+            .tooltip_text(gettext("Replay Session"))
+            .css_classes(["flat"])
+            .build();
+        {
+            let (popover, area, session) = (popover.clone(), area.clone(), session.clone());
+            replay_button.connect_clicked(move |_| {
+                popover.popdown();
+                replay_dialog::show(&area, &session);
+            });
+        }
+        let session_box = gtk::Box::builder().spacing(6).build();
+        session_box.append(&label);
+        session_box.append(&replay_button);
+        content.append(&session_box);
+        if let Some(rpe) = session.rpe {
+            content.append(
+                &gtk::Label::builder()
+                    .label(if false {
+                        // Translators: The rated exertion of a session in the popover of a day, e.g. "Exertion: 7/10"
+                        gettext("Exertion: {}/10")
+                    } else {
+                        gettext!("Exertion: {}/10", rpe)
+                    })
+                    .css_classes(["dim-label"])
+                    .xalign(0.0)
+                    .build(),
+            );
+        }
+        if !session.note.is_empty() {
+            content.append(
+                &gtk::Label::builder()
+                    .label(&session.note)
+                    .css_classes(["dim-label"])
+                    .wrap(true)
+                    .max_width_chars(40)
+                    .xalign(0.0)
+                    .build(),
+            );
+        }
+    }
+    popover.set_parent(area);
+    popover.connect_closed(|popover| {
+        popover.unparent();
+    });
+    popover.popup();
+}
+
+/// Builds the popover with the aggregated sessions of a single training.
+pub fn training_statistics_popover(training_id: &str) -> gtk::Popover {
+    let sessions = history::load_training_sessions(training_id);
+    let grid = gtk::Grid::builder()
+        .column_spacing(24)
+        .row_spacing(6)
+        .build();
+    if sessions.is_empty() {
+        grid.attach(
+            // Translators: Shown in the statistics popover of a training which was never carried out
+            &gtk::Label::new(Some(&gettext("No workouts yet"))),
+            0,
+            0,
+            1,
+            1,
+        );
+    } else {
+        let completed = sessions.iter().filter(|session| session.completed).count();
+        let streaks =
+            statistics::streaks(&sessions, settings::load_streak_rest_days_from_gsettings());
+        let rows = [
+            (
+                // Translators: The label of the number of completed sessions in the statistics popover of a training
+                gettext("Completed"),
+                if false {
+                    // Translators: The number of completed sessions of all sessions of a training, e.g. "4 of 5"
+                    gettext("{} of {}")
+                } else {
+                    gettext!("{} of {}", completed, sessions.len())
+                },
+            ),
+            (
+                // Translators: The label of the total time in the statistics popover of a training
+                gettext("Total Time"),
+                format_minutes(statistics::total_seconds(&sessions)),
+            ),
+            (
+                // Translators: The label of the average share of the carried out sets in the statistics popover of a training
+                gettext("Average Completion"),
+                format!("{:.0} %", statistics::completion_rate(&sessions) * 100.0),
+            ),
+            (
+                // Translators: The label of the longest streak in the statistics popover of a training
+                gettext("Best Streak"),
+                format_days(streaks.best),
+            ),
+        ];
+        for (row, (title, value)) in rows.into_iter().enumerate() {
+            grid.attach(
+                &gtk::Label::builder().label(title).xalign(0.0).build(),
+                0,
+                row as i32,
+                1,
+                1,
+            );
+            grid.attach(
+                &gtk::Label::builder()
+                    .label(value)
+                    .xalign(1.0)
+                    .css_classes(["numeric"])
+                    .build(),
+                1,
+                row as i32,
+                1,
+                1,
+            );
+        }
+    }
+    gtk::Popover::builder().child(&grid).build()
+}
+
+/// The size of the weekly goal ring on the statistics page.
+const GOAL_RING_SIZE_PX: i32 = 96;
+
+/// Returns a draw function of the progress ring of the weekly goal.
+pub fn draw_goal_ring(
+    fraction: f64,
+) -> impl Fn(&gtk::DrawingArea, &gtk::cairo::Context, i32, i32) + 'static {
+    move |area, cr, width, height| {
+        let mirrored = area.direction() == gtk::TextDirection::Rtl;
+        progress_ring::draw_progress_ring(
+            cr,
+            width,
+            height,
+            fraction,
+            &area.color(),
+            8.0,
+            mirrored,
+        );
+    }
+}
+
+pub fn format_goal_progress(progress: u32, goal: WeeklyGoal) -> String {
+    match goal.kind {
+        WeeklyGoalKind::Minutes => {
+            if false {
+                // Translators: The progress toward the weekly goal, e.g. "45 of 90 minutes"
+                ngettext("{} of {} minute", "{} of {} minutes", goal.target)
+            } else {
+                ngettext!(
+                    "{} of {} minute",
+                    "{} of {} minutes",
+                    goal.target,
+                    progress,
+                    goal.target
+                )
+            }
+        }
+        WeeklyGoalKind::Sessions => {
+            if false {
+                // Translators: The progress toward the weekly goal, e.g. "2 of 4 sessions"
+                ngettext("{} of {} session", "{} of {} sessions", goal.target)
+            } else {
+                ngettext!(
+                    "{} of {} session",
+                    "{} of {} sessions",
+                    goal.target,
+                    progress,
+                    goal.target
+                )
+            }
+        }
+    }
+}
+
+pub fn format_days(days: usize) -> String {
+    if false {
+        // Translators: The length of a streak of consecutive days with workouts
+        ngettext("{} day", "{} days", days as u32)
+    } else {
+        ngettext!("{} day", "{} days", days as u32, days)
+    }
+}
+
+fn format_minutes(seconds: usize) -> String {
+    let minutes = seconds / 60;
+    if false {
+        // Translators: The total time of the workouts on the statistics page
+        ngettext("{} minute", "{} minutes", minutes as u32)
+    } else {
+        ngettext!("{} minute", "{} minutes", minutes as u32, minutes)
+    }
+}
+
+#[relm4::component(pub)]
+impl SimpleComponent for StatisticsPage {
+    type Init = ();
+    type Input = StatisticsPageInput;
+    type Output = ();
+
+    view! {
+        gtk::Stack {
+            add_named[Some("empty")] = &adw::StatusPage {
+                set_icon_name: Some(icon_names::GRAPH),
+                // Translators: The title shown on the statistics page when there are no sessions recorded
+                set_title: &gettext("No Workouts Yet"),
+                // Translators: The description shown on the statistics page when there are no sessions recorded
+                set_description: Some(&gettext("Finished and aborted trainings are recorded here")),
+            },
+            add_named[Some("statistics")] = &gtk::ScrolledWindow {
+                set_vexpand: true,
+                adw::Clamp {
+                    gtk::Box {
+                        set_orientation: gtk::Orientation::Vertical,
+                        set_spacing: 24,
+                        set_margin_all: 12,
+                        adw::PreferencesGroup {
+                            // Translators: The title of the summary on the statistics page
+                            set_title: &gettext("Summary"),
+                            #[name = "workouts_row"]
+                            adw::ActionRow {
+                                // Translators: The title of the row which shows the number of workouts on the statistics page
+                                set_title: &gettext("Workouts"),
+                            },
+                            #[name = "total_time_row"]
+                            adw::ActionRow {
+                                // Translators: The title of the row which shows the total time of the workouts on the statistics page
+                                set_title: &gettext("Total Time"),
+                            },
+                        },
+                        adw::PreferencesGroup {
+                            // Translators: The title of the streaks on the statistics page
+                            set_title: &gettext("Streaks"),
+                            adw::ActionRow {
+                                // Translators: The title of the row which shows the streak which is still going on
+                                set_title: &gettext("Current Streak"),
+                                add_suffix = &gtk::Label {
+                                    #[watch]
+                                    set_label: &format_days(model.streaks.current),
+                                },
+                            },
+                            adw::ActionRow {
+                                // Translators: The title of the row which shows the longest streak
+                                set_title: &gettext("Best Streak"),
+                                add_suffix = &gtk::Label {
+                                    #[watch]
+                                    set_label: &format_days(model.streaks.best),
+                                },
+                            },
+                            adw::SpinRow {
+                                // Translators: The title of the row which sets the number of days without a workout which do not break a streak
+                                set_title: &gettext("Rest Days"),
+                                // Translators: The subtitle of the row which sets the number of days without a workout which do not break a streak
+                                set_subtitle: &gettext("Days off allowed within a streak"),
+                                set_adjustment: Some(&gtk::Adjustment::new(
+                                    settings::load_streak_rest_days_from_gsettings() as f64,
+                                    0.0,
+                                    3.0,
+                                    1.0,
+                                    1.0,
+                                    0.0,
+                                )),
+                                connect_value_notify[sender] => move |row| {
+                                    sender.input(StatisticsPageInput::StreakRestDays(row.value() as u32));
+                                },
+                            },
+                        },
+                        adw::PreferencesGroup {
+                            // Translators: The title of the weekly goal on the statistics page
+                            set_title: &gettext("Weekly Goal"),
+                            gtk::Box {
+                                set_orientation: gtk::Orientation::Horizontal,
+                                set_spacing: 18,
+                                set_margin_bottom: 12,
+                                #[watch]
+                                set_visible: model.weekly_goal.target > 0,
+                                gtk::DrawingArea {
+                                    add_css_class: "accent",
+                                    set_content_width: GOAL_RING_SIZE_PX,
+                                    set_content_height: GOAL_RING_SIZE_PX,
+                                    #[watch]
+                                    set_draw_func: draw_goal_ring(model.weekly_progress() as f64 / model.weekly_goal.target.max(1) as f64),
+                                },
+                                gtk::Label {
+                                    add_css_class: "title-3",
+                                    set_wrap: true,
+                                    #[watch]
+                                    set_label: &format_goal_progress(model.weekly_progress(), model.weekly_goal),
+                                },
+                            },
+                            adw::ComboRow {
+                                // Translators: The title of the row which selects what the weekly goal counts
+                                set_title: &gettext("Goal"),
+                                set_model: Some(&gtk::StringList::new(&[
+                                    // Translators: The weekly goal counts the minutes of training
+                                    &gettext("Minutes"),
+                                    // Translators: The weekly goal counts the sessions of training
+                                    &gettext("Sessions"),
+                                ])),
+                                set_selected: if model.weekly_goal.kind == WeeklyGoalKind::Sessions { 1 } else { 0 },
+                                connect_selected_notify[sender] => move |row| {
+                                    sender.input(StatisticsPageInput::WeeklyGoalKind(
+                                        if row.selected() == 1 { WeeklyGoalKind::Sessions } else { WeeklyGoalKind::Minutes },
+                                    ));
+                                },
+                            },
+                            adw::SpinRow {
+                                // Translators: The title of the row which sets the weekly goal
+                                set_title: &gettext("Target"),
+                                // Translators: The subtitle of the row which sets the weekly goal
+                                set_subtitle: &gettext("Set to 0 to disable the goal"),
+                                set_adjustment: Some(&gtk::Adjustment::new(
+                                    model.weekly_goal.target as f64,
+                                    0.0,
+                                    10000.0,
+                                    1.0,
+                                    10.0,
+                                    0.0,
+                                )),
+                                connect_value_notify[sender] => move |row| {
+                                    sender.input(StatisticsPageInput::WeeklyGoalTarget(row.value() as u32));
+                                },
+                            },
+                            adw::ActionRow {
+                                // Translators: The title of the row which shows in how many past weeks the goal was met
+                                set_title: &gettext("Goals Met"),
+                                #[watch]
+                                set_visible: model.goals_met.1 > 0,
+                                add_suffix = &gtk::Label {
+                                    #[watch]
+                                    set_label: &if false {
+                                        // Translators: In how many past weeks the goal was met, e.g. "3 of 5 weeks"
+                                        ngettext("{} of {} week", "{} of {} weeks", model.goals_met.1 as u32)
+                                    } else {
+                                        ngettext!("{} of {} week", "{} of {} weeks", model.goals_met.1 as u32, model.goals_met.0, model.goals_met.1)
+                                    },
+                                },
+                            },
+                        },
+                        #[name = "intensity_group"]
+                        adw::PreferencesGroup {
+                            // Translators: The title of the time of each intensity in the current week on the statistics page
+                            set_title: &gettext("Intensity This Week"),
+                            // Translators: The description of the time of each intensity in the current week on the statistics page
+                            set_description: Some(&gettext("The time of the periods whose intensity is chosen in the training")),
+                        },
+                        adw::PreferencesGroup {
+                            // Translators: The title of the chart of the workouts per week on the statistics page
+                            set_title: &gettext("Workouts per Week"),
+                            // Translators: The description of the chart of the workouts per week on the statistics page
+                            set_description: Some(&gettext("The last 12 weeks, the current week on the right")),
+                            #[name = "weekly_chart"]
+                            gtk::DrawingArea {
+                                add_css_class: "card",
+                                add_css_class: "accent",
+                                set_content_height: CHART_HEIGHT_PX,
+                            },
+                        },
+                        #[name = "recovery_group"]
+                        adw::PreferencesGroup {
+                            // Translators: The title of the chart of the heart rate recovery on the statistics page
+                            set_title: &gettext("Heart Rate Recovery"),
+                            // Translators: The description of the chart of the heart rate recovery on the statistics page
+                            set_description: Some(&gettext("The drop of the heart rate in the minute after the last 20 measured trainings, in beats per minute. A larger drop is a sign of better fitness.")),
+                            #[name = "recovery_chart"]
+                            gtk::DrawingArea {
+                                add_css_class: "card",
+                                add_css_class: "accent",
+                                set_content_height: CHART_HEIGHT_PX,
+                            },
+                        },
+                        adw::PreferencesGroup {
+                            // Translators: The title of the heatmap of the daily activity on the statistics page
+                            set_title: &gettext("Activity"),
+                            // Translators: The description of the heatmap of the daily activity on the statistics page
+                            set_description: Some(&gettext("The time spent training on each day of the last year. Click a day to see its workouts.")),
+                            #[name = "heatmap"]
+                            gtk::DrawingArea {
+                                add_css_class: "accent",
+                                set_content_height: HEATMAP_HEIGHT_PX,
+                                set_cursor_from_name: Some("pointer"),
+                            },
+                        },
+                        #[name = "trainings_group"]
+                        adw::PreferencesGroup {
+                            // Translators: The title of the per-training breakdown on the statistics page
+                            set_title: &gettext("Trainings"),
+                        },
+                    },
+                },
+            },
+        }
+    }
+
+    fn init(
+        _init: Self::Init,
+        root: Self::Root,
+        sender: ComponentSender<Self>,
+    ) -> ComponentParts<Self> {
+        let sessions = history::load_sessions();
+        let model = StatisticsPage {
+            streaks: statistics::streaks(
+                &sessions,
+                settings::load_streak_rest_days_from_gsettings(),
+            ),
+            weekly_goal: settings::load_weekly_goal_from_gsettings(),
+            goals_met: statistics::weekly_goals_met(&sessions, &history::load_weekly_goals()),
+            sessions: sessions.clone(),
+        };
+        let widgets = view_output!();
+        if sessions.is_empty() {
+            root.set_visible_child_name("empty");
+            return ComponentParts { model, widgets };
+        }
+        root.set_visible_child_name("statistics");
+        widgets
+            .workouts_row
+            .add_suffix(&gtk::Label::new(Some(&sessions.len().to_string())));
+        widgets
+            .total_time_row
+            .add_suffix(&gtk::Label::new(Some(&format_minutes(
+                statistics::total_seconds(&sessions),
+            ))));
+        let seconds_per_intensity = statistics::seconds_per_intensity(
+            &sessions,
+            statistics::week_of_day(statistics::today()),
+        );
+        for (intensity, seconds) in Intensity::ALL.into_iter().zip(seconds_per_intensity) {
+            let row = adw::ActionRow::builder().title(intensity.title()).build();
+            row.add_suffix(&gtk::Label::new(Some(&format_minutes(seconds))));
+            widgets.intensity_group.add(&row);
+        }
+        let counts = statistics::sessions_per_week(&sessions, CHART_WEEKS);
+        widgets
+            .weekly_chart
+            .set_draw_func(move |area, cr, width, height| {
+                let rtl = area.direction() == gtk::TextDirection::Rtl;
+                draw_weekly_chart(&counts, rtl, &area.color(), cr, width, height);
+            });
+        let recoveries = statistics::hr_recovery_trend(&sessions, RECOVERY_CHART_SESSIONS);
+        widgets.recovery_group.set_visible(!recoveries.is_empty());
+        widgets
+            .recovery_chart
+            .set_draw_func(move |area, cr, width, height| {
+                let rtl = area.direction() == gtk::TextDirection::Rtl;
+                draw_recovery_chart(&recoveries, rtl, &area.color(), cr, width, height);
+            });
+        let seconds_per_day = statistics::seconds_per_day(&sessions);
+        widgets
+            .heatmap
+            .set_draw_func(move |area, cr, width, height| {
+                let rtl = area.direction() == gtk::TextDirection::Rtl;
+                draw_heatmap(&seconds_per_day, rtl, &area.color(), cr, width, height);
+            });
+        for training in statistics::per_training(&sessions) {
+            let row = adw::ActionRow::builder()
+                .title(gtk::glib::markup_escape_text(&training.name))
+                .subtitle(if false {
+                    // Translators: The number of sessions of a training on the statistics page
+                    ngettext("{} session", "{} sessions", training.sessions as u32)
+                } else {
+                    ngettext!(
+                        "{} session",
+                        "{} sessions",
+                        training.sessions as u32,
+                        training.sessions
+                    )
+                })
+                .build();
+            row.add_suffix(&gtk::Label::new(Some(&format_minutes(training.total_s))));
+            widgets.trainings_group.add(&row);
+        }
+        let click = gtk::GestureClick::new();
+        click.connect_released(move |gesture, _, x, y| {
+            let Some(area) = gesture.widget().and_downcast::<gtk::DrawingArea>() else {
+                return;
+            };
+            let rtl = area.direction() == gtk::TextDirection::Rtl;
+            if let Some(day) = heatmap_day_at(x, y, area.width(), area.height(), rtl) {
+                show_day_popover(&area, &sessions, day, x, y);
+            }
+        });
+        widgets.heatmap.add_controller(click);
+        ComponentParts { model, widgets }
+    }
+
+    fn update(&mut self, message: Self::Input, _sender: ComponentSender<Self>) {
+        match message {
+            StatisticsPageInput::WeeklyGoalKind(kind) => {
+                self.set_weekly_goal(WeeklyGoal {
+                    kind,
+                    ..self.weekly_goal
+                });
+            }
+            StatisticsPageInput::WeeklyGoalTarget(target) => {
+                self.set_weekly_goal(WeeklyGoal {
+                    target,
+                    ..self.weekly_goal
+                });
+            }
+            StatisticsPageInput::StreakRestDays(rest_days) => {
+                settings::save_streak_rest_days_to_gsettings(rest_days);
+                self.streaks = statistics::streaks(&self.sessions, rest_days);
+            }
+        }
+    }
+}
@@ -0,0 +1,212 @@
+use crate::error::AppError;
+use crate::settings;
+use crate::training_setup::TrainingSetup;
+use gettextrs::gettext;
+use hiit_schema::{Workout, WorkoutList};
+use relm4::gtk::glib;
+use std::{
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+const TRAINING_LIST_FILE_NAME: &str = "exercises.json";
+const SNAPSHOTS_DIR_NAME: &str = "snapshots";
+const SNAPSHOT_FILE_PREFIX: &str = "hiit-";
+/// The number of daily snapshots which are kept.
+const MAX_SNAPSHOTS: usize = 7;
+
+#[derive(Debug, Clone)]
+pub struct StoredTrainingList {
+    /// Time of the last modification, in seconds since the Unix epoch.
+    pub modified: i64,
+    pub trainings: Vec<TrainingSetup>,
+}
+
+/// The data directory of the active profile.
+pub fn data_dir() -> PathBuf {
+    let base_dir = glib::user_data_dir().join("hiit");
+    match crate::profiles::active_profile_id() {
+        id if id == crate::profiles::DEFAULT_PROFILE_ID => base_dir,
+        id => base_dir.join("profiles").join(id),
+    }
+}
+
+fn training_list_path() -> PathBuf {
+    data_dir().join(TRAINING_LIST_FILE_NAME)
+}
+
+/// Whether the training list of the active profile was ever stored, i.e. the profile is not new.
+pub fn has_training_list() -> bool {
+    training_list_path().exists()
+}
+
+pub fn training_list_to_json<'a>(
+    trainings: impl Iterator<Item = &'a TrainingSetup>,
+    modified: i64,
+) -> String {
+    let list = WorkoutList {
+        modified,
+        trainings: trainings.map(Workout::from).collect(),
+    };
+    json::stringify_pretty(list.to_json(), 4)
+}
+
+pub fn parse_training_list_json(raw_json: &str) -> Option<StoredTrainingList> {
+    let list = WorkoutList::parse(raw_json)?;
+    Some(StoredTrainingList {
+        modified: list.modified,
+        trainings: list
+            .trainings
+            .into_iter()
+            .map(TrainingSetup::from)
+            .collect(),
+    })
+}
+
+fn backup_path(path: &Path) -> PathBuf {
+    let mut backup = path.as_os_str().to_owned();
+    backup.push(".bak");
+    PathBuf::from(backup)
+}
+
+/// Writes the file by replacing it with a completely written temporary file, so that it is never left
+/// half-written. The previous version is kept as a backup next to it.
+pub fn write_atomically(path: &Path, contents: &str) -> std::io::Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut temp_path = path.as_os_str().to_owned();
+    temp_path.push(".tmp");
+    let temp_path = PathBuf::from(temp_path);
+    {
+        let mut file = std::fs::File::create(&temp_path)?;
+        file.write_all(contents.as_bytes())?;
+        file.sync_all()?;
+    }
+    if path.exists() {
+        std::fs::copy(path, backup_path(path))?;
+    }
+    std::fs::rename(&temp_path, path)
+}
+
+/// Loads the training list from the data directory. On the first run, the list is migrated from the settings.
+pub fn load_training_list() -> Result<StoredTrainingList, AppError> {
+    let path = training_list_path();
+    match std::fs::read_to_string(&path) {
+        Ok(raw_json) => {
+            let parsed = parse_training_list_json(&raw_json);
+            if parsed.is_none() {
+                tracing::warn!(
+                    path = %path.display(),
+                    "{}",
+                    // Translators: Error message printed to the console when the training list file cannot be parsed
+                    gettext("Could not parse the training list")
+                );
+            }
+            parsed.ok_or(AppError::TrainingListDamaged)
+        }
+        Err(err)
+            if err.kind() == std::io::ErrorKind::NotFound
+                && crate::profiles::active_profile_id() != crate::profiles::DEFAULT_PROFILE_ID =>
+        {
+            Ok(StoredTrainingList {
+                modified: 0,
+                trainings: Vec::new(),
+            })
+        }
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+            let migrated = StoredTrainingList {
+                modified: settings::load_training_list_modified_from_gsettings(),
+                trainings: settings::load_training_list_from_gsettings(),
+            };
+            save_training_list(migrated.trainings.iter(), migrated.modified);
+            Ok(migrated)
+        }
+        Err(err) => Err(AppError::TrainingListUnreadable(err)),
+    }
+}
+
+/// Loads the previous version of the training list, if there is a valid one.
+pub fn load_training_list_backup() -> Option<StoredTrainingList> {
+    let raw_json = std::fs::read_to_string(backup_path(&training_list_path())).ok()?;
+    parse_training_list_json(&raw_json)
+}
+
+/// Moves the damaged training list out of the way, so it can still be recovered by hand.
+pub fn set_aside_damaged_training_list() {
+    let path = training_list_path();
+    let mut damaged_path = path.as_os_str().to_owned();
+    damaged_path.push(".damaged");
+    if let Err(err) = std::fs::rename(&path, damaged_path) {
+        // Translators: Error message printed to the console when the damaged training list file cannot be renamed
+        tracing::warn!(%err, "{}", gettext("Could not set aside the damaged training list"));
+    }
+}
+
+fn snapshots_dir() -> PathBuf {
+    data_dir().join(SNAPSHOTS_DIR_NAME)
+}
+
+/// Lists the snapshot files, the newest first.
+pub fn list_snapshots() -> Vec<PathBuf> {
+    let mut snapshots: Vec<PathBuf> = std::fs::read_dir(snapshots_dir())
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+                .filter(|path| snapshot_date(path).is_some())
+                .collect()
+        })
+        .unwrap_or_default();
+    // The dates are in ISO 8601 notation, so they sort chronologically
+    snapshots.sort();
+    snapshots.reverse();
+    snapshots
+}
+
+/// The date of the snapshot in `YYYY-MM-DD` notation, taken from its file name.
+pub fn snapshot_date(path: &Path) -> Option<String> {
+    let file_name = path.file_name()?.to_str()?;
+    let date = file_name
+        .strip_prefix(SNAPSHOT_FILE_PREFIX)?
+        .strip_suffix(".json")?;
+    (date.len() == 10).then(|| date.to_string())
+}
+
+/// Saves the data as it is before the first save of the day, and removes the oldest snapshots.
+fn take_daily_snapshot() -> std::io::Result<()> {
+    let Ok(raw_json) = std::fs::read_to_string(training_list_path()) else {
+        return Ok(());
+    };
+    let Some(previous) = parse_training_list_json(&raw_json) else {
+        return Ok(());
+    };
+    let today = glib::DateTime::now_local()
+        .and_then(|now| now.format("%Y-%m-%d"))
+        .map_err(|err| std::io::Error::other(err.to_string()))?;
+    let path = snapshots_dir().join(format!("{}{}.json", SNAPSHOT_FILE_PREFIX, today));
+    if path.exists() {
+        return Ok(());
+    }
+    write_atomically(
+        &path,
+        &crate::backup::create_backup(previous.trainings.iter(), previous.modified),
+    )?;
+    for outdated in list_snapshots().into_iter().skip(MAX_SNAPSHOTS) {
+        std::fs::remove_file(outdated)?;
+    }
+    Ok(())
+}
+
+pub fn save_training_list<'a>(trainings: impl Iterator<Item = &'a TrainingSetup>, modified: i64) {
+    if let Err(err) = take_daily_snapshot() {
+        // Translators: Error message printed to the console when the snapshot of the data cannot be saved
+        tracing::warn!(%err, "{}", gettext("Could not save the snapshot"));
+    }
+    if let Err(err) = write_atomically(
+        &training_list_path(),
+        &training_list_to_json(trainings, modified),
+    ) {
+        // Translators: Error message printed to the console when the training list file cannot be written
+        tracing::warn!(%err, "{}", gettext("Could not save the training list"));
+    }
+}
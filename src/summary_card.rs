@@ -0,0 +1,241 @@
+//! An image of a finished session for posting to chats or social media: the training, the date,
+//! the duration, the rounds and the burned calories, above the periods in their colors.
+
+use crate::history::SessionRecord;
+use crate::replay_dialog::{format_clock, phase_color};
+use crate::training_timer::format_kcal;
+use gettextrs::gettext;
+use relm4::{
+    adw::{self, prelude::*},
+    gtk::{self, cairo, gdk, glib},
+};
+
+/// The size of link previews on most social media.
+const CARD_WIDTH_PX: i32 = 1200;
+const CARD_HEIGHT_PX: i32 = 630;
+const CARD_MARGIN_PX: f64 = 60.0;
+const TIMELINE_HEIGHT_PX: f64 = 40.0;
+const PREVIEW_WIDTH_PX: i32 = 480;
+
+/// Draws the text centered on `center_x`, shrunk to fit into `max_width` if needed.
+/// The toy text API of cairo is used, as pango cannot draw on an image without a widget.
+fn show_centered(
+    cr: &cairo::Context,
+    text: &str,
+    center_x: f64,
+    baseline_y: f64,
+    font_size: f64,
+    max_width: f64,
+) {
+    cr.set_font_size(font_size);
+    let Ok(extents) = cr.text_extents(text) else {
+        return;
+    };
+    if extents.x_advance() > max_width {
+        cr.set_font_size(font_size * max_width / extents.x_advance());
+    }
+    let Ok(extents) = cr.text_extents(text) else {
+        return;
+    };
+    cr.move_to(center_x - extents.x_advance() / 2.0, baseline_y);
+    let _ = cr.show_text(text);
+}
+
+fn draw_card(session: &SessionRecord, cr: &cairo::Context) {
+    let (width, height) = (CARD_WIDTH_PX as f64, CARD_HEIGHT_PX as f64);
+    let text_width = width - 2.0 * CARD_MARGIN_PX;
+    // The colors of the dark style, so that the periods stand out
+    cr.set_source_rgb(0.141, 0.122, 0.192);
+    let _ = cr.paint();
+    cr.select_font_face("Sans", cairo::FontSlant::Normal, cairo::FontWeight::Normal);
+    cr.set_source_rgba(1.0, 1.0, 1.0, 0.7);
+    show_centered(
+        cr,
+        &glib::application_name().unwrap_or_default(),
+        width / 2.0,
+        CARD_MARGIN_PX + 30.0,
+        32.0,
+        text_width,
+    );
+    cr.select_font_face("Sans", cairo::FontSlant::Normal, cairo::FontWeight::Bold);
+    cr.set_source_rgb(1.0, 1.0, 1.0);
+    show_centered(
+        cr,
+        &session.training_name,
+        width / 2.0,
+        230.0,
+        80.0,
+        text_width,
+    );
+    let date = glib::DateTime::from_unix_local(session.started)
+        .and_then(|date_time| date_time.format("%x"))
+        .map(String::from)
+        .unwrap_or_default();
+    cr.select_font_face("Sans", cairo::FontSlant::Normal, cairo::FontWeight::Normal);
+    cr.set_source_rgba(1.0, 1.0, 1.0, 0.7);
+    show_centered(cr, &date, width / 2.0, 300.0, 36.0, text_width);
+
+    let mut stats = vec![
+        (
+            // Translators: The title of the duration of the session on the shared summary image
+            gettext("Duration"),
+            format_clock(session.duration_s),
+        ),
+        (
+            // Translators: The title of the number of rounds of the session on the shared summary image
+            gettext("Rounds"),
+            format!("{}/{}", session.rounds.len(), session.sets),
+        ),
+    ];
+    if let Some(kcal) = session.kcal {
+        // Translators: The title of the estimated burned calories on the shared summary image
+        stats.push((gettext("Calories"), format_kcal(kcal)));
+    }
+    let column_width = text_width / stats.len() as f64;
+    for (index, (title, value)) in stats.iter().enumerate() {
+        let center_x = CARD_MARGIN_PX + column_width * (index as f64 + 0.5);
+        cr.select_font_face("Sans", cairo::FontSlant::Normal, cairo::FontWeight::Bold);
+        cr.set_source_rgb(1.0, 1.0, 1.0);
+        show_centered(cr, value, center_x, 440.0, 64.0, column_width - 20.0);
+        cr.select_font_face("Sans", cairo::FontSlant::Normal, cairo::FontWeight::Normal);
+        cr.set_source_rgba(1.0, 1.0, 1.0, 0.7);
+        show_centered(cr, title, center_x, 490.0, 28.0, column_width - 20.0);
+    }
+
+    let periods = session.timeline();
+    let total_s: usize = periods.iter().map(|period| period.length_s).sum();
+    if total_s == 0 {
+        return;
+    }
+    let mut start_x = 0.0;
+    for period in &periods {
+        let period_width = width * period.length_s as f64 / total_s as f64;
+        let (red, green, blue) = phase_color(period.phase);
+        cr.set_source_rgb(red, green, blue);
+        cr.rectangle(
+            start_x,
+            height - TIMELINE_HEIGHT_PX,
+            period_width,
+            TIMELINE_HEIGHT_PX,
+        );
+        let _ = cr.fill();
+        start_x += period_width;
+    }
+}
+
+/// Renders the image of the session, or `None` if cairo runs out of memory.
+pub fn render(session: &SessionRecord) -> Option<gdk::Texture> {
+    let mut surface =
+        cairo::ImageSurface::create(cairo::Format::ARgb32, CARD_WIDTH_PX, CARD_HEIGHT_PX).ok()?;
+    {
+        let cr = cairo::Context::new(&surface).ok()?;
+        draw_card(session, &cr);
+    }
+    let stride = surface.stride() as usize;
+    let data = glib::Bytes::from_owned(surface.data().ok()?.to_vec());
+    Some(
+        gdk::MemoryTexture::new(
+            CARD_WIDTH_PX,
+            CARD_HEIGHT_PX,
+            // ARgb32 is stored as native 32-bit integers
+            if cfg!(target_endian = "little") {
+                gdk::MemoryFormat::B8g8r8a8Premultiplied
+            } else {
+                gdk::MemoryFormat::A8r8g8b8Premultiplied
+            },
+            &data,
+            stride,
+        )
+        .upcast(),
+    )
+}
+
+async fn save_to_file(window: Option<gtk::Window>, name: String, texture: gdk::Texture) {
+    let file_dialog = gtk::FileDialog::builder()
+        // Translators: The title of the file dialog which saves the summary image of a session
+        .title(gettext("Save Summary"))
+        .initial_name(format!("{}.png", name))
+        .build();
+    let file = match file_dialog.save_future(window.as_ref()).await {
+        Ok(file) => file,
+        Err(err) => {
+            if !err.matches(gtk::DialogError::Dismissed) {
+                // Translators: Error message printed to the console when the file dialog fails
+                tracing::warn!(%err, "{}", gettext("Could not choose the file"));
+            }
+            return;
+        }
+    };
+    if let Err((_, err)) = file
+        .replace_contents_future(
+            texture.save_to_png_bytes(),
+            None,
+            false,
+            gtk::gio::FileCreateFlags::REPLACE_DESTINATION,
+        )
+        .await
+    {
+        // Translators: Error message printed to the console when the summary image cannot be saved to a file
+        tracing::warn!(%err, "{}", gettext("Could not save the summary"));
+    }
+}
+
+/// Shows the image of the session, to copy it or to save it as PNG.
+pub fn show(parent: &impl IsA<gtk::Widget>, session: &SessionRecord) {
+    let Some(texture) = render(session) else {
+        // Translators: Error message printed to the console when the summary image cannot be drawn
+        tracing::warn!("{}", gettext("Could not draw the summary"));
+        return;
+    };
+    let picture = gtk::Picture::builder()
+        .paintable(&texture)
+        .width_request(PREVIEW_WIDTH_PX)
+        .height_request(PREVIEW_WIDTH_PX * CARD_HEIGHT_PX / CARD_WIDTH_PX)
+        .build();
+    picture.add_css_class("card");
+    let copy_button = gtk::Button::builder()
+        // Translators: Button which copies the summary image of a session to the clipboard
+        .label(gettext("Copy Image"))
+        .css_classes(["suggested-action", "pill"])
+        .build();
+    {
+        let texture = texture.clone();
+        copy_button.connect_clicked(move |button| button.clipboard().set_texture(&texture));
+    }
+    let save_button = gtk::Button::builder()
+        // Translators: Button which saves the summary image of a session to a PNG file
+        .label(gettext("Save Image…"))
+        .css_classes(["pill"])
+        .build();
+    let name = session.training_name.clone();
+    save_button.connect_clicked(move |button| {
+        let window = button.root().and_downcast::<gtk::Window>();
+        relm4::spawn_local(save_to_file(window, name.clone(), texture.clone()));
+    });
+    let buttons = gtk::Box::builder()
+        .orientation(gtk::Orientation::Horizontal)
+        .halign(gtk::Align::Center)
+        .spacing(12)
+        .build();
+    buttons.append(&copy_button);
+    buttons.append(&save_button);
+    let content = gtk::Box::builder()
+        .orientation(gtk::Orientation::Vertical)
+        .spacing(18)
+        .margin_top(24)
+        .margin_bottom(24)
+        .margin_start(24)
+        .margin_end(24)
+        .build();
+    content.append(&picture);
+    content.append(&buttons);
+    let toolbar_view = adw::ToolbarView::new();
+    toolbar_view.add_top_bar(&adw::HeaderBar::new());
+    toolbar_view.set_content(Some(&content));
+    adw::Dialog::builder()
+        // Translators: The title of the dialog which shows the summary image of a session
+        .title(gettext("Share Summary"))
+        .child(&toolbar_view)
+        .build()
+        .present(Some(parent));
+}
@@ -0,0 +1,604 @@
+use crate::history;
+use crate::storage;
+use crate::training_setup::TrainingSetup;
+use gettextrs::gettext;
+use relm4::gtk::{
+    self,
+    gio::{self, prelude::*},
+    glib,
+};
+
+const TRAININGS_FILE_NAME: &str = "trainings.json";
+/// The recorded sessions, one JSON object per line like the local history.
+const HISTORY_FILE_NAME: &str = "history.jsonl";
+/// The fields of a session which are entered after it was recorded, so that one device may
+/// have them while the other does not.
+const ANNOTATION_KEYS: [&str; 3] = ["rpe", "note", "hr_recovery_bpm"];
+
+#[derive(Debug, Clone)]
+pub struct SyncPayload {
+    pub modified: i64,
+    pub trainings: Vec<TrainingSetup>,
+}
+
+#[derive(Debug)]
+pub struct SyncResult {
+    pub trainings: Vec<TrainingSetup>,
+    pub local_changed: bool,
+    /// Sessions recorded on another device were added to the local history.
+    pub history_changed: bool,
+}
+
+pub fn unix_now() -> i64 {
+    glib::DateTime::now_utc()
+        .map(|now| now.to_unix())
+        .unwrap_or_default()
+}
+
+fn payload_to_json(payload: &SyncPayload) -> String {
+    storage::training_list_to_json(payload.trainings.iter(), payload.modified)
+}
+
+fn payload_from_json(raw: &str) -> Result<SyncPayload, glib::Error> {
+    storage::parse_training_list_json(raw)
+        .map(|list| SyncPayload {
+            modified: list.modified,
+            trainings: list.trainings,
+        })
+        .ok_or_else(|| {
+            glib::Error::new(
+                gio::IOErrorEnum::InvalidData,
+                // Translators: Error message printed to the console when the synchronized training file is malformed
+                &gettext("The remote training list is malformed"),
+            )
+        })
+}
+
+fn history_from_json(raw: &str) -> Result<Vec<json::JsonValue>, glib::Error> {
+    raw.lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| json::parse(line).ok().filter(json::JsonValue::is_object))
+        .collect::<Option<Vec<json::JsonValue>>>()
+        .ok_or_else(|| {
+            glib::Error::new(
+                gio::IOErrorEnum::InvalidData,
+                // Translators: Error message printed to the console when the synchronized history file is malformed
+                &gettext("The remote history is malformed"),
+            )
+        })
+}
+
+fn history_to_json(sessions: &[json::JsonValue]) -> String {
+    sessions
+        .iter()
+        .map(|session| json::stringify(session.clone()) + "\n")
+        .collect()
+}
+
+async fn ensure_mounted(
+    folder: &gio::File,
+    window: Option<&gtk::Window>,
+) -> Result<(), glib::Error> {
+    if folder.is_native() {
+        return Ok(());
+    }
+    let mount_operation = gtk::MountOperation::new(window);
+    match folder
+        .mount_enclosing_volume_future(gio::MountMountFlags::NONE, Some(&mount_operation))
+        .await
+    {
+        Err(err) if err.matches(gio::IOErrorEnum::AlreadyMounted) => Ok(()),
+        result => result,
+    }
+}
+
+async fn pull(file: &gio::File) -> Result<Option<SyncPayload>, glib::Error> {
+    match file.load_contents_future().await {
+        Ok((contents, _)) => {
+            let raw = String::from_utf8_lossy(&contents);
+            payload_from_json(&raw).map(Some)
+        }
+        Err(err) if err.matches(gio::IOErrorEnum::NotFound) => Ok(None),
+        Err(err) => Err(err),
+    }
+}
+
+async fn pull_history(file: &gio::File) -> Result<Vec<json::JsonValue>, glib::Error> {
+    match file.load_contents_future().await {
+        Ok((contents, _)) => history_from_json(&String::from_utf8_lossy(&contents)),
+        Err(err) if err.matches(gio::IOErrorEnum::NotFound) => Ok(Vec::new()),
+        Err(err) => Err(err),
+    }
+}
+
+async fn push(file: &gio::File, payload: &SyncPayload) -> Result<(), glib::Error> {
+    push_contents(file, payload_to_json(payload)).await
+}
+
+async fn push_contents(file: &gio::File, contents: String) -> Result<(), glib::Error> {
+    file.replace_contents_future(
+        contents,
+        None,
+        false,
+        gio::FileCreateFlags::REPLACE_DESTINATION,
+    )
+    .await
+    .map(|_| ())
+    .map_err(|(_, err)| err)
+}
+
+/// Whether the trainings only differ in their identity, e.g. the same sample added on two devices.
+fn has_same_content(lhs: &TrainingSetup, rhs: &TrainingSetup) -> bool {
+    *lhs == TrainingSetup {
+        id: lhs.id.clone(),
+        revision: lhs.revision,
+        ..rhs.clone()
+    }
+}
+
+fn merge_field<T: PartialEq + Clone>(base: &T, local: &T, remote: &T, conflict: &mut bool) -> T {
+    if local == remote || remote == base {
+        local.clone()
+    } else if local == base {
+        remote.clone()
+    } else {
+        *conflict = true;
+        local.clone()
+    }
+}
+
+fn conflicted_copy(training: &TrainingSetup) -> TrainingSetup {
+    TrainingSetup {
+        id: TrainingSetup::new_id(),
+        revision: 0,
+        name: if false {
+            // Translators: The name of a training which was modified on two devices at the same time. {} is replaced by the original name.
+            gettext("{} (conflicted copy)")
+        } else {
+            gettext!("{} (conflicted copy)", training.name)
+        },
+        ..training.clone()
+    }
+}
+
+/// Merges the two revisions of the same training field-by-field.
+/// When a field was changed on both sides, the local value is kept and the remote revision
+/// is preserved as a separate conflicted copy.
+fn merge_training(
+    base: Option<&TrainingSetup>,
+    local: &TrainingSetup,
+    remote: &TrainingSetup,
+) -> Vec<TrainingSetup> {
+    let Some(base) = base else {
+        return if has_same_content(local, remote) {
+            vec![local.clone()]
+        } else {
+            vec![local.clone(), conflicted_copy(remote)]
+        };
+    };
+    if local.revision == base.revision {
+        return vec![remote.clone()];
+    }
+    if remote.revision == base.revision {
+        return vec![local.clone()];
+    }
+    let mut conflict = false;
+    let merged = TrainingSetup {
+        id: local.id.clone(),
+        revision: local.revision.max(remote.revision) + 1,
+        name: merge_field(&base.name, &local.name, &remote.name, &mut conflict),
+        sets: merge_field(&base.sets, &local.sets, &remote.sets, &mut conflict),
+        exercise_s: merge_field(
+            &base.exercise_s,
+            &local.exercise_s,
+            &remote.exercise_s,
+            &mut conflict,
+        ),
+        rest_s: merge_field(&base.rest_s, &local.rest_s, &remote.rest_s, &mut conflict),
+        prepare_s: merge_field(
+            &base.prepare_s,
+            &local.prepare_s,
+            &remote.prepare_s,
+            &mut conflict,
+        ),
+        // Pinning only changes the order of the list, so it never conflicts
+        pinned: local.pinned,
+        progression: merge_field(
+            &base.progression,
+            &local.progression,
+            &remote.progression,
+            &mut conflict,
+        ),
+        mode: merge_field(&base.mode, &local.mode, &remote.mode, &mut conflict),
+        exercise_intensity: merge_field(
+            &base.exercise_intensity,
+            &local.exercise_intensity,
+            &remote.exercise_intensity,
+            &mut conflict,
+        ),
+        rest_intensity: merge_field(
+            &base.rest_intensity,
+            &local.rest_intensity,
+            &remote.rest_intensity,
+            &mut conflict,
+        ),
+        images: merge_field(&base.images, &local.images, &remote.images, &mut conflict),
+        warmup: merge_field(&base.warmup, &local.warmup, &remote.warmup, &mut conflict),
+    };
+    if conflict {
+        vec![merged, conflicted_copy(remote)]
+    } else {
+        vec![merged]
+    }
+}
+
+fn find<'a>(list: &'a [TrainingSetup], id: &str) -> Option<&'a TrainingSetup> {
+    list.iter().find(|training| training.id == id)
+}
+
+/// Three-way merge of the local and remote training lists, using the list as of the last
+/// synchronization as the common ancestor. Removals win over unmodified trainings only.
+pub fn merge(
+    base: &[TrainingSetup],
+    local: &[TrainingSetup],
+    remote: &[TrainingSetup],
+) -> Vec<TrainingSetup> {
+    let mut merged = Vec::new();
+    for local_training in local {
+        let base_training = find(base, &local_training.id);
+        match (base_training, find(remote, &local_training.id)) {
+            (_, Some(remote_training)) => {
+                merged.extend(merge_training(
+                    base_training,
+                    local_training,
+                    remote_training,
+                ));
+            }
+            (Some(base_training), None) => {
+                if local_training.revision != base_training.revision {
+                    merged.push(local_training.clone());
+                }
+            }
+            (None, None) => merged.push(local_training.clone()),
+        }
+    }
+    for remote_training in remote {
+        if find(local, &remote_training.id).is_some() {
+            continue;
+        }
+        match find(base, &remote_training.id) {
+            Some(base_training) => {
+                if remote_training.revision != base_training.revision {
+                    merged.push(remote_training.clone());
+                }
+            }
+            None => {
+                // A training which was added on both sides since the last synchronization is
+                // only added once, e.g. the samples of a new profile on each device
+                let added_locally = local.iter().any(|local_training| {
+                    find(base, &local_training.id).is_none()
+                        && find(remote, &local_training.id).is_none()
+                        && has_same_content(local_training, remote_training)
+                });
+                if !added_locally {
+                    merged.push(remote_training.clone());
+                }
+            }
+        }
+    }
+    merged
+}
+
+/// Identifies a recorded session, which is never recorded twice in the same second.
+fn session_key(session: &json::JsonValue) -> (Option<&str>, Option<i64>) {
+    (session["training_id"].as_str(), session["started"].as_i64())
+}
+
+fn has_value(value: &json::JsonValue) -> bool {
+    !value.is_null() && value.as_str() != Some("")
+}
+
+/// Merges the local and the remote history. Sessions are only ever added, so the merge is the
+/// union of both, the oldest first. The annotations of a session which only one side has, e.g.
+/// the exertion entered on the phone, are carried over to the other side.
+fn merge_history(local: &[json::JsonValue], remote: &[json::JsonValue]) -> Vec<json::JsonValue> {
+    let mut merged: Vec<json::JsonValue> = local.to_vec();
+    for remote_session in remote {
+        match merged
+            .iter_mut()
+            .find(|session| session_key(session) == session_key(remote_session))
+        {
+            Some(session) => {
+                for key in ANNOTATION_KEYS {
+                    if !has_value(&session[key]) && has_value(&remote_session[key]) {
+                        session[key] = remote_session[key].clone();
+                    }
+                }
+            }
+            None => merged.push(remote_session.clone()),
+        }
+    }
+    merged.sort_by_key(|session| session["started"].as_i64().unwrap_or_default());
+    merged
+}
+
+/// Synchronizes the local history with the one stored in the remote folder.
+/// Returns if the local history changed.
+async fn synchronize_history(folder: &gio::File) -> Result<bool, glib::Error> {
+    let file = folder.child(HISTORY_FILE_NAME);
+    let remote = pull_history(&file).await?;
+    // Read after the download, so that a session recorded meanwhile is not lost
+    let local = history::load_raw_sessions();
+    let merged = merge_history(&local, &remote);
+    if merged != remote {
+        push_contents(&file, history_to_json(&merged)).await?;
+    }
+    let local_changed = merged != local;
+    if local_changed {
+        let merged = merge_history(&history::load_raw_sessions(), &merged);
+        history::replace_raw_sessions(merged.iter());
+    }
+    tracing::info!(count = merged.len(), local_changed, "Synchronized history");
+    Ok(local_changed)
+}
+
+/// Synchronizes the local training list and the history with the ones stored in the remote
+/// folder. `base` is the training list as of the last successful synchronization.
+pub async fn synchronize(
+    folder_uri: String,
+    local: SyncPayload,
+    base: Vec<TrainingSetup>,
+    window: Option<gtk::Window>,
+) -> Result<SyncResult, glib::Error> {
+    tracing::info!(folder = %folder_uri, "Synchronizing trainings");
+    let folder = gio::File::for_uri(&folder_uri);
+    ensure_mounted(&folder, window.as_ref()).await?;
+    let file = folder.child(TRAININGS_FILE_NAME);
+    let remote = pull(&file).await?;
+    let trainings = match &remote {
+        Some(remote) => merge(&base, &local.trainings, &remote.trainings),
+        None => local.trainings.clone(),
+    };
+    if remote
+        .as_ref()
+        .is_none_or(|remote| remote.trainings != trainings)
+    {
+        let modified = remote
+            .as_ref()
+            .map_or(local.modified, |remote| remote.modified.max(local.modified));
+        push(
+            &file,
+            &SyncPayload {
+                modified,
+                trainings: trainings.clone(),
+            },
+        )
+        .await?;
+    }
+    let local_changed = trainings != local.trainings;
+    tracing::info!(
+        count = trainings.len(),
+        local_changed,
+        "Synchronized trainings"
+    );
+    let history_changed = synchronize_history(&folder).await?;
+    Ok(SyncResult {
+        local_changed,
+        trainings,
+        history_changed,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::warmup::Warmup;
+
+    fn training(id: &str, name: &str) -> TrainingSetup {
+        TrainingSetup {
+            id: id.into(),
+            revision: 1,
+            name: name.into(),
+            exercise_s: 20,
+            rest_s: 10,
+            sets: 8,
+            prepare_s: 5,
+            pinned: false,
+            progression: None,
+            mode: crate::workout_mode::CLASSIC_MODE_ID.into(),
+            exercise_intensity: None,
+            rest_intensity: None,
+            images: Vec::new(),
+            warmup: Warmup::Countdown,
+        }
+    }
+
+    /// The training after an edit on one device.
+    fn edited(training: &TrainingSetup, edit: impl FnOnce(&mut TrainingSetup)) -> TrainingSetup {
+        let mut edited = training.clone();
+        edit(&mut edited);
+        edited.revision += 1;
+        edited
+    }
+
+    fn ids(trainings: &[TrainingSetup]) -> Vec<&str> {
+        trainings
+            .iter()
+            .map(|training| training.id.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn keeps_unchanged_trainings() {
+        let base = vec![training("a", "Tabata"), training("b", "Plank")];
+        assert_eq!(merge(&base, &base, &base), base);
+    }
+
+    #[test]
+    fn takes_edit_of_either_side() {
+        let base = vec![training("a", "Tabata"), training("b", "Plank")];
+        let local = vec![edited(&base[0], |t| t.sets = 10), base[1].clone()];
+        let remote = vec![base[0].clone(), edited(&base[1], |t| t.rest_s = 30)];
+        assert_eq!(
+            merge(&base, &local, &remote),
+            vec![local[0].clone(), remote[1].clone()]
+        );
+    }
+
+    #[test]
+    fn merges_concurrent_edits_of_different_fields() {
+        let base = vec![training("a", "Tabata")];
+        let local = vec![edited(&base[0], |t| t.sets = 10)];
+        let remote = vec![edited(&edited(&base[0], |t| t.rest_s = 30), |t| {
+            t.name = "Long Tabata".into()
+        })];
+        let merged = merge(&base, &local, &remote);
+        assert_eq!(merged.len(), 1);
+        assert_eq!(merged[0].id, "a");
+        assert_eq!(merged[0].name, "Long Tabata");
+        assert_eq!((merged[0].sets, merged[0].rest_s), (10, 30));
+        // Newer than both sides, so that both take the merge on the next synchronization
+        assert_eq!(merged[0].revision, remote[0].revision + 1);
+    }
+
+    #[test]
+    fn keeps_conflicted_copy_of_concurrent_edits_of_same_field() {
+        let base = vec![training("a", "Tabata")];
+        let local = vec![edited(&base[0], |t| t.sets = 10)];
+        let remote = vec![edited(&base[0], |t| {
+            t.sets = 12;
+            t.rest_s = 30;
+        })];
+        let merged = merge(&base, &local, &remote);
+        assert_eq!(merged.len(), 2);
+        assert_eq!(merged[0].id, "a");
+        assert_eq!((merged[0].sets, merged[0].rest_s), (10, 30));
+        let copy = &merged[1];
+        assert_ne!(copy.id, "a");
+        assert_eq!(copy.revision, 0);
+        assert_eq!(copy.name, "Tabata (conflicted copy)");
+        assert_eq!((copy.sets, copy.rest_s), (12, 30));
+    }
+
+    #[test]
+    fn conflicted_copy_has_own_identity() {
+        let original = edited(&training("a", "Tabata"), |t| t.pinned = true);
+        let copy = conflicted_copy(&original);
+        assert_ne!(copy.id, original.id);
+        assert_ne!(copy.id, conflicted_copy(&original).id);
+        assert_eq!(copy.revision, 0);
+        assert_eq!(
+            TrainingSetup {
+                id: original.id.clone(),
+                revision: original.revision,
+                name: original.name.clone(),
+                ..copy
+            },
+            original
+        );
+    }
+
+    #[test]
+    fn removes_trainings_deleted_on_either_side() {
+        let base = vec![training("a", "Tabata"), training("b", "Plank")];
+        let local = vec![base[1].clone()];
+        let remote = vec![base[0].clone()];
+        assert_eq!(merge(&base, &local, &remote), vec![]);
+    }
+
+    #[test]
+    fn edit_wins_over_delete() {
+        let base = vec![training("a", "Tabata"), training("b", "Plank")];
+        // Deleted locally, edited remotely
+        let local = vec![edited(&base[1], |t| t.sets = 4)];
+        let remote = vec![edited(&base[0], |t| t.sets = 10)];
+        let merged = merge(&base, &local, &remote);
+        assert_eq!(merged, vec![local[0].clone(), remote[0].clone()]);
+    }
+
+    #[test]
+    fn adds_trainings_added_on_both_sides() {
+        let base = vec![training("a", "Tabata")];
+        let local = vec![base[0].clone(), training("b", "Plank")];
+        let remote = vec![base[0].clone(), training("c", "Burpees")];
+        assert_eq!(ids(&merge(&base, &local, &remote)), vec!["a", "b", "c"]);
+    }
+
+    #[test]
+    fn adds_same_training_once() {
+        // E.g. the same sample added on two devices before the first synchronization
+        let local = vec![training("a", "Tabata")];
+        let remote = vec![training("b", "Tabata")];
+        assert_eq!(ids(&merge(&[], &local, &remote)), vec!["a"]);
+    }
+
+    #[test]
+    fn adds_training_with_same_numbers_and_other_mode() {
+        let local = vec![training("a", "Tabata")];
+        let remote = vec![TrainingSetup {
+            mode: "pomodoro".into(),
+            ..training("b", "Tabata")
+        }];
+        assert_eq!(ids(&merge(&[], &local, &remote)), vec!["a", "b"]);
+    }
+
+    #[test]
+    fn adds_duplicate_of_synchronized_training() {
+        // E.g. a copy which the user made on another device
+        let base = vec![training("a", "Tabata")];
+        let remote = vec![base[0].clone(), training("b", "Tabata")];
+        assert_eq!(ids(&merge(&base, &base, &remote)), vec!["a", "b"]);
+    }
+
+    #[test]
+    fn keeps_both_without_common_ancestor() {
+        let local = vec![training("a", "Tabata")];
+        let remote = vec![edited(&local[0], |t| t.sets = 12)];
+        let merged = merge(&[], &local, &remote);
+        assert_eq!(merged.len(), 2);
+        assert_eq!(merged[0], local[0]);
+        assert_ne!(merged[1].id, "a");
+        assert_eq!(merged[1].sets, 12);
+    }
+
+    fn session(training_id: &str, started: i64) -> json::JsonValue {
+        json::object! {
+            training_id: training_id,
+            training_name: "Tabata",
+            started: started,
+            duration_s: 240,
+        }
+    }
+
+    #[test]
+    fn merges_history_as_union() {
+        let local = vec![session("a", 100), session("a", 300)];
+        let remote = vec![session("b", 200), session("a", 300)];
+        let merged = merge_history(&local, &remote);
+        let started: Vec<Option<i64>> = merged
+            .iter()
+            .map(|session| session["started"].as_i64())
+            .collect();
+        assert_eq!(started, vec![Some(100), Some(200), Some(300)]);
+        assert_eq!(merge_history(&merged, &local), merged);
+    }
+
+    #[test]
+    fn carries_annotations_over() {
+        let mut local = session("a", 100);
+        local["rpe"] = 7.into();
+        let mut remote = session("a", 100);
+        remote["rpe"] = 5.into();
+        remote["note"] = "Knee hurt".into();
+        let merged = merge_history(&[local], &[remote]);
+        assert_eq!(merged.len(), 1);
+        assert_eq!(merged[0]["rpe"].as_u8(), Some(7));
+        assert_eq!(merged[0]["note"].as_str(), Some("Knee hurt"));
+    }
+
+    #[test]
+    fn rejects_malformed_history() {
+        assert!(history_from_json("{\"started\": 1}\n\n[1, 2]\n").is_err());
+        assert_eq!(history_from_json("").map(|sessions| sessions.len()), Ok(0));
+    }
+}
@@ -1,29 +1,57 @@
+use crate::intensity::Intensity;
+use crate::statistics::PROGRESSION_SESSIONS;
 use crate::training_setup::*;
-use gettextrs::gettext;
+use crate::warmup::{self, Warmup, WarmupStep};
+use crate::warmup_editor::{WarmupEditor, WarmupEditorInit};
+use crate::workout_mode::{self, Field, WorkoutMode, MODES};
+use gettextrs::{gettext, ngettext};
 use relm4::{
     adw::{self, prelude::*},
     binding::*,
-    gtk, RelmObjectExt, RelmWidgetExt,
+    gtk, Component, ComponentController, Controller, RelmObjectExt, RelmWidgetExt,
 };
 
 #[derive(Debug)]
 pub struct TrainingEditor {
     role: TrainingEditorRole,
+    /// The index of the mode in [`MODES`].
+    mode: usize,
+    id: String,
+    revision: u64,
     name: StringBinding,
     sets: U32Binding,
     exercise_s: U32Binding,
     rest_s: U32Binding,
     prepare_s: U32Binding,
+    /// The trainings which can follow this one, in the order of the choices after "None".
+    progression_ids: Vec<String>,
+    /// The selected choice of the next progression, 0 for none.
+    progression: U32Binding,
+    /// The selected choices of the intensities, 0 for none and then as in [`Intensity::ALL`].
+    exercise_intensity: U32Binding,
+    rest_intensity: U32Binding,
+    /// The URIs of the images of the rounds in order.
+    images: Vec<String>,
+    /// The choice of the warmup, as in [`Warmup::choice`].
+    warmup: usize,
+    /// The steps of a custom warmup, kept while another choice is selected.
+    warmup_steps: Vec<WarmupStep>,
+    warmup_editor: Controller<WarmupEditor>,
 }
 
 #[derive(Debug)]
 pub enum TrainingEditorRole {
     New,
     Edit,
+    Import,
 }
 
 #[derive(Debug)]
 pub enum TrainingEditorInput {
+    SetMode(usize),
+    SetImages(Vec<String>),
+    SetWarmup(usize),
+    SetWarmupSteps(Vec<WarmupStep>),
     Create,
     Cancel,
 }
@@ -37,9 +65,77 @@ pub const SPIN_ROW_LOWER: f64 = 1f64;
 pub const SPIN_ROW_UPPER: f64 = 1000000f64;
 pub const SPIN_ROW_STEP: f64 = 1f64;
 
+impl TrainingEditor {
+    fn mode(&self) -> &'static dyn WorkoutMode {
+        MODES[self.mode]
+    }
+
+    fn field_title(&self, field: Field) -> String {
+        self.mode().field_title(field).unwrap_or_default()
+    }
+
+    fn has_field(&self, field: Field) -> bool {
+        self.mode().field_title(field).is_some()
+    }
+}
+
+fn intensity_choice(intensity: Option<Intensity>) -> u32 {
+    intensity.map_or(0, |intensity| intensity as u32 + 1)
+}
+
+fn chosen_intensity(choice: u32) -> Option<Intensity> {
+    (choice as usize)
+        .checked_sub(1)
+        .and_then(|index| Intensity::ALL.get(index))
+        .copied()
+}
+
+/// Lets the user pick the images of the rounds, in the order of their selection.
+async fn choose_images(window: Option<gtk::Window>, sender: relm4::Sender<TrainingEditorInput>) {
+    let filter = gtk::FileFilter::new();
+    filter.add_mime_type("image/*");
+    // Translators: The name of the file type filter in the file dialog which picks the images of the rounds
+    filter.set_name(Some(&gettext("Images")));
+    let filters = gtk::gio::ListStore::new::<gtk::FileFilter>();
+    filters.append(&filter);
+    let file_dialog = gtk::FileDialog::builder()
+        // Translators: The title of the file dialog which picks the images of the rounds of a training
+        .title(gettext("Choose Images"))
+        .filters(&filters)
+        .build();
+    match file_dialog.open_multiple_future(window.as_ref()).await {
+        Ok(files) => {
+            let images = files
+                .iter::<gtk::gio::File>()
+                .filter_map(Result::ok)
+                .map(|file| file.uri().into())
+                .collect();
+            sender.emit(TrainingEditorInput::SetImages(images));
+        }
+        Err(err) if err.matches(gtk::DialogError::Dismissed) => {}
+        Err(err) => {
+            // Translators: Error message printed to the console when the file dialog fails
+            tracing::warn!(%err, "{}", gettext("Could not choose the file"));
+        }
+    }
+}
+
+fn images_description(count: usize) -> String {
+    if count == 0 {
+        // Translators: The subtitle of the row of the images of the rounds when none is chosen
+        gettext("Shown on the timer, one per round")
+    } else if false {
+        // Translators: The subtitle of the row of the images of the rounds, e.g. "6 images"
+        ngettext("{} image", "{} images", count as u32)
+    } else {
+        ngettext!("{} image", "{} images", count as u32, count)
+    }
+}
+
 #[relm4::component(pub)]
 impl relm4::SimpleComponent for TrainingEditor {
-    type Init = (TrainingEditorRole, TrainingSetup);
+    /// The role, the training and the other trainings, which can be chosen to follow it.
+    type Init = (TrainingEditorRole, TrainingSetup, Vec<TrainingSetup>);
     type Input = TrainingEditorInput;
     type Output = Option<TrainingEditorOutput>;
 
@@ -56,6 +152,8 @@ impl relm4::SimpleComponent for TrainingEditor {
                             TrainingEditorRole::New => gettext("New Training"),
                             // Translators: The editor window's title when modifying a training
                             TrainingEditorRole::Edit => gettext("Edit Training"),
+                            // Translators: The editor window's title when importing a training from a link or file
+                            TrainingEditorRole::Import => gettext("Import Training"),
                         },
                     },
                     set_show_end_title_buttons: false,
@@ -70,78 +168,179 @@ impl relm4::SimpleComponent for TrainingEditor {
                             TrainingEditorRole::New => gettext("Create"),
                             // Translators: Button to close the editor window and update an existing training
                             TrainingEditorRole::Edit => gettext("Update"),
+                            // Translators: Button to close the editor window and add the imported training
+                            TrainingEditorRole::Import => gettext("Import"),
                         },
                         set_class_active: ("suggested-action", true),
                         connect_clicked => TrainingEditorInput::Create,
                     }
                 },
-                adw::Clamp {
-                    set_margin_all: 20,
-                    gtk::Box
-                    {
-                        set_orientation: gtk::Orientation::Vertical,
-                        adw::PreferencesGroup
+                // Large text would clip the rows otherwise
+                gtk::ScrolledWindow {
+                    set_hscrollbar_policy: gtk::PolicyType::Never,
+                    set_propagate_natural_height: true,
+                    adw::Clamp {
+                        set_margin_all: 20,
+                        gtk::Box
                         {
-                            set_margin_bottom: 10,
-                            adw::EntryRow {
-                                // Translators: The title of the field for the name of the training in the editor window
-                                set_title: &gettext("Name"),
-                                add_binding: (&model.name, "text"),
+                            set_orientation: gtk::Orientation::Vertical,
+                            adw::PreferencesGroup
+                            {
+                                set_margin_bottom: 10,
+                                adw::EntryRow {
+                                    // Translators: The title of the field for the name of the training in the editor window
+                                    set_title: &gettext("Name"),
+                                    add_binding: (&model.name, "text"),
+                                },
                             },
-                        },
-                        adw::PreferencesGroup
-                        {
-                            adw::SpinRow {
-                                // Translators: The title of the field for the number of sets in the training in the editor window
-                                set_title: &gettext("Number of Sets"),
-                                #[wrap(Some)]
-                                set_adjustment = &gtk::Adjustment {
-                                    set_lower: SPIN_ROW_LOWER,
-                                    set_upper: SPIN_ROW_UPPER,
-                                    set_step_increment: SPIN_ROW_STEP,
-                                    add_binding: (&model.sets, "value"),
+                            adw::PreferencesGroup
+                            {
+                                adw::ComboRow {
+                                    // Translators: The title of the field for the kind of training in the editor window, e.g. Tabata or EMOM
+                                    set_title: &gettext("Mode"),
+                                    set_model: Some(&mode_names),
+                                    set_selected: model.mode as u32,
+                                    connect_selected_notify[sender] => move |row| {
+                                        sender.input(TrainingEditorInput::SetMode(row.selected() as usize));
+                                    },
+                                },
+                                adw::SpinRow {
+                                    #[watch]
+                                    set_visible: model.has_field(Field::Sets),
+                                    #[watch]
+                                    set_title: &model.field_title(Field::Sets),
+                                    #[wrap(Some)]
+                                    set_adjustment = &gtk::Adjustment {
+                                        set_lower: SPIN_ROW_LOWER,
+                                        set_upper: SPIN_ROW_UPPER,
+                                        set_step_increment: SPIN_ROW_STEP,
+                                        add_binding: (&model.sets, "value"),
+                                    },
+                                },
+                                adw::SpinRow {
+                                    #[watch]
+                                    set_visible: model.has_field(Field::Rest),
+                                    #[watch]
+                                    set_title: &model.field_title(Field::Rest),
+                                    // Translators: The subtitle of the field for the duration which refers to the unit. Singular form in some localizations.
+                                    set_subtitle: &gettext("seconds"),
+                                    #[wrap(Some)]
+                                    set_adjustment = &gtk::Adjustment {
+                                        set_lower: SPIN_ROW_LOWER,
+                                        set_upper: SPIN_ROW_UPPER,
+                                        set_step_increment: SPIN_ROW_STEP,
+                                        add_binding: (&model.rest_s, "value"),
+                                    },
+                                },
+                                adw::SpinRow {
+                                    #[watch]
+                                    set_visible: model.has_field(Field::Exercise),
+                                    #[watch]
+                                    set_title: &model.field_title(Field::Exercise),
+                                    // Translators: The subtitle of the field for the duration which refers to the unit. Singular form in some localizations.
+                                    set_subtitle: &gettext("seconds"),
+                                    #[wrap(Some)]
+                                    set_adjustment = &gtk::Adjustment {
+                                        set_lower: SPIN_ROW_LOWER,
+                                        set_upper: SPIN_ROW_UPPER,
+                                        set_step_increment: SPIN_ROW_STEP,
+                                        add_binding: (&model.exercise_s, "value"),
+                                    },
+                                },
+                                adw::ComboRow {
+                                    // Translators: The title of the field for what is done during the preparation in the editor window
+                                    set_title: &gettext("Warmup"),
+                                    set_model: Some(&warmup_names),
+                                    set_selected: model.warmup as u32,
+                                    connect_selected_notify[sender] => move |row| {
+                                        sender.input(TrainingEditorInput::SetWarmup(row.selected() as usize));
+                                    },
                                 },
+                                adw::SpinRow {
+                                    #[watch]
+                                    set_visible: model.warmup == warmup::COUNTDOWN_CHOICE,
+                                    // Translators: The title of the field for the preparation duration in the training in the editor window
+                                    set_title: &gettext("Preparation Time"),
+                                    // Translators: The subtitle of the field for the duration which refers to the unit. Singular form in some localizations.
+                                    set_subtitle: &gettext("seconds"),
+                                    #[wrap(Some)]
+                                    set_adjustment = &gtk::Adjustment {
+                                        set_lower: 0.0,
+                                        set_upper: SPIN_ROW_UPPER,
+                                        set_step_increment: SPIN_ROW_STEP,
+                                        add_binding: (&model.prepare_s, "value"),
+                                    },
+                                },
+                            },
+                            #[local_ref]
+                            warmup_editor -> adw::PreferencesGroup {
+                                set_margin_top: 10,
+                                #[watch]
+                                set_visible: model.warmup == warmup::STEPS_CHOICE,
                             },
-                            adw::SpinRow {
-                                // Translators: The title of the field for the rest duration in the training in the editor window
-                                set_title: &gettext("Rest Time"),
-                                // Translators: The subtitle of the field for the duration which refers to the unit. Singular form in some localizations.
-                                set_subtitle: &gettext("seconds"),
-                                #[wrap(Some)]
-                                set_adjustment = &gtk::Adjustment {
-                                    set_lower: SPIN_ROW_LOWER,
-                                    set_upper: SPIN_ROW_UPPER,
-                                    set_step_increment: SPIN_ROW_STEP,
-                                    add_binding: (&model.rest_s, "value"),
+                            adw::PreferencesGroup
+                            {
+                                set_margin_top: 10,
+                                adw::ComboRow {
+                                    // Translators: The title of the field for how hard the exercise is meant to be in the editor window
+                                    set_title: &gettext("Exercise Intensity"),
+                                    set_model: Some(&intensity_names),
+                                    add_binding: (&model.exercise_intensity, "selected"),
+                                },
+                                adw::ComboRow {
+                                    // Translators: The title of the field for how hard the rest is meant to be in the editor window
+                                    set_title: &gettext("Rest Intensity"),
+                                    set_model: Some(&intensity_names),
+                                    add_binding: (&model.rest_intensity, "selected"),
                                 },
                             },
-                            adw::SpinRow {
-                                // Translators: The title of the field for the exercise duration in the training in the editor window
-                                set_title: &gettext("Exercise Time"),
-                                // Translators: The subtitle of the field for the duration which refers to the unit. Singular form in some localizations.
-                                set_subtitle: &gettext("seconds"),
-                                #[wrap(Some)]
-                                set_adjustment = &gtk::Adjustment {
-                                    set_lower: SPIN_ROW_LOWER,
-                                    set_upper: SPIN_ROW_UPPER,
-                                    set_step_increment: SPIN_ROW_STEP,
-                                    add_binding: (&model.exercise_s, "value"),
+                            adw::PreferencesGroup
+                            {
+                                set_margin_top: 10,
+                                adw::ActionRow {
+                                    // Translators: The title of the row of the images shown during the rounds, e.g. how to do each stretch, in the editor window
+                                    set_title: &gettext("Round Images"),
+                                    #[watch]
+                                    set_subtitle: &images_description(model.images.len()),
+                                    add_suffix = &gtk::Button {
+                                        set_valign: gtk::Align::Center,
+                                        add_css_class: "flat",
+                                        set_icon_name: "edit-clear-symbolic",
+                                        // Translators: Tooltip of the button which removes the images of the rounds in the editor window
+                                        set_tooltip: &gettext("Remove Images"),
+                                        #[watch]
+                                        set_visible: !model.images.is_empty(),
+                                        connect_clicked => TrainingEditorInput::SetImages(Vec::new()),
+                                    },
+                                    add_suffix = &gtk::Button {
+                                        set_valign: gtk::Align::Center,
+                                        // Translators: The button which picks the images of the rounds in the editor window
+                                        set_label: &gettext("Choose…"),
+                                        connect_clicked[sender] => move |button| {
+                                            let window = button.root().and_downcast::<gtk::Window>();
+                                            relm4::spawn_local(choose_images(window, sender.input_sender().clone()));
+                                        },
+                                    },
                                 },
                             },
-                            adw::SpinRow {
-                                // Translators: The title of the field for the preparation duration in the training in the editor window
-                                set_title: &gettext("Preparation Time"),
-                                // Translators: The subtitle of the field for the duration which refers to the unit. Singular form in some localizations.
-                                set_subtitle: &gettext("seconds"),
-                                #[wrap(Some)]
-                                set_adjustment = &gtk::Adjustment {
-                                    set_lower: 0.0,
-                                    set_upper: SPIN_ROW_UPPER,
-                                    set_step_increment: SPIN_ROW_STEP,
-                                    add_binding: (&model.prepare_s, "value"),
+                            adw::PreferencesGroup
+                            {
+                                set_margin_top: 10,
+                                set_visible: !model.progression_ids.is_empty(),
+                                adw::ComboRow {
+                                    // Translators: The title of the field for the harder training which follows this one in the editor window
+                                    set_title: &gettext("Next Progression"),
+                                    set_subtitle: &if false {
+                                        // Translators: The subtitle of the field for the harder training which follows this one. {} is the number of sessions.
+                                        gettext("Suggested after {} sessions in a row with every round done")
+                                    } else {
+                                        gettext!("Suggested after {} sessions in a row with every round done", PROGRESSION_SESSIONS)
+                                    },
+                                    set_model: Some(&progression_names),
+                                    add_binding: (&model.progression, "selected"),
                                 },
                             },
-                        },
+                        }
                     }
                 }
             }
@@ -153,31 +352,118 @@ impl relm4::SimpleComponent for TrainingEditor {
         root: Self::Root,
         sender: relm4::ComponentSender<Self>,
     ) -> relm4::ComponentParts<Self> {
+        let mode_names = gtk::StringList::new(&[]);
+        for mode in MODES {
+            mode_names.append(&mode.name());
+        }
+        let intensity_names = gtk::StringList::new(&[
+            // Translators: The choice of the editor window for a period without an intensity
+            &gettext("Not Set"),
+        ]);
+        for intensity in Intensity::ALL {
+            intensity_names.append(&intensity.title());
+        }
+        let progression_names = gtk::StringList::new(&[
+            // Translators: The choice of the editor window for a training without a next progression
+            &gettext("None"),
+        ]);
+        for candidate in &init.2 {
+            progression_names.append(&candidate.name);
+        }
+        let progression_ids: Vec<String> =
+            init.2.into_iter().map(|candidate| candidate.id).collect();
+        let progression = init
+            .1
+            .progression
+            .as_ref()
+            .and_then(|id| progression_ids.iter().position(|candidate| candidate == id))
+            .map_or(0, |index| index as u32 + 1);
+        let warmup_names = gtk::StringList::new(&[]);
+        for name in Warmup::choice_names() {
+            warmup_names.append(&name);
+        }
+        let warmup_steps = match &init.1.warmup {
+            Warmup::Steps(steps) => steps.clone(),
+            _ => Vec::new(),
+        };
+        let warmup_editor = WarmupEditor::builder()
+            .launch(WarmupEditorInit {
+                // Translators: The title of the list of the warmup steps in the editor window
+                title: gettext("Warmup Steps"),
+                description: None,
+                steps: warmup_steps.clone(),
+            })
+            .forward(sender.input_sender(), TrainingEditorInput::SetWarmupSteps);
         let model = TrainingEditor {
             name: StringBinding::new(init.1.name.clone()),
             sets: U32Binding::new(init.1.sets as u32),
             rest_s: U32Binding::new(init.1.rest_s as u32),
             exercise_s: U32Binding::new(init.1.exercise_s as u32),
             prepare_s: U32Binding::new(init.1.prepare_s as u32),
+            progression: U32Binding::new(progression),
+            exercise_intensity: U32Binding::new(intensity_choice(init.1.exercise_intensity)),
+            rest_intensity: U32Binding::new(intensity_choice(init.1.rest_intensity)),
+            images: init.1.images,
+            warmup: init.1.warmup.choice(),
+            warmup_steps,
+            warmup_editor,
+            mode: MODES
+                .iter()
+                .position(|mode| mode.id() == workout_mode::mode(&init.1.mode).id())
+                .unwrap_or(0),
+            progression_ids,
+            id: init.1.id,
+            revision: init.1.revision,
             role: init.0,
         };
+        let warmup_editor = model.warmup_editor.widget();
         let widgets = view_output!();
         relm4::ComponentParts { model, widgets }
     }
 
     fn update(&mut self, message: Self::Input, sender: relm4::ComponentSender<Self>) {
         match message {
-            TrainingEditorInput::Cancel => sender.output(None).unwrap(),
+            TrainingEditorInput::SetMode(mode) => {
+                self.mode = mode.min(MODES.len() - 1);
+                if let Some(preset) = self.mode().preset() {
+                    self.sets.set(preset.sets as u32);
+                    self.exercise_s.set(preset.exercise_s as u32);
+                    self.rest_s.set(preset.rest_s as u32);
+                    self.prepare_s.set(preset.prepare_s as u32);
+                }
+            }
+            TrainingEditorInput::SetImages(images) => {
+                self.images = images;
+            }
+            TrainingEditorInput::SetWarmup(warmup) => {
+                self.warmup = warmup;
+            }
+            TrainingEditorInput::SetWarmupSteps(steps) => {
+                self.warmup_steps = steps;
+            }
+            TrainingEditorInput::Cancel => {
+                let _ = sender.output(None);
+            }
             TrainingEditorInput::Create => {
-                sender
-                    .output(Some(TrainingEditorOutput::Create(TrainingSetup {
-                        name: self.name.get(),
-                        exercise_s: self.exercise_s.get() as usize,
-                        rest_s: self.rest_s.get() as usize,
-                        sets: self.sets.get() as usize,
-                        prepare_s: self.prepare_s.get() as usize,
-                    })))
-                    .unwrap();
+                let _ = sender.output(Some(TrainingEditorOutput::Create(TrainingSetup {
+                    id: self.id.clone(),
+                    revision: self.revision + 1,
+                    name: self.name.get(),
+                    exercise_s: self.exercise_s.get() as usize,
+                    rest_s: self.rest_s.get() as usize,
+                    sets: self.sets.get() as usize,
+                    prepare_s: self.prepare_s.get() as usize,
+                    pinned: false,
+                    progression: (self.progression.get() as usize)
+                        .checked_sub(1)
+                        .and_then(|index| self.progression_ids.get(index))
+                        .cloned(),
+                    mode: self.mode().id().to_owned(),
+                    exercise_intensity: chosen_intensity(self.exercise_intensity.get()),
+                    rest_intensity: chosen_intensity(self.rest_intensity.get()),
+                    images: self.images.clone(),
+                    warmup: Warmup::from_choice(self.warmup, self.warmup_steps.clone()),
+                })));
             }
         }
     }
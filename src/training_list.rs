@@ -0,0 +1,106 @@
+use crate::training_setup::{TrainingRow, TrainingSetup, TrainingSetupOutput};
+use relm4::{gtk, typed_view::list::TypedListView};
+use std::{cell::RefCell, rc::Rc};
+
+/// The trainings on the list page. Only the rows in view have widgets, which are
+/// recycled while scrolling, so that hundreds of imported trainings stay fast.
+pub struct TrainingList {
+    view: TypedListView<TrainingRow, gtk::NoSelection>,
+    sender: relm4::Sender<TrainingSetupOutput>,
+    /// The lowercase text which the names of the shown trainings contain.
+    search_text: Rc<RefCell<String>>,
+}
+
+impl TrainingList {
+    pub fn new(sender: relm4::Sender<TrainingSetupOutput>) -> Self {
+        let mut view = TypedListView::new();
+        let search_text = Rc::new(RefCell::new(String::new()));
+        {
+            let search_text = search_text.clone();
+            view.add_filter(move |row: &TrainingRow| {
+                row.setup
+                    .name
+                    .to_lowercase()
+                    .contains(search_text.borrow().as_str())
+            });
+        }
+        Self {
+            view,
+            sender,
+            search_text,
+        }
+    }
+
+    pub fn widget(&self) -> &gtk::ListView {
+        &self.view.view
+    }
+
+    pub fn len(&self) -> usize {
+        self.view.len() as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.view.is_empty()
+    }
+
+    pub fn get(&self, index: usize) -> Option<TrainingSetup> {
+        self.view
+            .get(index as u32)
+            .map(|row| row.borrow().setup.clone())
+    }
+
+    /// All trainings in the order of the list, including those hidden by the search.
+    pub fn trainings(&self) -> Vec<TrainingSetup> {
+        (0..self.len())
+            .filter_map(|index| self.get(index))
+            .collect()
+    }
+
+    pub fn position(&self, id: &str) -> Option<usize> {
+        self.view
+            .find(|row| row.setup.id == id)
+            .map(|index| index as usize)
+    }
+
+    pub fn push_back(&mut self, setup: TrainingSetup) {
+        self.view
+            .append(TrainingRow::new(setup, self.sender.clone()));
+    }
+
+    pub fn insert(&mut self, index: usize, setup: TrainingSetup) {
+        self.view
+            .insert(index as u32, TrainingRow::new(setup, self.sender.clone()));
+    }
+
+    pub fn remove(&mut self, index: usize) -> Option<TrainingSetup> {
+        let setup = self.get(index)?;
+        self.view.remove(index as u32);
+        Some(setup)
+    }
+
+    /// Replaces the training at `index`. The row is bound again, as it is not notified of
+    /// changes to the training otherwise.
+    pub fn replace(&mut self, index: usize, setup: TrainingSetup) {
+        if self.remove(index).is_some() {
+            self.insert(index, setup);
+        }
+    }
+
+    pub fn set_trainings(&mut self, trainings: impl IntoIterator<Item = TrainingSetup>) {
+        self.view.clear();
+        let sender = self.sender.clone();
+        self.view.extend_from_iter(
+            trainings
+                .into_iter()
+                .map(|setup| TrainingRow::new(setup, sender.clone())),
+        );
+    }
+
+    /// Shows only the trainings whose names contain the search text.
+    pub fn filter(&mut self, search_text: &str) {
+        *self.search_text.borrow_mut() = search_text.to_lowercase();
+        // Setting the filter again makes the list evaluate it again
+        self.view.set_filter_status(0, false);
+        self.view.set_filter_status(0, true);
+    }
+}
@@ -1,50 +1,274 @@
+use crate::hiit_core::{Intervals, Phase};
+use crate::history::SessionRecord;
+use crate::intensity::Intensity;
+use crate::print_plan;
 use crate::settings;
-use crate::training_editor::*;
+use crate::share_dialog::{self, ShareDialog};
+use crate::statistics_page;
+use crate::warmup::{self, Warmup};
+use crate::workout_mode::{self, WorkoutMode, CLASSIC_MODE_ID};
 use futures::prelude::*;
 use gettextrs::gettext;
-use libadwaita::prelude::AdwDialogExt;
+use hiit_schema::Workout;
+use libadwaita::prelude::{AdwDialogExt, AlertDialogExt};
 use relm4::{
     gtk::{self, prelude::*},
     prelude::*,
+    typed_view::list::RelmListItem,
     RelmWidgetExt,
 };
 use relm4_icons::icon_names;
-use std::time::Duration;
+use std::{cell::RefCell, rc::Rc, time::Duration};
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct TrainingSetup {
+    pub id: String,
+    pub revision: u64,
     pub name: String,
     pub exercise_s: usize,
     pub rest_s: usize,
     pub sets: usize,
     pub prepare_s: usize,
+    /// Pinned trainings are kept at the top of the list.
+    pub pinned: bool,
+    /// The id of the harder training which is suggested once this one is mastered.
+    pub progression: Option<String>,
+    /// The id of the [`WorkoutMode`], which turns the fields into the intervals.
+    pub mode: String,
+    /// How hard the exercise is meant to be, if it was chosen.
+    pub exercise_intensity: Option<Intensity>,
+    pub rest_intensity: Option<Intensity>,
+    /// The URIs of the images of the rounds in order, e.g. how to do each stretch.
+    pub images: Vec<String>,
+    /// The steps of the preparation, instead of a countdown of `prepare_s`.
+    pub warmup: Warmup,
+}
+
+impl From<Workout> for TrainingSetup {
+    /// Workouts without an identity, e.g. from older versions, get a new one.
+    fn from(workout: Workout) -> Self {
+        TrainingSetup {
+            id: workout.id.unwrap_or_else(TrainingSetup::new_id),
+            revision: workout.revision,
+            name: gettext(workout.name),
+            exercise_s: workout.exercise_s,
+            rest_s: workout.rest_s,
+            sets: workout.sets,
+            prepare_s: workout.prepare_s,
+            pinned: workout.pinned,
+            progression: workout.progression,
+            mode: workout.mode.unwrap_or_else(|| CLASSIC_MODE_ID.to_owned()),
+            exercise_intensity: workout
+                .exercise_intensity
+                .as_deref()
+                .and_then(Intensity::from_setting),
+            rest_intensity: workout
+                .rest_intensity
+                .as_deref()
+                .and_then(Intensity::from_setting),
+            images: workout.images,
+            warmup: if workout.default_warmup {
+                Warmup::Default
+            } else if workout.warmup.is_empty() {
+                Warmup::Countdown
+            } else {
+                Warmup::Steps(workout.warmup)
+            },
+        }
+    }
+}
+
+impl From<&TrainingSetup> for Workout {
+    fn from(training: &TrainingSetup) -> Self {
+        Workout {
+            version: hiit_schema::SCHEMA_VERSION,
+            id: Some(training.id.clone()),
+            revision: training.revision,
+            name: training.name.clone(),
+            sets: training.sets,
+            exercise_s: training.exercise_s,
+            rest_s: training.rest_s,
+            prepare_s: training.prepare_s,
+            pinned: training.pinned,
+            progression: training.progression.clone(),
+            mode: (training.mode != CLASSIC_MODE_ID).then(|| training.mode.clone()),
+            exercise_intensity: training
+                .exercise_intensity
+                .map(|intensity| intensity.to_setting().to_owned()),
+            rest_intensity: training
+                .rest_intensity
+                .map(|intensity| intensity.to_setting().to_owned()),
+            images: training.images.clone(),
+            warmup: match &training.warmup {
+                Warmup::Steps(steps) => steps.clone(),
+                _ => Vec::new(),
+            },
+            default_warmup: training.warmup == Warmup::Default,
+        }
+    }
 }
 
 impl TrainingSetup {
+    pub fn new_id() -> String {
+        relm4::gtk::glib::uuid_string_random().to_string()
+    }
+
+    pub fn mode(&self) -> &'static dyn WorkoutMode {
+        workout_mode::mode(&self.mode)
+    }
+
+    /// The lengths of the periods, which drive the timer. The preparation lasts as long as the
+    /// steps of the warmup, if there are any.
+    pub fn intervals(&self) -> Intervals {
+        let mut intervals = self.mode().intervals(self);
+        let warmup_s = warmup::total_s(&self.warmup.steps());
+        if warmup_s > 0 {
+            intervals.prepare_s = warmup_s;
+        }
+        intervals
+    }
+
+    /// The name of the phase in the mode of the training, e.g. "Inhale" rather than "Exercise",
+    /// while `remaining_sets` are not done yet.
+    pub fn phase_title(&self, phase: Phase, remaining_sets: usize) -> String {
+        let mode = self.mode();
+        let intervals = self.intervals();
+        if phase == Phase::Rest
+            && intervals.is_long_rest_after(intervals.sets.saturating_sub(remaining_sets))
+        {
+            if let Some(title) = mode.long_rest_title() {
+                return title;
+            }
+        }
+        mode.phase_title(phase)
+    }
+
+    /// The current round and the number of rounds, while `remaining_sets` are not done yet.
+    /// A round is a step of the mode, e.g. a stretch with both of its sides.
+    pub fn round(&self, remaining_sets: usize) -> (usize, usize) {
+        let sets = self.intervals().sets;
+        let sets_per_step = self.mode().sets_per_step();
+        let rounds = sets.div_ceil(sets_per_step);
+        let round = ((sets - remaining_sets.min(sets)) / sets_per_step + 1).min(rounds);
+        (round, rounds)
+    }
+
+    /// The rounds which are not done yet, including the current one.
+    pub fn remaining_rounds(&self, remaining_sets: usize) -> usize {
+        remaining_sets.div_ceil(self.mode().sets_per_step())
+    }
+
+    /// The image of the current round, or of the next one during the rest before it.
+    pub fn round_image(&self, remaining_sets: usize) -> Option<&str> {
+        let (round, _) = self.round(remaining_sets);
+        self.images.get(round - 1).map(String::as_str)
+    }
+
+    /// How hard the period is meant to be. The preparation has no intensity.
+    pub fn intensity(&self, phase: Phase) -> Option<Intensity> {
+        match phase {
+            Phase::Preparation => None,
+            Phase::Exercise => self.exercise_intensity,
+            Phase::Rest => self.rest_intensity,
+        }
+    }
+
+    /// A copy with its own identity, e.g. "Tabata (Copy)".
+    pub fn duplicate(&self) -> TrainingSetup {
+        TrainingSetup {
+            id: TrainingSetup::new_id(),
+            revision: 0,
+            name: if false {
+                // Translators: The name of a duplicated training. {} is replaced by the original name.
+                gettext("{} (Copy)")
+            } else {
+                gettext!("{} (Copy)", self.name)
+            },
+            pinned: false,
+            ..self.clone()
+        }
+    }
+
+    /// Rebuilds the training of a recorded session, e.g. after the training was deleted.
+    pub fn from_session(session: &SessionRecord) -> Option<TrainingSetup> {
+        // A skipped round is shorter than the training
+        let round = session
+            .rounds
+            .iter()
+            .find(|round| !round.skipped)
+            .or(session.rounds.first())?;
+        Some(TrainingSetup {
+            id: TrainingSetup::new_id(),
+            revision: 0,
+            name: session.training_name.clone(),
+            exercise_s: round.exercise_s,
+            rest_s: round.rest_s,
+            sets: session.sets,
+            pinned: false,
+            progression: None,
+            mode: CLASSIC_MODE_ID.to_owned(),
+            exercise_intensity: None,
+            rest_intensity: None,
+            images: Vec::new(),
+            warmup: Warmup::Countdown,
+            ..TrainingSetup::default()
+        })
+    }
+
+    /// The length of the training as the timer runs it, with the gap to get ready of the
+    /// preferences after every rest.
     pub fn total_duration(&self) -> Duration {
-        Duration::from_secs(
-            (self.exercise_s * self.sets + self.rest_s * (self.sets - 1) + self.prepare_s) as u64,
-        )
+        let transition_s = settings::load_transition_s_from_gsettings();
+        Duration::from_secs(self.intervals().total_s(transition_s) as u64)
     }
 }
 
+/// The trainings which a new profile starts with.
+pub fn sample_trainings() -> Vec<TrainingSetup> {
+    let sample = |name: String, sets, exercise_s, rest_s| TrainingSetup {
+        id: TrainingSetup::new_id(),
+        revision: 0,
+        name,
+        exercise_s,
+        rest_s,
+        sets,
+        prepare_s: 10,
+        pinned: false,
+        progression: None,
+        mode: CLASSIC_MODE_ID.to_owned(),
+        exercise_intensity: None,
+        rest_intensity: None,
+        images: Vec::new(),
+        warmup: Warmup::Countdown,
+    };
+    vec![
+        // Translators: The name of a sample training of 8 rounds of 20 seconds exercise and 10 seconds rest
+        sample(gettext("Tabata"), 8, 20, 10),
+        // Translators: The name of a sample training of 30 seconds exercise and 30 seconds rest
+        sample(gettext("HIIT 30/30"), 10, 30, 30),
+        // Translators: The name of a sample training of long exercises with short rests
+        sample(gettext("Plank Intervals"), 5, 45, 15),
+        TrainingSetup {
+            mode: "stretching".to_owned(),
+            // Translators: The name of a sample stretching routine of 6 stretches held for 30 seconds on each side
+            ..sample(gettext("Full Body Stretch"), 6, 30, 10)
+        },
+    ]
+}
+
 impl Default for TrainingSetup {
     fn default() -> Self {
         settings::load_default_training_setup()
     }
 }
 
-#[derive(Debug)]
-pub enum TrainingSetupInput {
-    Edit(gtk::Root),
-    Update(TrainingSetup),
-    Load,
-}
-
 #[derive(Debug)]
 pub enum TrainingSetupOutput {
-    Remove(DynamicIndex),
+    Remove(String),
     Load(TrainingSetup),
+    Edit(TrainingSetup),
+    Duplicate(TrainingSetup),
+    TogglePin(String),
 }
 
 fn format_duration(d: &Duration) -> String {
@@ -54,168 +278,432 @@ fn format_duration(d: &Duration) -> String {
     format!("{}:{:02}", minutes, seconds)
 }
 
-#[relm4::factory(pub)]
-impl FactoryComponent for TrainingSetup {
-    type Init = TrainingSetup;
-    type Input = TrainingSetupInput;
-    type Output = TrainingSetupOutput;
-    type CommandOutput = ();
-    type ParentWidget = gtk::Box;
-
-    view! {
-        gtk::Box {
-            set_hexpand: true,
-            set_class_active: ("card", true),
-            set_margin_top: 5,
-            set_margin_start: 5,
-            set_margin_end: 5,
-            inline_css: "padding: 10px",
+pub fn format_seconds(seconds: usize) -> String {
+    if true {
+        gettext!("{} s", seconds)
+    } else {
+        // Translators: the format label for indicating the number of seconds in the training list item. Please use a short abbreviation for seconds, e.g. "s".
+        gettext("{} s")
+    }
+}
+
+/// A row of the training list, with the sender for the actions of its buttons.
+#[derive(Clone)]
+pub struct TrainingRow {
+    pub setup: TrainingSetup,
+    sender: relm4::Sender<TrainingSetupOutput>,
+}
+
+impl TrainingRow {
+    pub fn new(setup: TrainingSetup, sender: relm4::Sender<TrainingSetupOutput>) -> Self {
+        Self { setup, sender }
+    }
+}
+
+/// The training shown by a recycled row widget, while it is bound.
+#[derive(Clone, Default)]
+struct BoundRow(Rc<RefCell<Option<TrainingRow>>>);
+
+impl BoundRow {
+    fn send(&self, output: impl FnOnce(TrainingSetup) -> TrainingSetupOutput) {
+        if let Some(row) = self.0.borrow().as_ref() {
+            row.sender.emit(output(row.setup.clone()));
+        }
+    }
+
+    fn setup(&self) -> Option<TrainingSetup> {
+        self.0.borrow().as_ref().map(|row| row.setup.clone())
+    }
+}
+
+pub struct TrainingRowWidgets {
+    bound: BoundRow,
+    pin_icon: gtk::Image,
+    name_label: gtk::Label,
+    duration_label: gtk::Label,
+    sets_label: gtk::Label,
+    exercise_label: gtk::Label,
+    rest_label: gtk::Label,
+    prepare_label: gtk::Label,
+}
+
+fn confirm_remove(row: &gtk::Widget, bound: &BoundRow) {
+    let dialog = libadwaita::AlertDialog::new(
+        // Translators: The heading of the dialog which confirms deleting a training
+        Some(&gettext("Delete Training?")),
+        None,
+    );
+    // Translators: The response of the dialog which keeps the training
+    dialog.add_response("cancel", &gettext("_Cancel"));
+    // Translators: The response of the dialog which deletes the training
+    dialog.add_response("delete", &gettext("_Delete"));
+    dialog.set_response_appearance("delete", libadwaita::ResponseAppearance::Destructive);
+    dialog.set_default_response(Some("cancel"));
+    dialog.set_close_response("cancel");
+    let bound = bound.clone();
+    dialog.connect_response(Some("delete"), move |_, _| {
+        bound.send(|setup| TrainingSetupOutput::Remove(setup.id));
+    });
+    dialog.present(Some(row));
+}
+
+fn share(row: &gtk::Widget, setup: TrainingSetup) {
+    let dialog = ShareDialog::builder().launch(setup);
+    dialog.widget().present(Some(row));
+    let mut dialog = dialog.into_stream();
+    relm4::spawn_local(async move {
+        dialog.next().await;
+    });
+}
+
+fn export(row: &gtk::Widget, setup: TrainingSetup) {
+    let json = json::stringify_pretty(settings::training_setup_to_json(&setup), 4);
+    relm4::spawn_local(share_dialog::export_to_file(
+        row.root().and_downcast(),
+        setup.name,
+        json,
+    ));
+}
+
+/// Shows the menu of the row where it was right-clicked or long-pressed.
+fn show_context_menu(row: &gtk::Widget, x: f64, y: f64, bound: &BoundRow) {
+    let Some(setup) = bound.setup() else {
+        return;
+    };
+    let actions = gtk::gio::SimpleActionGroup::new();
+    let add_action = |name: &str, activate: Box<dyn Fn(&gtk::Widget)>| {
+        let action = gtk::gio::SimpleAction::new(name, None);
+        let row = row.downgrade();
+        action.connect_activate(move |_, _| {
+            if let Some(row) = row.upgrade() {
+                activate(&row);
+            }
+        });
+        actions.add_action(&action);
+    };
+    add_action(
+        "edit",
+        Box::new({
+            let bound = bound.clone();
+            move |_| bound.send(TrainingSetupOutput::Edit)
+        }),
+    );
+    add_action(
+        "duplicate",
+        Box::new({
+            let bound = bound.clone();
+            move |_| bound.send(TrainingSetupOutput::Duplicate)
+        }),
+    );
+    add_action(
+        "export",
+        Box::new({
+            let bound = bound.clone();
+            move |row| {
+                if let Some(setup) = bound.setup() {
+                    export(row, setup);
+                }
+            }
+        }),
+    );
+    add_action(
+        "print",
+        Box::new({
+            let bound = bound.clone();
+            move |row| {
+                if let Some(setup) = bound.setup() {
+                    print_plan::print(row, setup);
+                }
+            }
+        }),
+    );
+    add_action(
+        "pin",
+        Box::new({
+            let bound = bound.clone();
+            move |_| bound.send(|setup| TrainingSetupOutput::TogglePin(setup.id))
+        }),
+    );
+    add_action(
+        "delete",
+        Box::new({
+            let bound = bound.clone();
+            move |row| confirm_remove(row, &bound)
+        }),
+    );
+    row.insert_action_group("row", Some(&actions));
+
+    let menu = gtk::gio::Menu::new();
+    // Translators: The entry of the context menu of a training which opens the training editor
+    menu.append(Some(&gettext("_Edit")), Some("row.edit"));
+    // Translators: The entry of the context menu of a training which creates a copy of it
+    menu.append(Some(&gettext("D_uplicate")), Some("row.duplicate"));
+    // Translators: The entry of the context menu of a training which saves it to a file
+    menu.append(Some(&gettext("E_xport…")), Some("row.export"));
+    // Translators: The entry of the context menu of a training which prints its plan or saves it as PDF
+    menu.append(Some(&gettext("P_rint…")), Some("row.print"));
+    menu.append(
+        Some(&if setup.pinned {
+            // Translators: The entry of the context menu of a training which no longer keeps it at the top of the list
+            gettext("U_npin")
+        } else {
+            // Translators: The entry of the context menu of a training which keeps it at the top of the list
+            gettext("_Pin")
+        }),
+        Some("row.pin"),
+    );
+    let delete_section = gtk::gio::Menu::new();
+    // Translators: The entry of the context menu of a training which deletes it
+    delete_section.append(Some(&gettext("_Delete")), Some("row.delete"));
+    menu.append_section(None, &delete_section);
+
+    let popover = gtk::PopoverMenu::from_model(Some(&menu));
+    popover.set_parent(row);
+    popover.set_has_arrow(false);
+    popover.set_pointing_to(Some(&gtk::gdk::Rectangle::new(x as i32, y as i32, 1, 1)));
+    popover.connect_closed(|popover| {
+        // Unparent after the activated action was handled
+        let popover = popover.clone();
+        gtk::glib::idle_add_local_once(move || popover.unparent());
+    });
+    popover.popup();
+}
+
+/// The label and the colored indicator of a period in a row.
+fn period_label(css_class: &str, label: &str) -> gtk::Box {
+    relm4::view! {
+        period_box = gtk::Box {
+            set_spacing: 6,
             gtk::Box {
-                set_orientation: gtk::Orientation::Vertical,
-                gtk::CenterBox {
-                    set_hexpand: true,
-                    set_orientation: gtk::Orientation::Horizontal,
-                    #[wrap(Some)]
-                    set_start_widget = &gtk::Label {
-                        add_css_class: "title-4",
-                        #[watch]
-                        set_label: &self.name,
+                add_css_class: "phase-indicator",
+                add_css_class: css_class,
+                set_valign: gtk::Align::Center,
+            },
+            gtk::Label {
+                set_halign: gtk::Align::Start,
+                set_label: label,
+            },
+        }
+    }
+    period_box
+}
+
+impl RelmListItem for TrainingRow {
+    type Root = gtk::Box;
+    type Widgets = TrainingRowWidgets;
+
+    fn setup(list_item: &gtk::ListItem) -> (gtk::Box, TrainingRowWidgets) {
+        // The row handles the keys itself, rather than the list
+        list_item.set_focusable(false);
+        list_item.set_activatable(false);
+        let bound = BoundRow::default();
+        relm4::view! {
+            root = gtk::Box {
+                set_hexpand: true,
+                set_focusable: true,
+                add_controller = gtk::EventControllerKey {
+                    connect_key_pressed[bound] => move |controller, key, _, modifiers| {
+                        if !modifiers.is_empty() {
+                            return gtk::glib::Propagation::Proceed;
+                        }
+                        let Some(row) = controller.widget() else {
+                            return gtk::glib::Propagation::Proceed;
+                        };
+                        match key {
+                            gtk::gdk::Key::Return | gtk::gdk::Key::KP_Enter => {
+                                bound.send(TrainingSetupOutput::Load);
+                            }
+                            gtk::gdk::Key::F2 => bound.send(TrainingSetupOutput::Edit),
+                            gtk::gdk::Key::Delete | gtk::gdk::Key::KP_Delete => {
+                                confirm_remove(&row, &bound);
+                            }
+                            gtk::gdk::Key::Menu => {
+                                show_context_menu(&row, row.width() as f64 / 2.0, row.height() as f64 / 2.0, &bound);
+                            }
+                            _ => return gtk::glib::Propagation::Proceed,
+                        }
+                        gtk::glib::Propagation::Stop
+                    },
+                },
+                add_controller = gtk::GestureClick {
+                    set_button: gtk::gdk::BUTTON_SECONDARY,
+                    connect_pressed[bound] => move |gesture, _, x, y| {
+                        if let Some(row) = gesture.widget() {
+                            show_context_menu(&row, x, y, &bound);
+                        }
                     },
-                    #[wrap(Some)]
-                    set_end_widget = &gtk::Label {
-                        add_css_class: "title-4",
-                        #[watch]
-                        set_label: &format_duration(&self.total_duration()),
+                },
+                add_controller = gtk::GestureLongPress {
+                    set_touch_only: true,
+                    connect_pressed[bound] => move |gesture, x, y| {
+                        if let Some(row) = gesture.widget() {
+                            show_context_menu(&row, x, y, &bound);
+                        }
                     },
                 },
-                gtk::CenterBox {
-                    set_hexpand: true,
-                    set_orientation: gtk::Orientation::Horizontal,
-                    #[wrap(Some)]
-                    set_start_widget = &gtk::Grid {
-                        set_column_spacing: 24,
-                        attach[0, 0, 1, 1] = &gtk::Label {
-                            set_halign: gtk::Align::Start,
-                            // Translators: the label of the number of sets row in the training list item
-                            set_label: &gettext("Sets"),
+                set_class_active: ("card", true),
+                set_margin_top: 5,
+                set_margin_start: 5,
+                set_margin_end: 5,
+                inline_css: "padding: 10px",
+                gtk::Box {
+                    set_orientation: gtk::Orientation::Vertical,
+                    gtk::CenterBox {
+                        set_hexpand: true,
+                        set_orientation: gtk::Orientation::Horizontal,
+                        #[wrap(Some)]
+                        set_start_widget = &gtk::Box {
+                            set_spacing: 6,
+                            #[name = "pin_icon"]
+                            gtk::Image {
+                                set_icon_name: Some("view-pin-symbolic"),
+                                // Translators: tooltip of the icon of a training which is kept at the top of the list
+                                set_tooltip: &gettext("Pinned"),
+                            },
+                            #[name = "name_label"]
+                            gtk::Label {
+                                add_css_class: "title-4",
+                            },
                         },
-                        attach[1, 0, 1, 1] = &gtk::Label {
-                            set_halign: gtk::Align::Start,
-                            #[watch]
-                            set_label: &self.sets.to_string(),
+                        #[wrap(Some)]
+                        #[name = "duration_label"]
+                        set_end_widget = &gtk::Label {
+                            add_css_class: "title-4",
                         },
-                        attach[0, 1, 1, 1] = &gtk::Label {
-                            set_halign: gtk::Align::Start,
+                    },
+                    gtk::CenterBox {
+                        set_hexpand: true,
+                        set_orientation: gtk::Orientation::Horizontal,
+                        #[wrap(Some)]
+                        set_start_widget = &gtk::Grid {
+                            set_column_spacing: 24,
+                            attach[0, 0, 1, 1] = &gtk::Label {
+                                set_halign: gtk::Align::Start,
+                                // Translators: the label of the number of sets row in the training list item
+                                set_label: &gettext("Sets"),
+                            },
+                            #[name = "sets_label"]
+                            attach[1, 0, 1, 1] = &gtk::Label {
+                                set_halign: gtk::Align::Start,
+                            },
                             // Translators: the label of the exercise time row in the training list item
-                            set_label: &gettext("Exercise"),
-                        },
-                        attach[1, 1, 1, 1] = &gtk::Label {
-                            set_halign: gtk::Align::Start,
-                            #[watch]
-                            set_label: &if true {
-                                gettext!("{} s", self.exercise_s)
-                            } else {
-                                // Translators: the format label for indicating the number of seconds in the training list item. Please use a short abbreviation for seconds, e.g. "s".
-                                gettext("{} s")
+                            attach[0, 1, 1, 1] = &period_label("timer-exercise", &gettext("Exercise")),
+                            #[name = "exercise_label"]
+                            attach[1, 1, 1, 1] = &gtk::Label {
+                                set_halign: gtk::Align::Start,
                             },
-                        },
-                        attach[0, 2, 1, 1] = &gtk::Label {
-                            set_halign: gtk::Align::Start,
                             // Translators: the label of the rest time row in the training list item
-                            set_label: &gettext("Rest"),
-                        },
-                        attach[1, 2, 1, 1] = &gtk::Label {
-                            set_halign: gtk::Align::Start,
-                            #[watch]
-                            set_label: &if true {
-                                gettext!("{} s", self.rest_s)
-                            } else {
-                                // Translators: the format label for indicating the number of seconds in the training list item. Please use a short abbreviation for seconds, e.g. "s".
-                                gettext("{} s")
-                            },                        },
-                        attach[0, 3, 1, 1] = &gtk::Label {
-                            set_halign: gtk::Align::Start,
+                            attach[0, 2, 1, 1] = &period_label("timer-rest", &gettext("Rest")),
+                            #[name = "rest_label"]
+                            attach[1, 2, 1, 1] = &gtk::Label {
+                                set_halign: gtk::Align::Start,
+                            },
                             // Translators: the label of the preparation time row in the training list item
-                            set_label: &gettext("Preparation"),
+                            attach[0, 3, 1, 1] = &period_label("timer-warmup", &gettext("Preparation")),
+                            #[name = "prepare_label"]
+                            attach[1, 3, 1, 1] = &gtk::Label {
+                                set_halign: gtk::Align::Start,
+                            },
                         },
-                        attach[1, 3, 1, 1] = &gtk::Label {
-                            set_halign: gtk::Align::Start,
-                            #[watch]
-                            set_label: &if true {
-                                gettext!("{} s", self.prepare_s)
-                            } else {
-                                // Translators: the format label for indicating the number of seconds in the training list item. Please use a short abbreviation for seconds, e.g. "s".
-                                gettext("{} s")
-                            },                        },
-                    },
-                    #[wrap(Some)]
-                    set_end_widget = &gtk::Box {
-                        gtk::Box {
-                            set_orientation: gtk::Orientation::Horizontal,
-                            set_valign: gtk::Align::End,
-                            gtk::Button {
-                                set_icon_name: icon_names::EDIT,
-                                connect_clicked[sender] => move |btn| {
-                                    sender.input(TrainingSetupInput::Edit(btn.root().unwrap()));
+                        #[wrap(Some)]
+                        set_end_widget = &gtk::Box {
+                            gtk::Box {
+                                set_orientation: gtk::Orientation::Horizontal,
+                                set_valign: gtk::Align::End,
+                                gtk::Button {
+                                    set_icon_name: icon_names::EDIT,
+                                    connect_clicked[bound] => move |_| {
+                                        bound.send(TrainingSetupOutput::Edit);
+                                    },
+                                    set_margin_end: 10,
+                                    // Translators: tooltip text for exercise card button to open the training editor
+                                    set_tooltip: &gettext("Edit Training"),
                                 },
-                                set_margin_end: 10,
-                                // Translators: tooltip text for exercise card button to open the training editor
-                                set_tooltip: &gettext("Edit Training"),
-                            },
-                            gtk::Button {
-                                set_icon_name: "edit-delete",
-                                connect_clicked[sender, index] => move |_| {
-                                    sender.output(TrainingSetupOutput::Remove(index.clone())).unwrap();
+                                gtk::MenuButton {
+                                    set_icon_name: icon_names::GRAPH,
+                                    set_create_popup_func: {
+                                        let bound = bound.clone();
+                                        move |button| {
+                                            if let Some(setup) = bound.setup() {
+                                                button.set_popover(Some(&statistics_page::training_statistics_popover(&setup.id)));
+                                            }
+                                        }
+                                    },
+                                    set_margin_end: 10,
+                                    // Translators: tooltip text for exercise card button to show the statistics of the training
+                                    set_tooltip: &gettext("Training Statistics"),
+                                },
+                                gtk::Button {
+                                    set_icon_name: icon_names::SHARE,
+                                    connect_clicked[bound] => move |button| {
+                                        if let Some(setup) = bound.setup() {
+                                            share(button.upcast_ref(), setup);
+                                        }
+                                    },
+                                    set_margin_end: 10,
+                                    // Translators: tooltip text for exercise card button to share the training
+                                    set_tooltip: &gettext("Share Training"),
+                                },
+                                gtk::Button {
+                                    set_icon_name: "edit-delete",
+                                    connect_clicked[bound] => move |_| {
+                                        bound.send(|setup| TrainingSetupOutput::Remove(setup.id));
+                                    },
+                                    set_margin_end: 10,
+                                    // Translators: tooltip text for exercise card button to delete the training
+                                    set_tooltip: &gettext("Delete Training"),
+                                },
+                                gtk::Button {
+                                    set_icon_name: icon_names::PLAY,
+                                    connect_clicked[bound] => move |_| {
+                                        bound.send(TrainingSetupOutput::Load);
+                                    },
+                                    // Translators: tooltip text for exercise card button to start the training timer
+                                    set_tooltip: &gettext("Start Training"),
                                 },
-                                set_margin_end: 10,
-                                // Translators: tooltip text for exercise card button to delete the training
-                                set_tooltip: &gettext("Delete Training"),
-                            },
-                            gtk::Button {
-                                set_icon_name: icon_names::PLAY,
-                                connect_clicked => TrainingSetupInput::Load,
-                                // Translators: tooltip text for exercise card button to start the training timer
-                                set_tooltip: &gettext("Start Training"),
                             },
                         },
                     },
                 },
-            },
+            }
         }
+        let widgets = TrainingRowWidgets {
+            bound,
+            pin_icon,
+            name_label,
+            duration_label,
+            sets_label,
+            exercise_label,
+            rest_label,
+            prepare_label,
+        };
+        (root, widgets)
     }
 
-    fn init_model(
-        init: Self::Init,
-        _index: &Self::Index,
-        _sender: relm4::FactorySender<Self>,
-    ) -> Self {
-        init
-    }
-
-    fn update(&mut self, message: Self::Input, sender: relm4::FactorySender<Self>) {
-        match message {
-            TrainingSetupInput::Edit(root) => {
-                let editor =
-                    TrainingEditor::builder().launch((TrainingEditorRole::Edit, self.clone()));
-                editor
-                    .widget()
-                    .present(Some(&root.toplevel_window().unwrap()));
-                let mut editor = editor.into_stream();
-                relm4::spawn_local(async move {
-                    if let Some(TrainingEditorOutput::Create(setup)) = editor.next().await.unwrap()
-                    {
-                        sender.input(TrainingSetupInput::Update(setup));
-                    }
-                });
-            }
-            TrainingSetupInput::Update(setup) => {
-                *self = setup;
-            }
-            TrainingSetupInput::Load => {
-                sender
-                    .output(TrainingSetupOutput::Load(self.clone()))
-                    .unwrap();
-            }
-        }
+    fn bind(&mut self, widgets: &mut TrainingRowWidgets, root: &mut gtk::Box) {
+        let setup = &self.setup;
+        root.update_property(&[gtk::accessible::Property::Label(&setup.name)]);
+        widgets.pin_icon.set_visible(setup.pinned);
+        widgets.name_label.set_label(&setup.name);
+        widgets
+            .duration_label
+            .set_label(&format_duration(&setup.total_duration()));
+        // The lengths which the timer runs, e.g. the fixed ones of Tabata
+        let intervals = setup.intervals();
+        widgets.sets_label.set_label(&intervals.sets.to_string());
+        widgets
+            .exercise_label
+            .set_label(&format_seconds(intervals.exercise_s));
+        widgets
+            .rest_label
+            .set_label(&format_seconds(intervals.rest_s));
+        widgets
+            .prepare_label
+            .set_label(&format_seconds(setup.prepare_s));
+        widgets.bound.0.replace(Some(self.clone()));
+    }
+
+    fn unbind(&mut self, widgets: &mut TrainingRowWidgets, _root: &mut gtk::Box) {
+        widgets.bound.0.replace(None);
     }
 }
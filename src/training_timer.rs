@@ -1,87 +1,578 @@
 mod audio_player;
+mod big_digits;
+mod event_bus;
+mod heart_rate;
+mod hooks;
+#[cfg(feature = "voice-recognition")]
+mod keyword_spotter;
+mod mini_window;
+mod period_ring;
+mod script;
 mod timer;
+mod voice_control;
 
-use gettextrs::gettext;
+use gettextrs::{gettext, ngettext};
 use relm4::{
-    adw,
+    adw::{self, prelude::*},
     binding::*,
-    gtk::{self, prelude::*},
+    gtk,
     prelude::*,
     RelmObjectExt, RelmWidgetExt,
 };
 use relm4_icons::icon_names;
 use timer::{TimerModel, TimerOutput};
 
-use crate::{settings::GlobalTrainingSetup, training_setup::TrainingSetup};
-use audio_player::{AudioPlayerInput, AudioPlayerModel};
+use crate::{
+    audio_backend::{AudioBackend, NullBackend, RodioBackend},
+    calories, crash,
+    error::AppError,
+    hiit_core::{IntervalTimer, Phase, TimerEvent, TimerPosition},
+    history::{
+        self, HeartRateSample, PeriodLog, RoundLog, SessionMark, SessionMarkKind, SessionRecord,
+    },
+    intensity::Intensity,
+    power_saving,
+    settings::{GlobalTrainingSetup, HookCommands},
+    summary_card,
+    training_setup::TrainingSetup,
+    warmup::{self, WarmupStep},
+};
+use audio_player::{AudioPlayerInput, AudioPlayerModel, AudioPlayerOutput};
+use event_bus::{EventBus, WorkoutEvent};
+use heart_rate::{HeartRateMonitor, Reading, RecoveryMeasurement};
+use mini_window::MiniWindow;
+use period_ring::{PeriodProgress, PeriodRing};
+use script::ScriptCommand;
+use std::time::Duration;
+use voice_control::VoiceControl;
 
 use self::audio_player::AudioPlayerModelInit;
 
-#[derive(PartialEq)]
-enum TrainingState {
-    Preparation,
-    Exercise,
-    Rest,
-}
-
 pub struct TrainingTimer {
     setup: TrainingSetup,
     global_setup: GlobalTrainingSetup,
-    state: TrainingState,
-    remaining_sets: usize,
-    remaining_s: usize,
+    core: IntervalTimer,
     running: bool,
     timer: Option<relm4::WorkerController<TimerModel>>,
+    /// The interval between the ticks of the running timer.
+    tick: Duration,
     audio_player: relm4::WorkerController<AudioPlayerModel>,
+    /// Hands the course of the workout to the hooks and other subsystems.
+    events: EventBus,
+    /// The steps of the preparation, looked up once so that it keeps its length.
+    warmup: Vec<WarmupStep>,
+    /// Directs the voice commands to this timer while its workout is in progress.
+    voice_control: Option<VoiceControl>,
+    /// Runs while the workout is in progress and its heart rate recovery is measured.
+    heart_rate_monitor: Option<HeartRateMonitor>,
+    /// The last reading of the heart rate monitor.
+    heart_rate: Option<Reading>,
+    /// The heart rate recovery which is measured after the end of the training.
+    recovery: Option<RecoveryMeasurement>,
+    /// Only the remaining time is shown, as large as the window allows.
+    big_digits: bool,
+    /// Laid out for phones in portrait orientation.
+    narrow: bool,
+    /// Shown for a group on a big screen.
+    tv_mode: bool,
+    mini_window: Option<MiniWindow>,
+    ring: PeriodRing,
+    /// Fades the countdown in when a new period begins.
+    period_animation: Option<adw::TimedAnimation>,
+    session: SessionRecord,
+    /// The readings of the heart rate monitor during the session, recorded with it.
+    heart_rate_samples: Vec<HeartRateSample>,
+    /// The time the timer ran in the current period, for the replay of the session.
+    period_s: usize,
+    session_recorded: bool,
+    /// The exertion or the note were entered after the session was recorded.
+    session_annotated: bool,
+    /// A cue could not be played, which was reported already.
+    audio_failed: bool,
+    /// Monotonic time when the timer was paused, in microseconds.
+    paused_since: Option<i64>,
+    /// Only the countdown changed since the last update, so the rest of the view is left as it is.
+    tick_only: bool,
+    /// The image of the round changed since the last update.
+    round_image_changed: bool,
+    /// The training was started, rather than only shown paused.
+    started: bool,
 }
 
 impl TrainingTimer {
     fn new(
         setup: TrainingSetup,
         global_setup: GlobalTrainingSetup,
-        output: rodio::OutputStreamHandle,
+        output: Option<rodio::OutputStreamHandle>,
         sender: &ComponentSender<TrainingTimer>,
     ) -> Self {
-        let beep_volume = global_setup.beep_volume.get();
+        let mut backend: Box<dyn AudioBackend> = match output {
+            Some(output) => Box::new(RodioBackend::new(output, global_setup.beep_volume.get())),
+            None => Box::new(NullBackend),
+        };
+        backend.select_device(&global_setup.audio_device.get());
+        let mut events = EventBus::default();
+        events.subscribe(|event| tracing::info!(?event, "Workout"));
+        events.subscribe(hooks::subscriber(
+            HookCommands::new_from_gsettings(),
+            setup.clone(),
+        ));
+        if let Some(script) = script::subscriber(&setup, sender.input_sender().clone()) {
+            events.subscribe(script);
+        }
         Self {
-            state: if setup.prepare_s > 0 {
-                TrainingState::Preparation
-            } else {
-                TrainingState::Exercise
-            },
+            warmup: setup.warmup.steps(),
+            big_digits: global_setup.big_digits.get(),
+            narrow: false,
+            tv_mode: false,
+            mini_window: None,
+            ring: PeriodRing::default(),
+            period_animation: None,
+            core: IntervalTimer::new(setup.intervals())
+                .with_transition_s(global_setup.transition_s.get() as usize),
             global_setup,
-            remaining_sets: setup.sets,
-            remaining_s: if setup.prepare_s > 0 {
-                setup.prepare_s
-            } else {
-                setup.exercise_s
-            },
             running: true,
-            timer: build_timer(sender),
+            timer: None,
+            tick: Duration::from_secs(1),
             setup,
             audio_player: AudioPlayerModel::builder()
-                .detach_worker(AudioPlayerModelInit {
-                    output_stream: output,
-                    volume: beep_volume,
-                })
-                .forward(sender.input_sender(), |_msg| TrainingTimerInput::Tick),
+                .detach_worker(AudioPlayerModelInit { backend })
+                .forward(sender.input_sender(), |output| match output {
+                    AudioPlayerOutput::Failed(err) => TrainingTimerInput::AudioFailed(err),
+                }),
+            events,
+            voice_control: None,
+            heart_rate_monitor: None,
+            heart_rate: None,
+            recovery: None,
+            session: SessionRecord::default(),
+            heart_rate_samples: Vec::new(),
+            period_s: 0,
+            session_recorded: false,
+            session_annotated: false,
+            audio_failed: false,
+            paused_since: None,
+            tick_only: false,
+            round_image_changed: false,
+            started: false,
         }
     }
 
-    fn reset(&mut self, sender: &ComponentSender<TrainingTimer>) {
-        self.state = if self.setup.prepare_s > 0 {
-            TrainingState::Preparation
+    fn start_session(&mut self) {
+        self.started = true;
+        self.session = SessionRecord {
+            training_id: self.setup.id.clone(),
+            training_name: self.setup.name.clone(),
+            started: crate::sync::unix_now(),
+            sets: self.setup.intervals().sets,
+            ..Default::default()
+        };
+        self.heart_rate_samples.clear();
+        self.period_s = 0;
+        self.session_recorded = false;
+        self.session_annotated = false;
+        self.paused_since = None;
+    }
+
+    fn pause_session(&mut self) {
+        if self.paused_since.is_none() {
+            self.paused_since = Some(gtk::glib::monotonic_time());
+        }
+    }
+
+    fn resume_session(&mut self) {
+        if let Some(paused_since) = self.paused_since.take() {
+            let paused_s = ((gtk::glib::monotonic_time() - paused_since) / 1_000_000) as usize;
+            self.session.pause_s += paused_s;
+            if paused_s > 0 {
+                self.mark_session(SessionMarkKind::Paused { pause_s: paused_s });
+            }
+        }
+    }
+
+    /// Records the session in the history, unless it was already recorded or has not really started.
+    fn end_session(&mut self, completed: bool) {
+        if self.session_recorded || self.session.duration_s == 0 {
+            return;
+        }
+        self.resume_session();
+        // The period which was left before its end
+        if self.period_s > 0 {
+            self.log_period(self.core.phase(), true);
+        }
+        self.session.completed = completed;
+        self.session.kcal = calories::estimate_kcal(
+            &self.session.periods,
+            self.setup.mode(),
+            self.global_setup.body_weight_kg.get(),
+        );
+        history::append_session(&self.session, &self.heart_rate_samples);
+        self.session_recorded = true;
+    }
+
+    /// Keeps the last reading of each second of the session.
+    fn record_heart_rate(&mut self, bpm: u32) {
+        let at_s = self.session.duration_s;
+        if let Some(last) = self
+            .heart_rate_samples
+            .last_mut()
+            .filter(|last| last.at_s == at_s)
+        {
+            last.bpm = bpm;
+        } else {
+            self.heart_rate_samples.push(HeartRateSample { at_s, bpm });
+        }
+    }
+
+    fn mark_session(&mut self, kind: SessionMarkKind) {
+        self.session.marks.push(SessionMark {
+            at_s: self.session.duration_s,
+            kind,
+        });
+    }
+
+    fn log_period(&mut self, phase: Phase, skipped: bool) {
+        self.session.periods.push(PeriodLog {
+            phase,
+            length_s: self.period_s,
+            skipped,
+            intensity: self.setup.intensity(phase),
+        });
+        self.period_s = 0;
+    }
+
+    /// Stores the exertion and the note with the recorded session.
+    fn save_annotation(&mut self) {
+        if self.session_recorded && self.session_annotated {
+            history::annotate_session(&self.session);
+            self.session_annotated = false;
+        }
+    }
+
+    /// Announces the new period to screen readers, e.g. "Rest, 20 seconds".
+    fn period_announcement(&self) -> String {
+        if self.core.remaining_sets() == 0 {
+            // Translators: Announced by screen readers when the training has come to the end
+            return gettext("Finished!");
+        }
+        if false {
+            // Translators: Announced by screen readers when a period begins. The first {} is the period, e.g. "Rest", the second its duration, e.g. "20 seconds".
+            gettext("{}, {}")
         } else {
-            TrainingState::Exercise
+            gettext!(
+                "{}, {}",
+                self.phase_title(),
+                format_seconds(self.core.remaining_s())
+            )
+        }
+    }
+
+    /// Tells of the new period with a notification, for modes which run while working in other windows.
+    fn notify_period(&self) {
+        let Some(app) = gtk::gio::Application::default() else {
+            return;
         };
-        self.remaining_sets = self.setup.sets;
-        self.remaining_s = if self.setup.prepare_s > 0 {
-            self.setup.prepare_s
+        let notification = gtk::gio::Notification::new(&self.phase_title());
+        let end = gtk::glib::DateTime::now_local()
+            .and_then(|now| now.add_seconds(self.core.remaining_s() as f64))
+            .and_then(|end| end.format("%R"));
+        if let Ok(end) = end {
+            notification.set_body(Some(&if false {
+                // Translators: The body of the notification of a new period, e.g. of a Pomodoro timer. {} is the time when it ends, e.g. "14:35".
+                gettext("Until {}")
+            } else {
+                gettext!("Until {}", end)
+            }));
+        }
+        app.send_notification(Some(PERIOD_NOTIFICATION_ID), &notification);
+    }
+
+    /// The name of the current phase in the mode of the training, e.g. "Inhale" rather than "Exercise",
+    /// or of the current step of the warmup.
+    fn phase_title(&self) -> String {
+        if self.core.is_getting_ready() {
+            // Translators: Shown on the timer page in the gap to get ready between a rest and the next exercise
+            return gettext("Get Ready");
+        }
+        match self.warmup_step().and_then(|index| self.warmup.get(index)) {
+            Some(step) => warmup::step_title(step),
+            None => self
+                .setup
+                .phase_title(self.core.phase(), self.core.remaining_sets()),
+        }
+    }
+
+    /// The index of the current step of the warmup, during the preparation.
+    fn warmup_step(&self) -> Option<usize> {
+        if self.core.phase() != Phase::Preparation {
+            return None;
+        }
+        warmup::step_at(&self.warmup, self.core.phase_s() - self.core.remaining_s())
+    }
+
+    /// The image of the current round, e.g. how to do the stretch.
+    fn round_image(&self) -> Option<gtk::gio::File> {
+        self.setup
+            .round_image(self.core.remaining_sets())
+            .map(gtk::gio::File::for_uri)
+    }
+
+    /// How hard the current period is meant to be.
+    fn intensity(&self) -> Option<Intensity> {
+        self.setup.intensity(self.core.phase())
+    }
+
+    fn period_output(&self) -> TrainingTimerOutput {
+        TrainingTimerOutput::Period(
+            state_css_class(self.core.phase()),
+            self.intensity().map(Intensity::css_class),
+        )
+    }
+
+    /// The current round, shown at the top of the timer on phones, e.g. "Round 3 of 8".
+    fn round_counter(&self) -> String {
+        let (round, rounds) = self.setup.round(self.core.remaining_sets());
+        if false {
+            // Translators: Shown on the timer page of phones. The first {} is the current round, the second the number of rounds.
+            gettext("Round {} of {}")
         } else {
-            self.setup.exercise_s
+            gettext!("Round {} of {}", round, rounds)
+        }
+    }
+
+    /// The station of each participant in the current round, one per line, e.g. "Alex: Station 2".
+    /// Everybody moves on to the next station every round.
+    fn station_rotation(&self) -> String {
+        let participants = self.global_setup.participants.get();
+        let participants: Vec<&str> = participants
+            .split(',')
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .collect();
+        let round = self.setup.intervals().sets - self.core.remaining_sets().max(1);
+        participants
+            .iter()
+            .enumerate()
+            .map(|(index, name)| {
+                let station = (index + round) % participants.len() + 1;
+                if false {
+                    // Translators: The station of a participant in TV mode, e.g. "Alex: Station 2"
+                    gettext("{}: Station {}")
+                } else {
+                    gettext!("{}: Station {}", name, station)
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// Previews the period after the current one, e.g. "Next: Rest (15 s)".
+    fn up_next(&self) -> String {
+        if let Some(step) = self
+            .warmup_step()
+            .and_then(|index| self.warmup.get(index + 1))
+        {
+            return format_up_next(&warmup::step_title(step), step.duration_s);
+        }
+        let intervals = self.setup.intervals();
+        // The sets which are left once the next period began
+        let (state, duration_s, remaining_sets) = match self.core.phase() {
+            Phase::Exercise if self.core.remaining_sets() <= 1 => {
+                // Translators: Shown on the timer page during the last exercise of the training
+                return gettext("Next: Finish");
+            }
+            Phase::Exercise => (
+                Phase::Rest,
+                intervals.rest_s_after(intervals.sets - self.core.remaining_sets() + 1),
+                self.core.remaining_sets() - 1,
+            ),
+            Phase::Preparation | Phase::Rest => (
+                Phase::Exercise,
+                intervals.exercise_s,
+                self.core.remaining_sets(),
+            ),
         };
+        format_up_next(&self.setup.phase_title(state, remaining_sets), duration_s)
+    }
+
+    /// The progress of the training in a single line, e.g. "00:45 · Rest — Morning HIIT",
+    /// or `None` when it has come to the end.
+    fn progress_title(&self) -> Option<String> {
+        if self.core.remaining_sets() == 0 {
+            None
+        } else if false {
+            // Translators: The title of the window during a training, e.g. "00:45 · Rest — Morning HIIT"
+            Some(gettext("{} · {} — {}"))
+        } else {
+            Some(gettext!(
+                "{} · {} — {}",
+                big_digits_text(self.core.remaining_s()),
+                self.phase_title(),
+                self.setup.name
+            ))
+        }
+    }
+
+    /// Shows the progress in the title of the window, so that it is visible in the task switcher.
+    fn update_window_title(&self, root: &adw::Clamp) {
+        if let Some(window) = root.root().and_downcast::<gtk::Window>() {
+            window.set_title(self.progress_title().as_deref());
+        }
+    }
+
+    fn publish_phase(&self) {
+        self.events.publish(WorkoutEvent::PhaseChanged {
+            phase: self.core.phase(),
+            remaining_sets: self.core.remaining_sets(),
+        });
+    }
+
+    fn publish_start(&self) {
+        self.events.publish(WorkoutEvent::WorkoutStarted {
+            remaining_sets: self.core.remaining_sets(),
+        });
+        self.publish_phase();
+    }
+
+    fn update_progress(&self) {
+        self.ring.set_progress(PeriodProgress {
+            remaining: self.core.remaining(),
+            period_s: self.core.phase_s(),
+        });
+    }
+
+    /// How often the timer ticks: as chosen in the preferences, or once per second while power saving.
+    fn tick_interval(&self) -> Duration {
+        if power_saving::is_enabled(&self.global_setup.power_saving) {
+            Duration::from_secs(1)
+        } else {
+            Duration::from_millis(self.global_setup.tick_interval_ms.get().into())
+        }
+    }
+
+    fn start_timer(&mut self, sender: &ComponentSender<TrainingTimer>) {
+        self.tick = self.tick_interval();
+        self.timer = build_timer(sender, self.tick);
+    }
+
+    /// Keeps where the started training stands, for continuing it after a crash.
+    fn record_position(&self) {
+        crash::record_session(
+            (self.started && !self.core.is_finished()).then(|| (&self.setup, self.core.position())),
+        );
+    }
+
+    /// Sounds, logs and hooks into what happened on the timer.
+    /// Returns if the countdown before the end of a period ticked.
+    fn handle_events(&mut self, events: Vec<TimerEvent>) -> bool {
+        let mut countdown_tick = false;
+        if !events.is_empty() {
+            self.record_position();
+        }
+        for event in events {
+            if let TimerEvent::PhaseEnded { phase, skipped, .. } = event {
+                self.log_period(phase, skipped);
+            }
+            match event {
+                TimerEvent::Tick { remaining_s } => {
+                    tracing::debug!(remaining_s, "Tick");
+                    self.session.duration_s += 1;
+                    self.period_s += 1;
+                    // The countdown ends with the rest, before the gap to get ready
+                    let get_ready_s = self.core.get_ready_s();
+                    if remaining_s > 0 && remaining_s <= get_ready_s {
+                        self.audio_player.emit(AudioPlayerInput::GetReady);
+                        countdown_tick = true;
+                    } else if remaining_s > get_ready_s
+                        && remaining_s - get_ready_s <= self.global_setup.countdown_s.get() as usize
+                        && !self.setup.mode().is_calm()
+                    {
+                        self.audio_player.emit(AudioPlayerInput::Ping);
+                        countdown_tick = true;
+                    }
+                }
+                // A skipped period is logged with the time which was actually spent in it
+                TimerEvent::PhaseEnded {
+                    phase: Phase::Exercise,
+                    elapsed_s,
+                    skipped,
+                } => {
+                    self.session.rounds.push(RoundLog {
+                        exercise_s: elapsed_s,
+                        rest_s: 0,
+                        skipped,
+                    });
+                    self.events.publish(WorkoutEvent::RoundCompleted);
+                }
+                TimerEvent::PhaseEnded {
+                    phase: Phase::Rest,
+                    elapsed_s,
+                    ..
+                } => {
+                    if let Some(round) = self.session.rounds.last_mut() {
+                        round.rest_s = elapsed_s;
+                    }
+                }
+                TimerEvent::PhaseEnded {
+                    phase: Phase::Preparation,
+                    ..
+                } => {}
+                TimerEvent::PhaseStarted { phase, .. } => {
+                    self.audio_player.emit(if self.setup.mode().is_calm() {
+                        AudioPlayerInput::Ping
+                    } else if phase == Phase::Rest {
+                        AudioPlayerInput::NextRest
+                    } else {
+                        AudioPlayerInput::NextExercise
+                    });
+                    if self.setup.mode().notifies_periods() {
+                        self.notify_period();
+                    }
+                    self.publish_phase();
+                }
+                TimerEvent::Finished => {
+                    self.timer = None;
+                    self.running = false;
+                    self.audio_player.emit(if self.setup.mode().is_calm() {
+                        AudioPlayerInput::Ping
+                    } else {
+                        AudioPlayerInput::Finished
+                    });
+                    self.events.publish(WorkoutEvent::Finished);
+                    self.end_session(true);
+                    self.recovery = self.heart_rate.and_then(RecoveryMeasurement::start);
+                }
+            }
+        }
+        countdown_tick
+    }
+
+    /// Runs the listener of the voice commands while the workout is in progress, and the reader
+    /// of the heart rate monitor until the recovery after the workout is measured too. Neither
+    /// runs for a training which is only shown.
+    fn update_listeners(&mut self, sender: &ComponentSender<TrainingTimer>) {
+        let in_progress = self.started && !self.core.is_finished();
+        if in_progress != self.voice_control.is_some() {
+            self.voice_control =
+                in_progress.then(|| voice_control::listen(sender.input_sender().clone()));
+        }
+        let measuring = in_progress || self.recovery.is_some();
+        if measuring != self.heart_rate_monitor.is_some() {
+            self.heart_rate = None;
+            self.heart_rate_monitor =
+                measuring.then(|| heart_rate::start(sender.input_sender().clone()));
+        }
+    }
+
+    fn reset(&mut self, sender: &ComponentSender<TrainingTimer>) {
+        self.end_session(false);
+        self.save_annotation();
+        self.recovery = None;
+        self.core.reset();
         self.running = true;
-        self.timer = build_timer(sender);
+        self.start_timer(sender);
+        self.start_session();
+        self.publish_start();
     }
 }
 
@@ -90,21 +581,169 @@ pub enum TrainingTimerInput {
     Tick,
     StartStop,
     Pause,
+    /// Continues the paused timer, and does nothing while it runs.
+    Resume,
     Reset,
+    Skip,
+    Rewind,
+    SetBigDigits(bool),
+    SetNarrow(bool),
+    SetTvMode(bool),
+    ToggleMiniWindow,
+    SetExertion(u8),
+    SetNote(String),
+    /// A reading of the heart rate monitor, in beats per minute.
+    HeartRate(u32),
+    /// The heart rate recovery may have been measured for too long without a reading.
+    RecoveryTimeout,
+    /// Makes the beeps quieter, e.g. while the window is in the background.
+    SetDucked(bool),
+    Script(ScriptCommand),
+    /// Shows the image of the finished session for sharing it.
+    ShareSummary,
+    /// The summary of the finished training was dismissed.
+    Done,
+    AudioFailed(AppError),
+}
+
+#[derive(Debug)]
+pub enum TrainingTimerOutput {
+    /// The CSS classes of the current period and its intensity, which tint the whole timer page.
+    Period(&'static str, Option<&'static str>),
+    /// The progress of the training, for following it while the timer page is not shown.
+    Progress {
+        title: Option<String>,
+        running: bool,
+    },
+    /// The training with the id is finished and the summary was dismissed, the timer is not needed anymore.
+    Done(String),
+    Error(AppError),
 }
 
 fn build_timer(
     sender: &ComponentSender<TrainingTimer>,
+    tick: Duration,
 ) -> Option<relm4::WorkerController<TimerModel>> {
     Some(
         TimerModel::builder()
-            .detach_worker(())
+            .detach_worker(tick)
             .forward(sender.input_sender(), |timer_output| match timer_output {
                 TimerOutput::Tick => TrainingTimerInput::Tick,
             }),
     )
 }
 
+/// The preview of the next period or warmup step, e.g. "Next: Rest (15 s)".
+fn format_up_next(title: &str, duration_s: usize) -> String {
+    if false {
+        // Translators: Shown on the timer page to preview the next period. The first {} is the period, e.g. "Rest", the second its duration in seconds.
+        gettext("Next: {} ({} s)")
+    } else {
+        gettext!("Next: {} ({} s)", title, duration_s)
+    }
+}
+
+pub fn state_title(state: Phase) -> String {
+    match state {
+        // Translators: Shown on the timer page during preparation
+        Phase::Preparation => gettext("Preparation"),
+        // Translators: Shown on the timer page during exercise
+        Phase::Exercise => gettext("Exercise"),
+        // Translators: Shown on the timer page during rest
+        Phase::Rest => gettext("Rest"),
+    }
+}
+
+/// The estimated burned energy, e.g. "About 120 kcal".
+pub fn format_kcal(kcal: u32) -> String {
+    if false {
+        // Translators: The estimated burned calories of a session, e.g. "About 120 kcal"
+        gettext("About {} kcal")
+    } else {
+        gettext!("About {} kcal", kcal)
+    }
+}
+
+/// The drop of the heart rate in the minute after the training, e.g. "−25 bpm in a minute".
+pub fn format_hr_recovery(bpm: u32) -> String {
+    if false {
+        // Translators: The heart rate recovery of a session, e.g. "−25 bpm in a minute". bpm are beats per minute.
+        gettext("−{} bpm in a minute")
+    } else {
+        gettext!("−{} bpm in a minute", bpm)
+    }
+}
+
+/// The CSS classes of the periods, for removing them from the timer page.
+pub const PERIOD_CSS_CLASSES: [&str; 3] = ["timer-warmup", "timer-exercise", "timer-rest"];
+
+fn state_css_class(state: Phase) -> &'static str {
+    match state {
+        Phase::Preparation => "timer-warmup",
+        Phase::Exercise => "timer-exercise",
+        Phase::Rest => "timer-rest",
+    }
+}
+
+fn format_seconds(seconds: usize) -> String {
+    if false {
+        // Translators: A duration read out by screen readers, e.g. "20 seconds"
+        ngettext("{} second", "{} seconds", seconds as u32)
+    } else {
+        ngettext!("{} second", "{} seconds", seconds as u32, seconds)
+    }
+}
+
+/// The remaining time as it is read out by screen readers, instead of the separate digits.
+fn remaining_accessible_label(remaining_s: usize) -> String {
+    if remaining_s == 0 {
+        // Translators: Shown in the timer page when the training has come to the end
+        gettext("Finished!")
+    } else if false {
+        // Translators: The remaining time of the period read out by screen readers, e.g. "20 seconds remaining"
+        gettext("{} remaining")
+    } else {
+        gettext!("{} remaining", format_seconds(remaining_s))
+    }
+}
+
+/// The minimum horizontal speed of a swipe which skips or starts over the period, in pixels per second.
+const SWIPE_VELOCITY: f64 = 500.0;
+const ROUND_IMAGE_HEIGHT_PX: i32 = 180;
+/// Replaces the notification of the previous period.
+const PERIOD_NOTIFICATION_ID: &str = "period";
+
+/// How long the screen flashes instead of a beep.
+const FLASH_DURATION: std::time::Duration = std::time::Duration::from_millis(150);
+
+/// Flashes the timer briefly, as a visual substitute for the beeps.
+fn flash(root: &adw::Clamp) {
+    root.add_css_class("flash");
+    let root = root.clone();
+    gtk::glib::timeout_add_local_once(FLASH_DURATION, move || root.remove_css_class("flash"));
+}
+
+/// The size of the ring around the countdown, smaller on phones.
+fn ring_size_px(narrow: bool) -> i32 {
+    if narrow {
+        220
+    } else {
+        280
+    }
+}
+
+/// The default maximum width of the timer, when it is not in big digit mode.
+const DEFAULT_WIDTH_PX: i32 = 600;
+
+pub fn big_digits_text(remaining_s: usize) -> String {
+    if remaining_s == 0 {
+        // Translators: Shown in the timer page when the training has come to the end
+        gettext("Finished!")
+    } else {
+        format!("{:02}:{:02}", remaining_s / 60, remaining_s % 60)
+    }
+}
+
 fn remaining_str_mins(remaining_s: usize) -> String {
     if remaining_s == 0 {
         String::from("")
@@ -141,130 +780,374 @@ fn width_chars(remaining_s: usize, default: i32) -> i32 {
 pub struct TrainingTimerInit {
     pub setup: TrainingSetup,
     pub global_setup: GlobalTrainingSetup,
-    pub output_handle: rodio::OutputStreamHandle,
+    pub output_handle: Option<rodio::OutputStreamHandle>,
+    pub narrow: bool,
+    pub tv_mode: bool,
+    /// Starts the training right away, rather than showing it paused.
+    pub start: bool,
+    /// Where the training continues, e.g. after a crash, rather than from the beginning.
+    pub position: Option<TimerPosition>,
 }
 
 #[relm4::component(pub)]
 impl Component for TrainingTimer {
     type Init = TrainingTimerInit;
     type Input = TrainingTimerInput;
-    type Output = ();
+    type Output = TrainingTimerOutput;
     type CommandOutput = ();
 
     view! {
         #[name = "root_clamp"]
         adw::Clamp {
             set_orientation: gtk::Orientation::Horizontal,
+            #[track(!model.tick_only)]
+            set_maximum_size: if model.big_digits || model.tv_mode { i32::MAX } else { DEFAULT_WIDTH_PX },
+            #[track(!model.tick_only)]
+            set_class_active: ("big-digits", model.big_digits),
+            #[track(!model.tick_only)]
+            set_class_active: ("tv-mode", model.tv_mode),
+            #[track(!model.tick_only)]
+            set_class_active: ("power-saving", power_saving::is_enabled(&model.global_setup.power_saving)),
+            // Swiping towards the start skips the period, towards the end it starts the period over
+            add_controller = gtk::GestureSwipe {
+                set_touch_only: true,
+                connect_swipe[sender] => move |gesture, velocity_x, velocity_y| {
+                    if velocity_x.abs() < SWIPE_VELOCITY || velocity_x.abs() < velocity_y.abs() {
+                        return;
+                    }
+                    let is_rtl = gesture
+                        .widget()
+                        .is_some_and(|widget| widget.direction() == gtk::TextDirection::Rtl);
+                    if (velocity_x < 0.0) != is_rtl {
+                        sender.input(TrainingTimerInput::Skip);
+                    } else {
+                        sender.input(TrainingTimerInput::Rewind);
+                    }
+                },
+            },
             gtk::Box {
                 set_orientation: gtk::Orientation::Vertical,
-                set_valign: gtk::Align::Center,
-                gtk::Box {
-                    add_css_class: "timer",
-                    add_css_class: "card",
-                    #[watch]
-                    set_class_active: ("timer-warmup", model.state == TrainingState::Preparation),
-                    #[watch]
-                    set_class_active: ("timer-exercise", model.state == TrainingState::Exercise),
-                    #[watch]
-                    set_class_active: ("timer-rest", model.state == TrainingState::Rest),
-                    set_spacing: 5,
-                    set_orientation: gtk::Orientation::Vertical,
-                    set_valign: gtk::Align::Center,
-                    set_margin_all: 20,
+                #[track(!model.tick_only)]
+                set_valign: if model.big_digits || model.narrow { gtk::Align::Fill } else { gtk::Align::Center },
+                gtk::Label {
+                    add_css_class: "timer-title",
+                    set_margin_top: 12,
+                    #[track(!model.tick_only)]
+                    set_visible: model.big_digits,
+                    #[track(!model.tick_only)]
+                    set_label: &model.phase_title(),
+                },
+                gtk::DrawingArea {
                     set_vexpand: true,
-                    gtk::Label {
-                        add_css_class: "timer-title",
-                        #[watch]
-                        set_label: &match model.state {
-                            // Translators: Shown on the timer page during preparation
-                            TrainingState::Preparation => gettext("Preparation"),
-                            // Translators: Shown on the timer page during exercise
-                            TrainingState::Exercise => gettext("Exercise"),
-                            // Translators: Shown on the timer page during rest
-                            TrainingState::Rest => gettext("Rest"),
+                    set_hexpand: true,
+                    #[track(!model.tick_only)]
+                    set_visible: model.big_digits,
+                    add_controller = gtk::GestureClick {
+                        connect_released[sender] => move |_, _, _, _| {
+                            sender.input(TrainingTimerInput::StartStop);
                         },
                     },
+                    #[watch]
+                    set_draw_func: big_digits::draw_big_digits(big_digits_text(model.core.remaining_s())),
+                    #[watch]
+                    update_property: &[gtk::accessible::Property::Label(&remaining_accessible_label(model.core.remaining_s()))],
+                },
+                // Large text scrolls instead of being clipped
+                gtk::ScrolledWindow {
+                    #[track(!model.tick_only)]
+                    set_visible: !model.big_digits,
+                    set_hscrollbar_policy: gtk::PolicyType::Never,
+                    set_propagate_natural_height: true,
+                    set_vexpand: true,
                     gtk::Box {
-                        add_css_class: "timer-label",
-                        set_orientation: gtk::Orientation::Horizontal,
-                        set_halign: gtk::Align::Center,
-                        set_direction: gtk::TextDirection::Ltr,
+                        add_css_class: "timer",
+                        set_spacing: 5,
+                        set_orientation: gtk::Orientation::Vertical,
+                        #[track(!model.tick_only)]
+                        set_valign: if model.narrow { gtk::Align::Fill } else { gtk::Align::Center },
+                        #[track(!model.tick_only)]
+                        set_margin_all: if model.narrow { 8 } else { 20 },
+                        set_vexpand: true,
                         gtk::Label {
-                            #[watch]
-                            set_width_chars: width_chars(model.remaining_s, 2),
-                            set_xalign: 1.0,
-                            #[watch]
-                            set_label: &remaining_str_mins(model.remaining_s),
+                            add_css_class: "tv-training-name",
+                            #[track(!model.tick_only)]
+                            set_visible: model.tv_mode,
+                            set_label: &model.setup.name,
                         },
                         gtk::Label {
-                            #[watch]
-                            set_width_chars: width_chars(model.remaining_s, 1),
-                            #[watch]
-                            set_label: &remaining_str_colon(model.remaining_s),
+                            add_css_class: "title-2",
+                            #[track(!model.tick_only)]
+                            set_visible: model.narrow || model.tv_mode,
+                            #[track(!model.tick_only)]
+                            set_label: &model.round_counter(),
                         },
                         gtk::Label {
-                            #[watch]
-                            set_width_chars: width_chars(model.remaining_s, 2),
-                            set_xalign: 0.0,
-                            #[watch]
-                            set_label: &remaining_str_secs(model.remaining_s),
+                            add_css_class: "timer-title",
+                            #[track(!model.tick_only)]
+                            set_label: &model.phase_title(),
                         },
-                    },
-                    gtk::Box {
-                        set_orientation: gtk::Orientation::Horizontal,
-                        set_halign: gtk::Align::Center,
-                        set_spacing: 12,
-                        gtk::Button {
-                            set_css_classes: &["circular", "large-button"],
-                            set_icon_name: icon_names::REFRESH,
-                            set_valign: gtk::Align::Center,
-                            connect_clicked => TrainingTimerInput::Reset,
-                            #[watch]
-                            set_class_active: ("suggested-action", model.remaining_s == 0),
-                            // Translators: tooltip text for the reset button
-                            set_tooltip: &gettext("Restart Training"),
+                        gtk::Label {
+                            add_css_class: "title-4",
+                            #[track(!model.tick_only)]
+                            set_visible: model.intensity().is_some(),
+                            #[track(!model.tick_only)]
+                            set_label: &model.intensity().map(Intensity::title).unwrap_or_default(),
+                        },
+                        gtk::Picture {
+                            set_height_request: ROUND_IMAGE_HEIGHT_PX,
+                            set_content_fit: gtk::ContentFit::Contain,
+                            #[track(model.round_image_changed)]
+                            set_visible: model.round_image().is_some(),
+                            #[track(model.round_image_changed)]
+                            set_file: model.round_image().as_ref(),
                         },
-                        gtk::Button {
-                            set_css_classes: &["circular", "huge-button"],
-                            #[watch]
-                            set_sensitive: model.remaining_s != 0,
-                            connect_clicked => TrainingTimerInput::StartStop,
-                            gtk::Image {
+                        #[name = "ring_overlay"]
+                        gtk::Overlay {
+                            set_halign: gtk::Align::Center,
+                            // Tapping the countdown pauses or resumes the training
+                            add_controller = gtk::GestureClick {
+                                connect_released[sender] => move |_, _, _, _| {
+                                    sender.input(TrainingTimerInput::StartStop);
+                                },
+                            },
+                            #[wrap(Some)]
+                            #[name = "ring"]
+                            set_child = &gtk::DrawingArea {
+                                add_css_class: "period-ring",
+                                // The digits are larger than the ring in TV mode
+                                #[track(!model.tick_only)]
+                                set_visible: !model.tv_mode,
+                                #[track(!model.tick_only)]
+                                set_content_width: ring_size_px(model.narrow),
+                                #[track(!model.tick_only)]
+                                set_content_height: ring_size_px(model.narrow),
+                            },
+                            #[name = "countdown"]
+                            add_overlay = &gtk::Box {
+                                add_css_class: "timer-label",
                                 #[watch]
-                                set_icon_name: Some(if model.running { icon_names::PAUSE } else { icon_names::PLAY }),
+                                update_property: &[gtk::accessible::Property::Label(&remaining_accessible_label(model.core.remaining_s()))],
+                                set_orientation: gtk::Orientation::Horizontal,
+                                set_halign: gtk::Align::Center,
+                                set_valign: gtk::Align::Center,
+                                set_direction: gtk::TextDirection::Ltr,
+                                gtk::Label {
+                                    #[watch]
+                                    set_width_chars: width_chars(model.core.remaining_s(), 2),
+                                    set_xalign: 1.0,
+                                    #[watch]
+                                    set_label: &remaining_str_mins(model.core.remaining_s()),
+                                },
+                                gtk::Label {
+                                    #[watch]
+                                    set_width_chars: width_chars(model.core.remaining_s(), 1),
+                                    #[watch]
+                                    set_label: &remaining_str_colon(model.core.remaining_s()),
+                                },
+                                gtk::Label {
+                                    #[watch]
+                                    set_width_chars: width_chars(model.core.remaining_s(), 2),
+                                    set_xalign: 0.0,
+                                    #[watch]
+                                    set_label: &remaining_str_secs(model.core.remaining_s()),
+                                },
                             },
-                            #[watch]
-                            // Translators: tooltip text for the pause/resume button
-                            set_tooltip: &if model.running { gettext("Pause Training") } else { gettext("Resume Training") },
-
                         },
-                        #[name = "volume_button"]
-                        gtk::ScaleButton {
-                            set_valign: gtk::Align::Center,
-                            set_icons: &["audio-volume-muted-symbolic", "audio-volume-high-symbolic", "audio-volume-medium-symbolic"],
-                            set_adjustment = &gtk::Adjustment {
-                                set_lower: 0f64,
-                                set_upper: 1f64,
-                                add_binding: (&model.global_setup.beep_volume, "value"),
-                                connect_value_changed[audio_sender] => move |adj| {
-                                    audio_sender.emit(AudioPlayerInput::SetVolume(adj.value()))
+                        gtk::Label {
+                            add_css_class: "title-4",
+                            add_css_class: "up-next",
+                            #[track(!model.tick_only)]
+                            set_visible: model.core.remaining_sets() > 0,
+                            #[track(!model.tick_only)]
+                            set_label: &model.up_next(),
+                        },
+                        gtk::Box {
+                            set_orientation: gtk::Orientation::Horizontal,
+                            set_halign: gtk::Align::Center,
+                            set_spacing: 12,
+                            // On phones, the controls are in the action bar at the bottom
+                            #[track(!model.tick_only)]
+                            set_visible: !model.narrow,
+                            gtk::Button {
+                                set_css_classes: &["circular", "large-button"],
+                                set_icon_name: icon_names::REFRESH,
+                                set_valign: gtk::Align::Center,
+                                connect_clicked => TrainingTimerInput::Reset,
+                                #[track(!model.tick_only)]
+                                set_class_active: ("suggested-action", model.core.remaining_s() == 0),
+                                // Translators: tooltip text for the reset button
+                                set_tooltip: &gettext("Restart Training"),
+                                update_property: &[gtk::accessible::Property::Label(&gettext("Restart Training"))],
+                            },
+                            gtk::Button {
+                                set_css_classes: &["circular", "huge-button"],
+                                #[track(!model.tick_only)]
+                                set_sensitive: model.core.remaining_s() != 0,
+                                connect_clicked => TrainingTimerInput::StartStop,
+                                gtk::Image {
+                                    #[track(!model.tick_only)]
+                                    set_icon_name: Some(if model.running { icon_names::PAUSE } else { icon_names::PLAY }),
                                 },
+                                #[track(!model.tick_only)]
+                                // Translators: tooltip text for the pause/resume button
+                                set_tooltip: &if model.running { gettext("Pause Training") } else { gettext("Resume Training") },
+                                #[track(!model.tick_only)]
+                                update_property: &[gtk::accessible::Property::Label(&if model.running { gettext("Pause Training") } else { gettext("Resume Training") })],
+
                             },
-                            // Translators: tooltip text for the volume button
-                            set_tooltip: &gettext("Set Volume"),
+                            #[name = "volume_button"]
+                            gtk::ScaleButton {
+                                set_valign: gtk::Align::Center,
+                                set_icons: &["audio-volume-muted-symbolic", "audio-volume-high-symbolic", "audio-volume-medium-symbolic"],
+                                set_adjustment = &gtk::Adjustment {
+                                    set_lower: 0f64,
+                                    set_upper: 1f64,
+                                    add_binding: (&model.global_setup.beep_volume, "value"),
+                                    connect_value_changed[audio_sender] => move |adj| {
+                                        audio_sender.emit(AudioPlayerInput::SetVolume(adj.value()))
+                                    },
+                                },
+                                // Translators: tooltip text for the volume button
+                                set_tooltip: &gettext("Set Volume"),
+                            }
                         }
-                    }
+                    },
                 },
                 gtk::Label {
-                    #[watch]
+                    add_css_class: "stations",
+                    set_justify: gtk::Justification::Center,
+                    set_margin_bottom: 12,
+                    #[track(!model.tick_only)]
+                    set_visible: model.tv_mode && !model.big_digits && model.core.remaining_sets() > 0 && !model.station_rotation().is_empty(),
+                    #[track(!model.tick_only)]
+                    set_label: &model.station_rotation(),
+                },
+                gtk::Label {
+                    #[track(!model.tick_only)]
+                    set_visible: !model.big_digits && !model.narrow && !model.tv_mode,
+                    #[track(!model.tick_only)]
                     set_label: &if false {
                         // Translators: Label showing the number of remaining sets on the timer page
                         gettext("Remaining Sets: {}")
                     } else {
-                        gettext!("Remaining Sets: {}", model.remaining_sets)
+                        gettext!("Remaining Sets: {}", model.setup.remaining_rounds(model.core.remaining_sets()))
                     },
                     set_margin_bottom: 12,
                 },
+                gtk::ListBox {
+                    add_css_class: "boxed-list",
+                    set_selection_mode: gtk::SelectionMode::None,
+                    set_margin_start: 20,
+                    set_margin_end: 20,
+                    set_margin_bottom: 20,
+                    #[track(!model.tick_only)]
+                    set_visible: model.session_recorded && model.session.completed && !model.big_digits,
+                    adw::ActionRow {
+                        // Translators: The title of the row with the estimated burned calories after the training
+                        set_title: &gettext("Calories"),
+                        #[track(!model.tick_only)]
+                        set_visible: model.session.kcal.is_some(),
+                        #[track(!model.tick_only)]
+                        set_subtitle: &model.session.kcal.map(format_kcal).unwrap_or_default(),
+                    },
+                    adw::ActionRow {
+                        // Translators: The title of the row with the drop of the heart rate in the minute after the training
+                        set_title: &gettext("Heart Rate Recovery"),
+                        #[track(!model.tick_only)]
+                        set_visible: model.recovery.is_some() || model.session.hr_recovery_bpm.is_some(),
+                        #[track(!model.tick_only)]
+                        set_subtitle: &match model.session.hr_recovery_bpm {
+                            Some(bpm) => format_hr_recovery(bpm),
+                            // Translators: The subtitle of the row of the heart rate recovery while it is measured after the training
+                            None => gettext("Measuring for a minute, keep the monitor on"),
+                        },
+                    },
+                    adw::SpinRow {
+                        // Translators: The title of the row to rate the perceived exertion after the training
+                        set_title: &gettext("Exertion"),
+                        // Translators: The subtitle of the row to rate the perceived exertion after the training
+                        set_subtitle: &gettext("How hard was it, from 1 to 10"),
+                        set_adjustment: Some(&gtk::Adjustment::new(0.0, 0.0, 10.0, 1.0, 1.0, 0.0)),
+                        #[track(!model.session_recorded)]
+                        #[block_signal(exertion_handler)]
+                        set_value: 0.0,
+                        connect_value_notify[sender] => move |row| {
+                            sender.input(TrainingTimerInput::SetExertion(row.value() as u8));
+                        } @exertion_handler,
+                    },
+                    adw::EntryRow {
+                        // Translators: The title of the field for a note about the finished training
+                        set_title: &gettext("Note"),
+                        #[track(!model.session_recorded)]
+                        #[block_signal(note_handler)]
+                        set_text: "",
+                        connect_changed[sender] => move |row| {
+                            sender.input(TrainingTimerInput::SetNote(row.text().into()));
+                        } @note_handler,
+                    },
+                },
+                gtk::Box {
+                    set_orientation: gtk::Orientation::Horizontal,
+                    set_halign: gtk::Align::Center,
+                    set_spacing: 12,
+                    set_margin_bottom: 20,
+                    #[track(!model.tick_only)]
+                    set_visible: model.session_recorded && model.session.completed && !model.big_digits,
+                    gtk::Button {
+                        add_css_class: "pill",
+                        set_use_underline: true,
+                        // Translators: The label of the button which shows an image of the finished training for posting it
+                        set_label: &gettext("_Share Summary"),
+                        connect_clicked => TrainingTimerInput::ShareSummary,
+                    },
+                    gtk::Button {
+                        set_css_classes: &["pill", "suggested-action"],
+                        set_use_underline: true,
+                        // Translators: The label of the button which dismisses the summary of the finished training and returns to the training list
+                        set_label: &gettext("_Done"),
+                        connect_clicked => TrainingTimerInput::Done,
+                    },
+                },
+                // Within reach of the thumbs on phones
+                gtk::ActionBar {
+                    #[track(!model.tick_only)]
+                    set_visible: model.narrow && !model.big_digits,
+                    pack_start = &gtk::Button {
+                        set_css_classes: &["circular", "large-button"],
+                        set_icon_name: icon_names::REFRESH,
+                        set_valign: gtk::Align::Center,
+                        connect_clicked => TrainingTimerInput::Reset,
+                        #[track(!model.tick_only)]
+                        set_class_active: ("suggested-action", model.core.remaining_s() == 0),
+                        // Translators: Tooltip of the button which starts the training from the beginning
+                        set_tooltip: &gettext("Restart Training"),
+                        update_property: &[gtk::accessible::Property::Label(&gettext("Restart Training"))],
+                    },
+                    #[wrap(Some)]
+                    set_center_widget = &gtk::Button {
+                        set_css_classes: &["circular", "huge-button"],
+                        #[track(!model.tick_only)]
+                        set_sensitive: model.core.remaining_s() != 0,
+                        connect_clicked => TrainingTimerInput::StartStop,
+                        #[track(!model.tick_only)]
+                        set_icon_name: if model.running { icon_names::PAUSE } else { icon_names::PLAY },
+                        #[track(!model.tick_only)]
+                        set_tooltip: &if model.running { gettext("Pause Training") } else { gettext("Resume Training") },
+                        #[track(!model.tick_only)]
+                        update_property: &[gtk::accessible::Property::Label(&if model.running { gettext("Pause Training") } else { gettext("Resume Training") })],
+                    },
+                    pack_end = &gtk::Button {
+                        set_css_classes: &["circular", "large-button"],
+                        set_icon_name: "media-skip-forward-symbolic",
+                        set_valign: gtk::Align::Center,
+                        #[track(!model.tick_only)]
+                        set_sensitive: model.core.remaining_sets() > 0,
+                        connect_clicked => TrainingTimerInput::Skip,
+                        // Translators: tooltip text for the button which skips to the next period
+                        set_tooltip: &gettext("Skip Period"),
+                        update_property: &[gtk::accessible::Property::Label(&gettext("Skip Period"))],
+                    },
+                },
             },
         }
     }
@@ -274,15 +1157,54 @@ impl Component for TrainingTimer {
         root: Self::Root,
         sender: relm4::ComponentSender<Self>,
     ) -> ComponentParts<Self> {
-        let model = TrainingTimer::new(init.setup, init.global_setup, init.output_handle, &sender);
+        let mut model =
+            TrainingTimer::new(init.setup, init.global_setup, init.output_handle, &sender);
+        if let Some(position) = init.position {
+            model.core.restore(position);
+        }
+        model.narrow = init.narrow;
+        model.tv_mode = init.tv_mode;
         let audio_sender = model.audio_player.sender();
         let widgets = view_output!();
+        model.ring.attach(&widgets.ring);
+        // Skipped to the end by libadwaita when animations are disabled in the system settings
+        model.period_animation = Some(
+            adw::TimedAnimation::builder()
+                .widget(&widgets.ring_overlay)
+                .value_from(0.2)
+                .value_to(1.0)
+                .duration(400)
+                .easing(adw::Easing::EaseOutCubic)
+                .target(&adw::PropertyAnimationTarget::new(
+                    &widgets.ring_overlay,
+                    "opacity",
+                ))
+                .build(),
+        );
+        // "Finished!" may be wider than the ring
+        widgets
+            .ring_overlay
+            .set_measure_overlay(&widgets.countdown, true);
         widgets
             .volume_button
             .first_child()
             .unwrap()
             .set_css_classes(&["circular", "toggle", "large-button"]);
         model.audio_player.emit(AudioPlayerInput::NextWarmup);
+        if init.start {
+            model.start_timer(&sender);
+            model.start_session();
+            model.publish_start();
+        } else {
+            model.running = false;
+        }
+        model.update_listeners(&sender);
+        model.update_progress();
+        let _ = sender.output(model.period_output());
+        let _ = sender.output(TrainingTimerOutput::Progress {
+            title: model.progress_title(),
+            running: model.running,
+        });
         ComponentParts { model, widgets }
     }
 
@@ -290,62 +1212,219 @@ impl Component for TrainingTimer {
         &mut self,
         message: Self::Input,
         sender: relm4::ComponentSender<Self>,
-        _root: &Self::Root,
+        root: &Self::Root,
     ) {
+        let previous_period = (
+            self.core.phase(),
+            self.core.remaining_sets(),
+            self.warmup_step(),
+            self.core.is_getting_ready(),
+        );
+        let previous_recovery = (self.recovery.is_some(), self.session.hr_recovery_bpm);
+        let mut countdown_tick = false;
+        // The readings of the heart rate monitor come as often as the ticks
+        let is_tick = matches!(
+            message,
+            TrainingTimerInput::Tick | TrainingTimerInput::HeartRate(_)
+        );
         match message {
             TrainingTimerInput::StartStop => {
-                if self.remaining_s == 0 && self.remaining_sets == 0 {
+                if self.core.remaining_s() == 0 && self.core.remaining_sets() == 0 {
                     return;
                 } else if self.running {
                     self.timer = None;
+                    self.pause_session();
+                    self.events.publish(WorkoutEvent::Paused);
                 } else {
-                    self.timer = build_timer(&sender);
+                    self.start_timer(&sender);
+                    if self.started {
+                        self.resume_session();
+                        self.events.publish(WorkoutEvent::Resumed);
+                    } else {
+                        self.start_session();
+                        self.publish_start();
+                    }
                 }
                 self.running = !self.running;
             }
             TrainingTimerInput::Pause => {
                 self.timer = None;
+                if self.running {
+                    self.pause_session();
+                    self.events.publish(WorkoutEvent::Paused);
+                }
                 self.running = false;
             }
+            TrainingTimerInput::Resume => {
+                if !self.running {
+                    sender.input(TrainingTimerInput::StartStop);
+                }
+            }
             TrainingTimerInput::Tick => {
                 assert!(self.running);
-                self.remaining_s -= 1;
-                if self.remaining_s == 0 {
-                    match self.state {
-                        TrainingState::Preparation => {
-                            self.state = TrainingState::Exercise;
-                            self.remaining_s = self.setup.exercise_s;
-                            self.audio_player.emit(AudioPlayerInput::NextExercise);
-                        }
-                        TrainingState::Exercise => {
-                            self.remaining_sets -= 1;
-                            if self.remaining_sets == 0 {
-                                self.timer = None;
-                                self.running = false;
-                                self.audio_player.emit(AudioPlayerInput::Finished);
-                            } else {
-                                self.state = TrainingState::Rest;
-                                self.remaining_s = self.setup.rest_s;
-                                self.audio_player.emit(AudioPlayerInput::NextRest);
-                            }
-                        }
-                        TrainingState::Rest => {
-                            self.state = TrainingState::Exercise;
-                            self.remaining_s = self.setup.exercise_s;
-                            self.audio_player.emit(AudioPlayerInput::NextExercise);
-                        }
-                    }
-                } else if self.remaining_s <= 5 {
-                    self.audio_player.emit(AudioPlayerInput::Ping);
-                }
+                let events = self.core.step(self.tick);
+                countdown_tick = self.handle_events(events);
             }
             TrainingTimerInput::Reset => {
                 self.reset(&sender);
             }
+            TrainingTimerInput::Skip => {
+                let events = self.core.skip();
+                self.handle_events(events);
+            }
+            TrainingTimerInput::Rewind => {
+                let added_s = self.core.phase_s() - self.core.remaining_s();
+                if self.started && !self.core.is_finished() && added_s > 0 {
+                    self.mark_session(SessionMarkKind::Rewound { added_s });
+                }
+                self.core.rewind();
+            }
+            TrainingTimerInput::SetBigDigits(big_digits) => {
+                self.big_digits = big_digits;
+            }
+            TrainingTimerInput::SetNarrow(narrow) => {
+                self.narrow = narrow;
+            }
+            TrainingTimerInput::SetTvMode(tv_mode) => {
+                self.tv_mode = tv_mode;
+            }
+            TrainingTimerInput::ToggleMiniWindow => match self.mini_window.take() {
+                Some(mini_window) if mini_window.is_open() => mini_window.close(),
+                _ => {
+                    self.mini_window = Some(MiniWindow::new(root.root().and_downcast()));
+                }
+            },
+            TrainingTimerInput::SetExertion(rpe) => {
+                self.session.rpe = (rpe > 0).then_some(rpe);
+                self.session_annotated = true;
+            }
+            TrainingTimerInput::SetNote(note) => {
+                self.session.note = note;
+                self.session_annotated = true;
+            }
+            TrainingTimerInput::Script(ScriptCommand::PlaySound) => {
+                self.audio_player.emit(AudioPlayerInput::Ping);
+            }
+            TrainingTimerInput::Script(ScriptCommand::SetNextInterval(seconds)) => {
+                self.core.set_next_phase_s(seconds);
+            }
+            TrainingTimerInput::HeartRate(bpm) => {
+                self.heart_rate = Some(Reading::new(bpm));
+                if self.started && !self.session_recorded {
+                    self.record_heart_rate(bpm);
+                }
+                if let Some(recovery_bpm) = self.recovery.and_then(|recovery| recovery.finish(bpm))
+                {
+                    self.recovery = None;
+                    if self.session_recorded {
+                        self.session.hr_recovery_bpm = Some(recovery_bpm);
+                        self.session_annotated = true;
+                        self.save_annotation();
+                    }
+                }
+            }
+            TrainingTimerInput::RecoveryTimeout => {
+                if self.recovery.is_some_and(|recovery| recovery.timed_out()) {
+                    self.recovery = None;
+                }
+            }
+            TrainingTimerInput::SetDucked(ducked) => {
+                self.audio_player.emit(AudioPlayerInput::Duck(ducked));
+            }
+            TrainingTimerInput::ShareSummary => {
+                summary_card::show(root, &self.session);
+            }
+            TrainingTimerInput::Done => {
+                self.save_annotation();
+                let _ = sender.output(TrainingTimerOutput::Done(self.setup.id.clone()));
+            }
+            TrainingTimerInput::AudioFailed(err) => {
+                // The user is told once, the timer keeps going without the beeps
+                if !self.audio_failed {
+                    self.audio_failed = true;
+                    let _ = sender.output(TrainingTimerOutput::Error(err));
+                } else {
+                    tracing::debug!("{}", err);
+                }
+            }
+        }
+        if self.recovery.is_some() && !previous_recovery.0 {
+            let sender = sender.input_sender().clone();
+            gtk::glib::timeout_add_local_once(heart_rate::RECOVERY_TIMEOUT, move || {
+                sender.emit(TrainingTimerInput::RecoveryTimeout);
+            });
+        }
+        self.update_listeners(&sender);
+        if self
+            .mini_window
+            .as_ref()
+            .is_some_and(|mini_window| !mini_window.is_open())
+        {
+            self.mini_window = None;
+        }
+        if !is_tick {
+            self.record_position();
+        }
+        self.update_window_title(root);
+        self.update_progress();
+        if let Some(mini_window) = &self.mini_window {
+            mini_window.update(
+                &big_digits_text(self.core.remaining_s()),
+                state_css_class(self.core.phase()),
+            );
+        }
+        if self.core.phase() != previous_period.0 {
+            let _ = sender.output(self.period_output());
+        }
+        let _ = sender.output(TrainingTimerOutput::Progress {
+            title: self.progress_title(),
+            running: self.running,
+        });
+        let warmup_step = self.warmup_step();
+        // The next step of the warmup begins without a change of the period
+        if is_tick
+            && previous_period.2.is_some()
+            && warmup_step.is_some()
+            && warmup_step != previous_period.2
+        {
+            self.audio_player.emit(AudioPlayerInput::Ping);
+        }
+        let period_changed = (
+            self.core.phase(),
+            self.core.remaining_sets(),
+            warmup_step,
+            self.core.is_getting_ready(),
+        ) != previous_period;
+        self.tick_only = is_tick
+            && !period_changed
+            && (self.recovery.is_some(), self.session.hr_recovery_bpm) == previous_recovery;
+        self.round_image_changed = self.setup.round_image(previous_period.1)
+            != self.setup.round_image(self.core.remaining_sets());
+        if (period_changed || countdown_tick) && self.global_setup.screen_flash.get() {
+            flash(root);
+        }
+        if period_changed {
+            if let Some(animation) = &self.period_animation {
+                animation.play();
+            }
+            root.announce(
+                &self.period_announcement(),
+                gtk::AccessibleAnnouncementPriority::High,
+            );
         }
     }
 
     fn shutdown(&mut self, widgets: &mut Self::Widgets, _output: relm4::Sender<Self::Output>) {
+        // Leaving the timer before the end aborts the session
+        self.end_session(false);
+        crash::record_session(None);
+        self.save_annotation();
+        if let Some(mini_window) = self.mini_window.take() {
+            mini_window.close();
+        }
+        if let Some(window) = widgets.root_clamp.root().and_downcast::<gtk::Window>() {
+            window.set_title(None);
+        }
         if let Some(parent) = widgets.root_clamp.parent() {
             parent
                 .downcast::<adw::ToolbarView>()
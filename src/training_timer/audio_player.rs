@@ -1,82 +1,76 @@
-use gettextrs::gettext;
-use relm4::{self, gtk::gio, prelude::*};
-use rodio::{self, Source};
+use crate::audio_backend::{AudioBackend, Cue};
+use crate::error::AppError;
+use relm4::{self, prelude::*};
 
 pub struct AudioPlayerModel {
-    output_stream: rodio::OutputStreamHandle,
-    ping_bytes: gtk::glib::Bytes,
-    volume: f64,
-}
-
-impl AudioPlayerModel {
-    fn play_ping(&self, times: u32) {
-        let cursor = std::io::Cursor::new(self.ping_bytes.clone());
-        let decoder = rodio::Decoder::new_wav(cursor)
-            // Translators: Error message printed to the console when an error occurs with WAV decoding
-            .unwrap_or_else(|err| panic!("{}: {}", gettext("Could not decode WAV"), err));
-        let new_duration = decoder.total_duration().unwrap() * times;
-        let d = decoder
-            .repeat_infinite()
-            .take_duration(new_duration)
-            .amplify(self.volume as f32);
-        self.output_stream
-            .play_raw(d.convert_samples())
-            // Translators: Error message printed to the console when an error occurs with audio playback
-            .unwrap_or_else(|err| panic!("{}: {}", gettext("Could not play audio"), err));
-    }
+    backend: Box<dyn AudioBackend>,
 }
 
 #[derive(Debug)]
 pub enum AudioPlayerInput {
     Ping,
+    /// A tick of the gap to get ready for the next exercise.
+    GetReady,
     NextWarmup,
     NextExercise,
     NextRest,
     Finished,
     SetVolume(f64),
+    Duck(bool),
+}
+
+#[derive(Debug)]
+pub enum AudioPlayerOutput {
+    /// A cue could not be played.
+    Failed(AppError),
 }
 
 pub struct AudioPlayerModelInit {
-    pub output_stream: rodio::OutputStreamHandle,
-    pub volume: f64,
+    pub backend: Box<dyn AudioBackend>,
+}
+
+impl AudioPlayerModel {
+    fn play(&self, cue: Cue, sender: &ComponentSender<Self>) {
+        if let Err(err) = self.backend.play_cue(cue) {
+            let _ = sender.output(AudioPlayerOutput::Failed(err));
+        }
+    }
 }
 
 impl relm4::Worker for AudioPlayerModel {
     type Init = AudioPlayerModelInit;
     type Input = AudioPlayerInput;
-    type Output = ();
+    type Output = AudioPlayerOutput;
 
     fn init(init: Self::Init, _sender: ComponentSender<Self>) -> Self {
-        let ping_bytes = gio::resources_lookup_data(
-            "/xyz/safeworlds/hiit/audio/ping.wav",
-            gio::ResourceLookupFlags::NONE,
-        )
-        // Translators: Error message printed to the console when cannot load data from resource
-        .unwrap_or_else(|err| panic!("{}: {}", gettext("Could not open resource data"), err));
         Self {
-            output_stream: init.output_stream,
-            volume: init.volume,
-            ping_bytes,
+            backend: init.backend,
         }
     }
 
-    fn update(&mut self, message: Self::Input, _sender: ComponentSender<Self>) {
+    fn update(&mut self, message: Self::Input, sender: ComponentSender<Self>) {
         match message {
             AudioPlayerInput::Ping => {
-                self.play_ping(1);
+                self.play(Cue::Countdown, &sender);
+            }
+            AudioPlayerInput::GetReady => {
+                self.play(Cue::GetReady, &sender);
             }
             AudioPlayerInput::NextWarmup => {}
             AudioPlayerInput::NextExercise => {
-                self.play_ping(2);
+                self.play(Cue::NextExercise, &sender);
             }
             AudioPlayerInput::NextRest => {
-                self.play_ping(2);
+                self.play(Cue::NextRest, &sender);
             }
             AudioPlayerInput::Finished => {
-                self.play_ping(3);
+                self.play(Cue::Finished, &sender);
             }
             AudioPlayerInput::SetVolume(vol) => {
-                self.volume = vol;
+                self.backend.set_volume(vol);
+            }
+            AudioPlayerInput::Duck(ducked) => {
+                self.backend.duck(ducked);
             }
         }
     }
@@ -0,0 +1,42 @@
+use relm4::gtk::{self, cairo, prelude::*};
+
+/// The share of the drawing area which the digits may fill.
+const FILL_RATIO: f64 = 0.9;
+
+/// Returns a draw function which renders the text as large as it fits into the drawing area.
+pub fn draw_big_digits(
+    text: String,
+) -> impl Fn(&gtk::DrawingArea, &cairo::Context, i32, i32) + 'static {
+    move |area, cr, width, height| {
+        if text.is_empty() {
+            return;
+        }
+        cr.select_font_face("Sans", cairo::FontSlant::Normal, cairo::FontWeight::Bold);
+        // Measure at a reference size, then scale to fill the area
+        cr.set_font_size(100.0);
+        let Ok(extents) = cr.text_extents(&text) else {
+            return;
+        };
+        if extents.width() <= 0.0 || extents.height() <= 0.0 {
+            return;
+        }
+        let scale = (width as f64 * FILL_RATIO / extents.width())
+            .min(height as f64 * FILL_RATIO / extents.height());
+        cr.set_font_size(100.0 * scale);
+        let Ok(extents) = cr.text_extents(&text) else {
+            return;
+        };
+        let color = area.color();
+        cr.set_source_rgba(
+            color.red() as f64,
+            color.green() as f64,
+            color.blue() as f64,
+            color.alpha() as f64,
+        );
+        cr.move_to(
+            (width as f64 - extents.width()) / 2.0 - extents.x_bearing(),
+            (height as f64 - extents.height()) / 2.0 - extents.y_bearing(),
+        );
+        let _ = cr.show_text(&text);
+    }
+}
@@ -0,0 +1,40 @@
+use crate::hiit_core::Phase;
+
+/// What happens in the course of a workout, for the subsystems which follow it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WorkoutEvent {
+    WorkoutStarted {
+        remaining_sets: usize,
+    },
+    /// A period began, including the first one. The remaining sets include the current one.
+    PhaseChanged {
+        phase: Phase,
+        remaining_sets: usize,
+    },
+    /// The exercise of a round came to the end or was skipped.
+    RoundCompleted,
+    Paused,
+    Resumed,
+    /// The last exercise came to the end.
+    Finished,
+}
+
+type Subscriber = Box<dyn Fn(&WorkoutEvent)>;
+
+/// Hands the events of the timer to the subscribers, in the order in which they subscribed.
+#[derive(Default)]
+pub struct EventBus {
+    subscribers: Vec<Subscriber>,
+}
+
+impl EventBus {
+    pub fn subscribe(&mut self, subscriber: impl Fn(&WorkoutEvent) + 'static) {
+        self.subscribers.push(Box::new(subscriber));
+    }
+
+    pub fn publish(&self, event: WorkoutEvent) {
+        for subscriber in &self.subscribers {
+            subscriber(&event);
+        }
+    }
+}
@@ -0,0 +1,121 @@
+use super::TrainingTimerInput;
+use crate::settings;
+use gettextrs::gettext;
+use relm4::gtk::{gio, glib, prelude::*};
+use std::{ffi::OsStr, time::Duration};
+
+/// The heart rate recovery is the drop of the heart rate in this time after the training.
+const RECOVERY_S: i64 = 60;
+/// A reading which is older at the end of the training does not tell the heart rate at the end,
+/// e.g. because the reader stalled.
+const MAX_BASELINE_AGE_S: i64 = 5;
+/// The measurement is given up if no reading came this long after the end of the training.
+pub const RECOVERY_TIMEOUT: Duration = Duration::from_secs(RECOVERY_S as u64 + 15);
+
+/// The reader of a heart rate monitor, e.g. a script around `gatttool` for a Bluetooth chest
+/// strap, which runs along with the workout. It writes the heart rate in beats per minute to its
+/// standard output, one reading per line.
+pub struct HeartRateMonitor {
+    subprocess: Option<gio::Subprocess>,
+}
+
+impl Drop for HeartRateMonitor {
+    fn drop(&mut self) {
+        if let Some(subprocess) = &self.subprocess {
+            subprocess.force_exit();
+        }
+    }
+}
+
+/// A reading of the heart rate monitor.
+#[derive(Debug, Clone, Copy)]
+pub struct Reading {
+    pub bpm: u32,
+    /// Monotonic time of the reading, in microseconds.
+    at: i64,
+}
+
+impl Reading {
+    pub fn new(bpm: u32) -> Self {
+        Self {
+            bpm,
+            at: glib::monotonic_time(),
+        }
+    }
+}
+
+/// The first number in the line, e.g. "HR: 142 bpm".
+fn reading(line: &str) -> Option<u32> {
+    line.split(|c: char| !c.is_ascii_digit())
+        .find(|number| !number.is_empty())
+        .and_then(|number| number.parse().ok())
+        .filter(|bpm| *bpm > 0)
+}
+
+/// Starts the reader configured in the `heart-rate-monitor` key, if there is one.
+pub fn start(sender: relm4::Sender<TrainingTimerInput>) -> HeartRateMonitor {
+    HeartRateMonitor {
+        subprocess: spawn(&settings::load_heart_rate_monitor_from_gsettings(), sender),
+    }
+}
+
+fn spawn(command_line: &str, sender: relm4::Sender<TrainingTimerInput>) -> Option<gio::Subprocess> {
+    if command_line.is_empty() {
+        return None;
+    }
+    let launcher = gio::SubprocessLauncher::new(gio::SubprocessFlags::STDOUT_PIPE);
+    let subprocess =
+        match launcher.spawn(&[OsStr::new("sh"), OsStr::new("-c"), OsStr::new(command_line)]) {
+            Ok(subprocess) => subprocess,
+            Err(err) => {
+                tracing::warn!(
+                    command = %command_line,
+                    error = %err,
+                    "{}",
+                    // Translators: Error message printed to the console when the reader of the heart rate monitor cannot be started
+                    gettext("Could not start the heart rate monitor")
+                );
+                return None;
+            }
+        };
+    let stdout = gio::DataInputStream::new(&subprocess.stdout_pipe()?);
+    relm4::spawn_local(async move {
+        while let Ok(Some(line)) = stdout.read_line_utf8_future(glib::Priority::DEFAULT).await {
+            if let Some(bpm) = reading(&line) {
+                sender.emit(TrainingTimerInput::HeartRate(bpm));
+            }
+        }
+    });
+    Some(subprocess)
+}
+
+/// The measurement of the heart rate recovery, from the heart rate at the end of the training.
+#[derive(Debug, Clone, Copy)]
+pub struct RecoveryMeasurement {
+    end_bpm: u32,
+    /// Monotonic time of the end of the training, in microseconds.
+    ended: i64,
+}
+
+impl RecoveryMeasurement {
+    /// Starts the measurement from the last reading, or returns `None` if that is too old to
+    /// tell the heart rate at the end of the training.
+    pub fn start(last: Reading) -> Option<Self> {
+        let ended = glib::monotonic_time();
+        (ended - last.at <= MAX_BASELINE_AGE_S * 1_000_000).then_some(Self {
+            end_bpm: last.bpm,
+            ended,
+        })
+    }
+
+    /// The drop of the heart rate in beats per minute, once the time of the measurement is over.
+    pub fn finish(&self, bpm: u32) -> Option<u32> {
+        (glib::monotonic_time() - self.ended >= RECOVERY_S * 1_000_000)
+            .then(|| self.end_bpm.saturating_sub(bpm))
+    }
+
+    /// Whether no reading came in time, so that the measurement is given up.
+    pub fn timed_out(&self) -> bool {
+        glib::monotonic_time() - self.ended >= RECOVERY_TIMEOUT.as_micros() as i64
+    }
+}
@@ -0,0 +1,118 @@
+use super::event_bus::WorkoutEvent;
+use crate::{hiit_core::Phase, settings::HookCommands, training_setup::TrainingSetup};
+use gettextrs::gettext;
+use relm4::gtk::gio;
+use std::ffi::OsStr;
+
+#[derive(Debug, Clone, Copy)]
+enum HookEvent {
+    Start,
+    Exercise,
+    Rest,
+    Finish,
+}
+
+impl HookEvent {
+    fn name(self) -> &'static str {
+        match self {
+            HookEvent::Start => "start",
+            HookEvent::Exercise => "exercise",
+            HookEvent::Rest => "rest",
+            HookEvent::Finish => "finish",
+        }
+    }
+
+    fn command(self, commands: &HookCommands) -> &str {
+        match self {
+            HookEvent::Start => &commands.start,
+            HookEvent::Exercise => &commands.exercise,
+            HookEvent::Rest => &commands.rest,
+            HookEvent::Finish => &commands.finish,
+        }
+    }
+}
+
+/// Runs the hooks of the training when the timer publishes their events.
+pub fn subscriber(commands: HookCommands, setup: TrainingSetup) -> impl Fn(&WorkoutEvent) {
+    move |event| {
+        let (hook_event, remaining_sets) = match *event {
+            WorkoutEvent::WorkoutStarted { remaining_sets } => (HookEvent::Start, remaining_sets),
+            WorkoutEvent::PhaseChanged {
+                phase: Phase::Exercise,
+                remaining_sets,
+            } => (HookEvent::Exercise, remaining_sets),
+            WorkoutEvent::PhaseChanged {
+                phase: Phase::Rest,
+                remaining_sets,
+            } => (HookEvent::Rest, remaining_sets),
+            WorkoutEvent::Finished => (HookEvent::Finish, 0),
+            _ => return,
+        };
+        run_hook(&commands, hook_event, &setup, remaining_sets);
+    }
+}
+
+/// Runs the user-configured shell command of the event in the background.
+/// The details of the event are passed in `HIIT_*` environment variables, the output of the command is logged.
+fn run_hook(
+    commands: &HookCommands,
+    event: HookEvent,
+    setup: &TrainingSetup,
+    remaining_sets: usize,
+) {
+    let command = event.command(commands);
+    if command.is_empty() {
+        return;
+    }
+    let launcher = gio::SubprocessLauncher::new(
+        gio::SubprocessFlags::STDOUT_PIPE | gio::SubprocessFlags::STDERR_PIPE,
+    );
+    launcher.setenv("HIIT_EVENT", event.name(), true);
+    launcher.setenv("HIIT_TRAINING", &setup.name, true);
+    launcher.setenv("HIIT_REMAINING_SETS", remaining_sets.to_string(), true);
+    let subprocess =
+        match launcher.spawn(&[OsStr::new("sh"), OsStr::new("-c"), OsStr::new(command)]) {
+            Ok(subprocess) => subprocess,
+            Err(err) => {
+                tracing::warn!(
+                    %command,
+                    error = %err,
+                    "{}",
+                    // Translators: Error message printed to the console when the command hook cannot be started
+                    gettext("Could not run command hook")
+                );
+                return;
+            }
+        };
+    let command = command.to_owned();
+    relm4::spawn_local(async move {
+        match subprocess.communicate_utf8_future(None).await {
+            Ok((stdout, stderr)) => {
+                for line in stdout.iter().flat_map(|stdout| stdout.lines()) {
+                    tracing::info!(%command, "{}", line);
+                }
+                for line in stderr.iter().flat_map(|stderr| stderr.lines()) {
+                    tracing::warn!(%command, "{}", line);
+                }
+                if !subprocess.is_successful() {
+                    tracing::warn!(
+                        %command,
+                        status = subprocess.exit_status(),
+                        "{}",
+                        // Translators: Error message printed to the console when the command hook exits with an error
+                        gettext("Command hook failed")
+                    );
+                }
+            }
+            Err(err) => {
+                tracing::warn!(
+                    %command,
+                    error = %err,
+                    "{}",
+                    // Translators: Error message printed to the console when the output of the command hook cannot be read
+                    gettext("Could not read the output of the command hook")
+                );
+            }
+        }
+    });
+}
@@ -0,0 +1,119 @@
+use cpal::{
+    traits::{DeviceTrait, HostTrait, StreamTrait},
+    SampleFormat,
+};
+use futures::{channel::mpsc, StreamExt};
+use gettextrs::gettext;
+use std::{path::PathBuf, sync::mpsc as std_mpsc, thread};
+
+/// Listens to the microphone for a few words with an offline Vosk model, on a thread of its own.
+/// It stops when it is dropped.
+pub struct KeywordSpotter {
+    /// Dropped to stop the thread.
+    _stop: std_mpsc::Sender<()>,
+}
+
+/// Starts to listen for the words, and hands the phrases which contain any of them to
+/// `on_phrase` on the main loop.
+pub fn start(
+    model_path: PathBuf,
+    words: Vec<String>,
+    on_phrase: impl Fn(&str) + 'static,
+) -> KeywordSpotter {
+    let (stop, stopped) = std_mpsc::channel();
+    let (phrases, mut heard_phrases) = mpsc::unbounded();
+    thread::spawn(move || {
+        if let Err(err) = listen(&model_path, &words, phrases, stopped) {
+            tracing::warn!(
+                model = %model_path.display(),
+                error = %err,
+                "{}",
+                // Translators: Error message printed to the console when the listener of the voice commands cannot be started
+                gettext("Could not start the voice control")
+            );
+        }
+    });
+    relm4::spawn_local(async move {
+        while let Some(phrase) = heard_phrases.next().await {
+            on_phrase(&phrase);
+        }
+    });
+    KeywordSpotter { _stop: stop }
+}
+
+/// Recognizes the words in the sound of the default microphone, until the spotter is dropped.
+fn listen(
+    model_path: &std::path::Path,
+    words: &[String],
+    phrases: mpsc::UnboundedSender<String>,
+    stopped: std_mpsc::Receiver<()>,
+) -> Result<(), String> {
+    vosk::set_log_level(vosk::LogLevel::Error);
+    let mut model = vosk::Model::new(model_path.to_string_lossy())
+        .ok_or("the speech model cannot be loaded")?;
+    // The words which the model does not know, e.g. the translations of the words for an English
+    // model, would fail the whole grammar
+    let mut grammar: Vec<&str> = words
+        .iter()
+        .map(String::as_str)
+        .filter(|word| model.find_word(word).is_some())
+        .collect();
+    if grammar.is_empty() {
+        return Err("the speech model knows none of the commands".to_owned());
+    }
+    // Anything else is heard as unknown, rather than as the closest command
+    grammar.push("[unk]");
+    let device = cpal::default_host()
+        .default_input_device()
+        .ok_or("there is no microphone")?;
+    let config = device
+        .default_input_config()
+        .map_err(|err| err.to_string())?;
+    let mut recognizer =
+        vosk::Recognizer::new_with_grammar(&model, config.sample_rate().0 as f32, &grammar)
+            .ok_or("the recognizer cannot be created")?;
+    let channels = usize::from(config.channels());
+    let mut on_samples = move |samples: &[i16]| {
+        // The first channel is enough to make out the words
+        let mono: Vec<i16> = samples.iter().step_by(channels).copied().collect();
+        if let Ok(vosk::DecodingState::Finalized) = recognizer.accept_waveform(&mono) {
+            if let Some(result) = recognizer.result().single() {
+                if !result.text.is_empty() && result.text != "[unk]" {
+                    let _ = phrases.unbounded_send(result.text.to_owned());
+                }
+            }
+        }
+    };
+    let on_error = |err: cpal::StreamError| tracing::warn!(%err, "Microphone failed");
+    let stream_config = config.config();
+    let stream = match config.sample_format() {
+        SampleFormat::I16 => device.build_input_stream(
+            &stream_config,
+            move |samples: &[i16], _: &_| on_samples(samples),
+            on_error,
+            None,
+        ),
+        SampleFormat::F32 => device.build_input_stream(
+            &stream_config,
+            move |samples: &[f32], _: &_| {
+                let samples: Vec<i16> = samples
+                    .iter()
+                    .map(|sample| (sample.clamp(-1.0, 1.0) * f32::from(i16::MAX)) as i16)
+                    .collect();
+                on_samples(&samples)
+            },
+            on_error,
+            None,
+        ),
+        format => {
+            return Err(format!(
+                "the microphone records {format}, which is not supported"
+            ))
+        }
+    }
+    .map_err(|err| err.to_string())?;
+    stream.play().map_err(|err| err.to_string())?;
+    // Nothing is ever sent, the receive ends when the spotter is dropped
+    let _ = stopped.recv();
+    Ok(())
+}
@@ -0,0 +1,84 @@
+use gettextrs::gettext;
+use relm4::gtk::{self, prelude::*};
+
+/// A small undecorated window which only shows the remaining time, colored by the period.
+/// GTK 4 cannot keep a window above the others, so the window manager's "Always on Top" is needed for that.
+pub struct MiniWindow {
+    window: gtk::Window,
+    content: gtk::Box,
+    label: gtk::Label,
+}
+
+impl MiniWindow {
+    pub fn new(main_window: Option<gtk::Window>) -> Self {
+        let label = gtk::Label::builder()
+            .css_classes(["timer-label"])
+            .hexpand(true)
+            .vexpand(true)
+            .build();
+        let close_button = gtk::Button::builder()
+            .icon_name("window-close-symbolic")
+            .css_classes(["circular", "flat"])
+            .halign(gtk::Align::End)
+            .valign(gtk::Align::Start)
+            // Translators: tooltip for the button which closes the mini timer window
+            .tooltip_text(gettext("Close Mini Timer"))
+            .build();
+        let overlay = gtk::Overlay::builder().child(&label).build();
+        overlay.add_overlay(&close_button);
+        let content = gtk::Box::builder().css_classes(["timer"]).build();
+        content.append(&overlay);
+        let window = gtk::Window::builder()
+            // Translators: The title of the small window which only shows the remaining time
+            .title(gettext("Mini Timer"))
+            .decorated(false)
+            .resizable(false)
+            .default_width(220)
+            .default_height(110)
+            // The whole window can be dragged to move it
+            .child(&gtk::WindowHandle::builder().child(&content).build())
+            .build();
+        window.set_application(
+            main_window
+                .as_ref()
+                .and_then(|window| window.application())
+                .as_ref(),
+        );
+        {
+            let window = window.clone();
+            close_button.connect_clicked(move |_| window.close());
+        }
+        // Double-clicking brings back the main window
+        let gesture = gtk::GestureClick::new();
+        gesture.connect_pressed(move |_, n_press, _, _| {
+            if n_press == 2 {
+                if let Some(main_window) = &main_window {
+                    main_window.present();
+                }
+            }
+        });
+        content.add_controller(gesture);
+        window.present();
+        Self {
+            window,
+            content,
+            label,
+        }
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.window.is_visible()
+    }
+
+    pub fn update(&self, remaining: &str, css_class: &str) {
+        self.label.set_label(remaining);
+        for class in ["timer-warmup", "timer-exercise", "timer-rest"] {
+            self.content.remove_css_class(class);
+        }
+        self.content.add_css_class(css_class);
+    }
+
+    pub fn close(&self) {
+        self.window.close();
+    }
+}
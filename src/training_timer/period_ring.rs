@@ -0,0 +1,60 @@
+use relm4::gtk::{self, prelude::*};
+use std::{cell::Cell, rc::Rc, time::Duration};
+
+use crate::progress_ring;
+
+/// The width of the ring around the countdown, in pixels.
+const LINE_WIDTH: f64 = 12.0;
+
+/// The progress of the current period, shared between the timer and the ring.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct PeriodProgress {
+    /// The time left of the period, including the part of the current second which has not elapsed yet.
+    pub remaining: Duration,
+    pub period_s: usize,
+}
+
+impl PeriodProgress {
+    /// The remaining share of the period.
+    fn fraction(&self) -> f64 {
+        if self.period_s == 0 {
+            return 0.0;
+        }
+        self.remaining.as_secs_f64() / self.period_s as f64
+    }
+}
+
+/// The ring around the countdown which depletes over the period in the color of its drawing area.
+/// It is redrawn at every tick of the timer, so its smoothness follows the tick interval.
+#[derive(Default)]
+pub struct PeriodRing {
+    area: Option<gtk::DrawingArea>,
+    progress: Rc<Cell<PeriodProgress>>,
+}
+
+impl PeriodRing {
+    pub fn attach(&mut self, area: &gtk::DrawingArea) {
+        let progress = self.progress.clone();
+        area.set_draw_func(move |area, cr, width, height| {
+            // Like the hands of a clock, the ring runs clockwise in right-to-left locales too
+            progress_ring::draw_progress_ring(
+                cr,
+                width,
+                height,
+                progress.get().fraction(),
+                &area.color(),
+                LINE_WIDTH,
+                false,
+            );
+        });
+        self.area = Some(area.clone());
+    }
+
+    pub fn set_progress(&self, progress: PeriodProgress) {
+        if self.progress.replace(progress) != progress {
+            if let Some(area) = &self.area {
+                area.queue_draw();
+            }
+        }
+    }
+}
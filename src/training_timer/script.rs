@@ -0,0 +1,266 @@
+use super::event_bus::WorkoutEvent;
+use super::TrainingTimerInput;
+use crate::{hiit_core::Phase, training_setup::TrainingSetup};
+use gettextrs::gettext;
+use relm4::gtk::{gio, glib};
+use rhai::{CallFnOptions, Dynamic, Engine, EvalAltResult, FuncArgs, Map, Scope, AST};
+use std::{cell::RefCell, ffi::OsStr};
+
+/// The steps after which a handler of the script is stopped, so that a loop cannot freeze the timer.
+const MAX_OPERATIONS: u64 = 100_000;
+
+/// What the script asks the timer to do.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ScriptCommand {
+    /// Plays the countdown beep.
+    PlaySound,
+    /// Makes the next exercise or rest last the given seconds.
+    SetNextInterval(usize),
+}
+
+/// The Rhai script of the user, e.g. `~/.config/hiit/script.rhai`, which follows the workout.
+struct Script {
+    engine: Engine,
+    ast: AST,
+    /// The object map which the handlers reach as `this`, kept from one event to the next.
+    state: RefCell<Dynamic>,
+}
+
+impl Script {
+    /// Calls the handler of the script, if it defines one with the number of arguments.
+    fn call(&self, name: &str, args: impl FuncArgs) {
+        let mut arguments = Vec::new();
+        args.parse(&mut arguments);
+        if !self
+            .ast
+            .iter_functions()
+            .any(|function| function.name == name && function.params.len() == arguments.len())
+        {
+            return;
+        }
+        let mut state = self.state.borrow_mut();
+        let options = CallFnOptions::new()
+            .eval_ast(false)
+            .bind_this_ptr(&mut state);
+        if let Err(err) = self.engine.call_fn_with_options::<Dynamic>(
+            options,
+            &mut Scope::new(),
+            &self.ast,
+            name,
+            arguments,
+        ) {
+            tracing::warn!(
+                handler = name,
+                %err,
+                "{}",
+                // Translators: Error message printed to the console when a function of the user's script stops with an error
+                gettext("The script failed")
+            );
+        }
+    }
+
+    fn handle(&self, event: &WorkoutEvent) {
+        match *event {
+            WorkoutEvent::WorkoutStarted { remaining_sets } => {
+                self.call("on_started", (remaining_sets as i64,))
+            }
+            WorkoutEvent::PhaseChanged {
+                phase,
+                remaining_sets,
+            } => {
+                let phase = match phase {
+                    Phase::Preparation => "preparation",
+                    Phase::Exercise => "exercise",
+                    Phase::Rest => "rest",
+                };
+                self.call("on_phase", (phase, remaining_sets as i64))
+            }
+            WorkoutEvent::RoundCompleted => self.call("on_round", ()),
+            WorkoutEvent::Paused => self.call("on_paused", ()),
+            WorkoutEvent::Resumed => self.call("on_resumed", ()),
+            WorkoutEvent::Finished => self.call("on_finished", ()),
+        }
+    }
+}
+
+fn script_path() -> std::path::PathBuf {
+    glib::user_config_dir().join("hiit").join("script.rhai")
+}
+
+/// Reads out the text with the speech synthesis of the system.
+fn speak(text: &str) {
+    if let Err(err) = gio::Subprocess::newv(
+        &[OsStr::new("spd-say"), OsStr::new(text)],
+        gio::SubprocessFlags::NONE,
+    ) {
+        tracing::warn!(
+            %err,
+            "{}",
+            // Translators: Error message printed to the console when the text of the user's script cannot be read out
+            gettext("Could not speak the text of the script")
+        );
+    }
+}
+
+/// The engine which runs the script, with the functions of the timer and without access to the system.
+fn engine(training_name: &str, sender: relm4::Sender<TrainingTimerInput>) -> Engine {
+    let mut engine = Engine::new();
+    engine
+        .set_max_operations(MAX_OPERATIONS)
+        .set_max_call_levels(32)
+        .set_max_expr_depths(64, 32)
+        .set_max_string_size(10_000)
+        .set_max_array_size(1_000)
+        .set_max_map_size(1_000)
+        .disable_symbol("eval")
+        .on_print(|text| tracing::info!(target: "script", "{}", text))
+        .on_debug(|text, _, position| tracing::debug!(target: "script", %position, "{}", text));
+    let training_name = training_name.to_owned();
+    engine.register_fn("training_name", move || training_name.clone());
+    engine.register_fn("speak", |text: &str| speak(text));
+    let play_sender = sender.clone();
+    engine.register_fn("play_sound", move || {
+        play_sender.emit(TrainingTimerInput::Script(ScriptCommand::PlaySound))
+    });
+    engine.register_fn(
+        "set_next_interval",
+        move |seconds: i64| -> Result<(), Box<EvalAltResult>> {
+            match usize::try_from(seconds) {
+                Ok(seconds) if seconds > 0 => {
+                    sender.emit(TrainingTimerInput::Script(ScriptCommand::SetNextInterval(
+                        seconds,
+                    )));
+                    Ok(())
+                }
+                _ => Err(format!(
+                    "the interval must be a positive number of seconds, not {}",
+                    seconds
+                )
+                .into()),
+            }
+        },
+    );
+    engine
+}
+
+/// Loads the script, and runs its statements outside of the functions once.
+fn load(source: &str, engine: Engine) -> Result<Script, Box<EvalAltResult>> {
+    let ast = engine.compile(source)?;
+    engine.run_ast(&ast)?;
+    Ok(Script {
+        engine,
+        ast,
+        state: RefCell::new(Map::new().into()),
+    })
+}
+
+/// Loads the script of the user if there is one, and hands it the events of the workout.
+/// The script defines functions for the events it follows, e.g. `on_phase(phase, sets)`,
+/// and calls `speak`, `play_sound` and `set_next_interval` to act on the timer.
+pub fn subscriber(
+    setup: &TrainingSetup,
+    sender: relm4::Sender<TrainingTimerInput>,
+) -> Option<impl Fn(&WorkoutEvent)> {
+    let path = script_path();
+    if !path.exists() {
+        return None;
+    }
+    let script = std::fs::read_to_string(&path)
+        .map_err(|err| err.to_string())
+        .and_then(|source| {
+            load(&source, engine(&setup.name, sender)).map_err(|err| err.to_string())
+        });
+    match script {
+        Ok(script) => Some(move |event: &WorkoutEvent| script.handle(event)),
+        Err(err) => {
+            tracing::warn!(
+                path = %path.display(),
+                error = %err,
+                "{}",
+                // Translators: Error message printed to the console when the user's script cannot be loaded
+                gettext("Could not run the script")
+            );
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(source: &str, events: &[WorkoutEvent]) -> Vec<ScriptCommand> {
+        let (sender, receiver) = relm4::channel();
+        let script = load(source, engine("Tabata", sender)).unwrap();
+        for event in events {
+            script.handle(event);
+        }
+        drop(script);
+        std::iter::from_fn(|| receiver.recv_sync())
+            .map(|input| match input {
+                TrainingTimerInput::Script(command) => command,
+                _ => unreachable!(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn calls_the_handlers_of_the_events() {
+        let commands = run(
+            r#"
+            fn on_phase(phase, sets) {
+                if phase == "rest" && sets == 1 { set_next_interval(30); }
+            }
+            fn on_finished() { play_sound(); }
+            "#,
+            &[
+                WorkoutEvent::PhaseChanged {
+                    phase: Phase::Rest,
+                    remaining_sets: 2,
+                },
+                WorkoutEvent::PhaseChanged {
+                    phase: Phase::Rest,
+                    remaining_sets: 1,
+                },
+                WorkoutEvent::Paused,
+                WorkoutEvent::Finished,
+            ],
+        );
+        assert_eq!(
+            commands,
+            vec![ScriptCommand::SetNextInterval(30), ScriptCommand::PlaySound]
+        );
+    }
+
+    #[test]
+    fn keeps_the_state_between_events() {
+        let commands = run(
+            r#"
+            fn on_round() {
+                this.rounds = (this.rounds ?? 0) + 1;
+                if this.rounds == 2 { play_sound(); }
+            }
+            "#,
+            &[WorkoutEvent::RoundCompleted; 3],
+        );
+        assert_eq!(commands, vec![ScriptCommand::PlaySound]);
+    }
+
+    #[test]
+    fn stops_a_handler_which_does_not_end() {
+        let commands = run(
+            "fn on_paused() { loop {} } fn on_resumed() { play_sound(); }",
+            &[WorkoutEvent::Paused, WorkoutEvent::Resumed],
+        );
+        assert_eq!(commands, vec![ScriptCommand::PlaySound]);
+    }
+
+    #[test]
+    fn rejects_an_invalid_interval() {
+        let commands = run(
+            "fn on_started(sets) { set_next_interval(-5); play_sound(); }",
+            &[WorkoutEvent::WorkoutStarted { remaining_sets: 3 }],
+        );
+        assert!(commands.is_empty());
+    }
+}
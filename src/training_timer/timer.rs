@@ -10,19 +10,23 @@ pub enum TimerOutput {
 
 impl Worker for TimerModel {
     type Output = TimerOutput;
-    type Init = ();
+    /// The interval between the ticks.
+    type Init = Duration;
     type Input = ();
 
-    fn init(_init: Self::Init, sender: relm4::ComponentSender<Self>) -> Self {
+    fn init(init: Self::Init, sender: relm4::ComponentSender<Self>) -> Self {
         let output_sender = sender.output_sender().clone();
         sender.command(move |_out, shutdown| {
             shutdown
                 .register(async move {
-                    let mut interval = tokio::time::interval(Duration::from_secs(1));
+                    let mut interval = tokio::time::interval(init);
                     interval.tick().await;
                     loop {
                         interval.tick().await;
-                        output_sender.send(TimerOutput::Tick).unwrap();
+                        // The timer was dropped
+                        if output_sender.send(TimerOutput::Tick).is_err() {
+                            break;
+                        }
                     }
                 })
                 .drop_on_shutdown()
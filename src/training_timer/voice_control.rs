@@ -0,0 +1,178 @@
+use super::TrainingTimerInput;
+use crate::settings;
+use gettextrs::gettext;
+use relm4::gtk::{gio, glib, prelude::*};
+use std::{cell::RefCell, ffi::OsStr};
+
+/// What hears the words of the user.
+enum Source {
+    /// The listener of the user, which writes the words it hears to its standard output,
+    /// one phrase per line.
+    Command(gio::Subprocess),
+    /// The recognizer of the app around an offline speech model.
+    #[cfg(feature = "voice-recognition")]
+    Recognizer {
+        _spotter: super::keyword_spotter::KeywordSpotter,
+    },
+}
+
+impl Drop for Source {
+    fn drop(&mut self) {
+        match self {
+            Source::Command(subprocess) => subprocess.force_exit(),
+            // Stops along with its own drop
+            #[cfg(feature = "voice-recognition")]
+            Source::Recognizer { .. } => {}
+        }
+    }
+}
+
+/// There is a single listener for all windows, which runs while any of them has a workout
+/// in progress.
+struct Listener {
+    source: Option<Source>,
+    /// The timers with a workout in progress, the commands go to the last one started.
+    timers: Vec<(u64, relm4::Sender<TrainingTimerInput>)>,
+    next_id: u64,
+}
+
+thread_local! {
+    static LISTENER: RefCell<Listener> = const {
+        RefCell::new(Listener {
+            source: None,
+            timers: Vec::new(),
+            next_id: 0,
+        })
+    };
+}
+
+/// Directs the voice commands to a timer while it is kept. The listener stops once no timer
+/// keeps it anymore.
+pub struct VoiceControl {
+    id: u64,
+}
+
+impl Drop for VoiceControl {
+    fn drop(&mut self) {
+        // Stopped outside of the borrow
+        let _source = LISTENER.with_borrow_mut(|listener| {
+            listener.timers.retain(|(id, _)| *id != self.id);
+            if listener.timers.is_empty() {
+                listener.source.take()
+            } else {
+                None
+            }
+        });
+    }
+}
+
+/// The words of the commands, in English and in the language of the user:
+/// pause, resume and skip.
+fn command_words() -> [(&'static str, String); 3] {
+    [
+        // Translators: The spoken command which pauses the timer. A single word in lower case.
+        ("pause", gettext("pause")),
+        // Translators: The spoken command which resumes the paused timer. A single word in lower case.
+        ("resume", gettext("resume")),
+        // Translators: The spoken command which skips to the next period. A single word in lower case.
+        ("skip", gettext("skip")),
+    ]
+}
+
+/// The timer input of the first command word in the phrase, e.g. "okay, pause".
+/// The English words are always understood, besides the words of the language of the user.
+fn command(phrase: &str) -> Option<TrainingTimerInput> {
+    let [pause, resume, skip] = command_words();
+    let is =
+        |word: &str, (english, translated): &(&str, String)| word == *english || word == translated;
+    phrase.to_lowercase().split_whitespace().find_map(|word| {
+        let word = word.trim_matches(|c: char| !c.is_alphanumeric());
+        if is(word, &pause) {
+            Some(TrainingTimerInput::Pause)
+        } else if is(word, &resume) {
+            Some(TrainingTimerInput::Resume)
+        } else if is(word, &skip) {
+            Some(TrainingTimerInput::Skip)
+        } else {
+            None
+        }
+    })
+}
+
+/// Hands the command in the phrase to the timer which was started last.
+fn hear(phrase: &str) {
+    let Some(input) = command(phrase) else {
+        return;
+    };
+    tracing::info!(%phrase, "Voice command");
+    let sender =
+        LISTENER.with_borrow(|listener| listener.timers.last().map(|(_, sender)| sender.clone()));
+    if let Some(sender) = sender {
+        sender.emit(input);
+    }
+}
+
+/// Starts the listener, unless it runs already for another window, and directs its commands
+/// to the timer.
+pub fn listen(sender: relm4::Sender<TrainingTimerInput>) -> VoiceControl {
+    let (id, is_running) = LISTENER.with_borrow_mut(|listener| {
+        let id = listener.next_id;
+        listener.next_id += 1;
+        listener.timers.push((id, sender));
+        (id, listener.source.is_some())
+    });
+    if !is_running {
+        let source = start();
+        LISTENER.with_borrow_mut(|listener| listener.source = source);
+    }
+    VoiceControl { id }
+}
+
+/// Starts the command in the `voice-command-listener` key, or else the recognizer of the app
+/// if the `voice-control` key enables it.
+fn start() -> Option<Source> {
+    let command_line = settings::load_voice_command_listener_from_gsettings();
+    if !command_line.is_empty() {
+        return spawn(&command_line).map(Source::Command);
+    }
+    #[cfg(feature = "voice-recognition")]
+    if settings::load_voice_control_from_gsettings() {
+        let words = command_words()
+            .into_iter()
+            .flat_map(|(english, translated)| [english.to_owned(), translated])
+            .collect::<std::collections::BTreeSet<_>>();
+        return Some(Source::Recognizer {
+            _spotter: super::keyword_spotter::start(
+                settings::load_voice_model_from_gsettings(),
+                words.into_iter().collect(),
+                hear,
+            ),
+        });
+    }
+    None
+}
+
+fn spawn(command_line: &str) -> Option<gio::Subprocess> {
+    let launcher = gio::SubprocessLauncher::new(gio::SubprocessFlags::STDOUT_PIPE);
+    let subprocess =
+        match launcher.spawn(&[OsStr::new("sh"), OsStr::new("-c"), OsStr::new(command_line)]) {
+            Ok(subprocess) => subprocess,
+            Err(err) => {
+                tracing::warn!(
+                    command = %command_line,
+                    error = %err,
+                    "{}",
+                    // Translators: Error message printed to the console when the listener of the voice commands cannot be started
+                    gettext("Could not start the voice control")
+                );
+                return None;
+            }
+        };
+    let stdout = gio::DataInputStream::new(&subprocess.stdout_pipe()?);
+    relm4::spawn_local(async move {
+        while let Ok(Some(line)) = stdout.read_line_utf8_future(glib::Priority::DEFAULT).await {
+            hear(line.trim_end());
+        }
+    });
+    Some(subprocess)
+}
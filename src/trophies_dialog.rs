@@ -0,0 +1,91 @@
+use crate::achievements::Achievement;
+use crate::statistics;
+use crate::statistics_page;
+use gettextrs::gettext;
+use relm4::{
+    adw::{self, prelude::*},
+    gtk,
+    prelude::*,
+};
+use relm4_icons::icon_names;
+
+pub struct TrophiesDialog {}
+
+#[derive(Debug)]
+pub enum TrophiesDialogOutput {
+    Closed,
+}
+
+#[relm4::component(pub)]
+impl SimpleComponent for TrophiesDialog {
+    type Init = Vec<Achievement>;
+    type Input = ();
+    type Output = TrophiesDialogOutput;
+
+    view! {
+        adw::Dialog {
+            // Translators: The title of the dialog which lists the achievements
+            set_title: &gettext("Trophies"),
+            set_content_width: 400,
+            set_content_height: 600,
+            connect_closed[sender] => move |_| {
+                let _ = sender.output(TrophiesDialogOutput::Closed);
+            },
+            #[wrap(Some)]
+            set_child = &adw::ToolbarView {
+                add_top_bar = &adw::HeaderBar {},
+                #[wrap(Some)]
+                set_content = &adw::PreferencesPage {
+                    #[name = "achievements_group"]
+                    add = &adw::PreferencesGroup {
+                        set_description: Some(&if false {
+                            // Translators: The number of earned achievements, e.g. "3 of 9 earned"
+                            gettext("{} of {} earned")
+                        } else {
+                            gettext!("{} of {} earned", earned_count, achievement_count)
+                        }),
+                    },
+                },
+            },
+        }
+    }
+
+    fn init(
+        init: Self::Init,
+        root: Self::Root,
+        sender: ComponentSender<Self>,
+    ) -> ComponentParts<Self> {
+        let model = TrophiesDialog {};
+        let earned_count = init
+            .iter()
+            .filter(|achievement| achievement.earned.is_some())
+            .count();
+        let achievement_count = init.len();
+        let widgets = view_output!();
+        for achievement in init {
+            let row = adw::ActionRow::builder()
+                .title(&achievement.title)
+                .subtitle(&achievement.description)
+                .build();
+            let icon = gtk::Image::from_icon_name(if achievement.earned.is_some() {
+                icon_names::TROPHY
+            } else {
+                icon_names::TROPHY_LOCK_REGULAR
+            });
+            row.add_prefix(&icon);
+            match achievement.earned {
+                Some(earned) => {
+                    row.add_suffix(
+                        &gtk::Label::builder()
+                            .label(statistics_page::format_day(statistics::local_day(earned)))
+                            .css_classes(["dim-label"])
+                            .build(),
+                    );
+                }
+                None => row.add_css_class("dim-label"),
+            }
+            widgets.achievements_group.add(&row);
+        }
+        ComponentParts { model, widgets }
+    }
+}
@@ -0,0 +1,87 @@
+//! The preparation as a sequence of named steps, e.g. jumping jacks for 30 seconds and arm
+//! circles for 20 seconds, rather than a single countdown. A training has steps of its own or
+//! runs the default warmup of the preferences.
+
+use crate::hiit_core::Phase;
+use crate::settings;
+use crate::training_timer::state_title;
+use gettextrs::gettext;
+pub use hiit_schema::WarmupStep;
+
+/// The choices of the editor, see [`Warmup::choice`].
+pub const COUNTDOWN_CHOICE: usize = 0;
+pub const DEFAULT_CHOICE: usize = 1;
+pub const STEPS_CHOICE: usize = 2;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum Warmup {
+    /// A countdown of the preparation time of the training.
+    #[default]
+    Countdown,
+    /// The default warmup of the preferences, or the countdown while it has no steps.
+    Default,
+    Steps(Vec<WarmupStep>),
+}
+
+impl Warmup {
+    /// The choices of the editor, in the order of [`Warmup::choice`].
+    pub fn choice_names() -> [String; 3] {
+        [
+            // Translators: The choice of the editor window for a preparation which only counts down
+            gettext("Countdown"),
+            // Translators: The choice of the editor window for the warmup set in the preferences
+            gettext("Default Warmup"),
+            // Translators: The choice of the editor window for a warmup with steps of its own
+            gettext("Custom Steps"),
+        ]
+    }
+
+    pub fn choice(&self) -> usize {
+        match self {
+            Warmup::Countdown => COUNTDOWN_CHOICE,
+            Warmup::Default => DEFAULT_CHOICE,
+            Warmup::Steps(_) => STEPS_CHOICE,
+        }
+    }
+
+    /// The warmup of a choice of the editor, with the steps which were entered for it.
+    pub fn from_choice(choice: usize, steps: Vec<WarmupStep>) -> Warmup {
+        match choice {
+            DEFAULT_CHOICE => Warmup::Default,
+            STEPS_CHOICE if !steps.is_empty() => Warmup::Steps(steps),
+            _ => Warmup::Countdown,
+        }
+    }
+
+    /// The steps which are run, none for a countdown.
+    pub fn steps(&self) -> Vec<WarmupStep> {
+        match self {
+            Warmup::Countdown => Vec::new(),
+            Warmup::Default => settings::load_default_warmup_from_gsettings(),
+            Warmup::Steps(steps) => steps.clone(),
+        }
+    }
+}
+
+/// The length of all steps in seconds.
+pub fn total_s(steps: &[WarmupStep]) -> usize {
+    steps.iter().map(|step| step.duration_s).sum()
+}
+
+/// The index of the step which runs `elapsed_s` into the preparation.
+pub fn step_at(steps: &[WarmupStep], elapsed_s: usize) -> Option<usize> {
+    let mut end_s = 0;
+    steps.iter().position(|step| {
+        end_s += step.duration_s;
+        elapsed_s < end_s
+    })
+}
+
+/// The name of the step, or of the preparation if the step has none.
+pub fn step_title(step: &WarmupStep) -> String {
+    if step.name.is_empty() {
+        state_title(Phase::Preparation)
+    } else {
+        step.name.clone()
+    }
+}
@@ -0,0 +1,162 @@
+use crate::training_editor::SPIN_ROW_UPPER;
+use crate::warmup::WarmupStep;
+use gettextrs::gettext;
+use relm4::{
+    adw::{self, prelude::*},
+    gtk, RelmWidgetExt,
+};
+
+/// The length of a newly added step in seconds.
+const NEW_STEP_S: usize = 30;
+
+/// Edits a list of warmup steps, each with its name and its duration.
+#[derive(Debug)]
+pub struct WarmupEditor {
+    steps: Vec<WarmupStep>,
+    group: adw::PreferencesGroup,
+    /// The rows of the steps, rebuilt when a step is added or removed.
+    rows: Vec<adw::EntryRow>,
+}
+
+pub struct WarmupEditorInit {
+    pub title: String,
+    pub description: Option<String>,
+    pub steps: Vec<WarmupStep>,
+}
+
+#[derive(Debug)]
+pub enum WarmupEditorInput {
+    Add,
+    Remove(usize),
+    SetName(usize, String),
+    SetDuration(usize, usize),
+}
+
+fn step_title(index: usize) -> String {
+    if false {
+        // Translators: The title of the field for the name of a warmup step, e.g. "Step 2"
+        gettext("Step {}")
+    } else {
+        gettext!("Step {}", index + 1)
+    }
+}
+
+impl WarmupEditor {
+    fn build_rows(&mut self, sender: &relm4::ComponentSender<Self>) {
+        for row in self.rows.drain(..) {
+            self.group.remove(&row);
+        }
+        for (index, step) in self.steps.iter().enumerate() {
+            let row = adw::EntryRow::builder()
+                .title(step_title(index))
+                .text(&step.name)
+                .build();
+            let duration = gtk::SpinButton::with_range(1.0, SPIN_ROW_UPPER, 1.0);
+            duration.set_value(step.duration_s as f64);
+            duration.set_valign(gtk::Align::Center);
+            // Translators: Tooltip of the field for the duration of a warmup step
+            duration.set_tooltip(&gettext("Seconds"));
+            let remove_button = gtk::Button::builder()
+                .icon_name("user-trash-symbolic")
+                .valign(gtk::Align::Center)
+                .css_classes(["flat"])
+                .build();
+            // Translators: Tooltip of the button which removes a warmup step
+            remove_button.set_tooltip(&gettext("Remove Step"));
+            row.add_suffix(&duration);
+            row.add_suffix(&remove_button);
+            {
+                let sender = sender.clone();
+                row.connect_changed(move |row| {
+                    sender.input(WarmupEditorInput::SetName(index, row.text().into()));
+                });
+            }
+            {
+                let sender = sender.clone();
+                duration.connect_value_changed(move |duration| {
+                    sender.input(WarmupEditorInput::SetDuration(
+                        index,
+                        duration.value() as usize,
+                    ));
+                });
+            }
+            {
+                let sender = sender.clone();
+                remove_button
+                    .connect_clicked(move |_| sender.input(WarmupEditorInput::Remove(index)));
+            }
+            self.group.add(&row);
+            self.rows.push(row);
+        }
+    }
+}
+
+#[relm4::component(pub)]
+impl relm4::SimpleComponent for WarmupEditor {
+    type Init = WarmupEditorInit;
+    type Input = WarmupEditorInput;
+    /// The steps after each change.
+    type Output = Vec<WarmupStep>;
+
+    view! {
+        adw::PreferencesGroup {
+            set_title: &init.title,
+            set_description: init.description.as_deref(),
+            #[wrap(Some)]
+            set_header_suffix = &gtk::Button {
+                add_css_class: "flat",
+                set_icon_name: "list-add-symbolic",
+                // Translators: Tooltip of the button which adds a step to the warmup
+                set_tooltip: &gettext("Add Step"),
+                connect_clicked => WarmupEditorInput::Add,
+            },
+        }
+    }
+
+    fn init(
+        init: Self::Init,
+        root: Self::Root,
+        sender: relm4::ComponentSender<Self>,
+    ) -> relm4::ComponentParts<Self> {
+        let mut model = WarmupEditor {
+            steps: init.steps.clone(),
+            group: root.clone(),
+            rows: Vec::new(),
+        };
+        let widgets = view_output!();
+        model.build_rows(&sender);
+        relm4::ComponentParts { model, widgets }
+    }
+
+    fn update(&mut self, message: Self::Input, sender: relm4::ComponentSender<Self>) {
+        match message {
+            WarmupEditorInput::Add => {
+                self.steps.push(WarmupStep {
+                    name: String::new(),
+                    duration_s: NEW_STEP_S,
+                });
+                self.build_rows(&sender);
+                if let Some(row) = self.rows.last() {
+                    row.grab_focus();
+                }
+            }
+            WarmupEditorInput::Remove(index) => {
+                if index < self.steps.len() {
+                    self.steps.remove(index);
+                    self.build_rows(&sender);
+                }
+            }
+            WarmupEditorInput::SetName(index, name) => {
+                if let Some(step) = self.steps.get_mut(index) {
+                    step.name = name;
+                }
+            }
+            WarmupEditorInput::SetDuration(index, duration_s) => {
+                if let Some(step) = self.steps.get_mut(index) {
+                    step.duration_s = duration_s;
+                }
+            }
+        }
+        let _ = sender.output(self.steps.clone());
+    }
+}
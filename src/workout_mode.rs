@@ -0,0 +1,442 @@
+//! The kinds of trainings, e.g. classic intervals or EMOM. A mode turns the fields of a training
+//! into the intervals of the timer and names its fields and phases, so neither the timer nor the
+//! editor know about the modes. A new mode implements [`WorkoutMode`] and is added to [`MODES`].
+
+use crate::hiit_core::{Intervals, Phase};
+use crate::training_setup::TrainingSetup;
+use crate::training_timer::state_title;
+use gettextrs::gettext;
+
+/// The fields of a training which depend on the mode. The preparation is part of every mode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Field {
+    Sets,
+    Exercise,
+    Rest,
+}
+
+/// The values of the fields which the editor fills in when a mode is chosen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Preset {
+    pub prepare_s: usize,
+    pub exercise_s: usize,
+    pub rest_s: usize,
+    pub sets: usize,
+}
+
+pub trait WorkoutMode: Sync {
+    /// Identifies the mode in the workout notation, e.g. "emom".
+    fn id(&self) -> &'static str;
+
+    /// The name of the mode in the editor.
+    fn name(&self) -> String;
+
+    /// The title of the field in the editor, or `None` if the mode does not use it.
+    fn field_title(&self, field: Field) -> Option<String>;
+
+    /// The periods of the training which the timer runs through.
+    fn intervals(&self, setup: &TrainingSetup) -> Intervals;
+
+    /// The name of the phase on the timer.
+    fn phase_title(&self, phase: Phase) -> String {
+        state_title(phase)
+    }
+
+    /// Values which the editor fills in when the mode is chosen, e.g. 25 minutes of focus.
+    fn preset(&self) -> Option<Preset> {
+        None
+    }
+
+    /// The name of the long rests on the timer, if they are named apart from the other rests.
+    fn long_rest_title(&self) -> Option<String> {
+        None
+    }
+
+    /// Plays a single soft ping when a period begins, without the countdown and the beeps.
+    fn is_calm(&self) -> bool {
+        false
+    }
+
+    /// Notifies of every new period, e.g. for breaks at the desk while working in other windows.
+    fn notifies_periods(&self) -> bool {
+        false
+    }
+
+    /// The number of sets which make up a step of the training, e.g. both sides of a stretch.
+    fn sets_per_step(&self) -> usize {
+        1
+    }
+
+    /// The metabolic equivalent of the exercise, for estimating the burned calories.
+    /// Vigorous calisthenics and circuit training are about 8.
+    fn exercise_met(&self) -> f64 {
+        8.0
+    }
+
+    /// The metabolic equivalent of the rest, e.g. walking around slowly.
+    fn rest_met(&self) -> f64 {
+        2.5
+    }
+}
+
+/// Sets of exercise and rest, all of them chosen freely.
+struct Classic;
+
+impl WorkoutMode for Classic {
+    fn id(&self) -> &'static str {
+        CLASSIC_MODE_ID
+    }
+
+    fn name(&self) -> String {
+        // Translators: The mode of a training with freely chosen sets of exercise and rest
+        gettext("Intervals")
+    }
+
+    fn field_title(&self, field: Field) -> Option<String> {
+        Some(match field {
+            // Translators: The title of the field for the number of sets in the training in the editor window
+            Field::Sets => gettext("Number of Sets"),
+            // Translators: The title of the field for the exercise duration in the training in the editor window
+            Field::Exercise => gettext("Exercise Time"),
+            // Translators: The title of the field for the rest duration in the training in the editor window
+            Field::Rest => gettext("Rest Time"),
+        })
+    }
+
+    fn intervals(&self, setup: &TrainingSetup) -> Intervals {
+        Intervals {
+            prepare_s: setup.prepare_s,
+            exercise_s: setup.exercise_s,
+            rest_s: setup.rest_s,
+            sets: setup.sets,
+            long_rest_s: 0,
+            long_rest_every: 0,
+        }
+    }
+}
+
+/// Eight sets of 20 seconds exercise and 10 seconds rest.
+struct Tabata;
+
+impl WorkoutMode for Tabata {
+    fn id(&self) -> &'static str {
+        "tabata"
+    }
+
+    fn name(&self) -> String {
+        // Translators: The mode of a training of 8 sets of 20 seconds exercise and 10 seconds rest
+        gettext("Tabata")
+    }
+
+    fn field_title(&self, _field: Field) -> Option<String> {
+        None
+    }
+
+    fn intervals(&self, setup: &TrainingSetup) -> Intervals {
+        Intervals {
+            prepare_s: setup.prepare_s,
+            exercise_s: 20,
+            rest_s: 10,
+            sets: 8,
+            long_rest_s: 0,
+            long_rest_every: 0,
+        }
+    }
+}
+
+/// Every minute on the minute: the exercise starts at the full minute, the rest of the minute is rest.
+struct Emom;
+
+impl WorkoutMode for Emom {
+    fn id(&self) -> &'static str {
+        "emom"
+    }
+
+    fn name(&self) -> String {
+        // Translators: The mode of a training which starts an exercise every minute on the minute
+        gettext("EMOM")
+    }
+
+    fn field_title(&self, field: Field) -> Option<String> {
+        match field {
+            // Translators: The title of the field for the number of minutes of an EMOM training in the editor window
+            Field::Sets => Some(gettext("Number of Minutes")),
+            // Translators: The title of the field for the exercise duration of each minute of an EMOM training in the editor window
+            Field::Exercise => Some(gettext("Exercise per Minute")),
+            Field::Rest => None,
+        }
+    }
+
+    fn intervals(&self, setup: &TrainingSetup) -> Intervals {
+        // At least a second is left for the rest
+        let exercise_s = setup.exercise_s.clamp(1, 59);
+        Intervals {
+            prepare_s: setup.prepare_s,
+            exercise_s,
+            rest_s: 60 - exercise_s,
+            sets: setup.sets,
+            long_rest_s: 0,
+            long_rest_every: 0,
+        }
+    }
+}
+
+/// As many rounds as possible: a single exercise period, the rounds are counted by the user.
+struct Amrap;
+
+impl WorkoutMode for Amrap {
+    fn id(&self) -> &'static str {
+        "amrap"
+    }
+
+    fn name(&self) -> String {
+        // Translators: The mode of a training of as many rounds as possible within a time
+        gettext("AMRAP")
+    }
+
+    fn field_title(&self, field: Field) -> Option<String> {
+        match field {
+            // Translators: The title of the field for the duration of an AMRAP training in the editor window
+            Field::Exercise => Some(gettext("Time Cap")),
+            Field::Sets | Field::Rest => None,
+        }
+    }
+
+    fn intervals(&self, setup: &TrainingSetup) -> Intervals {
+        Intervals {
+            prepare_s: setup.prepare_s,
+            exercise_s: setup.exercise_s,
+            rest_s: 0,
+            sets: 1,
+            long_rest_s: 0,
+            long_rest_every: 0,
+        }
+    }
+}
+
+/// Paced breathing: the exercise is breathing in, the rest breathing out.
+struct Breathing;
+
+impl WorkoutMode for Breathing {
+    fn id(&self) -> &'static str {
+        "breathing"
+    }
+
+    fn name(&self) -> String {
+        // Translators: The mode of a training of paced breathing
+        gettext("Breathing")
+    }
+
+    fn field_title(&self, field: Field) -> Option<String> {
+        Some(match field {
+            // Translators: The title of the field for the number of breaths of a breathing training in the editor window
+            Field::Sets => gettext("Number of Breaths"),
+            // Translators: The title of the field for the duration of breathing in in the editor window
+            Field::Exercise => gettext("Inhale Time"),
+            // Translators: The title of the field for the duration of breathing out in the editor window
+            Field::Rest => gettext("Exhale Time"),
+        })
+    }
+
+    fn intervals(&self, setup: &TrainingSetup) -> Intervals {
+        Classic.intervals(setup)
+    }
+
+    fn phase_title(&self, phase: Phase) -> String {
+        match phase {
+            Phase::Preparation => state_title(phase),
+            // Translators: Shown on the timer page while breathing in
+            Phase::Exercise => gettext("Inhale"),
+            // Translators: Shown on the timer page while breathing out
+            Phase::Rest => gettext("Exhale"),
+        }
+    }
+
+    // Both are about sitting quietly
+    fn exercise_met(&self) -> f64 {
+        1.3
+    }
+
+    fn rest_met(&self) -> f64 {
+        1.3
+    }
+}
+
+/// Focus and short breaks, with a long break after every four sets.
+struct Pomodoro;
+
+const POMODORO_LONG_BREAK_EVERY: usize = 4;
+
+impl WorkoutMode for Pomodoro {
+    fn id(&self) -> &'static str {
+        "pomodoro"
+    }
+
+    fn name(&self) -> String {
+        // Translators: The mode of a focus timer with work periods and breaks
+        gettext("Pomodoro")
+    }
+
+    fn field_title(&self, field: Field) -> Option<String> {
+        Some(match field {
+            // Translators: The title of the field for the number of work periods of a Pomodoro timer in the editor window
+            Field::Sets => gettext("Number of Pomodoros"),
+            // Translators: The title of the field for the duration of a work period of a Pomodoro timer in the editor window
+            Field::Exercise => gettext("Focus Time"),
+            // Translators: The title of the field for the duration of a short break of a Pomodoro timer in the editor window
+            Field::Rest => gettext("Break Time"),
+        })
+    }
+
+    fn intervals(&self, setup: &TrainingSetup) -> Intervals {
+        Intervals {
+            prepare_s: setup.prepare_s,
+            exercise_s: setup.exercise_s,
+            rest_s: setup.rest_s,
+            sets: setup.sets,
+            // 15 minutes after breaks of 5 minutes
+            long_rest_s: setup.rest_s * 3,
+            long_rest_every: POMODORO_LONG_BREAK_EVERY,
+        }
+    }
+
+    fn preset(&self) -> Option<Preset> {
+        Some(Preset {
+            prepare_s: 0,
+            exercise_s: 25 * 60,
+            rest_s: 5 * 60,
+            sets: POMODORO_LONG_BREAK_EVERY,
+        })
+    }
+
+    fn phase_title(&self, phase: Phase) -> String {
+        match phase {
+            Phase::Preparation => state_title(phase),
+            // Translators: Shown on the timer page during a work period of a Pomodoro timer
+            Phase::Exercise => gettext("Focus"),
+            // Translators: Shown on the timer page during a break of a Pomodoro timer
+            Phase::Rest => gettext("Break"),
+        }
+    }
+
+    fn long_rest_title(&self) -> Option<String> {
+        // Translators: Shown on the timer page during the long break after every fourth work period of a Pomodoro timer
+        Some(gettext("Long Break"))
+    }
+
+    fn is_calm(&self) -> bool {
+        true
+    }
+
+    fn notifies_periods(&self) -> bool {
+        true
+    }
+
+    // Working at a desk and walking around in the breaks
+    fn exercise_met(&self) -> f64 {
+        1.5
+    }
+}
+
+/// Stretches held on each side in turn: the exercise is a hold, the short rest between the
+/// sides is for switching sides, and the rest after both sides is for the next stretch.
+struct Stretching;
+
+/// The time for switching from the one side of a stretch to the other.
+const SWITCH_SIDES_S: usize = 5;
+
+impl WorkoutMode for Stretching {
+    fn id(&self) -> &'static str {
+        "stretching"
+    }
+
+    fn name(&self) -> String {
+        // Translators: The mode of a training of stretches held on both sides
+        gettext("Stretching")
+    }
+
+    fn field_title(&self, field: Field) -> Option<String> {
+        Some(match field {
+            // Translators: The title of the field for the number of stretches of a stretching routine in the editor window
+            Field::Sets => gettext("Number of Stretches"),
+            // Translators: The title of the field for how long each side of a stretch is held in the editor window
+            Field::Exercise => gettext("Hold Time per Side"),
+            // Translators: The title of the field for the time between two stretches in the editor window
+            Field::Rest => gettext("Time between Stretches"),
+        })
+    }
+
+    fn intervals(&self, setup: &TrainingSetup) -> Intervals {
+        Intervals {
+            prepare_s: setup.prepare_s,
+            exercise_s: setup.exercise_s,
+            rest_s: SWITCH_SIDES_S,
+            sets: setup.sets * 2,
+            long_rest_s: setup.rest_s,
+            long_rest_every: 2,
+        }
+    }
+
+    fn preset(&self) -> Option<Preset> {
+        Some(Preset {
+            prepare_s: 10,
+            exercise_s: 30,
+            rest_s: 10,
+            sets: 6,
+        })
+    }
+
+    fn phase_title(&self, phase: Phase) -> String {
+        match phase {
+            Phase::Preparation => state_title(phase),
+            // Translators: Shown on the timer page while a stretch is held
+            Phase::Exercise => gettext("Hold"),
+            // Translators: Shown on the timer page between the two sides of a stretch
+            Phase::Rest => gettext("Switch Sides"),
+        }
+    }
+
+    fn long_rest_title(&self) -> Option<String> {
+        // Translators: Shown on the timer page between two stretches
+        Some(gettext("Next Stretch"))
+    }
+
+    fn is_calm(&self) -> bool {
+        true
+    }
+
+    fn sets_per_step(&self) -> usize {
+        2
+    }
+
+    fn exercise_met(&self) -> f64 {
+        2.3
+    }
+
+    fn rest_met(&self) -> f64 {
+        2.3
+    }
+}
+
+/// The mode of trainings which do not state one.
+pub const CLASSIC_MODE_ID: &str = "classic";
+
+/// All modes, in the order of the editor.
+pub static MODES: [&dyn WorkoutMode; 7] = [
+    &Classic,
+    &Tabata,
+    &Emom,
+    &Amrap,
+    &Breathing,
+    &Pomodoro,
+    &Stretching,
+];
+
+/// The mode with the id, or the classic mode for unknown ones, e.g. from a newer version.
+pub fn mode(id: &str) -> &'static dyn WorkoutMode {
+    MODES
+        .iter()
+        .copied()
+        .find(|mode| mode.id() == id)
+        .unwrap_or(MODES[0])
+}